@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+use crate::analyzer::WindowScore;
+use crate::generate::parse_span;
+use crate::metrics::{Comparison, Threshold};
+use crate::stats::format_duration;
+
+// `--alert 'error_rate>5'` or `--alert 'bursts>0@10m'`: a rule on the
+// newest `--score-window` of a live run, with its own cooldown after `@`.
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    threshold: Threshold,
+    cooldown: Option<Duration>,
+}
+
+impl AlertRule {
+    const STATS: &'static [&'static str] = &["anomaly", "error_rate", "errors", "bursts", "fatal", "entries"];
+
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (rule, cooldown) = match spec.rsplit_once('@') {
+            Some((rule, span)) => (rule, Some(parse_span(span)?.to_std().map_err(|e| e.to_string())?)),
+            None => (spec, None),
+        };
+        let threshold = Threshold::parse(rule)?;
+        if !Self::STATS.contains(&threshold.metric.as_str()) {
+            return Err(format!(
+                "Unknown statistic '{}' (expected one of {})",
+                threshold.metric,
+                Self::STATS.join(", ")
+            ));
+        }
+        Ok(Self { threshold, cooldown })
+    }
+
+    // the dedup key: every message about this rule carries it
+    pub fn key(&self) -> String {
+        self.threshold.label()
+    }
+
+    fn value(&self, window: &WindowScore) -> f64 {
+        let count = |level: &str| *window.level_counts.get(level).unwrap_or(&0) as f64;
+        match self.threshold.metric.as_str() {
+            "anomaly" => window.anomaly_score,
+            "error_rate" => window.stats.error_rate,
+            "errors" => count("ERROR") + count("FATAL"),
+            "bursts" => window.stats.error_bursts.len() as f64,
+            "fatal" => count("FATAL"),
+            _ => window.entries as f64,
+        }
+    }
+
+    // the further past the threshold, the worse
+    fn worse(&self, a: f64, b: f64) -> f64 {
+        match self.threshold.comparison {
+            Comparison::Above | Comparison::AtLeast => a.max(b),
+            Comparison::Below | Comparison::AtMost => a.min(b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlertState {
+    Firing,
+    // still past the threshold a cooldown after the last message
+    Ongoing,
+    Resolved,
+}
+
+impl AlertState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Firing => "FIRING",
+            Self::Ongoing => "ONGOING",
+            Self::Resolved => "RESOLVED",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub key: String,
+    pub state: AlertState,
+    pub value: f64,
+    pub peak: f64,
+    pub active_for: Duration,
+    // checks past the threshold that sent nothing since the last message
+    pub suppressed: usize,
+}
+
+impl Alert {
+    pub fn describe(&self) -> String {
+        let active = format_duration(self.active_for.as_secs() as i64);
+        match self.state {
+            AlertState::Firing => format!("FIRING {}: now {:.1}", self.key, self.value),
+            AlertState::Ongoing => format!(
+                "ONGOING {} for {}: now {:.1}, peak {:.1}, {} updates held back",
+                self.key, active, self.value, self.peak, self.suppressed
+            ),
+            AlertState::Resolved => format!("RESOLVED {} after {}: peak {:.1}", self.key, active, self.peak),
+        }
+    }
+}
+
+#[derive(Default)]
+struct RuleState {
+    // past the threshold since
+    since: Option<Instant>,
+    peak: f64,
+    // whether this incident's FIRING went out
+    notified: bool,
+    last_sent: Option<Instant>,
+    suppressed: usize,
+}
+
+// Turns each check of the live statistics into as few messages as keep
+// someone informed: one FIRING when a rule starts holding, an ONGOING
+// update at most once per cooldown while it keeps holding, and a RESOLVED
+// when it stops. A rule that clears and holds again within its cooldown is
+// held back rather than firing anew, so a flapping value doesn't page
+// twice.
+pub struct Alerter {
+    rules: Vec<(AlertRule, RuleState)>,
+    cooldown: Duration,
+}
+
+impl Alerter {
+    pub fn new(rules: &[AlertRule], cooldown: Duration) -> Self {
+        Self { rules: rules.iter().map(|r| (r.clone(), RuleState::default())).collect(), cooldown }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn check(&mut self, window: &WindowScore, now: Instant) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for (rule, state) in &mut self.rules {
+            let value = rule.value(window);
+            let cooldown = rule.cooldown.unwrap_or(self.cooldown);
+            let cooled = state.last_sent.is_none_or(|t| now.duration_since(t) >= cooldown);
+            let mut send = |s: &mut RuleState, alert: AlertState| {
+                alerts.push(Alert {
+                    key: rule.key(),
+                    state: alert,
+                    value,
+                    peak: s.peak,
+                    active_for: s.since.map_or(Duration::ZERO, |t| now.duration_since(t)),
+                    suppressed: s.suppressed,
+                });
+                s.last_sent = Some(now);
+                s.suppressed = 0;
+            };
+
+            if rule.threshold.is_crossed(value) {
+                if state.since.is_none() {
+                    *state = RuleState { since: Some(now), peak: value, last_sent: state.last_sent, ..Default::default() };
+                }
+                state.peak = rule.worse(state.peak, value);
+                if cooled {
+                    let alert = if state.notified { AlertState::Ongoing } else { AlertState::Firing };
+                    state.notified = true;
+                    send(state, alert);
+                } else {
+                    state.suppressed += 1;
+                }
+            } else if state.since.is_some() {
+                if state.notified {
+                    send(state, AlertState::Resolved);
+                }
+                state.since = None;
+                state.notified = false;
+            }
+        }
+        alerts
+    }
+}
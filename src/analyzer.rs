@@ -1,9 +1,28 @@
 use rayon::prelude::*;
+use regex::Regex;
 use serde::Serialize;
-use std::collections::HashMap;
+use chrono::Duration;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
-use crate::parser::{LogEntry, LogLevel};
-use crate::stats::{self, Stats};
+use crate::cost::{self, CostReport};
+use crate::custody::Custody;
+use crate::endpoints::{self, EndpointOptions, EndpointSummary};
+use crate::buckets::{Bucketing, Zone};
+use crate::entities::{EntityExtractor, EntityStats};
+use crate::filter::FilterStats;
+use crate::groups::{self, GroupSummary};
+use crate::http::{self, HttpSummary};
+use crate::metrics::{self, MetricSpec, MetricSummary, Threshold, ThresholdEvent};
+use crate::model::{self, BaselineComparison, BaselineModel};
+use crate::parser::{LogEntry, LogLevel, ParseStats};
+use crate::ratelimit::{self, RateLimit, RateLimitReport};
+use crate::retention::{self, RetentionPolicy, RetentionResult};
+use crate::scoring::{Composite, ScoreInput, ScorePart};
+use crate::sources::{self, BudgetResult, ErrorBudget, SourceCoverage, SourceStats};
+use crate::startup::{self, BannerLine};
+use crate::templates::{self, ErrorPrecursor};
+use crate::versions::{self, VersionComparison};
+use crate::stats::{self, BurstParams, LiveStats, Stats};
 
 const STOPWORDS: &[&str] = &[
     "the", "and", "for", "with", "from", "that", "this", "have", "has",
@@ -12,46 +31,563 @@ const STOPWORDS: &[&str] = &[
     "than", "more", "some", "over", "such", "after", "before", "while",
 ];
 
+// bounds and rules for the automatic keyword cutoff (no --top): stop once
+// the words listed cover AUTO_COVERAGE of all keyword occurrences, or at an
+// elbow where the next word is under ELBOW_DROP of the one before it
+const AUTO_MIN_KEYWORDS: usize = 4;
+const AUTO_MAX_KEYWORDS: usize = 25;
+const AUTO_COVERAGE: f64 = 0.5;
+const ELBOW_DROP: f64 = 0.5;
+const TREND_BUCKETS: usize = 8;
+// timeline buckets when no --bucket width is given
+const TIMELINE_BUCKETS: usize = 120;
+// a --bucket too narrow for the span is widened to stay under this
+const MAX_TIMELINE_BUCKETS: usize = 10_000;
+const ENTITY_LIMIT: usize = 5;
+const PRECURSOR_LIMIT: usize = 5;
+const PRECURSOR_MIN_OCCURRENCES: usize = 3;
+// share of a template's tokens (wildcards aside) a message must match, in
+// place, to be folded into it
+const TEMPLATE_SIMILARITY: f64 = 0.6;
+// entries kept in memory for scorers that look at individual entries
+const LIVE_WINDOW: usize = 50_000;
+const LIVE_SCORE_WINDOW_MINUTES: i64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trend {
+    Rising,
+    Falling,
+    Stable,
+}
+
+// Which tokens count as keywords. IDs and hashes are unique per line and
+// crowd real words out of the top list on high-cardinality logs.
+#[derive(Debug, Clone, Copy)]
+pub struct KeywordOptions {
+    pub min_len: usize,
+    pub max_len: Option<usize>,
+    pub drop_numbers: bool,
+    pub drop_hex: bool,
+    pub drop_uuids: bool,
+}
+
+impl Default for KeywordOptions {
+    fn default() -> Self {
+        Self {
+            min_len: 3,
+            max_len: None,
+            drop_numbers: false,
+            drop_hex: false,
+            drop_uuids: false,
+        }
+    }
+}
+
+impl KeywordOptions {
+    fn accepts(&self, token: &str) -> bool {
+        // in bytes, as the fixed three-byte minimum always was
+        let len = token.len();
+        if len < self.min_len || self.max_len.is_some_and(|max| len > max) {
+            return false;
+        }
+        if STOPWORDS.contains(&token) {
+            return false;
+        }
+        if self.drop_numbers && token.chars().all(|c| c.is_ascii_digit() || c == '.' || c == ',') {
+            return false;
+        }
+        if self.drop_uuids && is_uuid(token) {
+            return false;
+        }
+        if self.drop_hex && len >= 8 && token.chars().all(|c| c.is_ascii_hexdigit()) {
+            return false;
+        }
+        true
+    }
+}
+
+fn is_uuid(token: &str) -> bool {
+    let groups: Vec<&str> = token.split('-').collect();
+    groups.len() == 5
+        && groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12])
+        && groups.iter().all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateEntry {
+    pub template: String,
+    pub count: usize,
+    pub error_ratio: f64,
+    // the first message the template matched, as logged
+    pub example: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineBucket {
+    pub start: String,
+    pub total: usize,
+    // per level name; levels with no entries in the bucket are left out
+    pub levels: BTreeMap<&'static str, usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Timeline {
+    pub bucket_secs: i64,
+    // every bucket from the first entry's to the last one's, quiet ones included
+    pub buckets: Vec<TimelineBucket>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TailEntry {
+    #[serde(flatten)]
+    pub entry: LogEntry,
+    // seconds between this entry and the last one in the file
+    pub before_end: i64,
+}
+
 #[derive(Debug, Serialize)]
 pub struct KeywordEntry {
     pub word: String,
     pub count: usize,
     pub error_ratio: f64,
+    pub trend: Trend,
+    pub series: Vec<usize>,
 }
 
+/// Everything one run found: statistics, templates, keywords, metrics and
+/// the anomaly score, plus the parse statistics it was built from. This is
+/// what the text report renders and the JSON export serializes.
 #[derive(Debug, Serialize)]
 pub struct LogAnalysis {
+    // version, port and config lines near the start of the log
+    pub startup: Vec<BannerLine>,
     pub stats: Stats,
+    // entries per level per fixed interval
+    pub timeline: Timeline,
     pub level_counts: HashMap<String, usize>,
     pub top_keywords: Vec<KeywordEntry>,
+    pub top_templates: Vec<TemplateEntry>,
+    pub entities: EntityStats,
+    pub source_coverage: Vec<SourceCoverage>,
+    // per-source totals, most errors first
+    pub source_stats: Vec<SourceStats>,
+    pub groups: Vec<GroupSummary>,
+    // only when entries came from more than one version
+    pub versions: Option<VersionComparison>,
+    pub endpoints: EndpointSummary,
+    // status codes, error trend and slowest paths; only for access logs
+    pub http: Option<HttpSummary>,
+    // only with --rate-limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitReport>,
+    // only with --cost-per-gb
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<CostReport>,
+    // one per --retention policy
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub retention: Vec<RetentionResult>,
+    pub error_budgets: Vec<BudgetResult>,
+    pub error_precursors: Vec<ErrorPrecursor>,
+    pub metrics: Vec<MetricSummary>,
+    pub threshold_events: Vec<ThresholdEvent>,
+    // the last WARN+ entries, oldest first: how the log ended
+    pub tail: Vec<TailEntry>,
+    // only when a saved model was loaded
+    pub baseline: Option<BaselineComparison>,
+    // entries removed by each filter stage, at parse time and after
+    pub filter_stats: FilterStats,
+    // only with --evidence-mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custody: Option<Custody>,
     pub anomaly_score: f64,
-    pub unparsed_lines: usize,
+    // each scorer's contribution to `anomaly_score`
+    pub anomaly_parts: Vec<ScorePart>,
+    pub parse_stats: ParseStats,
 }
 
-pub struct LogAnalyzer {
-    entries: Vec<LogEntry>,
-    unparsed_lines: usize,
+/// Computes a [`LogAnalysis`] over parsed entries, which must be sorted by
+/// timestamp (as [`LogParser`](crate::parser::LogParser) returns them).
+/// Optional sections are switched on with the `with_*` builders; then call
+/// [`analyze`](Self::analyze).
+pub struct LogAnalyzer<'a> {
+    entries: &'a [LogEntry],
+    parse_stats: ParseStats,
+    metric_specs: Vec<MetricSpec>,
+    thresholds: Vec<Threshold>,
+    keyword_opts: KeywordOptions,
+    budgets: Vec<ErrorBudget>,
+    template_limit: usize,
+    tail_limit: usize,
+    group_by: Vec<String>,
+    version_pattern: Option<Regex>,
+    endpoint_opts: EndpointOptions,
+    rate_limit: Option<RateLimit>,
+    // price per GB for --cost-per-gb
+    cost_per_gb: Option<f64>,
+    retention: Vec<RetentionPolicy>,
+    // the inputs' size, when it is all in the entries
+    input_bytes: Option<u64>,
+    scorer: Composite,
+    baseline: Option<&'a BaselineModel>,
+    filter_stats: FilterStats,
+    custody: Option<Custody>,
+    zone: Zone,
+    bucket: Option<Duration>,
+    burst: BurstParams,
 }
 
-impl LogAnalyzer {
-    pub fn new(entries: Vec<LogEntry>, unparsed_lines: usize) -> Self {
-        Self { entries, unparsed_lines }
+impl<'a> LogAnalyzer<'a> {
+    pub fn new(entries: &'a [LogEntry], parse_stats: ParseStats) -> Self {
+        Self {
+            entries,
+            parse_stats,
+            metric_specs: Vec::new(),
+            thresholds: Vec::new(),
+            keyword_opts: KeywordOptions::default(),
+            budgets: Vec::new(),
+            template_limit: 10,
+            tail_limit: 5,
+            group_by: Vec::new(),
+            version_pattern: None,
+            endpoint_opts: EndpointOptions { limit: 10, ..Default::default() },
+            rate_limit: None,
+            cost_per_gb: None,
+            retention: Vec::new(),
+            input_bytes: None,
+            scorer: Composite::default(),
+            baseline: None,
+            // nothing filtered unless told otherwise
+            filter_stats: FilterStats { input: entries.len(), kept: entries.len(), ..Default::default() },
+            custody: None,
+            zone: Zone::default(),
+            bucket: None,
+            burst: BurstParams::default(),
+        }
+    }
+
+    // timeline interval; without one it is sized to the span
+    pub fn with_bucket(mut self, width: Option<Duration>) -> Self {
+        self.bucket = width;
+        self
+    }
+
+    pub fn with_burst(mut self, params: BurstParams) -> Self {
+        self.burst = params;
+        self
+    }
+
+    pub fn with_baseline(mut self, model: Option<&'a BaselineModel>) -> Self {
+        self.baseline = model;
+        self
     }
 
-    pub fn analyze(self, top_n: usize) -> LogAnalysis {
-        let stats = stats::compute(&self.entries);
-        let level_counts = count_by_level(&self.entries);
-        let top_keywords = extract_keywords(&self.entries, top_n);
-        let anomaly_score = compute_anomaly_score(&stats, &level_counts);
+    // wall-clock zone for hourly counts and time buckets
+    pub fn with_zone(mut self, zone: Zone) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    pub fn with_filter_stats(mut self, stats: FilterStats) -> Self {
+        self.filter_stats = stats;
+        self
+    }
+
+    pub fn with_custody(mut self, custody: Option<Custody>) -> Self {
+        self.custody = custody;
+        self
+    }
+
+    pub fn with_scorer(mut self, scorer: Composite) -> Self {
+        self.scorer = scorer;
+        self
+    }
+
+    pub fn with_endpoint_options(mut self, opts: EndpointOptions) -> Self {
+        self.endpoint_opts = opts;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, limit: Option<RateLimit>) -> Self {
+        self.rate_limit = limit;
+        self
+    }
+
+    pub fn with_cost(mut self, price_per_gb: Option<f64>) -> Self {
+        self.cost_per_gb = price_per_gb;
+        self
+    }
+
+    pub fn with_retention(mut self, policies: Vec<RetentionPolicy>) -> Self {
+        self.retention = policies;
+        self
+    }
+
+    // sizes entries for the cost and retention estimates
+    pub fn with_input_bytes(mut self, bytes: Option<u64>) -> Self {
+        self.input_bytes = bytes;
+        self
+    }
+
+    pub fn with_error_budgets(mut self, budgets: Vec<ErrorBudget>) -> Self {
+        self.budgets = budgets;
+        self
+    }
+
+    pub fn with_template_limit(mut self, limit: usize) -> Self {
+        self.template_limit = limit;
+        self
+    }
+
+    pub fn with_tail(mut self, limit: usize) -> Self {
+        self.tail_limit = limit;
+        self
+    }
+
+    pub fn with_version_pattern(mut self, pattern: Option<Regex>) -> Self {
+        self.version_pattern = pattern;
+        self
+    }
+
+    pub fn with_group_by(mut self, fields: Vec<String>) -> Self {
+        self.group_by = fields;
+        self
+    }
+
+    pub fn with_keyword_options(mut self, opts: KeywordOptions) -> Self {
+        self.keyword_opts = opts;
+        self
+    }
+
+    pub fn with_metrics(mut self, specs: Vec<MetricSpec>, thresholds: Vec<Threshold>) -> Self {
+        self.metric_specs = specs;
+        self.thresholds = thresholds;
+        self
+    }
+
+    // `top_n` of None sizes the keyword table to the log
+    pub fn analyze(self, top_n: Option<usize>) -> LogAnalysis {
+        let startup = startup::banner(self.entries);
+        let stats = stats::compute(self.entries, self.zone, self.burst);
+        let timeline = timeline(self.entries, self.bucket, self.zone);
+        let level_counts = count_by_level(self.entries);
+        let opts = &self.keyword_opts;
+        let mut top_keywords = extract_keywords(self.entries, top_n, opts);
+        compute_keyword_trends(self.entries, &mut top_keywords, opts);
+        let top_templates = cluster_templates(self.entries, self.template_limit);
+        let entities = EntityExtractor::new().extract(self.entries, ENTITY_LIMIT);
+        let source_coverage = sources::coverage(self.entries);
+        let source_stats = sources::source_stats(self.entries);
+        let groups = groups::summarize(self.entries, &self.group_by);
+        let versions = versions::compare(self.entries, self.version_pattern.as_ref());
+        let endpoints = endpoints::summarize(self.entries, &self.endpoint_opts);
+        let http = http::summarize(self.entries, &self.endpoint_opts.normalizer, self.endpoint_opts.limit, self.zone);
+        let rate_limit = self
+            .rate_limit
+            .and_then(|l| ratelimit::simulate(self.entries, &l, &self.endpoint_opts.normalizer, self.endpoint_opts.limit));
+        let cost = self.cost_per_gb.and_then(|price| cost::estimate(self.entries, price, self.input_bytes));
+        let retention = retention::simulate(self.entries, &self.retention, self.input_bytes);
+        let error_budgets = sources::error_budgets(self.entries, &self.budgets, self.zone);
+        let error_precursors =
+            templates::error_precursors(self.entries, PRECURSOR_MIN_OCCURRENCES, PRECURSOR_LIMIT);
+        let metrics = metrics::summarize(self.entries, &self.metric_specs, self.zone);
+        let threshold_events =
+            metrics::detect_crossings(self.entries, &self.metric_specs, &self.thresholds);
+        let baseline = self.baseline.map(|m| model::compare(self.entries, m));
+        let tail = severe_tail(self.entries, self.tail_limit);
+        let input = ScoreInput {
+            entries: self.entries,
+            stats: &stats,
+            level_counts: &level_counts,
+            zone: self.zone,
+        };
+        let anomaly_parts = self.scorer.breakdown(&input);
+        let anomaly_score = self.scorer.combine(&anomaly_parts);
 
         LogAnalysis {
+            startup,
             stats,
+            timeline,
             level_counts,
             top_keywords,
+            top_templates,
+            entities,
+            source_coverage,
+            source_stats,
+            groups,
+            versions,
+            endpoints,
+            http,
+            rate_limit,
+            cost,
+            retention,
+            error_budgets,
+            error_precursors,
+            metrics,
+            threshold_events,
+            tail,
+            baseline,
+            filter_stats: self.filter_stats,
+            custody: self.custody,
             anomaly_score,
-            unparsed_lines: self.unparsed_lines,
+            anomaly_parts,
+            parse_stats: self.parse_stats,
+        }
+    }
+}
+
+// Running totals for follow mode: entries are added as they are written and
+// `snapshot` rescores without re-reading what came before. Scorers that look
+// at individual entries only see the most recent LIVE_WINDOW of them.
+pub struct LiveAnalyzer {
+    stats: LiveStats,
+    level_counts: HashMap<String, usize>,
+    recent: VecDeque<LogEntry>,
+    scorer: Composite,
+    zone: Zone,
+    burst: BurstParams,
+    score_window: Duration,
+}
+
+pub struct LiveSnapshot {
+    pub stats: Stats,
+    pub level_counts: HashMap<String, usize>,
+    pub anomaly_score: f64,
+    pub anomaly_parts: Vec<ScorePart>,
+    // the same scorers over the newest `score_window` alone, so the score
+    // recovers once a past incident is over
+    pub window: Option<WindowScore>,
+}
+
+pub struct WindowScore {
+    pub span: Duration,
+    pub entries: usize,
+    pub stats: Stats,
+    pub level_counts: HashMap<String, usize>,
+    pub anomaly_score: f64,
+    pub anomaly_parts: Vec<ScorePart>,
+}
+
+impl LiveAnalyzer {
+    pub fn new(scorer: Composite, zone: Zone, burst: BurstParams) -> Self {
+        Self {
+            stats: LiveStats::new(zone, burst),
+            level_counts: HashMap::new(),
+            recent: VecDeque::new(),
+            scorer,
+            zone,
+            burst,
+            score_window: Duration::minutes(LIVE_SCORE_WINDOW_MINUTES),
         }
     }
+
+    pub fn with_score_window(mut self, span: Duration) -> Self {
+        self.score_window = span;
+        self
+    }
+
+    // `entries` must not be older than anything already added
+    pub fn update(&mut self, entries: Vec<LogEntry>) {
+        for entry in entries {
+            self.stats.push(&entry);
+            *self.level_counts.entry(entry.level.as_str().to_string()).or_insert(0) += 1;
+            if self.recent.len() == LIVE_WINDOW {
+                self.recent.pop_front();
+            }
+            // a straggler goes in its place, so the window stays sorted
+            let at = self.recent.partition_point(|e| e.timestamp <= entry.timestamp);
+            self.recent.insert(at, entry);
+        }
+    }
+
+    // newest last
+    pub fn recent(&self) -> impl DoubleEndedIterator<Item = &LogEntry> {
+        self.recent.iter()
+    }
+
+    pub fn snapshot(&mut self) -> LiveSnapshot {
+        let stats = self.stats.stats();
+        let input = ScoreInput {
+            entries: self.recent.make_contiguous(),
+            stats: &stats,
+            level_counts: &self.level_counts,
+            zone: self.zone,
+        };
+        let anomaly_parts = self.scorer.breakdown(&input);
+        let anomaly_score = self.scorer.combine(&anomaly_parts);
+        let window = self.window_score();
+
+        LiveSnapshot { stats, level_counts: self.level_counts.clone(), anomaly_score, anomaly_parts, window }
+    }
+
+    // Only what's still in `recent` counts, so a window holding more than
+    // LIVE_WINDOW entries is scored on its newest ones.
+    fn window_score(&mut self) -> Option<WindowScore> {
+        let entries = self.recent.make_contiguous();
+        let start = entries.last()?.timestamp - self.score_window;
+        let entries = &entries[entries.partition_point(|e| e.timestamp < start)..];
+
+        let stats = stats::compute(entries, self.zone, self.burst);
+        let mut level_counts: HashMap<String, usize> = HashMap::new();
+        for entry in entries {
+            *level_counts.entry(entry.level.as_str().to_string()).or_insert(0) += 1;
+        }
+        let input = ScoreInput { entries, stats: &stats, level_counts: &level_counts, zone: self.zone };
+        let anomaly_parts = self.scorer.breakdown(&input);
+        Some(WindowScore {
+            span: self.score_window,
+            entries: entries.len(),
+            anomaly_score: self.scorer.combine(&anomaly_parts),
+            anomaly_parts,
+            stats,
+            level_counts,
+        })
+    }
+}
+
+fn timeline(entries: &[LogEntry], width: Option<Duration>, zone: Zone) -> Timeline {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Timeline::default();
+    };
+    let buckets = match width {
+        Some(width) => Bucketing::new(width, zone).at_most(first.timestamp, last.timestamp, MAX_TIMELINE_BUCKETS),
+        None => Bucketing::fitting(first.timestamp, last.timestamp, TIMELINE_BUCKETS, zone),
+    };
+    let (origin, count) = buckets.span(first.timestamp, last.timestamp);
+
+    let mut per_bucket: Vec<BTreeMap<&'static str, usize>> = vec![BTreeMap::new(); count];
+    for entry in entries {
+        let b = buckets.index(origin, entry.timestamp).min(count - 1);
+        *per_bucket[b].entry(entry.level.as_str()).or_insert(0) += 1;
+    }
+
+    Timeline {
+        bucket_secs: buckets.width().num_seconds(),
+        buckets: per_bucket
+            .into_iter()
+            .enumerate()
+            .map(|(b, levels)| TimelineBucket {
+                start: (origin + buckets.width() * b as i32).format("%Y-%m-%d %H:%M:%S").to_string(),
+                total: levels.values().sum(),
+                levels,
+            })
+            .collect(),
+    }
+}
+
+fn severe_tail(entries: &[LogEntry], limit: usize) -> Vec<TailEntry> {
+    let Some(end) = entries.last().map(|e| e.timestamp) else {
+        return Vec::new();
+    };
+    let mut tail: Vec<TailEntry> = entries
+        .iter()
+        .rev()
+        .filter(|e| e.level.severity() >= LogLevel::Warn.severity())
+        .take(limit)
+        .map(|e| TailEntry { entry: e.clone(), before_end: (end - e.timestamp).num_seconds() })
+        .collect();
+    tail.reverse();
+    tail
 }
 
 fn count_by_level(entries: &[LogEntry]) -> HashMap<String, usize> {
@@ -62,7 +598,7 @@ fn count_by_level(entries: &[LogEntry]) -> HashMap<String, usize> {
     counts
 }
 
-fn extract_keywords(entries: &[LogEntry], limit: usize) -> Vec<KeywordEntry> {
+fn extract_keywords(entries: &[LogEntry], limit: Option<usize>, opts: &KeywordOptions) -> Vec<KeywordEntry> {
     // parallel word count per level
     let (total_counts, error_counts): (HashMap<String, usize>, HashMap<String, usize>) = entries
         .par_iter()
@@ -71,15 +607,7 @@ fn extract_keywords(entries: &[LogEntry], limit: usize) -> Vec<KeywordEntry> {
             let mut errors: HashMap<String, usize> = HashMap::new();
             let is_error = matches!(entry.level, LogLevel::Error | LogLevel::Fatal);
 
-            for word in entry.message.split_whitespace() {
-                let clean = word
-                    .trim_matches(|c: char| !c.is_alphanumeric())
-                    .to_lowercase();
-
-                if clean.len() < 3 || STOPWORDS.contains(&clean.as_str()) {
-                    continue;
-                }
-
+            for clean in keywords_in(&entry.message, opts) {
                 *total.entry(clean.clone()).or_insert(0) += 1;
                 if is_error {
                     *errors.entry(clean).or_insert(0) += 1;
@@ -110,37 +638,256 @@ fn extract_keywords(entries: &[LogEntry], limit: usize) -> Vec<KeywordEntry> {
             } else {
                 0.0
             };
-            KeywordEntry { word, count, error_ratio }
+            KeywordEntry {
+                word,
+                count,
+                error_ratio,
+                trend: Trend::Stable,
+                series: Vec::new(),
+            }
         })
         .collect();
 
-    result.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(b.error_ratio.partial_cmp(&a.error_ratio).unwrap()));
+    result.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(b.error_ratio.total_cmp(&a.error_ratio)));
+    let limit = limit.unwrap_or_else(|| {
+        let counts: Vec<usize> = result.iter().map(|kw| kw.count).collect();
+        auto_cutoff(&counts)
+    });
     result.truncate(limit);
     result
 }
 
-fn compute_anomaly_score(stats: &Stats, level_counts: &HashMap<String, usize>) -> f64 {
-    let mut score = 0.0_f64;
+// How many of `counts` (sorted, largest first) to list: a log with a few
+// dominant words stops early, a varied one runs to AUTO_MAX_KEYWORDS.
+fn auto_cutoff(counts: &[usize]) -> usize {
+    let total: usize = counts.iter().sum();
+    let mut covered = 0;
+    for (i, &count) in counts.iter().enumerate().take(AUTO_MAX_KEYWORDS) {
+        if i >= AUTO_MIN_KEYWORDS
+            && (covered as f64 >= AUTO_COVERAGE * total as f64
+                || (count as f64) < ELBOW_DROP * counts[i - 1] as f64)
+        {
+            return i;
+        }
+        covered += count;
+    }
+    counts.len().min(AUTO_MAX_KEYWORDS)
+}
+
+// Drain-style grouping: messages are first reduced to their fingerprint
+// (numbers, IDs and paths masked), then fingerprints with the same token
+// count and first token fold into one template when enough of their other
+// tokens match in place; the tokens that differ become wildcards. This
+// catches variable words the fingerprint can't tell from text, like user
+// or host names without digits.
+pub fn cluster_templates(entries: &[LogEntry], limit: usize) -> Vec<TemplateEntry> {
+    if limit == 0 {
+        return Vec::new();
+    }
 
-    // error rate weight
-    score += stats.error_rate * 0.4;
+    // fingerprint -> (count, errors, index of its first entry)
+    type Counts = HashMap<String, (usize, usize, usize)>;
+    let counts: Counts = entries
+        .par_iter()
+        .enumerate()
+        .fold(Counts::new, |mut acc, (i, entry)| {
+            let c = acc.entry(templates::fingerprint(&entry.message)).or_insert((0, 0, i));
+            c.0 += 1;
+            if matches!(entry.level, LogLevel::Error | LogLevel::Fatal) {
+                c.1 += 1;
+            }
+            acc
+        })
+        .reduce(Counts::new, |mut acc, other| {
+            for (k, (n, e, first)) in other {
+                let c = acc.entry(k).or_insert((0, 0, first));
+                c.0 += n;
+                c.1 += e;
+                c.2 = c.2.min(first);
+            }
+            acc
+        });
 
-    // burst penalty
-    score += stats.error_bursts.len() as f64 * 5.0;
+    // common forms first, so they seed the templates rarer ones fold into
+    let mut fingerprints: Vec<(String, (usize, usize, usize))> = counts.into_iter().collect();
+    fingerprints.sort_unstable_by(|a, b| b.1 .0.cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
 
-    // fatal presence
-    if *level_counts.get("FATAL").unwrap_or(&0) > 0 {
-        score += 20.0;
+    struct Cluster {
+        tokens: Vec<String>,
+        count: usize,
+        errors: usize,
+        first: usize,
     }
 
-    // MTBF: shorter = worse
-    if let Some(mtbf) = stats.mtbf_seconds {
-        if mtbf < 60.0 {
-            score += 15.0;
-        } else if mtbf < 300.0 {
-            score += 8.0;
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut by_shape: HashMap<(usize, String), Vec<usize>> = HashMap::new();
+    for (fingerprint, (count, errors, first)) in fingerprints {
+        let tokens: Vec<String> = fingerprint.split(' ').map(str::to_string).collect();
+        let shape = (tokens.len(), tokens.first().cloned().unwrap_or_default());
+        let candidates = by_shape.entry(shape).or_default();
+
+        let best = candidates
+            .iter()
+            .map(|&i| (i, similarity(&clusters[i].tokens, &tokens)))
+            .filter(|&(_, sim)| sim >= TEMPLATE_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+
+        match best {
+            Some((i, _)) => {
+                let cluster = &mut clusters[i];
+                for (have, token) in cluster.tokens.iter_mut().zip(&tokens) {
+                    if have != token {
+                        *have = templates::WILDCARD.to_string();
+                    }
+                }
+                cluster.count += count;
+                cluster.errors += errors;
+                cluster.first = cluster.first.min(first);
+            }
+            None => {
+                candidates.push(clusters.len());
+                clusters.push(Cluster { tokens, count, errors, first });
+            }
+        }
+    }
+
+    let mut result: Vec<TemplateEntry> = clusters
+        .into_iter()
+        .map(|c| TemplateEntry {
+            template: c.tokens.join(" "),
+            count: c.count,
+            error_ratio: c.errors as f64 / c.count as f64,
+            example: entries[c.first].message.clone(),
+        })
+        .collect();
+
+    result.sort_unstable_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.error_ratio.total_cmp(&a.error_ratio))
+            .then_with(|| a.template.cmp(&b.template))
+    });
+    result.truncate(limit);
+    result
+}
+
+// share of positions where both hold the same literal token
+fn similarity(template: &[String], tokens: &[String]) -> f64 {
+    let same = template
+        .iter()
+        .zip(tokens)
+        .filter(|(a, b)| a == b && a.as_str() != templates::WILDCARD)
+        .count();
+    same as f64 / template.len().max(1) as f64
+}
+
+// Whether `message` is one of a template's members: its fingerprint has the
+// template's length and matches every literal token in place.
+pub fn in_template(template: &str, message: &str) -> bool {
+    let fingerprint = templates::fingerprint(message);
+    let mut tokens = fingerprint.split(' ');
+    let mut literals = template.split(' ');
+    loop {
+        match (literals.next(), tokens.next()) {
+            (None, None) => return true,
+            (Some(want), Some(token)) if want == templates::WILDCARD || want == token => {}
+            _ => return false,
         }
     }
+}
+
+pub fn keywords_in<'m>(
+    message: &'m str,
+    opts: &'m KeywordOptions,
+) -> impl Iterator<Item = String> + 'm {
+    message.split_whitespace().filter_map(move |word| {
+        let clean = word
+            .trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
 
-    score.min(100.0)
+        opts.accepts(&clean).then_some(clean)
+    })
+}
+
+// Splits the covered time span into equal buckets, counts each top keyword
+// per bucket and classifies the least-squares slope relative to the mean.
+fn compute_keyword_trends(
+    entries: &[LogEntry],
+    keywords: &mut [KeywordEntry],
+    opts: &KeywordOptions,
+) {
+    if keywords.is_empty() || entries.is_empty() {
+        return;
+    }
+
+    let index: HashMap<&str, usize> = keywords
+        .iter()
+        .enumerate()
+        .map(|(i, kw)| (kw.word.as_str(), i))
+        .collect();
+
+    let first = entries[0].timestamp;
+    let span = (entries[entries.len() - 1].timestamp - first).num_seconds().max(0) + 1;
+    let mut series = vec![vec![0usize; TREND_BUCKETS]; keywords.len()];
+
+    for entry in entries {
+        let offset = (entry.timestamp - first).num_seconds().max(0);
+        let bucket = (offset * TREND_BUCKETS as i64 / span) as usize;
+        for word in keywords_in(&entry.message, opts) {
+            if let Some(&i) = index.get(word.as_str()) {
+                series[i][bucket.min(TREND_BUCKETS - 1)] += 1;
+            }
+        }
+    }
+
+    for (kw, s) in keywords.iter_mut().zip(series) {
+        kw.trend = classify_trend(&s);
+        kw.series = s;
+    }
+}
+
+fn classify_trend(series: &[usize]) -> Trend {
+    let n = series.len() as f64;
+    let total: usize = series.iter().sum();
+    if total < 5 || series.len() < 2 {
+        return Trend::Stable;
+    }
+
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = total as f64 / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for (x, &y) in series.iter().enumerate() {
+        let dx = x as f64 - mean_x;
+        cov += dx * (y as f64 - mean_y);
+        var += dx * dx;
+    }
+
+    // change across the whole span, as a fraction of the average bucket
+    let relative_change = cov / var * (n - 1.0) / mean_y;
+    if relative_change > 0.5 {
+        Trend::Rising
+    } else if relative_change < -0.5 {
+        Trend::Falling
+    } else {
+        Trend::Stable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::LogParser;
+
+    #[test]
+    fn stray_timestamp_does_not_explode_a_fixed_bucket() {
+        let text = "[1990-01-01 00:00:00] INFO clock not set\n[2026-01-15 10:00:00] ERROR disk full\n";
+        let (entries, _) = LogParser::new().parse_bytes(text.as_bytes());
+        assert_eq!(entries.len(), 2);
+
+        let timeline = timeline(&entries, Some(Duration::minutes(1)), Zone::default());
+
+        assert!(timeline.buckets.len() <= MAX_TIMELINE_BUCKETS);
+        assert!(timeline.bucket_secs > 60);
+        assert_eq!(timeline.buckets.iter().map(|b| b.total).sum::<usize>(), 2);
+    }
 }
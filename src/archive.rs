@@ -0,0 +1,129 @@
+use bzip2::read::MultiBzDecoder;
+use flate2::read::{GzDecoder, MultiGzDecoder};
+use glob::Pattern;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    pub fn detect(path: &str) -> Option<Self> {
+        let lower = path.to_lowercase();
+        if lower.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if lower.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+// Whole-file compression of a single log, as logrotate leaves behind
+// (`app.log.1.gz`). Detected from the magic bytes rather than the name, so
+// misnamed files work and `.gz` files that were never compressed still do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Zstd,
+}
+
+impl Compression {
+    pub fn detect(head: &[u8]) -> Option<Self> {
+        if head.starts_with(&[0x1f, 0x8b]) {
+            Some(Self::Gzip)
+        } else if head.starts_with(b"BZh") {
+            Some(Self::Bzip2)
+        } else if head.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+// The file's contents, decompressed on the fly when it is compressed.
+// Concatenated streams (`cat a.gz b.gz`) are read through to the end.
+pub fn open_decompressed(file: File) -> io::Result<Box<dyn Read + Send>> {
+    let mut file = BufReader::new(file);
+    let mut magic = Vec::with_capacity(4);
+    (&mut file).take(4).read_to_end(&mut magic)?;
+    let compression = Compression::detect(&magic);
+    let reader = Cursor::new(magic).chain(file);
+
+    Ok(match compression {
+        Some(Compression::Gzip) => Box::new(MultiGzDecoder::new(reader)),
+        Some(Compression::Bzip2) => Box::new(MultiBzDecoder::new(reader)),
+        Some(Compression::Zstd) => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        None => Box::new(reader),
+    })
+}
+
+pub struct Member {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+// Regular-file members whose path matches `pattern` (all of them when None),
+// in archive order.
+pub fn read_members(
+    path: &str,
+    kind: ArchiveKind,
+    pattern: Option<&Pattern>,
+) -> io::Result<Vec<Member>> {
+    let wanted = |name: &str| pattern.is_none_or(|p| p.matches(name));
+    let file = BufReader::new(File::open(path)?);
+
+    match kind {
+        ArchiveKind::Zip => read_zip(file, wanted),
+        ArchiveKind::Tar => read_tar(file, wanted),
+        ArchiveKind::TarGz => read_tar(GzDecoder::new(file), wanted),
+    }
+}
+
+fn read_zip<R: Read + io::Seek>(reader: R, wanted: impl Fn(&str) -> bool) -> io::Result<Vec<Member>> {
+    let mut archive = zip::ZipArchive::new(reader).map_err(io::Error::other)?;
+    let mut members = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(io::Error::other)?;
+        if !entry.is_file() || !wanted(entry.name()) {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data)?;
+        members.push(Member { name, data });
+    }
+
+    Ok(members)
+}
+
+fn read_tar<R: Read>(reader: R, wanted: impl Fn(&str) -> bool) -> io::Result<Vec<Member>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        if !wanted(&name) {
+            continue;
+        }
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data)?;
+        members.push(Member { name, data });
+    }
+
+    Ok(members)
+}
@@ -0,0 +1,155 @@
+use colored::Colorize;
+use std::fs;
+use std::io;
+use std::time::{Duration, Instant};
+
+use crate::analyzer::LogAnalyzer;
+use crate::cli::BenchArgs;
+use crate::filter::{self, FilterConfig, KeywordMode};
+use crate::parser::{LogFormat, LogParser};
+use crate::report::ReportGenerator;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StageTimings {
+    pub parse: Duration,
+    pub filter: Duration,
+    pub analyze: Duration,
+    pub report: Duration,
+}
+
+impl StageTimings {
+    pub fn total(&self) -> Duration {
+        self.parse + self.filter + self.analyze + self.report
+    }
+}
+
+#[derive(Debug)]
+pub struct BenchReport {
+    pub file_path: String,
+    pub file_bytes: u64,
+    pub lines: usize,
+    pub entries: usize,
+    pub runs: Vec<StageTimings>,
+    pub peak_rss_kb: Option<u64>,
+}
+
+pub fn run(args: &BenchArgs) -> io::Result<BenchReport> {
+    let file_bytes = fs::metadata(&args.file_path)?.len();
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let parser = LogParser::with_format(format);
+    let reporter = ReportGenerator::new(false);
+
+    let mut runs = Vec::with_capacity(args.runs.max(1));
+    let mut lines = 0;
+    let mut entries_count = 0;
+
+    for _ in 0..args.runs.max(1) {
+        let mut t = StageTimings::default();
+
+        let start = Instant::now();
+        let (entries, parse_stats) = parser.parse_file_counted(&args.file_path)?;
+        t.parse = start.elapsed();
+
+        lines = parse_stats.total_lines;
+        entries_count = entries.len();
+
+        let mut cfg = FilterConfig::new();
+        if let Some(ref kw) = args.keyword {
+            cfg = cfg.with_keywords(vec![kw.clone()], KeywordMode::All);
+        }
+
+        let start = Instant::now();
+        let filtered = filter::apply(entries, &cfg);
+        t.filter = start.elapsed();
+
+        let start = Instant::now();
+        let analysis = LogAnalyzer::new(&filtered, parse_stats).analyze(Some(args.top));
+        t.analyze = start.elapsed();
+
+        let start = Instant::now();
+        reporter.render(&mut io::sink(), &args.file_path, &analysis, true)?;
+        t.report = start.elapsed();
+
+        runs.push(t);
+    }
+
+    Ok(BenchReport {
+        file_path: args.file_path.clone(),
+        file_bytes,
+        lines,
+        entries: entries_count,
+        runs,
+        peak_rss_kb: peak_rss_kb(),
+    })
+}
+
+impl BenchReport {
+    // fastest run per stage: the least noisy estimate on a busy machine
+    pub fn best(&self) -> StageTimings {
+        let min = |f: fn(&StageTimings) -> Duration| {
+            self.runs.iter().map(f).min().unwrap_or_default()
+        };
+        StageTimings {
+            parse: min(|t| t.parse),
+            filter: min(|t| t.filter),
+            analyze: min(|t| t.analyze),
+            report: min(|t| t.report),
+        }
+    }
+
+    pub fn print(&self, color: bool) {
+        let title = "logscope — Benchmark";
+        if color {
+            println!("\n{}", title.bold().cyan());
+        } else {
+            println!("\n{}", title);
+        }
+        println!("{}", "─".repeat(50));
+
+        println!("File    : {}", self.file_path);
+        println!("Size    : {:.2} MB", self.file_bytes as f64 / 1_048_576.0);
+        println!("Lines   : {}", self.lines);
+        println!("Entries : {}", self.entries);
+        println!("Runs    : {}\n", self.runs.len());
+
+        let best = self.best();
+        let parse_secs = best.parse.as_secs_f64().max(f64::EPSILON);
+
+        println!("Throughput (best run)");
+        println!("{}", "─".repeat(30));
+        println!("  {:>12.0} lines/sec", self.lines as f64 / parse_secs);
+        println!("  {:>12.2} MB/sec\n", self.file_bytes as f64 / 1_048_576.0 / parse_secs);
+
+        println!("Stage Timings (best run)");
+        println!("{}", "─".repeat(30));
+        let total = best.total().as_secs_f64().max(f64::EPSILON);
+        for (name, d) in [
+            ("parse", best.parse),
+            ("filter", best.filter),
+            ("analyze", best.analyze),
+            ("report", best.report),
+        ] {
+            let ms = d.as_secs_f64() * 1000.0;
+            let pct = d.as_secs_f64() / total * 100.0;
+            println!("  {:<8} {:>10.2} ms  ({:5.1}%)", name, ms, pct);
+        }
+        println!("  {:<8} {:>10.2} ms\n", "total", total * 1000.0);
+
+        match self.peak_rss_kb {
+            Some(kb) => println!("Peak RSS: {:.1} MB\n", kb as f64 / 1024.0),
+            None => println!("Peak RSS: unavailable on this platform\n"),
+        }
+    }
+}
+
+// VmHWM from procfs; None on platforms without it
+fn peak_rss_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find(|l| l.starts_with("VmHWM:"))?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+// Widths `Bucketing::fitting` picks from; each divides a day, so buckets of
+// any of them line up with midnight. Longer spans use whole days.
+const ROUND_WIDTHS: &[i64] = &[
+    1, 2, 5, 10, 15, 30, 60, 120, 300, 600, 900, 1800, 3600, 7200, 10800, 21600, 43200, 86400,
+];
+
+// `--tz`: the zone whose wall clock bucket boundaries follow. Log timestamps
+// are read as UTC (a format's own offset, like Apache's `%z`, is applied
+// while parsing) and shifted into the zone by the offset in force at each
+// one, so `--tz +05:30` starts hour buckets on the half hour UTC and days at
+// Indian midnight, and `--tz Europe/Berlin` or `local` follow daylight
+// saving through a log that spans the change. Without it timestamps are
+// bucketed as written.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zone(Option<ZoneRule>);
+
+#[derive(Debug, Clone, Copy)]
+enum ZoneRule {
+    Fixed(FixedOffset),
+    Named(Tz),
+    Local,
+}
+
+impl Zone {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let rule = match spec.to_lowercase().as_str() {
+            "utc" | "z" => FixedOffset::east_opt(0).map(ZoneRule::Fixed),
+            "local" => Some(ZoneRule::Local),
+            _ => parse_offset(spec).map(ZoneRule::Fixed).or_else(|| spec.parse().ok().map(ZoneRule::Named)),
+        };
+        rule.map(|r| Self(Some(r)))
+            .ok_or_else(|| format!("Invalid zone '{}' (expected e.g. +02:00, -0500, Europe/Berlin, utc or local)", spec))
+    }
+
+    // how timeline exports name the zone; None without --tz
+    pub fn label(&self) -> Option<String> {
+        self.0.map(|rule| match rule {
+            ZoneRule::Fixed(offset) if offset.local_minus_utc() == 0 => "UTC".to_string(),
+            ZoneRule::Fixed(offset) => offset.to_string(),
+            ZoneRule::Named(tz) => tz.name().to_string(),
+            ZoneRule::Local => "local".to_string(),
+        })
+    }
+
+    // `ts` on this zone's wall clock
+    pub fn local(&self, ts: NaiveDateTime) -> NaiveDateTime {
+        match self.0 {
+            Some(ZoneRule::Fixed(offset)) => ts + Duration::seconds(offset.local_minus_utc() as i64),
+            Some(ZoneRule::Named(tz)) => tz.from_utc_datetime(&ts).naive_local(),
+            Some(ZoneRule::Local) => Local.from_utc_datetime(&ts).naive_local(),
+            None => ts,
+        }
+    }
+}
+
+// `+02:00`, `-0500`, `+3`
+fn parse_offset(spec: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match spec.as_bytes().first()? {
+        b'+' => (1, &spec[1..]),
+        b'-' => (-1, &spec[1..]),
+        _ => return None,
+    };
+    let digits = rest.replace(':', "");
+    if !(1..=4).contains(&digits.len()) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = if digits.len() <= 2 { (digits.as_str(), "0") } else { digits.split_at(digits.len() - 2) };
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+// Fixed-width windows aligned to wall-clock boundaries in `zone`: a
+// 15-minute bucket starts at :00, :15, :30 or :45 and a day bucket at
+// midnight, whenever the first entry happens to fall. Bucket starts are
+// wall-clock times in the zone, ready to be shown as they are.
+#[derive(Debug, Clone, Copy)]
+pub struct Bucketing {
+    width: i64,
+    zone: Zone,
+}
+
+impl Bucketing {
+    pub fn new(width: Duration, zone: Zone) -> Self {
+        Self { width: width.num_seconds().max(1), zone }
+    }
+
+    // The narrowest round width that covers [first, last] in at most `max`
+    // buckets.
+    pub fn fitting(first: NaiveDateTime, last: NaiveDateTime, max: usize, zone: Zone) -> Self {
+        let max = max.max(1);
+        for &width in ROUND_WIDTHS {
+            let b = Self { width, zone };
+            if b.span(first, last).1 <= max {
+                return b;
+            }
+        }
+        let mut days = 2;
+        loop {
+            let b = Self { width: days * 86400, zone };
+            if b.span(first, last).1 <= max {
+                return b;
+            }
+            days += 1;
+        }
+    }
+
+    // `self`, unless covering [first, last] takes more than `max` buckets;
+    // then the narrowest round width that fits
+    pub fn at_most(self, first: NaiveDateTime, last: NaiveDateTime, max: usize) -> Self {
+        if self.span(first, last).1 <= max {
+            self
+        } else {
+            Self::fitting(first, last, max, self.zone)
+        }
+    }
+
+    pub fn width(&self) -> Duration {
+        Duration::seconds(self.width)
+    }
+
+    // start of the bucket holding `ts`
+    pub fn start(&self, ts: NaiveDateTime) -> NaiveDateTime {
+        let local = self.zone.local(ts);
+        let secs = local.and_utc().timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(self.width), 0)
+            .map(|d| d.naive_utc())
+            .unwrap_or(local)
+    }
+
+    // (start of the first bucket, number of buckets) from `first` to `last`
+    pub fn span(&self, first: NaiveDateTime, last: NaiveDateTime) -> (NaiveDateTime, usize) {
+        let origin = self.start(first);
+        (origin, self.index(origin, last) + 1)
+    }
+
+    // bucket of `ts` counted from the bucket starting at `origin`
+    pub fn index(&self, origin: NaiveDateTime, ts: NaiveDateTime) -> usize {
+        ((self.zone.local(ts) - origin).num_seconds().max(0) / self.width) as usize
+    }
+}
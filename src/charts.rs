@@ -0,0 +1,252 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::buckets::{Bucketing, Zone};
+use crate::parser::{LogEntry, LogLevel};
+
+const SIZE: (u32, u32) = (960, 480);
+const TIMELINE_BUCKETS: usize = 60;
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+type ChartResult = Result<(), Box<dyn Error>>;
+
+// Writes heatmap, level and error-rate SVG charts into `dir`; returns the
+// files. Days, hours and time buckets are on `zone`'s wall clock. SVG leaves
+// the text to the viewer, so no font has to be bundled.
+pub fn render(dir: &Path, entries: &[LogEntry], zone: Zone) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+
+    let grid = weekday_hour_grid(entries, zone);
+    let levels = level_counts(entries);
+    let timeline = error_timeline(entries, zone);
+
+    let mut written = Vec::new();
+    for chart in [Chart::Heatmap(&grid), Chart::Levels(&levels), Chart::ErrorRate(&timeline)] {
+        let path = dir.join(format!("{}.svg", chart.name()));
+        {
+            let root = SVGBackend::new(&path, SIZE).into_drawing_area();
+            root.fill(&WHITE)?;
+            chart.draw(&root)?;
+            root.present()?;
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+enum Chart<'a> {
+    Heatmap(&'a [[usize; 24]; 7]),
+    Levels(&'a [(LogLevel, usize)]),
+    ErrorRate(&'a [(NaiveDateTime, f64)]),
+}
+
+impl Chart<'_> {
+    fn name(&self) -> &str {
+        match self {
+            Self::Heatmap(_) => "heatmap",
+            Self::Levels(_) => "levels",
+            Self::ErrorRate(_) => "error_rate",
+        }
+    }
+
+    fn draw<DB: DrawingBackend>(&self, root: &DrawingArea<DB, Shift>) -> ChartResult
+    where
+        DB::ErrorType: 'static,
+    {
+        match self {
+            Self::Heatmap(grid) => draw_heatmap(root, grid),
+            Self::Levels(levels) => draw_levels(root, levels),
+            Self::ErrorRate(timeline) => draw_error_rate(root, timeline),
+        }
+    }
+}
+
+fn weekday_hour_grid(entries: &[LogEntry], zone: Zone) -> [[usize; 24]; 7] {
+    let mut grid = [[0; 24]; 7];
+    for e in entries {
+        let t = zone.local(e.timestamp);
+        grid[t.weekday().num_days_from_monday() as usize][t.hour() as usize] += 1;
+    }
+    grid
+}
+
+fn level_counts(entries: &[LogEntry]) -> Vec<(LogLevel, usize)> {
+    let order = [LogLevel::Fatal, LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Unknown];
+    order
+        .into_iter()
+        .map(|level| {
+            let n = entries.iter().filter(|e| e.level == level).count();
+            (level, n)
+        })
+        .filter(|(_, n)| *n > 0)
+        .collect()
+}
+
+// (bucket start, error rate in percent) over the file's span, in at most
+// TIMELINE_BUCKETS round wall-clock buckets
+fn error_timeline(entries: &[LogEntry], zone: Zone) -> Vec<(NaiveDateTime, f64)> {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Vec::new();
+    };
+    let buckets = Bucketing::fitting(first.timestamp, last.timestamp, TIMELINE_BUCKETS, zone);
+    let (origin, count) = buckets.span(first.timestamp, last.timestamp);
+
+    let mut totals = vec![0usize; count];
+    let mut errors = vec![0usize; count];
+    for e in entries {
+        let b = buckets.index(origin, e.timestamp).min(count - 1);
+        totals[b] += 1;
+        if e.level.severity() >= 3 {
+            errors[b] += 1;
+        }
+    }
+
+    (0..count)
+        .map(|b| {
+            let at = origin + buckets.width() * b as i32;
+            let rate = if totals[b] > 0 { errors[b] as f64 / totals[b] as f64 * 100.0 } else { 0.0 };
+            (at, rate)
+        })
+        .collect()
+}
+
+fn level_color(level: &LogLevel) -> RGBColor {
+    match level {
+        LogLevel::Fatal => RGBColor(142, 36, 170),
+        LogLevel::Error => RGBColor(211, 47, 47),
+        LogLevel::Warn => RGBColor(245, 166, 35),
+        LogLevel::Info => RGBColor(56, 142, 60),
+        LogLevel::Debug => RGBColor(120, 144, 156),
+        LogLevel::Unknown => RGBColor(189, 189, 189),
+    }
+}
+
+fn draw_heatmap<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, grid: &[[usize; 24]; 7]) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    let max = grid.iter().flatten().copied().max().unwrap_or(0).max(1) as f64;
+
+    let mut chart = ChartBuilder::on(root)
+        .margin(16)
+        .caption(format!("Activity by weekday and hour (darkest: {} entries)", max as usize), ("sans-serif", 22))
+        .x_label_area_size(40)
+        .y_label_area_size(48)
+        .build_cartesian_2d((0u32..24u32).into_segmented(), (0u32..7u32).into_segmented())?;
+
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_labels(24)
+        .y_labels(7)
+        .x_desc("Hour of day")
+        .x_label_formatter(&|h| match h {
+            SegmentValue::CenterOf(h) => format!("{:02}", h),
+            _ => String::new(),
+        })
+        .y_label_formatter(&|row| match row {
+            // Monday on top
+            SegmentValue::CenterOf(row) if *row < 7 => WEEKDAYS[6 - *row as usize].to_string(),
+            _ => String::new(),
+        })
+        .draw()?;
+
+    chart.draw_series((0..7u32).flat_map(|day| {
+        (0..24u32).map(move |hour| {
+            let v = grid[day as usize][hour as usize] as f64 / max;
+            let shade = (255.0 * (1.0 - v)) as u8;
+            let row = 6 - day;
+            Rectangle::new(
+                [
+                    (SegmentValue::Exact(hour), SegmentValue::Exact(row)),
+                    (SegmentValue::Exact(hour + 1), SegmentValue::Exact(row + 1)),
+                ],
+                RGBColor(255, shade, shade).filled(),
+            )
+        })
+    }))?;
+
+    Ok(())
+}
+
+fn draw_levels<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, levels: &[(LogLevel, usize)]) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    let max = levels.iter().map(|(_, n)| *n).max().unwrap_or(0).max(1);
+    let n = levels.len().max(1);
+
+    let mut chart = ChartBuilder::on(root)
+        .margin(16)
+        .caption("Log level distribution", ("sans-serif", 22))
+        .x_label_area_size(40)
+        .y_label_area_size(64)
+        .build_cartesian_2d((0..n).into_segmented(), 0..max + max / 10)?;
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(n)
+        .x_desc("Level")
+        .y_desc("Entries")
+        .x_label_formatter(&|i| match i {
+            SegmentValue::CenterOf(i) => levels.get(*i).map_or(String::new(), |(l, _)| l.as_str().to_string()),
+            _ => String::new(),
+        })
+        .draw()?;
+
+    chart.draw_series(levels.iter().enumerate().map(|(i, (level, count))| {
+        let mut bar = Rectangle::new(
+            [(SegmentValue::Exact(i), 0), (SegmentValue::Exact(i + 1), *count)],
+            level_color(level).filled(),
+        );
+        bar.set_margin(0, 0, 12, 12);
+        bar
+    }))?;
+
+    Ok(())
+}
+
+fn draw_error_rate<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    timeline: &[(NaiveDateTime, f64)],
+) -> ChartResult
+where
+    DB::ErrorType: 'static,
+{
+    let mut chart = ChartBuilder::on(root)
+        .margin(16)
+        .caption("Error rate over time", ("sans-serif", 22))
+        .x_label_area_size(40)
+        .y_label_area_size(56)
+        .build_cartesian_2d(0..timeline.len().max(1), 0f64..100f64)?;
+
+    let multi_day = match (timeline.first(), timeline.last()) {
+        (Some(a), Some(b)) => a.0.date() != b.0.date(),
+        _ => false,
+    };
+    chart
+        .configure_mesh()
+        .x_labels(6)
+        .x_desc("Bucket start")
+        .y_desc("Errors (% of entries)")
+        .x_label_formatter(&|i| {
+            timeline.get(*i).map_or(String::new(), |(t, _)| {
+                if multi_day { t.format("%m-%d %H:%M").to_string() } else { t.format("%H:%M").to_string() }
+            })
+        })
+        .draw()?;
+
+    let red = level_color(&LogLevel::Error);
+    chart.draw_series(AreaSeries::new(
+        timeline.iter().enumerate().map(|(i, (_, rate))| (i, *rate)),
+        0.0,
+        red.mix(0.2),
+    ).border_style(red.stroke_width(2)))?;
+
+    Ok(())
+}
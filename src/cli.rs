@@ -1,49 +1,921 @@
-use chrono::NaiveDateTime;
-use clap::Parser;
+use chrono::{Duration, NaiveDateTime};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use glob::Pattern;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::buckets::Zone;
+use crate::endpoints::{PathRule, RankWeights};
+use crate::export::{Partition, SampleRates};
+use crate::alerts::AlertRule;
+use crate::filter::{KeywordMode, SourceMatcher, TextPattern, TimeBound};
+use crate::find;
+use crate::generate::{parse_fraction, parse_span, GenFormat, Rate};
+use crate::journal;
+use crate::metrics::{MetricSpec, Threshold};
+use crate::parser::{CustomPattern, LatencyUnit, LevelAlias, LevelRule};
+use crate::schema::{FieldCondition, Schema};
+use crate::xml::XmlField;
+use crate::profile::Profile;
+use crate::ratelimit::{LimitBy, RateLimit};
+use crate::replay::{ReplayTarget, Speed};
+use crate::retention::RetentionPolicy;
+use crate::scoring::{Combine, ScorerSpec};
+use crate::sources::ErrorBudget;
 
 #[derive(Parser)]
 #[command(name = "logscope")]
 #[command(version = "0.2.0")]
 #[command(about = "Parse and analyze log files with detailed statistics")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
-    #[arg(help = "Path to the log file")]
-    pub file_path: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[arg(required_unless_present_any = ["listen_unix", "journal"], num_args = 1.., help = "Path(s) to the log file(s)")]
+    pub file_paths: Vec<String>,
+
+    #[arg(long, value_name = "PATH|URL", help = "Load shared settings from a TOML file or http(s) URL")]
+    pub config: Option<String>,
+
+    #[arg(long, value_enum, help = "Preset metrics, thresholds and report sections for a kind of log")]
+    pub profile: Option<Profile>,
+
+    #[arg(
+        short,
+        long,
+        value_name = "TEXT",
+        value_parser = TextPattern::parse,
+        help = "Keep entries whose message contains TEXT, case-insensitively, or matches 're:REGEX' (repeatable)"
+    )]
+    pub keyword: Vec<TextPattern>,
 
-    #[arg(short, long, help = "Filter by keyword (supports regex)")]
-    pub keyword: Option<String>,
+    #[arg(long, value_enum, default_value = "all", help = "With several --keyword, require all of them or any one")]
+    pub keyword_mode: KeywordMode,
 
-    #[arg(long, value_parser = parse_datetime, help = "Start time (YYYY-MM-DD HH:MM:SS)")]
-    pub from: Option<NaiveDateTime>,
+    #[arg(
+        long,
+        value_name = "TEXT",
+        value_parser = TextPattern::parse,
+        help = "Drop entries whose message contains TEXT or matches 're:REGEX' (repeatable)"
+    )]
+    pub exclude: Vec<TextPattern>,
 
-    #[arg(long, value_parser = parse_datetime, help = "End time (YYYY-MM-DD HH:MM:SS)")]
-    pub to: Option<NaiveDateTime>,
+    #[arg(
+        long,
+        visible_aliases = ["since", "last"],
+        value_name = "TIME",
+        value_parser = TimeBound::parse,
+        help = "Start time: YYYY-MM-DD HH:MM:SS, or 45m, 2h, today, yesterday relative to the newest entry (the clock with --follow)"
+    )]
+    pub from: Option<TimeBound>,
+
+    #[arg(long, value_name = "TIME", value_parser = TimeBound::parse, help = "End time, in the same forms as --from")]
+    pub to: Option<TimeBound>,
 
     #[arg(long, help = "Minimum log level (debug/info/warn/error/fatal)")]
     pub level: Option<String>,
 
-    #[arg(long, help = "Filter by source/logger name")]
+    #[arg(long, value_name = "TEXT", help = "Keep only sources whose name contains TEXT")]
     pub source: Option<String>,
 
-    #[arg(long, default_value = "10", help = "Number of top keywords to show")]
-    pub top: usize,
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        value_parser = SourceMatcher::parse,
+        help = "Keep only these sources: exact name, glob ('api-*') or 're:REGEX' (repeatable)"
+    )]
+    pub source_include: Vec<SourceMatcher>,
+
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        value_parser = SourceMatcher::parse,
+        help = "Drop these sources: exact name, glob ('health*') or 're:REGEX' (repeatable)"
+    )]
+    pub source_exclude: Vec<SourceMatcher>,
+
+    #[arg(long, help = "Number of top keywords to show (default: 4 to 25, depending on how varied the log is)")]
+    pub top: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "SPAN",
+        value_parser = parse_bucket,
+        help = "Timeline interval, e.g. 30s, 5m, 1h (default: sized to the log's span; widened past 10000 buckets)"
+    )]
+    pub bucket: Option<Duration>,
+
+    #[arg(
+        long,
+        value_name = "SPAN",
+        default_value = "60s",
+        value_parser = parse_burst_window,
+        help = "Window an error burst must fit in, e.g. 10s, 5m"
+    )]
+    pub burst_window: Duration,
 
-    #[arg(long, help = "Force log format (bracket/json/apache/syslog)")]
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "3",
+        value_parser = parse_burst_threshold,
+        help = "Errors within --burst-window that make a burst"
+    )]
+    pub burst_threshold: usize,
+
+    #[arg(long, value_name = "N", default_value = "5", help = "Show the last N warnings and errors at the end of the report (0 hides)")]
+    pub tail: usize,
+
+    #[arg(long, default_value = "10", help = "Number of message templates to show (0 skips clustering)")]
+    pub top_templates: usize,
+
+    #[arg(long, default_value = "3", help = "Ignore keyword tokens shorter than this many bytes")]
+    pub keyword_min_len: usize,
+
+    #[arg(long, help = "Ignore keyword tokens longer than this many bytes")]
+    pub keyword_max_len: Option<usize>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Drop ID-like tokens from keywords (comma-separated)"
+    )]
+    pub keyword_drop: Vec<TokenClass>,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
     pub format: Option<String>,
 
-    #[arg(long, help = "Export results: json or csv")]
+    #[arg(
+        long,
+        value_name = "'REGEX as NAME'",
+        value_parser = MetricSpec::parse,
+        help = "Extract a numeric metric from messages, e.g. 'took (\\d+)ms as latency', or read one from a field with 'field:NAME' (repeatable)"
+    )]
+    pub metric: Vec<MetricSpec>,
+
+    #[arg(
+        long,
+        value_name = "NAME>VALUE",
+        value_parser = Threshold::parse,
+        help = "Report when a --metric crosses a threshold, e.g. 'latency>2000' (repeatable)"
+    )]
+    pub threshold: Vec<Threshold>,
+
+    #[arg(
+        long,
+        value_name = "SOURCE=N",
+        value_parser = ErrorBudget::parse,
+        help = "Errors per hour a source may log, e.g. 'api=10' or '*=5' (repeatable)"
+    )]
+    pub error_budget: Vec<ErrorBudget>,
+
+    #[arg(
+        long,
+        value_name = "FIELD",
+        help = "Break entries and error rates down by a JSON field, e.g. tenant or region (repeatable)"
+    )]
+    pub group_by: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        value_parser = Regex::new,
+        help = "How version strings look in messages; the first capture group is the version"
+    )]
+    pub version_pattern: Option<Regex>,
+
+    #[arg(
+        long,
+        value_name = "REGEX=REPLACEMENT",
+        value_parser = PathRule::parse,
+        help = "Rewrite request paths before grouping endpoints, e.g. '^/static/.*=/static/*' (repeatable)"
+    )]
+    pub path_rule: Vec<PathRule>,
+
+    #[arg(long, default_value = "10", help = "Number of endpoints to show for access logs")]
+    pub top_endpoints: usize,
+
+    #[arg(
+        long,
+        value_name = "KEY=WEIGHT,...",
+        value_parser = RankWeights::parse,
+        default_value = "errors=0.5,latency=0.3,volume=0.2",
+        help = "Weights of 5xx rate, p95 latency and traffic in the worst-endpoint ranking"
+    )]
+    pub endpoint_weights: RankWeights,
+
+    #[arg(
+        long,
+        value_name = "REQUESTS/PERIOD",
+        value_parser = RateLimit::parse,
+        help = "What-if for access logs: count the requests a limit like 100/min or 20/10s would have rejected"
+    )]
+    pub rate_limit: Option<RateLimit>,
+
+    #[arg(long, value_enum, default_value = "client", requires = "rate_limit", help = "What --rate-limit counts requests per")]
+    pub rate_limit_by: LimitBy,
+
+    #[arg(
+        long,
+        value_name = "PRICE",
+        value_parser = parse_price,
+        help = "Estimate the monthly cost of ingesting this volume at PRICE per GB, by level and source, and what dropping DEBUG or repeats would save"
+    )]
+    pub cost_per_gb: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "POLICY",
+        value_parser = RetentionPolicy::parse,
+        help = "Project what a retention policy like 'error=90d,info=7d,debug=7d@1%' would store, per source (repeatable to compare)"
+    )]
+    pub retention: Vec<RetentionPolicy>,
+
+    #[arg(
+        long,
+        value_name = "SCORER[*WEIGHT]",
+        value_parser = ScorerSpec::parse,
+        help = "Anomaly scorer: heuristic, error-rate, iforest (ml builds) or a rule like 'error_rate>5' (repeatable)"
+    )]
+    pub scorer: Vec<ScorerSpec>,
+
+    #[arg(long, value_enum, default_value = "mean", help = "How several --scorer results combine")]
+    pub score_combine: Combine,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        value_parser = CustomPattern::parse,
+        help = "Parse lines with this regex instead of the built-in formats; named groups timestamp, message, level, source"
+    )]
+    pub pattern: Option<CustomPattern>,
+
+    #[arg(long, value_name = "FORMAT", requires = "pattern", help = "chrono format of the --pattern timestamp, e.g. '%d.%m.%Y %H:%M:%S'")]
+    pub timestamp_format: Option<String>,
+
+    #[arg(long, value_name = "BYTES", help = "Drop lines longer than this many bytes (default 1048576)")]
+    pub max_line_len: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Cut messages longer than this many bytes (marked with …) before analysis and export"
+    )]
+    pub max_message_len: Option<usize>,
+
+    #[arg(long, value_name = "RATIO", help = "Fail if more than this fraction (0-1) of lines is unparsed")]
+    pub max_unparsed_ratio: Option<f64>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["follow", "show_entries"],
+        help = "Exit with status 2 after the report if any entry is ERROR or FATAL"
+    )]
+    pub fail_on_errors: bool,
+
+    #[arg(
+        long,
+        value_name = "SCORE",
+        conflicts_with_all = ["follow", "show_entries"],
+        help = "Exit with status 2 after the report if the anomaly score is above SCORE"
+    )]
+    pub fail_above: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "PCT",
+        conflicts_with_all = ["follow", "show_entries"],
+        help = "Exit with status 2 after the report if more than PCT% of entries are ERROR or FATAL"
+    )]
+    pub max_error_rate: Option<f64>,
+
+    #[arg(long, help = "Keep identical entries that appear in more than one input file")]
+    pub keep_duplicates: bool,
+
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser = parse_glob,
+        help = "Only parse .zip/.tar/.tar.gz members matching this glob, e.g. '*/app*.log'"
+    )]
+    pub archive_member: Option<Pattern>,
+
+    #[arg(long, help = "Append unparsed continuation lines (stack traces) to the entry before them")]
+    pub multiline: bool,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        value_parser = Regex::new,
+        help = "Also treat lines matching this as continuations; implies --multiline"
+    )]
+    pub multiline_pattern: Option<Regex>,
+
+    #[arg(long, help = "Leave ANSI escape sequences (colors) in lines instead of stripping them before parsing")]
+    pub keep_ansi: bool,
+
+    #[arg(long, help = "Re-join lines torn apart by concurrent writers before parsing")]
+    pub repair_torn_lines: bool,
+
+    #[arg(
+        long,
+        value_name = "NAME=LEVEL",
+        value_parser = LevelAlias::parse,
+        help = "Treat a custom level name as a known level, e.g. 'SEVERE=error' (repeatable)"
+    )]
+    pub level_map: Vec<LevelAlias>,
+
+    #[arg(
+        long,
+        value_name = "REGEX=LEVEL",
+        value_parser = LevelRule::parse,
+        help = "Level for messages matching REGEX where the format has no severity, checked before the built-in guess (repeatable)"
+    )]
+    pub level_rule: Vec<LevelRule>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Element name of one record in XML logs [default: event]"
+    )]
+    pub xml_record: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "NAME=PATH",
+        value_parser = XmlField::parse,
+        help = "Take a field from each XML record, e.g. 'user=EventData/Data[@Name=\"TargetUserName\"]' or 'level=@priority' (repeatable)"
+    )]
+    pub xml_field: Vec<XmlField>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        value_parser = Schema::load,
+        help = "Field types (TOML, or JSON Schema for .json) so --where and field metrics compare numbers, durations, timestamps and enums as such"
+    )]
+    pub schema: Option<Schema>,
+
+    #[arg(
+        long = "where",
+        value_name = "FIELD OP VALUE",
+        value_parser = FieldCondition::parse,
+        help = "Keep entries whose field compares true, e.g. 'status>=500' or 'latency>1.5s' (=, !=, >, >=, <, <=; repeatable)"
+    )]
+    pub conditions: Vec<FieldCondition>,
+
+    #[arg(
+        long,
+        value_name = "MS",
+        help = "Log access-log requests slower than this as WARN even when the status is OK"
+    )]
+    pub slow_request: Option<f64>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "UNIT",
+        help = "Unit of a bare response time ending access-log lines (Apache %D is us); without it only decimals count, as seconds"
+    )]
+    pub latency_unit: Option<LatencyUnit>,
+
+    #[arg(
+        long,
+        value_name = "REGEX",
+        value_parser = Regex::new,
+        help = "Mask matches in messages before analysis and export (repeatable)"
+    )]
+    pub redact: Vec<Regex>,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "stream_json", "report_path", "email", "output_format", "chart_out", "focus_errors", "model", "save_model"
+        ],
+        help = "Keep reading the files as they grow and redraw a live dashboard"
+    )]
+    pub follow: bool,
+
+    #[arg(long, value_name = "SECS", default_value = "2", requires = "follow", help = "How often --follow checks for new lines")]
+    pub interval: f64,
+
+    #[arg(
+        long,
+        value_name = "SPAN",
+        default_value = "15m",
+        value_parser = parse_score_window,
+        requires = "follow",
+        help = "With --follow, also score the newest SPAN of entries on its own"
+    )]
+    pub score_window: Duration,
+
+    #[arg(
+        long,
+        value_name = "RULE",
+        value_parser = AlertRule::parse,
+        requires = "follow",
+        help = "With --follow, alert while the score window is past a limit, e.g. 'error_rate>5' or 'bursts>0@10m' (anomaly, error_rate, errors, bursts, fatal, entries; repeatable)"
+    )]
+    pub alert: Vec<AlertRule>,
+
+    #[arg(
+        long,
+        value_name = "SPAN",
+        default_value = "5m",
+        value_parser = parse_span,
+        requires = "follow",
+        help = "Least time between two messages about the same --alert rule (a rule's own @SPAN wins)"
+    )]
+    pub alert_cooldown: Duration,
+
+    #[arg(
+        long,
+        value_name = "SCORE",
+        value_parser = parse_alert_threshold,
+        requires = "alert_webhook",
+        help = "Alert --alert-webhook when the anomaly score reaches SCORE (0-100); with --follow, the score window's"
+    )]
+    pub alert_threshold: Option<f64>,
+
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "POST a JSON alert (score, bursts, top error templates; Slack-compatible) here for --alert-threshold and --alert rules"
+    )]
+    pub alert_webhook: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "follow",
+        help = "With --follow, also read lines sent to a unix datagram socket created at PATH"
+    )]
+    pub listen_unix: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "evidence_mode",
+        help = "Read systemd-journald through journalctl, alongside any files; --from/--to narrow what is read"
+    )]
+    pub journal: bool,
+
+    #[arg(long, value_name = "UNIT", requires = "journal", help = "With --journal, only this systemd unit's entries (repeatable)")]
+    pub unit: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "PRIORITY",
+        value_parser = journal::parse_priority,
+        requires = "journal",
+        help = "With --journal, only entries this severe or worse: 0-7, a name like warning, or FROM..TO"
+    )]
+    pub priority: Option<String>,
+
+    #[arg(long, value_name = "DIR", requires = "journal", help = "With --journal, read the journal files in DIR instead of this host's")]
+    pub journal_dir: Option<String>,
+
+    #[arg(long, value_name = "PATH", help = "Judge rare templates and unusual volume against a model saved by --save-model")]
+    pub model: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Save this run's templates and volume as a baseline model (merged with --model when given)"
+    )]
+    pub save_model: Option<String>,
+
+    #[arg(long, help = "Emit progress and results as newline-delimited JSON events on stdout")]
+    pub stream_json: bool,
+
+    #[arg(long, help = "Write the report to this file (uncolored) instead of stdout")]
+    pub report_path: Option<String>,
+
+    #[arg(long, value_name = "ADDRESS", help = "Email the report to this address (repeatable)")]
+    pub email: Vec<String>,
+
+    #[arg(long, default_value = "logscope@localhost", help = "Sender address for --email")]
+    pub email_from: String,
+
+    #[arg(long, value_name = "HOST:PORT", help = "Send --email through this SMTP relay instead of sendmail")]
+    pub smtp: Option<String>,
+
+    #[arg(long, default_value = "/usr/sbin/sendmail", help = "sendmail binary used for --email")]
+    pub sendmail_path: String,
+
+    #[arg(
+        long,
+        help = "Export results: json, csv, jsonl, sqlite, parquet, html (a self-contained dashboard), l2tcsv (log2timeline columns), prometheus (OpenMetrics; pushed when --output is a Pushgateway URL) or labeling (a sample to label, as csv)"
+    )]
     pub output_format: Option<String>,
 
-    #[arg(long, help = "Output file path for export")]
+    #[arg(long, help = "Output file path for export (a directory with --output-partition)")]
     pub output: Option<String>,
 
-    #[arg(long, help = "Disable colored output")]
+    #[arg(long, value_enum, help = "Split exported entries into one file per hour, day or level")]
+    pub output_partition: Option<Partition>,
+
+    #[arg(long, help = "Append exported entries to existing files instead of overwriting")]
+    pub output_append: bool,
+
+    #[arg(long, help = "Write a SHA-256 manifest of the exported files")]
+    pub manifest: bool,
+
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        help = "Sign the manifest with this command, e.g. 'minisign -Sm {}' (implies --manifest)"
+    )]
+    pub sign_cmd: Option<String>,
+
+    #[arg(
+        long,
+        conflicts_with = "follow",
+        help = "Hash the inputs and embed hashes, sizes, times and the command line in every export; keeps duplicates and refuses options that alter entries"
+    )]
+    pub evidence_mode: bool,
+
+    #[arg(
+        long,
+        requires = "evidence_mode",
+        help = "With --evidence-mode, allow options that alter or select entries, such as --redact, --multiline or filters (recorded in the export)"
+    )]
+    pub allow_mutation: bool,
+
+    #[arg(
+        long,
+        value_name = "LEVEL=RATE,...",
+        value_parser = SampleRates::parse,
+        help = "Downsample exported entries per level, e.g. 'debug=0.01,info=0.1'"
+    )]
+    pub export_sample: Option<SampleRates>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value = "200",
+        help = "With --output-format labeling, how many entries to pick, spread over levels and templates"
+    )]
+    pub label_sample: usize,
+
+    #[arg(long, value_name = "SEED", default_value = "1", help = "With --output-format labeling, the seed; the same seed picks the same entries")]
+    pub label_seed: u64,
+
+    #[arg(long, global = true, help = "Disable colored output")]
     pub no_color: bool,
 
     #[arg(long, help = "Show hourly activity heatmap")]
     pub heatmap: bool,
+
+    #[arg(
+        long,
+        value_name = "ZONE",
+        allow_hyphen_values = true,
+        value_parser = Zone::parse,
+        help = "Read timestamps as UTC and align hours, days and windows to this zone, e.g. +02:00, Europe/Berlin, local"
+    )]
+    pub tz: Option<Zone>,
+
+    #[arg(long, value_name = "DIR", help = "Write heatmap, level and error-rate SVG charts into this directory")]
+    pub chart_out: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "PCT",
+        num_args = 0..=1,
+        default_missing_value = "95",
+        help = "Also analyze the smallest window containing PCT% of errors (default 95)"
+    )]
+    pub focus_errors: Option<f64>,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["follow", "journal"],
+        help = "Print the entries the filters keep, grep-style with file and line, instead of the report"
+    )]
+    pub show_entries: bool,
+
+    #[arg(
+        short = 'C',
+        long,
+        value_name = "N",
+        default_value = "0",
+        requires = "show_entries",
+        help = "With --show-entries, also print N lines of the file before and after each entry"
+    )]
+    pub context: usize,
+
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = ArgAction::Count,
+        help = "Diagnostic verbosity (-v info, -vv debug, -vvv trace)"
+    )]
+    pub verbose: u8,
+
+    #[arg(long, global = true, help = "Write diagnostics to a file instead of stderr")]
+    pub log_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenClass {
+    Numbers,
+    Hex,
+    Uuids,
+    // all of the above
+    Ids,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(about = "Measure parse throughput, per-stage timings and peak memory")]
+    Bench(BenchArgs),
+
+    #[command(about = "Compare message templates between a baseline and a current log")]
+    Diff(DiffArgs),
+
+    #[command(about = "Parse once, then explore interactively with filters and queries")]
+    Repl(ReplArgs),
+
+    #[command(about = "Write a Markdown timeline of every entry for one trace, request or user ID")]
+    Trace(TraceArgs),
+
+    #[command(about = "Find the entries with a content hash (as exports give it) in other logs")]
+    FindHash(Box<FindHashArgs>),
+
+    #[command(about = "Re-emit a log's lines with their original timing, sped up or slowed down")]
+    Replay(ReplayArgs),
+
+    #[command(about = "Write realistic synthetic logs for testing pipelines and benchmarking")]
+    Generate(GenerateArgs),
+
+    #[command(about = "Record a run's daily volume and errors in a local store and show week-over-week trends")]
+    Trend(TrendArgs),
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[arg(help = "Path to the log file")]
+    pub file_path: String,
+
+    #[arg(long, default_value = "3", help = "Number of measured runs")]
+    pub runs: usize,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+
+    #[arg(short, long, value_parser = TextPattern::parse, help = "Keyword filter applied during the filter stage")]
+    pub keyword: Option<TextPattern>,
+
+    #[arg(long, default_value = "10", help = "Number of top keywords to compute")]
+    pub top: usize,
+}
+
+#[derive(Args)]
+pub struct TrendArgs {
+    #[arg(help = "Log file(s) to record; without any, only show the trend")]
+    pub file_paths: Vec<String>,
+
+    #[arg(long, value_name = "PATH", help = "Trend store to update (created on first use)")]
+    pub state: String,
+
+    #[arg(long, default_value = "8", help = "Most recent weeks to show")]
+    pub weeks: usize,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReplArgs {
+    #[arg(required = true, num_args = 1.., help = "Path(s) to the log file(s)")]
+    pub file_paths: Vec<String>,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TraceArgs {
+    #[arg(required = true, num_args = 1.., help = "Path(s) to the log file(s)")]
+    pub file_paths: Vec<String>,
+
+    #[arg(long, visible_alias = "user", value_name = "ID", help = "Trace, request or user ID to follow")]
+    pub trace_id: String,
+
+    #[arg(short, long, help = "Write the Markdown here instead of stdout")]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+
+    #[arg(long, value_name = "SECS", default_value = "5", help = "Call out quiet gaps of at least this long (0 disables)")]
+    pub gap: u64,
+}
+
+#[derive(Args)]
+pub struct FindHashArgs {
+    #[arg(value_parser = find::parse_hash, help = "Entry hash from an export, or its first 6+ characters")]
+    pub hash: String,
+
+    #[arg(required = true, num_args = 1.., help = "Path(s) to the log file(s) to search")]
+    pub file_paths: Vec<String>,
+
+    // what shapes an entry's level, source and message has to match the run
+    // that exported the hash
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+
+    #[arg(long, value_name = "REGEX", value_parser = CustomPattern::parse, help = "Parse lines with this regex")]
+    pub pattern: Option<CustomPattern>,
+
+    #[arg(long, value_name = "FORMAT", requires = "pattern", help = "chrono format of the --pattern timestamp")]
+    pub timestamp_format: Option<String>,
+
+    #[arg(long, default_value = "1048576", help = "Drop lines longer than this many bytes")]
+    pub max_line_len: usize,
+
+    #[arg(long, value_name = "GLOB", value_parser = parse_glob, help = "Only search archive members matching this glob")]
+    pub archive_member: Option<Pattern>,
+
+    #[arg(long, help = "Append unparsed continuation lines (stack traces) to the entry before them")]
+    pub multiline: bool,
+
+    #[arg(long, value_name = "REGEX", value_parser = Regex::new, help = "Also treat lines matching this as continuations")]
+    pub multiline_pattern: Option<Regex>,
+
+    #[arg(long, help = "Leave ANSI escape sequences (colors) in lines instead of stripping them before parsing")]
+    pub keep_ansi: bool,
+
+    #[arg(long, help = "Re-join lines torn apart by concurrent writers before parsing")]
+    pub repair_torn_lines: bool,
+
+    #[arg(long, value_name = "NAME=LEVEL", value_parser = LevelAlias::parse, help = "Treat a custom level name as a level")]
+    pub level_map: Vec<LevelAlias>,
+
+    #[arg(long, value_name = "REGEX=LEVEL", value_parser = LevelRule::parse, help = "Level for messages matching REGEX")]
+    pub level_rule: Vec<LevelRule>,
+
+    #[arg(long, value_name = "NAME", help = "Element name of one record in XML logs [default: event]")]
+    pub xml_record: Option<String>,
+
+    #[arg(long, value_name = "NAME=PATH", value_parser = XmlField::parse, help = "Take a field from each XML record")]
+    pub xml_field: Vec<XmlField>,
+
+    #[arg(long, value_name = "MS", help = "Log access-log requests slower than this as WARN")]
+    pub slow_request: Option<f64>,
+
+    #[arg(long, value_enum, value_name = "UNIT", help = "Unit of a bare response time ending access-log lines")]
+    pub latency_unit: Option<LatencyUnit>,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    #[arg(help = "Path to the log file")]
+    pub file_path: String,
+
+    #[arg(
+        long,
+        value_parser = Speed::parse,
+        default_value = "1x",
+        help = "Replay speed relative to the original timing, e.g. 10x or 0.5x; max sends without delays"
+    )]
+    pub speed: Speed,
+
+    #[arg(
+        long,
+        value_name = "TARGET",
+        value_parser = ReplayTarget::parse,
+        default_value = "stdout",
+        help = "Where to send lines: stdout or udp://HOST:PORT"
+    )]
+    pub to: ReplayTarget,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+
+    #[arg(long, help = "Replay continuation lines (stack traces) with the entry before them")]
+    pub multiline: bool,
+
+    #[arg(long, value_name = "REGEX", value_parser = Regex::new, help = "Also treat lines matching this as continuations")]
+    pub multiline_pattern: Option<Regex>,
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    #[arg(long, value_enum, default_value = "bracket", help = "Line format to write")]
+    pub format: GenFormat,
+
+    #[arg(long, value_parser = Rate::parse, default_value = "100/s", help = "Average entries per second, minute or hour, e.g. 100/s or 500/m")]
+    pub rate: Rate,
+
+    #[arg(long, value_name = "PCT", value_parser = parse_fraction, default_value = "2%", help = "Share of error entries outside bursts")]
+    pub error_rate: f64,
+
+    #[arg(long, value_parser = parse_span, default_value = "1h", help = "Time span covered, e.g. 90s, 30m, 1h, 2d")]
+    pub duration: chrono::Duration,
+
+    #[arg(
+        long,
+        value_parser = parse_datetime,
+        default_value = "2026-01-01 00:00:00",
+        help = "Timestamp of the first entry (YYYY-MM-DD HH:MM:SS)"
+    )]
+    pub start: NaiveDateTime,
+
+    #[arg(long, default_value = "4", help = "Number of distinct sources (at most 8)")]
+    pub sources: usize,
+
+    #[arg(long, default_value = "1", help = "Random seed; the same seed writes the same log")]
+    pub seed: u64,
+
+    #[arg(short, long, help = "Write here instead of stdout")]
+    pub output: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    #[arg(help = "Baseline log file")]
+    pub baseline: String,
+
+    #[arg(help = "Current log file")]
+    pub current: String,
+
+    #[arg(long, help = "Force log format (bracket/json/apache/syslog/macos/wevtutil/xml/journal/docker/cri)")]
+    pub format: Option<String>,
+
+    #[arg(long, default_value = "10", help = "Templates to show per section")]
+    pub top: usize,
+
+    #[arg(long, default_value = "2.0", help = "Share change (either direction) that counts as changed")]
+    pub min_ratio: f64,
+
+    #[arg(long, help = "Show a compact +/- view colored by severity instead of tables")]
+    pub unified: bool,
 }
 
 fn parse_datetime(s: &str) -> Result<NaiveDateTime, String> {
     NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
         .map_err(|e| format!("Invalid datetime: {}", e))
 }
+
+fn parse_bucket(s: &str) -> Result<Duration, String> {
+    parse_span(s).and_then(|d| {
+        if d > Duration::zero() {
+            Ok(d)
+        } else {
+            Err("Bucket width must be positive".to_string())
+        }
+    })
+}
+
+// whole seconds, as bursts are counted in
+fn parse_burst_window(s: &str) -> Result<Duration, String> {
+    parse_span(s).and_then(|d| {
+        if d >= Duration::seconds(1) {
+            Ok(d)
+        } else {
+            Err("Burst window must be at least 1s".to_string())
+        }
+    })
+}
+
+fn parse_score_window(s: &str) -> Result<Duration, String> {
+    parse_span(s).and_then(|d| {
+        if d >= Duration::seconds(1) {
+            Ok(d)
+        } else {
+            Err("Score window must be at least 1s".to_string())
+        }
+    })
+}
+
+fn parse_price(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(price) if price > 0.0 && price.is_finite() => Ok(price),
+        _ => Err(format!("Invalid price per GB: {}", s)),
+    }
+}
+
+fn parse_alert_threshold(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(score) if (0.0..=100.0).contains(&score) => Ok(score),
+        Ok(_) => Err("Alert threshold is an anomaly score, 0 to 100".to_string()),
+        Err(_) => Err(format!("Invalid score: {}", s)),
+    }
+}
+
+fn parse_burst_threshold(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("Burst threshold must be at least 1".to_string()),
+        Ok(n) => Ok(n),
+        Err(e) => Err(format!("Invalid burst threshold: {}", e)),
+    }
+}
+
+fn parse_glob(s: &str) -> Result<Pattern, String> {
+    Pattern::new(s).map_err(|e| format!("Invalid glob: {}", e))
+}
@@ -0,0 +1,261 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::cli::{Cli, TokenClass};
+use crate::endpoints::PathRule;
+use crate::metrics::{MetricSpec, Threshold};
+use crate::parser::{LevelAlias, LevelRule};
+use crate::profile::Profile;
+use crate::scoring::{Combine, ScorerSpec};
+use crate::schema::Schema;
+use crate::sources::ErrorBudget;
+use crate::xml::XmlField;
+
+// Remote configs are refetched at most this often; a stale copy is still
+// used when the server can't be reached.
+const CACHE_TTL: Duration = Duration::from_secs(3600);
+const FETCH_TIMEOUT_SECS: u32 = 10;
+
+// Shared settings, e.g. an organization-wide bundle:
+//
+//   profile = "backend"
+//   metrics = ['took (\d+)ms as latency']
+//   thresholds = ["latency>2000"]
+//   error_budgets = ["*=10"]
+//   keyword_drop = ["ids"]
+//   redact = ['password=\S+']
+//   slow_request_ms = 1500
+//   max_line_len = 65536
+//   max_message_len = 4096
+//   path_rules = ['^/static/.*=/static/*']
+//   scorers = ["heuristic*0.7", "error_rate>5*0.3"]
+//   level_rules = ['connection reset by peer=warn']
+//   xml_record = "record"
+//   xml_fields = ['user=EventData/Data[@Name="TargetUserName"]']
+//   schema = "fields.toml"
+//
+//   [levels]
+//   SEVERE = "error"
+//
+// Like a profile it only adds to what was given on the command line.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub profile: Option<Profile>,
+    pub metrics: Vec<String>,
+    pub thresholds: Vec<String>,
+    pub error_budgets: Vec<String>,
+    pub keyword_drop: Vec<TokenClass>,
+    pub redact: Vec<String>,
+    pub slow_request_ms: Option<f64>,
+    pub max_line_len: Option<usize>,
+    pub max_message_len: Option<usize>,
+    pub path_rules: Vec<String>,
+    pub scorers: Vec<String>,
+    pub score_combine: Option<Combine>,
+    pub levels: HashMap<String, String>,
+    pub level_rules: Vec<String>,
+    pub xml_record: Option<String>,
+    pub xml_fields: Vec<String>,
+    pub schema: Option<String>,
+}
+
+impl Config {
+    pub fn load(location: &str) -> Result<Self, String> {
+        let text = if is_url(location) {
+            fetch_cached(location)?
+        } else {
+            fs::read_to_string(location).map_err(|e| e.to_string())?
+        };
+        toml::from_str(&text).map_err(|e| format!("invalid config: {}", e))
+    }
+
+    pub fn apply(&self, args: &mut Cli) -> Result<(), String> {
+        if args.profile.is_none() {
+            args.profile = self.profile;
+        }
+
+        for spec in &self.metrics {
+            let metric = MetricSpec::parse(spec)?;
+            if !args.metric.iter().any(|m| m.name == metric.name) {
+                args.metric.push(metric);
+            }
+        }
+
+        for spec in &self.thresholds {
+            let threshold = Threshold::parse(spec)?;
+            if !args.threshold.iter().any(|t| t.metric == threshold.metric) {
+                args.threshold.push(threshold);
+            }
+        }
+
+        for spec in &self.error_budgets {
+            let budget = ErrorBudget::parse(spec)?;
+            if !args.error_budget.iter().any(|b| b.source == budget.source) {
+                args.error_budget.push(budget);
+            }
+        }
+
+        if args.keyword_drop.is_empty() {
+            args.keyword_drop = self.keyword_drop.clone();
+        }
+
+        for pattern in &self.redact {
+            args.redact.push(regex::Regex::new(pattern).map_err(|e| format!("invalid redact pattern: {}", e))?);
+        }
+
+        if args.slow_request.is_none() {
+            args.slow_request = self.slow_request_ms;
+        }
+        if args.max_line_len.is_none() {
+            args.max_line_len = self.max_line_len;
+        }
+        if args.max_message_len.is_none() {
+            args.max_message_len = self.max_message_len;
+        }
+
+        for spec in &self.path_rules {
+            args.path_rule.push(PathRule::parse(spec)?);
+        }
+
+        // scorers replace the built-in score, so the command line wins outright
+        if args.scorer.is_empty() {
+            for spec in &self.scorers {
+                args.scorer.push(ScorerSpec::parse(spec)?);
+            }
+            if let Some(combine) = self.score_combine {
+                args.score_combine = combine;
+            }
+        }
+
+        for (name, level) in &self.levels {
+            let alias = LevelAlias::parse(&format!("{}={}", name, level))?;
+            if !args.level_map.iter().any(|a| a.name == alias.name) {
+                args.level_map.push(alias);
+            }
+        }
+
+        // after the command line's, which are checked first
+        for spec in &self.level_rules {
+            args.level_rule.push(LevelRule::parse(spec)?);
+        }
+
+        if let (None, Some(path)) = (&args.schema, &self.schema) {
+            args.schema = Some(Schema::load(path)?);
+        }
+
+        if args.xml_record.is_none() {
+            args.xml_record = self.xml_record.clone();
+        }
+        for spec in &self.xml_fields {
+            let field = XmlField::parse(spec)?;
+            if !args.xml_field.iter().any(|f| f.name == field.name) {
+                args.xml_field.push(field);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_url(location: &str) -> bool {
+    location.starts_with("https://") || location.starts_with("http://")
+}
+
+fn cache_path(url: &str) -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+    let digest: String = Sha256::digest(url.as_bytes())
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Some(base.join("logscope").join(format!("config-{}.toml", digest)))
+}
+
+fn fetch_cached(url: &str) -> Result<String, String> {
+    let cache = cache_path(url);
+
+    if let Some(ref path) = cache {
+        let fresh = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.elapsed().ok())
+            .is_some_and(|age| age < CACHE_TTL);
+        if fresh {
+            if let Ok(text) = fs::read_to_string(path) {
+                debug!(url, cache = %path.display(), "using cached config");
+                return Ok(text);
+            }
+        }
+    }
+
+    match fetch(url) {
+        Ok(text) => {
+            if let Some(ref path) = cache {
+                let saved = path
+                    .parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(path, &text));
+                if let Err(e) = saved {
+                    warn!(error = %e, "cannot cache config");
+                }
+            }
+            Ok(text)
+        }
+        Err(e) => match cache.and_then(|path| fs::read_to_string(path).ok()) {
+            Some(text) => {
+                warn!(url, error = %e, "fetching config failed, using stale cached copy");
+                Ok(text)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+// curl is on every machine that would point us at an https URL, and saves
+// pulling a TLS stack into the binary
+fn fetch(url: &str) -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", &FETCH_TIMEOUT_SECS.to_string(), url])
+        .output()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("fetching {} failed: {}", url, stderr.trim()));
+    }
+    String::from_utf8(output.stdout).map_err(|_| format!("{} is not valid UTF-8", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn guard_limits_come_from_the_config_unless_given() {
+        let path = std::env::temp_dir().join(format!("logscope-config-{}.toml", std::process::id()));
+        fs::write(&path, "max_line_len = 65536\nmax_message_len = 4096\n").unwrap();
+        let config = Config::load(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+
+        let mut args = Cli::parse_from(["logscope", "app.log"]);
+        config.apply(&mut args).unwrap();
+        assert_eq!(args.max_line_len, Some(65536));
+        assert_eq!(args.max_message_len, Some(4096));
+
+        let mut args = Cli::parse_from(["logscope", "app.log", "--max-line-len", "1024"]);
+        config.apply(&mut args).unwrap();
+        assert_eq!(args.max_line_len, Some(1024));
+        assert_eq!(args.max_message_len, Some(4096));
+    }
+}
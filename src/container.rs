@@ -0,0 +1,153 @@
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::parser::loose_timestamp;
+use crate::repair::Line;
+
+// One line as a container runtime wrote it, around the application's own.
+// `partial` lines are the head of an application line the runtime split
+// (Docker at 16 KB, CRI at its buffer size); the rest follows on the next
+// line from the same stream.
+pub struct ContainerLine<'a> {
+    pub time: NaiveDateTime,
+    pub stream: &'a str,
+    pub partial: bool,
+    pub log: Cow<'a, str>,
+}
+
+#[derive(Deserialize)]
+struct DockerRecord<'a> {
+    log: String,
+    #[serde(borrow)]
+    stream: Option<&'a str>,
+    time: &'a str,
+}
+
+// Docker's json-file driver: {"log":"...\n","stream":"stderr","time":"..."}.
+// Only a line's last piece ends in a newline.
+pub fn docker(line: &str) -> Option<ContainerLine<'_>> {
+    if !line.starts_with("{\"log\":") {
+        return None;
+    }
+    let record: DockerRecord = serde_json::from_str(line).ok()?;
+    let partial = !record.log.ends_with('\n');
+    let log = record.log.strip_suffix('\n').map(str::to_string).unwrap_or(record.log);
+    Some(ContainerLine {
+        time: loose_timestamp(record.time)?,
+        stream: record.stream.unwrap_or("stdout"),
+        partial,
+        log: Cow::Owned(log.strip_suffix('\r').map(str::to_string).unwrap_or(log)),
+    })
+}
+
+// CRI (containerd, CRI-O, and so Kubernetes' /var/log/pods):
+// `2026-01-01T12:00:00.000000000Z stderr F message`, F for a full line or
+// the last piece of one, P for the others.
+pub fn cri(line: &str) -> Option<ContainerLine<'_>> {
+    let (time, rest) = line.split_once(' ')?;
+    let (stream, rest) = rest.split_once(' ')?;
+    if !matches!(stream, "stdout" | "stderr") {
+        return None;
+    }
+    let (tag, log) = rest.split_once(' ').unwrap_or((rest, ""));
+    // later CRI versions may add tags after the first, separated by ':'
+    let partial = match tag.split(':').next()? {
+        "F" => false,
+        "P" => true,
+        _ => return None,
+    };
+    Some(ContainerLine { time: loose_timestamp(time)?, stream, partial, log: Cow::Borrowed(log) })
+}
+
+fn container_line(line: &str) -> Option<ContainerLine<'_>> {
+    match line.as_bytes().first()? {
+        b'{' => docker(line),
+        b if b.is_ascii_digit() => cri(line),
+        _ => None,
+    }
+}
+
+// Whether a line the runtime split is still waiting for its end, given the
+// lines before some point, newest first: the newest line of a stream is a
+// partial one.
+pub fn split_pending<S: AsRef<str>>(before: impl Iterator<Item = S>) -> bool {
+    let mut ended: Vec<String> = Vec::new();
+    for line in before {
+        let Some(parsed) = container_line(line.as_ref()) else {
+            continue;
+        };
+        if ended.iter().any(|s| s == parsed.stream) {
+            continue;
+        }
+        if parsed.partial {
+            return true;
+        }
+        ended.push(parsed.stream.to_string());
+        // stdout and stderr both ended
+        if ended.len() == 2 {
+            break;
+        }
+    }
+    false
+}
+
+// Puts lines the runtime split back together: each run of partial lines is
+// joined to the next line of the same stream, which keeps its own time.
+// The joined line takes the first piece's line number; returns how many
+// pieces were folded into a later line.
+pub fn join_partial(lines: Vec<Line<'_>>) -> (Vec<Line<'_>>, usize) {
+    let is_partial = |line: &Option<Cow<str>>| line.as_deref().and_then(container_line).is_some_and(|c| c.partial);
+    if !lines.iter().any(|(_, line)| is_partial(line)) {
+        return (lines, 0);
+    }
+
+    let mut out: Vec<Line> = Vec::with_capacity(lines.len());
+    // per stream: the first piece's line number, the text so far and the
+    // newest piece as written
+    let mut pending: Vec<(String, usize, String, String)> = Vec::new();
+    let mut joined = 0;
+    for (num, line) in lines {
+        let Some(parsed) = line.as_deref().and_then(container_line) else {
+            out.push((num, line));
+            continue;
+        };
+        let at = pending.iter().position(|(stream, ..)| stream == parsed.stream);
+        let text = line.as_deref().unwrap_or_default();
+        match (at, parsed.partial) {
+            (Some(i), true) => {
+                pending[i].2.push_str(&parsed.log);
+                pending[i].3 = text.to_string();
+                joined += 1;
+            }
+            (None, true) => pending.push((parsed.stream.to_string(), num, parsed.log.to_string(), text.to_string())),
+            (Some(i), false) => {
+                let (_, first, mut head, _) = pending.remove(i);
+                head.push_str(&parsed.log);
+                joined += 1;
+                out.push((first, Some(Cow::Owned(rewrite(text, &parsed, &head)))));
+            }
+            (None, false) => out.push((num, line)),
+        }
+    }
+    // a line whose end never came (the input stops mid-line) is kept as far
+    // as it got
+    for (_, first, head, last) in pending {
+        if let Some(parsed) = container_line(&last) {
+            let line = rewrite(&last, &parsed, &head);
+            out.push((first, Some(Cow::Owned(line))));
+        }
+    }
+    out.sort_by_key(|(num, _)| *num);
+    (out, joined)
+}
+
+// the last piece's line, carrying the whole text
+fn rewrite(line: &str, last: &ContainerLine, text: &str) -> String {
+    let time = last.time.format("%Y-%m-%dT%H:%M:%S%.fZ");
+    if line.starts_with('{') {
+        serde_json::json!({ "log": format!("{}\n", text), "stream": last.stream, "time": time.to_string() }).to_string()
+    } else {
+        format!("{} {} F {}", time, last.stream, text)
+    }
+}
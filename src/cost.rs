@@ -0,0 +1,151 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+
+use crate::archive::{ArchiveKind, Compression};
+use crate::parser::{LogEntry, LogLevel};
+
+const GB: f64 = 1e9;
+const DAYS_PER_MONTH: f64 = 30.0;
+// per line, for the timestamp, level and separators around the message when
+// the inputs' size can't be measured
+const LINE_OVERHEAD: usize = 32;
+const SOURCE_LIMIT: usize = 10;
+// shorter spans are too little to scale up to a month
+const MIN_SPAN_SECS: i64 = 60;
+
+#[derive(Debug, Serialize)]
+pub struct CostShare {
+    pub name: String,
+    pub entries: usize,
+    pub bytes: u64,
+    pub monthly_cost: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CostReport {
+    pub price_per_gb: f64,
+    pub bytes: u64,
+    // whether `bytes` is the inputs' measured size rather than an estimate
+    // from the entries
+    pub measured: bool,
+    pub span_secs: i64,
+    // None when the entries span too little time to scale up
+    pub monthly_gb: Option<f64>,
+    pub monthly_cost: Option<f64>,
+    pub by_level: Vec<CostShare>,
+    // most expensive first
+    pub by_source: Vec<CostShare>,
+    pub drop_debug_savings: f64,
+    // repeats of an entry already seen (same level, source and message)
+    pub dedup_savings: f64,
+    pub combined_savings: f64,
+}
+
+// `--cost-per-gb`: what shipping these entries to a log platform that
+// charges per ingested GB would cost a month, at the rate they were written,
+// and what dropping DEBUG or repeated entries would save. Each entry weighs
+// its message plus a share of the line around it; `input_bytes`, when the
+// inputs were read whole from plain files, sets that share so the total is
+// what was actually read.
+pub fn estimate(entries: &[LogEntry], price_per_gb: f64, input_bytes: Option<u64>) -> Option<CostReport> {
+    let (first, last) = (entries.first()?.timestamp, entries.last()?.timestamp);
+    let overhead = line_overhead(entries, input_bytes);
+    let size = |e: &LogEntry| e.message.len() as f64 + 1.0 + overhead;
+
+    let span_secs = (last - first).num_seconds();
+    // bytes to cost a month
+    let scale = (span_secs >= MIN_SPAN_SECS).then(|| DAYS_PER_MONTH * 86400.0 / span_secs as f64);
+    let monthly = |bytes: f64| scale.map_or(0.0, |s| bytes * s / GB * price_per_gb);
+
+    let mut levels: HashMap<&'static str, (usize, f64)> = HashMap::new();
+    let mut sources: HashMap<&str, (usize, f64)> = HashMap::new();
+    let mut seen: HashSet<(&str, Option<&str>, &str)> = HashSet::new();
+    let (mut total, mut debug, mut repeats, mut either) = (0.0, 0.0, 0.0, 0.0);
+    for entry in entries {
+        let bytes = size(entry);
+        total += bytes;
+        let level = levels.entry(entry.level.as_str()).or_default();
+        level.0 += 1;
+        level.1 += bytes;
+        let source = sources.entry(entry.source.as_deref().unwrap_or("-")).or_default();
+        source.0 += 1;
+        source.1 += bytes;
+
+        let is_debug = entry.level == LogLevel::Debug;
+        let is_repeat = !seen.insert((entry.level.as_str(), entry.source.as_deref(), &entry.message));
+        if is_debug {
+            debug += bytes;
+        }
+        if is_repeat {
+            repeats += bytes;
+        }
+        if is_debug || is_repeat {
+            either += bytes;
+        }
+    }
+
+    let shares = |counts: Vec<(&str, (usize, f64))>| -> Vec<CostShare> {
+        let mut shares: Vec<CostShare> = counts
+            .into_iter()
+            .map(|(name, (entries, bytes))| CostShare {
+                name: name.to_string(),
+                entries,
+                bytes: bytes.round() as u64,
+                monthly_cost: monthly(bytes),
+            })
+            .collect();
+        shares.sort_unstable_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        shares
+    };
+    let mut by_source = shares(sources.into_iter().collect());
+    by_source.truncate(SOURCE_LIMIT);
+
+    Some(CostReport {
+        price_per_gb,
+        bytes: total.round() as u64,
+        measured: input_bytes.is_some(),
+        span_secs,
+        monthly_gb: scale.map(|s| total * s / GB),
+        monthly_cost: scale.map(|_| monthly(total)),
+        by_level: shares(levels.into_iter().collect()),
+        by_source,
+        drop_debug_savings: monthly(debug),
+        dedup_savings: monthly(repeats),
+        combined_savings: monthly(either),
+    })
+}
+
+// Bytes each entry's line holds beyond its message and newline: the inputs'
+// size spread evenly when it is known, else a fixed guess.
+pub fn line_overhead(entries: &[LogEntry], input_bytes: Option<u64>) -> f64 {
+    let messages: u64 = entries.iter().map(|e| e.message.len() as u64 + 1).sum();
+    match input_bytes {
+        Some(bytes) if !entries.is_empty() => bytes.saturating_sub(messages) as f64 / entries.len() as f64,
+        _ => LINE_OVERHEAD as f64,
+    }
+}
+
+// The inputs' size on disk, when every one is a plain, uncompressed file:
+// otherwise it says nothing about how much text was read.
+pub fn plain_input_bytes(paths: &[String]) -> Option<u64> {
+    let mut total = 0;
+    for path in paths {
+        if ArchiveKind::detect(path).is_some() {
+            return None;
+        }
+        let mut file = File::open(path).ok()?;
+        let meta = file.metadata().ok()?;
+        if !meta.is_file() {
+            return None;
+        }
+        let mut head = [0; 4];
+        let n = file.read(&mut head).ok()?;
+        if Compression::detect(&head[..n]).is_some() {
+            return None;
+        }
+        total += meta.len();
+    }
+    Some(total)
+}
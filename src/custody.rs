@@ -0,0 +1,87 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::manifest::sha256_file;
+
+pub const SIDECAR_NAME: &str = "CUSTODY.json";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InputRecord {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub modified: Option<String>,
+}
+
+// `--evidence-mode`: what was analyzed, when and how, embedded in every
+// export so the result can be tied back to the evidence it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct Custody {
+    pub tool: String,
+    pub command_line: Vec<String>,
+    // when the inputs were hashed, just before they were read
+    pub acquired_at: String,
+    pub inputs: Vec<InputRecord>,
+    // options that alter entries, let through by --allow-mutation
+    pub mutations: Vec<String>,
+    // inputs whose hash no longer matched once they had been read
+    pub changed_during_run: Vec<String>,
+}
+
+impl Custody {
+    pub fn acquire(paths: &[String], command_line: Vec<String>, mutations: Vec<String>) -> io::Result<Self> {
+        let acquired_at = Local::now().to_rfc3339();
+        let inputs = paths
+            .iter()
+            .map(|path| {
+                let meta = fs::metadata(path)?;
+                Ok(InputRecord {
+                    path: path.clone(),
+                    size: meta.len(),
+                    sha256: sha256_file(Path::new(path))?,
+                    modified: meta.modified().ok().map(|t| DateTime::<Local>::from(t).to_rfc3339()),
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self {
+            tool: format!("logscope {}", env!("CARGO_PKG_VERSION")),
+            command_line,
+            acquired_at,
+            inputs,
+            mutations,
+            changed_during_run: Vec::new(),
+        })
+    }
+
+    // Hashes the inputs again; run after parsing, a match means what was
+    // analyzed is what was hashed.
+    pub fn verify(&mut self) -> io::Result<()> {
+        for input in &self.inputs {
+            if sha256_file(Path::new(&input.path))? != input.sha256 {
+                self.changed_during_run.push(input.path.clone());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, self)?;
+        writeln!(out)?;
+        out.flush()
+    }
+}
+
+// Formats without room for metadata (csv, jsonl, l2tcsv) get the record
+// beside them: inside the directory for partitioned exports, next to the
+// file otherwise.
+pub fn sidecar_path(output: &str, partitioned: bool) -> PathBuf {
+    if partitioned {
+        Path::new(output).join(SIDECAR_NAME)
+    } else {
+        PathBuf::from(format!("{}.custody.json", output))
+    }
+}
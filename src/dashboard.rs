@@ -0,0 +1,251 @@
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::analyzer::LogAnalysis;
+use crate::buckets::{Bucketing, Zone};
+use crate::parser::{LogEntry, LogLevel};
+
+// time buckets in the embedded series
+const DASHBOARD_BUCKETS: usize = 400;
+// entries embedded for the table; warnings and errors get up to half
+const DASHBOARD_ENTRIES: usize = 5000;
+const MESSAGE_CHARS: usize = 300;
+const LEVELS: [LogLevel; 6] =
+    [LogLevel::Fatal, LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Unknown];
+
+#[derive(Serialize)]
+struct Series {
+    // bucket starts, on the export zone's wall clock
+    starts: Vec<String>,
+    width_secs: i64,
+    // [bucket, level index, source index, entries]; empty cells left out
+    cells: Vec<[usize; 4]>,
+}
+
+// One self-contained HTML file: the analysis, a per-level and per-source
+// time series and a sample of entries embedded as JSON, plus a little
+// script to filter and redraw them in the browser. Nothing is fetched.
+pub fn write(analysis: &LogAnalysis, entries: &[LogEntry], path: &Path, zone: Zone) -> Result<(), Box<dyn Error>> {
+    let mut sources: Vec<Arc<str>> = Vec::new();
+    let mut source_index: HashMap<Arc<str>, usize> = HashMap::new();
+    // index 0 is "no source"
+    let mut source_of = |entry: &LogEntry| match entry.source {
+        Some(ref s) => {
+            1 + *source_index.entry(Arc::clone(s)).or_insert_with(|| {
+                sources.push(Arc::clone(s));
+                sources.len() - 1
+            })
+        }
+        None => 0,
+    };
+    let level_of = |entry: &LogEntry| LEVELS.iter().position(|l| *l == entry.level).unwrap_or(LEVELS.len() - 1);
+
+    let series = match (entries.first(), entries.last()) {
+        (Some(first), Some(last)) => {
+            let buckets = Bucketing::fitting(first.timestamp, last.timestamp, DASHBOARD_BUCKETS, zone);
+            let (origin, count) = buckets.span(first.timestamp, last.timestamp);
+            let mut cells: HashMap<(usize, usize, usize), usize> = HashMap::new();
+            for entry in entries {
+                let b = buckets.index(origin, entry.timestamp).min(count - 1);
+                *cells.entry((b, level_of(entry), source_of(entry))).or_insert(0) += 1;
+            }
+            let mut cells: Vec<[usize; 4]> = cells.into_iter().map(|((b, l, s), n)| [b, l, s, n]).collect();
+            cells.sort_unstable();
+            Series {
+                starts: (0..count)
+                    .map(|b| (origin + buckets.width() * b as i32).format("%Y-%m-%d %H:%M:%S").to_string())
+                    .collect(),
+                width_secs: buckets.width().num_seconds(),
+                cells,
+            }
+        }
+        _ => Series { starts: Vec::new(), width_secs: 0, cells: Vec::new() },
+    };
+
+    let sample: Vec<_> = sample(entries)
+        .into_iter()
+        .map(|e| {
+            let message: String = e.message.chars().take(MESSAGE_CHARS).collect();
+            json!([zone.local(e.timestamp).format("%Y-%m-%d %H:%M:%S").to_string(), level_of(e), source_of(e), message])
+        })
+        .collect();
+
+    let data = json!({
+        "files": analysis.parse_stats.files.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+        "levels": LEVELS.iter().map(|l| l.as_str()).collect::<Vec<_>>(),
+        "sources": sources.iter().map(|s| s.as_ref()).collect::<Vec<_>>(),
+        "series": series,
+        "entries": sample,
+        "sampled_from": entries.len(),
+        "analysis": analysis,
+    });
+
+    // `</` would end the script element early
+    let data = serde_json::to_string(&data)?.replace("</", "<\\/");
+    fs::write(path, TEMPLATE.replace("__DATA__", &data))?;
+    Ok(())
+}
+
+// Evenly spaced picks, warnings and errors first so they aren't thinned out
+// by the routine lines around them; returned in timestamp order.
+fn sample(entries: &[LogEntry]) -> Vec<&LogEntry> {
+    let (severe, routine): (Vec<&LogEntry>, Vec<&LogEntry>) =
+        entries.iter().partition(|e| e.level.severity() >= LogLevel::Warn.severity());
+    let severe = spread(&severe, DASHBOARD_ENTRIES / 2);
+    let routine = spread(&routine, DASHBOARD_ENTRIES - severe.len());
+    let mut picked: Vec<&LogEntry> = severe.into_iter().chain(routine).collect();
+    picked.sort_by_key(|e| (e.timestamp, e.line_number));
+    picked
+}
+
+fn spread<'a>(entries: &[&'a LogEntry], max: usize) -> Vec<&'a LogEntry> {
+    if entries.len() <= max {
+        return entries.to_vec();
+    }
+    (0..max).map(|i| entries[i * entries.len() / max]).collect()
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>logscope dashboard</title>
+<style>
+body { font: 14px/1.4 system-ui, sans-serif; margin: 0; color: #222; background: #fafafa; }
+header { background: #263238; color: #fff; padding: 12px 20px; }
+header h1 { font-size: 18px; margin: 0; }
+header p { margin: 4px 0 0; color: #b0bec5; }
+main { padding: 16px 20px; }
+.cards { display: flex; gap: 12px; flex-wrap: wrap; margin-bottom: 16px; }
+.card { background: #fff; border: 1px solid #ddd; border-radius: 6px; padding: 10px 14px; min-width: 120px; }
+.card b { display: block; font-size: 20px; }
+.controls { display: flex; gap: 16px; align-items: center; flex-wrap: wrap; margin: 12px 0; }
+canvas { width: 100%; height: 260px; background: #fff; border: 1px solid #ddd; border-radius: 6px; }
+table { border-collapse: collapse; width: 100%; background: #fff; margin-top: 12px; }
+th, td { text-align: left; padding: 4px 8px; border-bottom: 1px solid #eee; vertical-align: top; }
+td.msg { font-family: ui-monospace, monospace; white-space: pre-wrap; word-break: break-all; }
+.lv-FATAL, .lv-ERROR { color: #c62828; } .lv-WARN { color: #b26a00; }
+.muted { color: #777; }
+</style>
+</head>
+<body>
+<header><h1>logscope dashboard</h1><p id="files"></p></header>
+<main>
+<div class="cards" id="cards"></div>
+<div class="controls">
+  <span id="level-boxes"></span>
+  <label>Source <select id="source"><option value="">all</option></select></label>
+  <label>Search <input id="search" type="search" placeholder="text in message"></label>
+</div>
+<canvas id="chart"></canvas>
+<p class="muted" id="range"></p>
+<table><thead><tr><th>Time</th><th>Level</th><th>Source</th><th>Message</th></tr></thead><tbody id="rows"></tbody></table>
+<p class="muted" id="note"></p>
+</main>
+<script id="data" type="application/json">__DATA__</script>
+<script>
+const D = JSON.parse(document.getElementById("data").textContent);
+const COLORS = ["#8e24aa", "#d32f2f", "#f5a623", "#4a90d9", "#9e9e9e", "#bdbdbd"];
+const TABLE_ROWS = 500;
+const A = D.analysis;
+const state = { levels: new Set(D.levels.map((_, i) => i)), source: "", search: "" };
+
+document.getElementById("files").textContent = D.files.join(", ");
+const cards = [
+  ["Entries", A.stats.total],
+  ["Error rate", A.stats.error_rate.toFixed(2) + "%"],
+  ["Anomaly score", A.anomaly_score.toFixed(0)],
+  ["Sources", D.sources.length],
+];
+for (const [label, value] of cards) {
+  const card = document.createElement("div");
+  card.className = "card";
+  card.textContent = label;
+  const b = document.createElement("b");
+  b.textContent = value;
+  card.appendChild(b);
+  document.getElementById("cards").appendChild(card);
+}
+
+D.levels.forEach((name, i) => {
+  const label = document.createElement("label");
+  const box = document.createElement("input");
+  box.type = "checkbox";
+  box.checked = true;
+  box.onchange = () => { box.checked ? state.levels.add(i) : state.levels.delete(i); draw(); };
+  label.append(box, " ", name, "  ");
+  label.style.color = COLORS[i];
+  document.getElementById("level-boxes").appendChild(label);
+});
+const sourceSelect = document.getElementById("source");
+D.sources.forEach((name, i) => sourceSelect.add(new Option(name, String(i + 1))));
+sourceSelect.onchange = () => { state.source = sourceSelect.value; draw(); };
+document.getElementById("search").oninput = (e) => { state.search = e.target.value.toLowerCase(); draw(); };
+
+function keep(level, source) {
+  return state.levels.has(level) && (state.source === "" || String(source) === state.source);
+}
+
+function drawChart() {
+  const canvas = document.getElementById("chart");
+  const dpr = window.devicePixelRatio || 1;
+  canvas.width = canvas.clientWidth * dpr;
+  canvas.height = canvas.clientHeight * dpr;
+  const ctx = canvas.getContext("2d");
+  ctx.scale(dpr, dpr);
+  const w = canvas.clientWidth, h = canvas.clientHeight, n = D.series.starts.length;
+  if (n === 0) return;
+  const stacks = Array.from({ length: n }, () => new Array(D.levels.length).fill(0));
+  for (const [b, l, s, count] of D.series.cells) if (keep(l, s)) stacks[b][l] += count;
+  const max = Math.max(1, ...stacks.map((s) => s.reduce((a, b) => a + b, 0)));
+  const bw = w / n;
+  stacks.forEach((stack, b) => {
+    let y = h;
+    for (let l = stack.length - 1; l >= 0; l--) {
+      const bh = (stack[l] / max) * (h - 10);
+      ctx.fillStyle = COLORS[l];
+      ctx.fillRect(b * bw, y - bh, Math.max(bw - 1, 1), bh);
+      y -= bh;
+    }
+  });
+  document.getElementById("range").textContent =
+    D.series.starts[0] + " → " + D.series.starts[n - 1] + " · " + D.series.width_secs + "s per bar · peak " + max;
+}
+
+function drawRows() {
+  const tbody = document.getElementById("rows");
+  tbody.replaceChildren();
+  let shown = 0, matched = 0;
+  for (const [ts, l, s, msg] of D.entries) {
+    if (!keep(l, s) || (state.search && !msg.toLowerCase().includes(state.search))) continue;
+    matched++;
+    if (shown >= TABLE_ROWS) continue;
+    const tr = tbody.insertRow();
+    tr.insertCell().textContent = ts;
+    const lv = tr.insertCell();
+    lv.textContent = D.levels[l];
+    lv.className = "lv-" + D.levels[l];
+    tr.insertCell().textContent = s ? D.sources[s - 1] : "";
+    const m = tr.insertCell();
+    m.className = "msg";
+    m.textContent = msg;
+    shown++;
+  }
+  document.getElementById("note").textContent =
+    shown + " of " + matched + " matching sample entries shown; the sample holds " +
+    D.entries.length + " of " + D.sampled_from + " entries, warnings and errors first.";
+}
+
+function draw() { drawChart(); drawRows(); }
+window.onresize = drawChart;
+draw();
+</script>
+</body>
+</html>
+"##;
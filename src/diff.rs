@@ -0,0 +1,465 @@
+use chrono::{Duration, NaiveDateTime};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use crate::analyzer::{self, KeywordOptions, LogAnalysis, LogAnalyzer};
+use crate::cli::DiffArgs;
+use crate::parser::{LogEntry, LogFormat, LogLevel, LogParser, ParseStats};
+use crate::report::anomaly_label;
+use crate::templates;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateChange {
+    pub template: String,
+    pub change: Change,
+    pub baseline: usize,
+    pub current: usize,
+    // share of all entries on each side, so files of different sizes compare
+    pub baseline_share: f64,
+    pub current_share: f64,
+    pub is_error: bool,
+}
+
+// a top keyword of the current log the baseline never logged
+#[derive(Debug, Serialize)]
+pub struct NewKeyword {
+    pub word: String,
+    pub count: usize,
+    pub error_ratio: f64,
+}
+
+// a burst of the current log whose main error template never burst in the
+// baseline
+#[derive(Debug, Serialize)]
+pub struct NewBurst {
+    pub window_start: String,
+    pub count: usize,
+    pub template: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateDiff {
+    pub baseline_path: String,
+    pub current_path: String,
+    pub baseline_entries: usize,
+    pub current_entries: usize,
+    pub baseline_templates: usize,
+    pub current_templates: usize,
+    pub baseline_errors: usize,
+    pub current_errors: usize,
+    pub baseline_warnings: usize,
+    pub current_warnings: usize,
+    // total variation distance between the two template mixes: the fraction
+    // of log volume that would have to move to turn one into the other
+    pub churn: f64,
+    pub changes: Vec<TemplateChange>,
+    pub baseline_score: f64,
+    pub current_score: f64,
+    pub baseline_bursts: usize,
+    pub current_bursts: usize,
+    pub new_keywords: Vec<NewKeyword>,
+    pub new_bursts: Vec<NewBurst>,
+}
+
+#[derive(Default)]
+struct TemplateCount {
+    count: usize,
+    errors: usize,
+}
+
+fn count_templates(entries: &[LogEntry]) -> HashMap<String, TemplateCount> {
+    let mut counts: HashMap<String, TemplateCount> = HashMap::new();
+    for entry in entries {
+        let tc = counts.entry(templates::fingerprint(&entry.message)).or_default();
+        tc.count += 1;
+        if entry.level.severity() >= 3 {
+            tc.errors += 1;
+        }
+    }
+    counts
+}
+
+fn count_at_severity(entries: &[LogEntry], min: u8) -> usize {
+    entries.iter().filter(|e| e.level.severity() >= min).count()
+}
+
+fn rate(count: usize, total: usize) -> f64 {
+    count as f64 / total.max(1) as f64
+}
+
+pub fn run(args: &DiffArgs) -> io::Result<TemplateDiff> {
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let parser = LogParser::with_format(format);
+
+    let (baseline, baseline_stats) = parser.parse_file_counted(&args.baseline)?;
+    let (current, current_stats) = parser.parse_file_counted(&args.current)?;
+
+    Ok(compare(
+        (&args.baseline, &baseline, baseline_stats),
+        (&args.current, &current, current_stats),
+        args.min_ratio,
+    ))
+}
+
+// A template counts as changed when its share of entries moved by at least
+// `min_ratio` in either direction. Both sides are also analyzed in full for
+// the anomaly score, keywords and bursts.
+pub fn compare(
+    (baseline_path, baseline, baseline_stats): (&str, &[LogEntry], ParseStats),
+    (current_path, current, current_stats): (&str, &[LogEntry], ParseStats),
+    min_ratio: f64,
+) -> TemplateDiff {
+    let before_analysis = LogAnalyzer::new(baseline, baseline_stats).analyze(None);
+    let after_analysis = LogAnalyzer::new(current, current_stats).analyze(None);
+
+    let before = count_templates(baseline);
+    let after = count_templates(current);
+    let total_before = baseline.len().max(1) as f64;
+    let total_after = current.len().max(1) as f64;
+
+    let mut churn = 0.0;
+    let mut changes = Vec::new();
+
+    let mut all: Vec<&String> = before.keys().chain(after.keys().filter(|t| !before.contains_key(*t))).collect();
+    all.sort_unstable();
+
+    for template in all {
+        let b = before.get(template);
+        let a = after.get(template);
+        let b_count = b.map_or(0, |t| t.count);
+        let a_count = a.map_or(0, |t| t.count);
+        let b_share = b_count as f64 / total_before;
+        let a_share = a_count as f64 / total_after;
+        churn += (a_share - b_share).abs();
+
+        let change = match (b_count, a_count) {
+            (0, _) => Change::Added,
+            (_, 0) => Change::Removed,
+            _ if a_share >= b_share * min_ratio || b_share >= a_share * min_ratio => Change::Changed,
+            _ => continue,
+        };
+
+        changes.push(TemplateChange {
+            template: template.clone(),
+            change,
+            baseline: b_count,
+            current: a_count,
+            baseline_share: b_share,
+            current_share: a_share,
+            is_error: b.is_some_and(|t| t.errors > 0) || a.is_some_and(|t| t.errors > 0),
+        });
+    }
+
+    // biggest movers first within each kind
+    changes.sort_by(|x, y| {
+        let moved = |c: &TemplateChange| (c.current_share - c.baseline_share).abs();
+        (x.change as u8)
+            .cmp(&(y.change as u8))
+            .then(moved(y).total_cmp(&moved(x)))
+    });
+
+    TemplateDiff {
+        baseline_path: baseline_path.to_string(),
+        current_path: current_path.to_string(),
+        baseline_entries: baseline.len(),
+        current_entries: current.len(),
+        baseline_templates: before.len(),
+        current_templates: after.len(),
+        baseline_errors: count_at_severity(baseline, 3),
+        current_errors: count_at_severity(current, 3),
+        baseline_warnings: count_at_severity(baseline, 2) - count_at_severity(baseline, 3),
+        current_warnings: count_at_severity(current, 2) - count_at_severity(current, 3),
+        churn: churn / 2.0,
+        changes,
+        baseline_score: before_analysis.anomaly_score,
+        current_score: after_analysis.anomaly_score,
+        baseline_bursts: before_analysis.stats.error_bursts.len(),
+        current_bursts: after_analysis.stats.error_bursts.len(),
+        new_keywords: new_keywords(baseline, &after_analysis),
+        new_bursts: new_bursts(baseline, &before_analysis, current, &after_analysis),
+    }
+}
+
+fn new_keywords(baseline: &[LogEntry], current: &LogAnalysis) -> Vec<NewKeyword> {
+    let opts = KeywordOptions::default();
+    let seen: HashSet<String> = baseline.iter().flat_map(|e| analyzer::keywords_in(&e.message, &opts)).collect();
+    current
+        .top_keywords
+        .iter()
+        .filter(|k| !seen.contains(&k.word))
+        .map(|k| NewKeyword { word: k.word.clone(), count: k.count, error_ratio: k.error_ratio })
+        .collect()
+}
+
+fn new_bursts(baseline: &[LogEntry], before: &LogAnalysis, current: &[LogEntry], after: &LogAnalysis) -> Vec<NewBurst> {
+    let known: HashSet<String> = burst_templates(baseline, before).into_iter().collect();
+    after
+        .stats
+        .error_bursts
+        .iter()
+        .zip(burst_templates(current, after))
+        .filter(|(_, template)| !known.contains(template))
+        .map(|(burst, template)| NewBurst { window_start: burst.window_start.clone(), count: burst.count, template })
+        .collect()
+}
+
+// the most common error template within each burst's window
+fn burst_templates(entries: &[LogEntry], analysis: &LogAnalysis) -> Vec<String> {
+    let window = Duration::seconds(analysis.stats.burst_params.window_secs);
+    analysis
+        .stats
+        .error_bursts
+        .iter()
+        .map(|burst| {
+            let Ok(start) = NaiveDateTime::parse_from_str(&burst.window_start, "%Y-%m-%d %H:%M:%S") else {
+                return String::new();
+            };
+            // bursts start on a whole second; the window's last second is kept whole too
+            let end = start + window + Duration::seconds(1);
+            let from = entries.partition_point(|e| e.timestamp < start);
+            let mut counts: HashMap<String, usize> = HashMap::new();
+            for entry in entries[from..].iter().take_while(|e| e.timestamp < end) {
+                if matches!(entry.level, LogLevel::Error | LogLevel::Fatal) {
+                    *counts.entry(templates::fingerprint(&entry.message)).or_insert(0) += 1;
+                }
+            }
+            counts
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .map(|(template, _)| template)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+impl TemplateDiff {
+    pub fn print(&self, color: bool, top: usize) {
+        let title = "logscope — Template Diff";
+        if color {
+            println!("\n{}", title.bold().cyan());
+        } else {
+            println!("\n{}", title);
+        }
+        println!("{}", "─".repeat(50));
+
+        println!(
+            "Baseline : {} ({} entries, {} templates)",
+            self.baseline_path, self.baseline_entries, self.baseline_templates
+        );
+        println!(
+            "Current  : {} ({} entries, {} templates)",
+            self.current_path, self.current_entries, self.current_templates
+        );
+        println!("Churn    : {:.1}% of log volume changed template", self.churn * 100.0);
+        let (before, after) = (
+            rate(self.baseline_errors, self.baseline_entries) * 100.0,
+            rate(self.current_errors, self.current_entries) * 100.0,
+        );
+        let errors = format!("Errors   : {:.2}% → {:.2}% ({:+.2}pp)", before, after, after - before);
+        let score = format!(
+            "Anomaly  : {:.0} ({}) → {:.0} ({}), bursts {} → {}",
+            self.baseline_score,
+            anomaly_label(self.baseline_score),
+            self.current_score,
+            anomaly_label(self.current_score),
+            self.baseline_bursts,
+            self.current_bursts
+        );
+        for (line, worse) in [(errors, after > before), (score, self.current_score > self.baseline_score)] {
+            if color && worse {
+                println!("{}", line.red());
+            } else {
+                println!("{}", line);
+            }
+        }
+        println!();
+
+        if !self.new_keywords.is_empty() {
+            println!("New Keywords ({})", self.new_keywords.len());
+            println!("{}", "─".repeat(30));
+            for k in self.new_keywords.iter().take(top) {
+                let line = format!("  {:>7}  {:<24} {:>5.1}% in errors", k.count, k.word, k.error_ratio * 100.0);
+                if color && k.error_ratio >= 0.5 {
+                    println!("{}", line.red());
+                } else {
+                    println!("{}", line);
+                }
+            }
+            println!();
+        }
+
+        if !self.new_bursts.is_empty() {
+            println!("New Bursts ({})", self.new_bursts.len());
+            println!("{}", "─".repeat(30));
+            for b in self.new_bursts.iter().take(top) {
+                let line = format!("  {} — {} errors, mostly {}", b.window_start, b.count, truncate(&b.template));
+                if color {
+                    println!("{}", line.red());
+                } else {
+                    println!("{}", line);
+                }
+            }
+            if self.new_bursts.len() > top {
+                println!("  … {} more", self.new_bursts.len() - top);
+            }
+            println!();
+        }
+
+        for (kind, heading) in [
+            (Change::Added, "New Templates"),
+            (Change::Removed, "Gone Templates"),
+            (Change::Changed, "Changed Frequency"),
+        ] {
+            let rows: Vec<&TemplateChange> = self.changes.iter().filter(|c| c.change == kind).collect();
+            if rows.is_empty() {
+                continue;
+            }
+
+            println!("{} ({})", heading, rows.len());
+            println!("{}", "─".repeat(30));
+            for c in rows.iter().take(top) {
+                let counts = match kind {
+                    Change::Added => format!("{:>7}", c.current),
+                    Change::Removed => format!("{:>7}", c.baseline),
+                    Change::Changed => format!(
+                        "{:>7} → {:<7} ({:.2}% → {:.2}%)",
+                        c.baseline,
+                        c.current,
+                        c.baseline_share * 100.0,
+                        c.current_share * 100.0
+                    ),
+                };
+                let template: String = c.template.chars().take(80).collect();
+                let line = format!("  {}  {}", counts, template);
+                if color && c.is_error {
+                    println!("{}", line.red());
+                } else {
+                    println!("{}", line);
+                }
+            }
+            if rows.len() > top {
+                println!("  … {} more", rows.len() - top);
+            }
+            println!();
+        }
+    }
+
+    // `diff -u`-like: `+`/`-` give the direction of each change, color its
+    // meaning (red for more errors, green for fewer), so regressions stand out.
+    pub fn print_unified(&self, color: bool, top: usize) {
+        println!("--- {} ({} entries)", self.baseline_path, self.baseline_entries);
+        println!("+++ {} ({} entries)", self.current_path, self.current_entries);
+
+        println!("@@ rates @@");
+        let rates = [
+            ("error_rate", self.baseline_errors, self.current_errors, 3),
+            ("warn_rate", self.baseline_warnings, self.current_warnings, 2),
+        ];
+        for (name, before, after, severity) in rates {
+            let b = rate(before, self.baseline_entries);
+            let a = rate(after, self.current_entries);
+            let line = format!("{:<11} {:.1}% → {:.1}%", name, b * 100.0, a * 100.0);
+            print_diff_line(color, delta_sign(b, a), &line, severity_delta(b, a, severity));
+        }
+        print_diff_line(
+            color,
+            delta_sign(self.baseline_entries as f64, self.current_entries as f64),
+            &format!("{:<11} {} → {}", "entries", self.baseline_entries, self.current_entries),
+            0,
+        );
+        print_diff_line(color, ' ', &format!("{:<11} {:.1}%", "churn", self.churn * 100.0), 0);
+        print_diff_line(
+            color,
+            delta_sign(self.baseline_score, self.current_score),
+            &format!("{:<11} {:.0} → {:.0}", "anomaly", self.baseline_score, self.current_score),
+            severity_delta(self.baseline_score, self.current_score, 3),
+        );
+
+        if !self.new_keywords.is_empty() {
+            println!("@@ keywords @@");
+            for k in self.new_keywords.iter().take(top) {
+                let line = format!("new keyword: {} ({})", k.word, k.count);
+                print_diff_line(color, '+', &line, if k.error_ratio >= 0.5 { 3 } else { 0 });
+            }
+        }
+        if !self.new_bursts.is_empty() {
+            println!("@@ bursts @@");
+            for b in self.new_bursts.iter().take(top) {
+                let line = format!("new burst: {} — {} errors, \"{}\"", b.window_start, b.count, truncate(&b.template));
+                print_diff_line(color, '+', &line, 3);
+            }
+        }
+
+        if self.changes.is_empty() {
+            return;
+        }
+
+        println!("@@ templates @@");
+        for c in self.changes.iter().filter(|c| c.change == Change::Added).take(top) {
+            let line = format!("new template: \"{}\" ({})", truncate(&c.template), c.current);
+            print_diff_line(color, '+', &line, if c.is_error { 3 } else { 0 });
+        }
+        for c in self.changes.iter().filter(|c| c.change == Change::Removed).take(top) {
+            let line = format!("gone template: \"{}\" ({})", truncate(&c.template), c.baseline);
+            print_diff_line(color, '-', &line, if c.is_error { -3 } else { 0 });
+        }
+        for c in self.changes.iter().filter(|c| c.change == Change::Changed).take(top) {
+            let line = format!(
+                "template: \"{}\" {:.2}% → {:.2}%",
+                truncate(&c.template),
+                c.baseline_share * 100.0,
+                c.current_share * 100.0
+            );
+            let sign = delta_sign(c.baseline_share, c.current_share);
+            let severity = if c.is_error { severity_delta(c.baseline_share, c.current_share, 3) } else { 0 };
+            print_diff_line(color, sign, &line, severity);
+        }
+    }
+}
+
+fn truncate(template: &str) -> String {
+    template.chars().take(80).collect()
+}
+
+fn delta_sign(before: f64, after: f64) -> char {
+    if after > before {
+        '+'
+    } else if after < before {
+        '-'
+    } else {
+        ' '
+    }
+}
+
+// positive when something at `severity` got more frequent, negative when it
+// got rarer, 0 when unchanged
+fn severity_delta(before: f64, after: f64, severity: i8) -> i8 {
+    match delta_sign(before, after) {
+        '+' => severity,
+        '-' => -severity,
+        _ => 0,
+    }
+}
+
+fn print_diff_line(color: bool, sign: char, text: &str, severity: i8) {
+    let line = format!("{} {}", sign, text);
+    if !color {
+        println!("{}", line);
+        return;
+    }
+    match severity {
+        3.. => println!("{}", line.red().bold()),
+        2 => println!("{}", line.yellow()),
+        ..=-2 => println!("{}", line.green()),
+        _ => println!("{}", line),
+    }
+}
@@ -0,0 +1,283 @@
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use crate::metrics::percentile;
+use crate::parser::LogEntry;
+
+pub const ID_PLACEHOLDER: &str = "{id}";
+
+// `--path-rule '^/static/.*=/static/*'`: rewrites the path before the
+// built-in ID detection runs; the replacement may use `$1`-style groups.
+#[derive(Debug, Clone)]
+pub struct PathRule {
+    pub pattern: Regex,
+    pub replacement: String,
+}
+
+impl PathRule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pattern, replacement) = spec
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Expected REGEX=REPLACEMENT, got '{}'", spec))?;
+        let pattern = Regex::new(pattern).map_err(|e| format!("Invalid path rule regex: {}", e))?;
+        Ok(Self { pattern, replacement: replacement.to_string() })
+    }
+}
+
+// Collapses request paths into endpoint templates so `/users/123/orders/456`
+// and `/users/7/orders/8` group together as `/users/{id}/orders/{id}`.
+#[derive(Debug, Clone, Default)]
+pub struct PathNormalizer {
+    rules: Vec<PathRule>,
+}
+
+impl PathNormalizer {
+    pub fn new(rules: Vec<PathRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn normalize(&self, path: &str) -> String {
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+
+        let mut path = path.to_string();
+        for rule in &self.rules {
+            if let Cow::Owned(rewritten) =
+                rule.pattern.replace_all(&path, rule.replacement.as_str())
+            {
+                path = rewritten;
+            }
+        }
+
+        path.split('/')
+            .map(|segment| if is_id_segment(segment) { ID_PLACEHOLDER } else { segment })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+// numbers, UUIDs, hashes and long random tokens; words like `v2` or `api`
+// are kept
+fn is_id_segment(segment: &str) -> bool {
+    let len = segment.len();
+    if len == 0 || segment.contains('{') {
+        return false;
+    }
+    if segment.bytes().all(|b| b.is_ascii_digit()) {
+        return true;
+    }
+
+    let hex_or_dash = segment.bytes().all(|b| b.is_ascii_hexdigit() || b == b'-');
+    let digits = segment.bytes().filter(|b| b.is_ascii_digit()).count();
+    if hex_or_dash && len >= 8 && digits > 0 {
+        return true;
+    }
+
+    len >= 16
+        && digits >= 2
+        && segment.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+// `--endpoint-weights 'errors=0.5,latency=0.3,volume=0.2'`: how much each
+// signal counts towards the worst-endpoint score. Keys left out keep their
+// default weight.
+#[derive(Debug, Clone, Copy)]
+pub struct RankWeights {
+    pub errors: f64,
+    pub latency: f64,
+    pub volume: f64,
+}
+
+impl Default for RankWeights {
+    fn default() -> Self {
+        Self { errors: 0.5, latency: 0.3, volume: 0.2 }
+    }
+}
+
+impl RankWeights {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut weights = Self::default();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected KEY=WEIGHT, got '{}'", part))?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|v: &f64| *v >= 0.0)
+                .ok_or_else(|| format!("Invalid weight: '{}'", value.trim()))?;
+            match key.trim() {
+                "errors" => weights.errors = value,
+                "latency" => weights.latency = value,
+                "volume" => weights.volume = value,
+                other => {
+                    return Err(format!("Unknown weight '{}' (expected errors, latency or volume)", other))
+                }
+            }
+        }
+        Ok(weights)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct EndpointOptions {
+    pub normalizer: PathNormalizer,
+    pub limit: usize,
+    pub weights: RankWeights,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStats {
+    pub method: String,
+    pub template: String,
+    pub requests: usize,
+    pub client_errors: usize,
+    pub server_errors: usize,
+    // raw paths folded into this template
+    pub distinct_paths: usize,
+    // None when the log carries no response times
+    pub p95_ms: Option<f64>,
+    pub score: f64,
+}
+
+impl EndpointStats {
+    pub fn server_error_rate(&self) -> f64 {
+        self.server_errors as f64 / self.requests as f64
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct EndpointSummary {
+    // busiest first
+    pub endpoints: Vec<EndpointStats>,
+    // highest score first; only endpoints with 5xx responses or latencies
+    pub worst: Vec<EndpointStats>,
+    pub distinct_paths: usize,
+    pub distinct_templates: usize,
+}
+
+impl EndpointSummary {
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+}
+
+struct Request<'a> {
+    method: &'a str,
+    path: &'a str,
+    template: String,
+    status: u16,
+    latency_ms: Option<f64>,
+}
+
+// Requests are the entries with a numeric `status` and a `path` field, as
+// the HTTP section counts them: access log lines and JSON logs alike.
+pub fn summarize(entries: &[LogEntry], opts: &EndpointOptions) -> EndpointSummary {
+    let requests: Vec<Request> = entries
+        .par_iter()
+        .filter_map(|entry| {
+            let field = |name: &str| entry.fields.get(name).and_then(|v| v.as_str());
+            let path = field("path")?;
+            Some(Request {
+                method: field("method").unwrap_or("-"),
+                path,
+                template: opts.normalizer.normalize(path),
+                status: entry.fields.get("status")?.as_u64()?.try_into().ok()?,
+                latency_ms: entry.fields.get("response_ms").and_then(|v| v.as_f64()),
+            })
+        })
+        .collect();
+
+    struct Acc<'a> {
+        requests: usize,
+        client_errors: usize,
+        server_errors: usize,
+        paths: HashSet<&'a str>,
+        latencies: Vec<f64>,
+    }
+
+    let mut by_template: HashMap<(&str, String), Acc> = HashMap::new();
+    let mut all_paths: HashSet<&str> = HashSet::new();
+    for req in requests {
+        let acc = by_template.entry((req.method, req.template)).or_insert_with(|| Acc {
+            requests: 0,
+            client_errors: 0,
+            server_errors: 0,
+            paths: HashSet::new(),
+            latencies: Vec::new(),
+        });
+        acc.requests += 1;
+        match req.status {
+            400..=499 => acc.client_errors += 1,
+            500..=599 => acc.server_errors += 1,
+            _ => {}
+        }
+        acc.paths.insert(req.path);
+        acc.latencies.extend(req.latency_ms);
+        all_paths.insert(req.path);
+    }
+
+    let distinct_templates = by_template.len();
+    let mut endpoints: Vec<EndpointStats> = by_template
+        .into_iter()
+        .map(|((method, template), mut acc)| {
+            acc.latencies.sort_unstable_by(|a, b| a.total_cmp(b));
+            EndpointStats {
+                method: method.to_string(),
+                template,
+                requests: acc.requests,
+                client_errors: acc.client_errors,
+                server_errors: acc.server_errors,
+                distinct_paths: acc.paths.len(),
+                p95_ms: (!acc.latencies.is_empty()).then(|| percentile(&acc.latencies, 0.95)),
+                score: 0.0,
+            }
+        })
+        .collect();
+    score(&mut endpoints, &opts.weights);
+
+    endpoints.sort_unstable_by(|a, b| {
+        b.requests
+            .cmp(&a.requests)
+            .then_with(|| a.template.cmp(&b.template))
+            .then_with(|| a.method.cmp(&b.method))
+    });
+
+    let mut worst: Vec<EndpointStats> = endpoints
+        .iter()
+        .filter(|e| e.server_errors > 0 || e.p95_ms.is_some())
+        .cloned()
+        .collect();
+    worst.sort_by(|a, b| b.score.total_cmp(&a.score));
+    worst.truncate(opts.limit);
+    endpoints.truncate(opts.limit);
+
+    EndpointSummary { endpoints, worst, distinct_paths: all_paths.len(), distinct_templates }
+}
+
+// Weighted sum of the 5xx rate, p95 latency relative to the slowest endpoint
+// and traffic on a log scale relative to the busiest one, so a rarely hit
+// endpoint that failed once doesn't outrank a busy one failing steadily.
+fn score(endpoints: &mut [EndpointStats], weights: &RankWeights) {
+    let max_p95 = endpoints.iter().filter_map(|e| e.p95_ms).fold(0.0, f64::max);
+    let max_requests = endpoints.iter().map(|e| e.requests).max().unwrap_or(0);
+    let volume_scale = (1.0 + max_requests as f64).ln();
+
+    for e in endpoints {
+        let latency = match e.p95_ms {
+            Some(p95) if max_p95 > 0.0 => p95 / max_p95,
+            _ => 0.0,
+        };
+        let volume = if volume_scale > 0.0 {
+            (1.0 + e.requests as f64).ln() / volume_scale
+        } else {
+            0.0
+        };
+        e.score = weights.errors * e.server_error_rate()
+            + weights.latency * latency
+            + weights.volume * volume;
+    }
+}
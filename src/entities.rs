@@ -0,0 +1,131 @@
+use rayon::prelude::*;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::parser::{LogEntry, LogLevel};
+
+#[derive(Debug, Serialize)]
+pub struct EntityCount {
+    pub value: String,
+    pub count: usize,
+    pub error_count: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct EntityStats {
+    pub ips: Vec<EntityCount>,
+    pub urls: Vec<EntityCount>,
+    pub paths: Vec<EntityCount>,
+    pub emails: Vec<EntityCount>,
+}
+
+impl EntityStats {
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty() && self.urls.is_empty() && self.paths.is_empty() && self.emails.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Ip,
+    Url,
+    Path,
+    Email,
+}
+
+type Counts = HashMap<(Kind, String), (usize, usize)>;
+
+pub struct EntityExtractor {
+    ip_re: Regex,
+    url_re: Regex,
+    path_re: Regex,
+    email_re: Regex,
+}
+
+impl Default for EntityExtractor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EntityExtractor {
+    pub fn new() -> Self {
+        Self {
+            ip_re: Regex::new(
+                r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b",
+            )
+            .unwrap(),
+            url_re: Regex::new(r#"\b(?:https?|ftp)://[^\s"'<>]+"#).unwrap(),
+            // unix paths with at least two components, or drive-letter paths
+            path_re: Regex::new(
+                r#"(?:^|[\s"'=(\[])((?:/[\w.-]+){2,}/?|[A-Za-z]:\\[\w.\\-]+)"#,
+            )
+            .unwrap(),
+            email_re: Regex::new(r"\b[\w.%+-]+@[\w-]+(?:\.[\w-]+)*\.[A-Za-z]{2,}\b").unwrap(),
+        }
+    }
+
+    pub fn extract(&self, entries: &[LogEntry], limit: usize) -> EntityStats {
+        let counts: Counts = entries
+            .par_iter()
+            .fold(HashMap::new, |mut acc: Counts, entry| {
+                let is_error = matches!(entry.level, LogLevel::Error | LogLevel::Fatal);
+                self.scan(&entry.message, |kind, value| {
+                    let slot = acc.entry((kind, value.to_string())).or_insert((0, 0));
+                    slot.0 += 1;
+                    if is_error {
+                        slot.1 += 1;
+                    }
+                });
+                acc
+            })
+            .reduce(HashMap::new, |mut a, b| {
+                for (k, (n, e)) in b {
+                    let slot = a.entry(k).or_insert((0, 0));
+                    slot.0 += n;
+                    slot.1 += e;
+                }
+                a
+            });
+
+        let mut stats = EntityStats::default();
+        for ((kind, value), (count, error_count)) in counts {
+            let bucket = match kind {
+                Kind::Ip => &mut stats.ips,
+                Kind::Url => &mut stats.urls,
+                Kind::Path => &mut stats.paths,
+                Kind::Email => &mut stats.emails,
+            };
+            bucket.push(EntityCount { value, count, error_count });
+        }
+
+        for bucket in [&mut stats.ips, &mut stats.urls, &mut stats.paths, &mut stats.emails] {
+            bucket.sort_unstable_by(|a, b| b.count.cmp(&a.count).then(a.value.cmp(&b.value)));
+            bucket.truncate(limit);
+        }
+
+        stats
+    }
+
+    fn scan(&self, message: &str, mut emit: impl FnMut(Kind, &str)) {
+        for m in self.url_re.find_iter(message) {
+            emit(Kind::Url, m.as_str().trim_end_matches(['.', ',', ')', ';', ':']));
+        }
+
+        // URLs are blanked so their hosts and paths aren't counted twice
+        let rest = self.url_re.replace_all(message, " ");
+
+        for m in self.email_re.find_iter(&rest) {
+            emit(Kind::Email, m.as_str());
+        }
+        for m in self.ip_re.find_iter(&rest) {
+            emit(Kind::Ip, m.as_str());
+        }
+        for caps in self.path_re.captures_iter(&rest) {
+            if let Some(m) = caps.get(1) {
+                emit(Kind::Path, m.as_str().trim_end_matches(['.', ',', ':']));
+            }
+        }
+    }
+}
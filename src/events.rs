@@ -0,0 +1,49 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+// `--stream-json`: newline-delimited JSON events on stdout, one object per
+// line with an "event" field, so a wrapper can show progress as it happens.
+// When disabled, status messages print as plain text and events are dropped.
+pub struct EventStream {
+    out: Option<Mutex<io::Stdout>>,
+}
+
+impl EventStream {
+    pub fn new(enabled: bool) -> Self {
+        Self { out: enabled.then(|| Mutex::new(io::stdout())) }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.out.is_some()
+    }
+
+    // `fields` must serialize to a JSON object
+    pub fn emit(&self, event: &str, fields: impl Serialize) {
+        let Some(ref out) = self.out else {
+            return;
+        };
+
+        let mut value = serde_json::to_value(fields).unwrap_or(Value::Null);
+        let mut frame = serde_json::Map::new();
+        frame.insert("event".to_string(), json!(event));
+        if let Value::Object(ref mut map) = value {
+            frame.append(map);
+        }
+
+        let mut out = out.lock().unwrap_or_else(|e| e.into_inner());
+        // a consumer that went away is not worth failing the analysis for
+        let _ = serde_json::to_writer(&mut *out, &frame);
+        let _ = writeln!(out);
+        let _ = out.flush();
+    }
+
+    pub fn status(&self, message: &str) {
+        if self.enabled() {
+            self.emit("status", json!({ "message": message }));
+        } else {
+            println!("{}", message);
+        }
+    }
+}
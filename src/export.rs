@@ -1,70 +1,596 @@
-use serde_json;
-use std::fs::File;
+use clap::ValueEnum;
+#[cfg(feature = "parquet")]
+use parquet::basic::{Compression, ZstdLevel};
+#[cfg(feature = "parquet")]
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+#[cfg(feature = "parquet")]
+use parquet::format::KeyValue;
+#[cfg(feature = "parquet")]
+use parquet::file::writer::SerializedFileWriter;
+#[cfg(feature = "parquet")]
+use parquet::schema::parser::parse_message_type;
+#[cfg(feature = "sqlite")]
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::{self, File, OpenOptions};
 use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
 
 use crate::analyzer::LogAnalysis;
-use crate::parser::LogEntry;
+use crate::buckets::Zone;
+use crate::custody;
+#[cfg(feature = "parquet")]
+use crate::custody::Custody;
+use crate::dashboard;
+use crate::labeling;
+use crate::parser::{LogEntry, LogLevel};
+use crate::prometheus;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ExportFormat {
     Json,
     Csv,
+    Jsonl, // one entry per line
+    Sqlite,
+    Parquet,
+    Html, // self-contained dashboard
+    L2tCsv, // log2timeline/psort columns, for forensic timeline tools
+    Prometheus, // OpenMetrics text, or a Pushgateway push when the output is a URL
+    Labeling, // a stratified sample as csv, with an empty column to label
 }
 
 impl ExportFormat {
+    // not FromStr: an unknown name is just None
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "json" => Some(Self::Json),
             "csv" => Some(Self::Csv),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            "sqlite" | "db" => Some(Self::Sqlite),
+            "parquet" => Some(Self::Parquet),
+            "html" | "dashboard" => Some(Self::Html),
+            "l2tcsv" | "l2t" => Some(Self::L2tCsv),
+            "prometheus" | "openmetrics" => Some(Self::Prometheus),
+            "labeling" | "labelling" => Some(Self::Labeling),
             _ => None,
         }
     }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            Self::Json => "json",
+            Self::Csv | Self::L2tCsv | Self::Labeling => "csv",
+            Self::Jsonl => "jsonl",
+            Self::Sqlite => "db",
+            Self::Parquet => "parquet",
+            Self::Html => "html",
+            Self::Prometheus => "prom",
+        }
+    }
+
+    // whether the output is a flat list of entries that can be split or appended to
+    fn is_entry_stream(&self) -> bool {
+        !matches!(self, Self::Json | Self::Sqlite | Self::Parquet | Self::Html | Self::Prometheus | Self::Labeling)
+    }
+
+    // JSON and Prometheus hold the analysis, not entries
+    pub fn exports_entries(&self) -> bool {
+        !matches!(self, Self::Json | Self::Prometheus)
+    }
+
+    // formats with room for the --evidence-mode record inside the file; the
+    // others get it beside them
+    fn embeds_custody(&self) -> bool {
+        matches!(self, Self::Json | Self::Sqlite | Self::Parquet | Self::Html)
+    }
+
+    // the cargo feature this build was made without that the format needs
+    pub fn missing_feature(&self) -> Option<&'static str> {
+        match self {
+            Self::Sqlite if !cfg!(feature = "sqlite") => Some("sqlite"),
+            Self::Parquet if !cfg!(feature = "parquet") => Some("parquet"),
+            _ => None,
+        }
+    }
+}
+
+// the columns psort's l2tcsv output has, which timeline tools import
+const L2T_HEADER: &str = "date,time,timezone,MACB,source,sourcetype,type,user,host,short,desc,version,filename,inode,notes,format,extra";
+const L2T_SHORT_CHARS: usize = 80;
+
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "message log_entry {
+    REQUIRED INT64 timestamp (TIMESTAMP(MICROS, false));
+    REQUIRED BYTE_ARRAY level (STRING);
+    OPTIONAL BYTE_ARRAY source (STRING);
+    REQUIRED BYTE_ARRAY message (STRING);
+    REQUIRED INT64 line_number;
+    REQUIRED BYTE_ARRAY hash (STRING);
+}";
+#[cfg(feature = "parquet")]
+const PARQUET_ROW_GROUP: usize = 128 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Partition {
+    Hour,
+    Day,
+    Level,
+}
+
+impl Partition {
+    // path of the partition file for `entry`, relative to the output
+    // directory; hours and days are on `zone`'s wall clock
+    fn relative_path(&self, entry: &LogEntry, format: ExportFormat, zone: Zone) -> PathBuf {
+        let ext = format.extension();
+        let t = zone.local(entry.timestamp);
+        match self {
+            Self::Hour => PathBuf::from(t.format("%Y-%m-%d").to_string()).join(format!("{}.{}", t.format("%H"), ext)),
+            Self::Day => PathBuf::from(format!("{}.{}", t.format("%Y-%m-%d"), ext)),
+            Self::Level => PathBuf::from(format!("{}.{}", entry.level.as_str().to_lowercase(), ext)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExportOptions {
+    pub partition: Option<Partition>,
+    pub append: bool,
+    pub zone: Zone,
+    // `--label-sample` / `--label-seed`, for the labeling format
+    pub label_sample: usize,
+    pub label_seed: u64,
+}
+
+// Per-level keep rates for exported entries, e.g. `debug=0.01,info=0.1`.
+// Levels not listed are kept in full.
+#[derive(Debug, Clone, Default)]
+pub struct SampleRates {
+    rates: HashMap<LogLevel, f64>,
 }
 
+impl SampleRates {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut rates = HashMap::new();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (level, rate) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected LEVEL=RATE, got '{}'", part))?;
+
+            let level = LogLevel::from_str(level.trim());
+            if level == LogLevel::Unknown {
+                return Err(format!("Unknown level in sample spec: '{}'", part));
+            }
+
+            let rate: f64 = rate
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid sample rate: '{}'", rate.trim()))?;
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("Sample rate must be between 0 and 1: '{}'", part));
+            }
+
+            rates.insert(level, rate);
+        }
+        Ok(Self { rates })
+    }
+
+    // Deterministic: the same entry is kept or dropped on every run, with
+    // any build, so repeated exports of one file produce the same artifact.
+    pub fn keeps(&self, entry: &LogEntry) -> bool {
+        let rate = *self.rates.get(&entry.level).unwrap_or(&1.0);
+        if rate >= 1.0 {
+            return true;
+        }
+        if rate <= 0.0 {
+            return false;
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update((entry.line_number as u64).to_le_bytes());
+        hasher.update(&entry.message);
+        let digest = hasher.finalize();
+        let mut head = [0; 8];
+        head.copy_from_slice(&digest[..8]);
+        (u64::from_le_bytes(head) as f64 / u64::MAX as f64) < rate
+    }
+
+    pub fn apply(&self, entries: &[LogEntry]) -> Vec<LogEntry> {
+        entries.iter().filter(|e| self.keeps(e)).cloned().collect()
+    }
+}
+
+/// Writes `analysis` and `entries` to `output_path` in `format`, adding the
+/// custody record when the analysis has one. Returns the files that were
+/// written, for the manifest.
 pub fn export_analysis(
     analysis: &LogAnalysis,
     entries: &[LogEntry],
     format: ExportFormat,
     output_path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    match format {
-        ExportFormat::Json => export_json(analysis, entries, output_path),
-        ExportFormat::Csv => export_csv(entries, output_path),
+    options: ExportOptions,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    if let Some(feature) = format.missing_feature() {
+        return Err(format!("{} exports need logscope built with --features {}", feature, feature).into());
+    }
+    if (options.partition.is_some() || options.append) && !format.is_entry_stream() {
+        return Err("partitioned and appended exports require csv, jsonl or l2tcsv".into());
+    }
+
+    if analysis.custody.is_some() && format == ExportFormat::Prometheus && prometheus::is_push(output_path) {
+        return Err("--evidence-mode can't record custody for a Pushgateway push; export to a file".into());
+    }
+
+    let mut files = match format {
+        ExportFormat::Json => {
+            export_json(analysis, output_path)?;
+            vec![PathBuf::from(output_path)]
+        }
+        #[cfg(feature = "sqlite")]
+        ExportFormat::Sqlite => {
+            export_sqlite(analysis, entries, Path::new(output_path))?;
+            vec![PathBuf::from(output_path)]
+        }
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet => {
+            export_parquet(entries, Path::new(output_path), analysis.custody.as_ref())?;
+            vec![PathBuf::from(output_path)]
+        }
+        #[cfg(not(feature = "sqlite"))]
+        ExportFormat::Sqlite => unreachable!("refused above"),
+        #[cfg(not(feature = "parquet"))]
+        ExportFormat::Parquet => unreachable!("refused above"),
+        ExportFormat::Html => {
+            dashboard::write(analysis, entries, Path::new(output_path), options.zone)?;
+            vec![PathBuf::from(output_path)]
+        }
+        ExportFormat::Prometheus => prometheus::export(analysis, output_path)?,
+        ExportFormat::Labeling => {
+            export_labeling(entries, Path::new(output_path), options)?;
+            vec![PathBuf::from(output_path)]
+        }
+        _ => match options.partition {
+            Some(partition) => {
+                export_partitioned(entries, format, Path::new(output_path), partition, options, &source_file(analysis))?
+            }
+            None => {
+                let mut sink = EntrySink::open(Path::new(output_path), format, options, &source_file(analysis))?;
+                for entry in entries {
+                    sink.write(entry)?;
+                }
+                sink.finish()?;
+                vec![PathBuf::from(output_path)]
+            }
+        },
+    };
+    if let (Some(ref custody), false) = (&analysis.custody, format.embeds_custody()) {
+        let path = custody::sidecar_path(output_path, options.partition.is_some());
+        custody.write(&path)?;
+        files.push(path);
     }
+    Ok(files)
 }
 
-fn export_json(
-    analysis: &LogAnalysis,
-    _entries: &[LogEntry],
-    path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+// Entries don't remember which input they came from, so only a single
+// input can be named per entry.
+fn source_file(analysis: &LogAnalysis) -> String {
+    match analysis.parse_stats.files.as_slice() {
+        [(path, _)] => path.clone(),
+        _ => "-".to_string(),
+    }
+}
+
+fn export_json(analysis: &LogAnalysis, path: &str) -> Result<(), Box<dyn std::error::Error>> {
     let file = File::create(path)?;
     let writer = BufWriter::new(file);
     serde_json::to_writer_pretty(writer, analysis)?;
     Ok(())
 }
 
-fn export_csv(
-    entries: &[LogEntry],
-    path: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::create(path)?;
-    let mut writer = BufWriter::new(file);
+// One row per entry in `entries` (extra JSON fields as a JSON column) and
+// one per top-level section of the analysis in `summary`, as JSON, so both
+// can be queried with SQLite's json_extract. An existing file is replaced.
+#[cfg(feature = "sqlite")]
+fn export_sqlite(analysis: &LogAnalysis, entries: &[LogEntry], path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    let mut conn = Connection::open(path)?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        "CREATE TABLE entries (
+             line_number INTEGER NOT NULL,
+             timestamp TEXT NOT NULL,
+             level TEXT NOT NULL,
+             source TEXT,
+             message TEXT NOT NULL,
+             fields TEXT,
+             hash TEXT NOT NULL
+         );
+         CREATE TABLE summary (section TEXT PRIMARY KEY, value TEXT NOT NULL);",
+    )?;
 
-    writeln!(writer, "timestamp,level,source,message")?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO entries (line_number, timestamp, level, source, message, fields, hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for entry in entries {
+            let fields = if entry.fields.is_empty() { None } else { Some(serde_json::to_string(&entry.fields)?) };
+            insert.execute(params![
+                entry.line_number as i64,
+                entry.timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string(),
+                entry.level.as_str(),
+                entry.source.as_deref(),
+                entry.message,
+                fields,
+                entry.content_hash(),
+            ])?;
+        }
+
+        let mut insert = tx.prepare("INSERT INTO summary (section, value) VALUES (?1, ?2)")?;
+        if let serde_json::Value::Object(sections) = serde_json::to_value(analysis)? {
+            for (section, value) in sections {
+                insert.execute(params![section, value.to_string()])?;
+            }
+        }
+    }
+
+    // built after the bulk insert, which is faster than keeping them current
+    tx.execute_batch(
+        "CREATE INDEX entries_timestamp ON entries (timestamp);
+         CREATE INDEX entries_level ON entries (level);
+         CREATE INDEX entries_hash ON entries (hash);",
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Columns in the order of PARQUET_SCHEMA, zstd-compressed, one row group
+// per PARQUET_ROW_GROUP entries. Timestamps are microseconds, not adjusted
+// to UTC since log timestamps carry no zone.
+// With --evidence-mode the custody record goes in the file's key-value
+// metadata as `logscope.custody`.
+#[cfg(feature = "parquet")]
+fn export_parquet(entries: &[LogEntry], path: &Path, custody: Option<&Custody>) -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let metadata = custody
+        .map(|c| serde_json::to_string(c).map(|json| vec![KeyValue::new("logscope.custody".to_string(), json)]))
+        .transpose()?;
+    let props = WriterProperties::builder()
+        .set_compression(Compression::ZSTD(ZstdLevel::try_new(3)?))
+        .set_key_value_metadata(metadata)
+        .build();
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, Arc::new(props))?;
+
+    for chunk in entries.chunks(PARQUET_ROW_GROUP) {
+        let mut row_group = writer.next_row_group()?;
+        let mut column = 0;
+        while let Some(mut col) = row_group.next_column()? {
+            match column {
+                0 => {
+                    let values: Vec<i64> = chunk.iter().map(|e| e.timestamp.and_utc().timestamp_micros()).collect();
+                    col.typed::<Int64Type>().write_batch(&values, None, None)?;
+                }
+                1 => {
+                    let values: Vec<ByteArray> = chunk.iter().map(|e| e.level.as_str().into()).collect();
+                    col.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+                }
+                2 => {
+                    // optional: definition level 0 marks a missing source
+                    let values: Vec<ByteArray> = chunk.iter().filter_map(|e| e.source.as_deref()).map(ByteArray::from).collect();
+                    let defined: Vec<i16> = chunk.iter().map(|e| e.source.is_some() as i16).collect();
+                    col.typed::<ByteArrayType>().write_batch(&values, Some(&defined), None)?;
+                }
+                3 => {
+                    let values: Vec<ByteArray> = chunk.iter().map(|e| e.message.as_str().into()).collect();
+                    col.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+                }
+                4 => {
+                    let values: Vec<i64> = chunk.iter().map(|e| e.line_number as i64).collect();
+                    col.typed::<Int64Type>().write_batch(&values, None, None)?;
+                }
+                _ => {
+                    let values: Vec<ByteArray> = chunk.iter().map(|e| e.content_hash().into_bytes().into()).collect();
+                    col.typed::<ByteArrayType>().write_batch(&values, None, None)?;
+                }
+            }
+            col.close()?;
+            column += 1;
+        }
+        row_group.close()?;
+    }
+
+    writer.close()?;
+    Ok(())
+}
+
+// `dir` is a directory; each entry goes to the file for its partition key.
+// Entries are time-sorted, so hour/day partitions are written one at a time.
+fn export_partitioned(
+    entries: &[LogEntry],
+    format: ExportFormat,
+    dir: &Path,
+    partition: Partition,
+    options: ExportOptions,
+    source_file: &str,
+) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut sinks: HashMap<PathBuf, EntrySink> = HashMap::new();
+    let mut written = BTreeSet::new();
 
     for entry in entries {
-        let source = entry.source.as_deref().unwrap_or("");
-        let msg = entry.message.replace('"', "\"\"");
-        writeln!(
-            writer,
-            "{},{},{},\"{}\"",
-            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
-            entry.level.as_str(),
-            source,
-            msg,
-        )?;
+        let path = dir.join(partition.relative_path(entry, format, options.zone));
+        if !sinks.contains_key(&path) {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            if partition != Partition::Level {
+                // earlier partitions are complete; don't hold a handle per hour
+                for (_, sink) in sinks.drain() {
+                    sink.finish()?;
+                }
+            }
+            let sink = EntrySink::open(&path, format, options, source_file)?;
+            sinks.insert(path.clone(), sink);
+            written.insert(path.clone());
+        }
+        sinks.get_mut(&path).expect("sink opened above").write(entry)?;
+    }
+
+    for (_, sink) in sinks {
+        sink.finish()?;
     }
+    Ok(written.into_iter().collect())
+}
+
+struct EntrySink {
+    writer: BufWriter<File>,
+    format: ExportFormat,
+    zone: Zone,
+    source_file: String,
+}
 
+impl EntrySink {
+    fn open(
+        path: &Path,
+        format: ExportFormat,
+        options: ExportOptions,
+        source_file: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let file = if options.append {
+            OpenOptions::new().create(true).append(true).open(path)?
+        } else {
+            File::create(path)?
+        };
+        // appending to an existing csv must not repeat the header
+        let needs_header = file.metadata()?.len() == 0;
+
+        let mut writer = BufWriter::new(file);
+        match format {
+            ExportFormat::Csv if needs_header => writeln!(writer, "timestamp,level,source,message,hash")?,
+            ExportFormat::L2tCsv if needs_header => writeln!(writer, "{}", L2T_HEADER)?,
+            _ => {}
+        }
+        Ok(Self { writer, format, zone: options.zone, source_file: source_file.to_string() })
+    }
+
+    fn write(&mut self, entry: &LogEntry) -> Result<(), Box<dyn std::error::Error>> {
+        match self.format {
+            ExportFormat::Csv => {
+                let source = entry.source.as_deref().unwrap_or("");
+                let msg = entry.message.replace('"', "\"\"");
+                writeln!(
+                    self.writer,
+                    "{},{},{},\"{}\",{}",
+                    entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    entry.level.as_str(),
+                    source,
+                    msg,
+                    entry.content_hash(),
+                )?;
+            }
+            ExportFormat::Jsonl => {
+                serde_json::to_writer(&mut self.writer, &Hashed { hash: entry.content_hash(), entry })?;
+                writeln!(self.writer)?;
+            }
+            ExportFormat::L2tCsv => self.write_l2t(entry)?,
+            ExportFormat::Json
+            | ExportFormat::Sqlite
+            | ExportFormat::Parquet
+            | ExportFormat::Html
+            | ExportFormat::Prometheus
+            | ExportFormat::Labeling => unreachable!("not an entry stream"),
+        }
+        Ok(())
+    }
+
+    // Times are on the --tz wall clock, named in the timezone column; without
+    // --tz they are as logged and the zone is unknown ("-").
+    fn write_l2t(&mut self, entry: &LogEntry) -> Result<(), Box<dyn std::error::Error>> {
+        let t = self.zone.local(entry.timestamp);
+        let source = entry.source.as_deref().unwrap_or("-");
+        let host = match entry.fields.get("host").or_else(|| entry.fields.get("hostname")) {
+            Some(serde_json::Value::String(h)) => h.clone(),
+            _ => "-".to_string(),
+        };
+        let message = entry.message.replace(['\r', '\n'], " ");
+        let short: String = message.chars().take(L2T_SHORT_CHARS).collect();
+        let desc = format!("[{}] {}: {}", entry.level.as_str(), source, message);
+        let extra = format!("level: {}; line: {}; hash: {}", entry.level.as_str(), entry.line_number, entry.content_hash());
+        let fields = [
+            t.format("%m/%d/%Y").to_string(),
+            t.format("%H:%M:%S").to_string(),
+            self.zone.label().unwrap_or_else(|| "-".to_string()),
+            "....".to_string(),
+            "LOG".to_string(),
+            format!("logscope {}", source),
+            "Entry Written".to_string(),
+            "-".to_string(),
+            host,
+            short,
+            desc,
+            "2".to_string(),
+            self.source_file.clone(),
+            "-".to_string(),
+            "-".to_string(),
+            "logscope".to_string(),
+            extra,
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        writeln!(self.writer, "{}", row.join(","))?;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+// `id` is for joining labels back; `weight` is how many entries of the log
+// each one stands for. The label column is left for the reviewer.
+fn export_labeling(entries: &[LogEntry], path: &Path, options: ExportOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "id,timestamp,level,source,template,stratum_size,weight,message,label")?;
+    for (id, picked) in labeling::sample(entries, options.label_sample, options.label_seed).iter().enumerate() {
+        let entry = picked.entry;
+        let row = [
+            (id + 1).to_string(),
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            entry.level.as_str().to_string(),
+            entry.source.as_deref().unwrap_or("").to_string(),
+            picked.template.clone(),
+            picked.stratum_size.to_string(),
+            format!("{:.2}", picked.weight),
+            entry.message.clone(),
+            String::new(),
+        ];
+        let row: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    writer.flush()?;
     Ok(())
 }
+
+// an entry as JSONL has it, with its content hash
+#[derive(Serialize)]
+struct Hashed<'a> {
+    #[serde(flatten)]
+    entry: &'a LogEntry,
+    hash: String,
+}
+
+// quoted only when it has to be
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
@@ -1,34 +1,179 @@
-use chrono::NaiveDateTime;
-use regex::Regex;
+use chrono::{Duration, NaiveDateTime, NaiveTime};
+use clap::ValueEnum;
+use glob::{MatchOptions, Pattern};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
 
 use crate::parser::{LogEntry, LogLevel};
+use crate::schema::FieldCondition;
 
+// One `--source-include`/`--source-exclude` pattern, matched against the
+// whole source name, case-insensitively: `cron` exactly, `health*` as a glob
+// (`*` and `?`), `re:^worker-\d+$` as a regex. `--source` keeps its older
+// meaning, any part of the name.
+#[derive(Debug, Clone)]
+pub enum SourceMatcher {
+    Contains(String),
+    Exact(String),
+    Glob(Pattern),
+    Regex(Regex),
+}
+
+impl SourceMatcher {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return Err("Empty source pattern".to_string());
+        }
+        if let Some(re) = spec.strip_prefix("re:") {
+            return Regex::new(&format!("(?i){}", re))
+                .map(Self::Regex)
+                .map_err(|e| format!("Invalid source regex '{}': {}", re, e));
+        }
+        if spec.contains(['*', '?', '[']) {
+            return Pattern::new(spec)
+                .map(Self::Glob)
+                .map_err(|e| format!("Invalid source glob '{}': {}", spec, e));
+        }
+        Ok(Self::Exact(spec.to_lowercase()))
+    }
+
+    pub fn containing(text: &str) -> Self {
+        Self::Contains(text.to_lowercase())
+    }
+
+    pub fn is_match(&self, source: &str) -> bool {
+        match self {
+            Self::Contains(text) => source.to_lowercase().contains(text.as_str()),
+            Self::Exact(name) => source.to_lowercase() == *name,
+            Self::Glob(pattern) => pattern.matches_with(source, IGNORE_CASE),
+            Self::Regex(re) => re.is_match(source),
+        }
+    }
+}
+
+const IGNORE_CASE: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+// One `--keyword`/`--exclude` pattern, found anywhere in the message,
+// case-insensitively: plain text literally, `re:REGEX` as a regex.
+#[derive(Debug, Clone)]
+pub struct TextPattern {
+    pub spec: String,
+    re: Regex,
+}
+
+impl TextPattern {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        if spec.is_empty() {
+            return Err("Empty pattern".to_string());
+        }
+        let pattern = match spec.strip_prefix("re:") {
+            Some(re) => re.to_string(),
+            None => regex::escape(spec),
+        };
+        let re = RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?;
+        Ok(Self { spec: spec.to_string(), re })
+    }
+
+    pub fn is_match(&self, message: &str) -> bool {
+        self.re.is_match(message)
+    }
+}
+
+// `--from`/`--to`: a timestamp, or a time relative to a reference — the
+// newest entry of the log, or the wall clock when following.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeBound {
+    At(NaiveDateTime),
+    // this long before the reference: `45m`, `2h ago`
+    Ago(Duration),
+    // midnight starting the reference's day, or the one before
+    Today,
+    Yesterday,
+}
+
+impl TimeBound {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        match spec.to_lowercase().as_str() {
+            "now" => return Ok(Self::Ago(Duration::zero())),
+            "today" => return Ok(Self::Today),
+            "yesterday" => return Ok(Self::Yesterday),
+            _ => {}
+        }
+        if let Ok(ts) = NaiveDateTime::parse_from_str(spec, "%Y-%m-%d %H:%M:%S") {
+            return Ok(Self::At(ts));
+        }
+        let span = spec.strip_suffix("ago").unwrap_or(spec).trim_end();
+        crate::generate::parse_span(span).map(Self::Ago).map_err(|_| {
+            format!("Invalid time '{}' (expected YYYY-MM-DD HH:MM:SS, a span like 45m or 2h, today or yesterday)", spec)
+        })
+    }
+
+    pub fn is_relative(&self) -> bool {
+        !matches!(self, Self::At(_))
+    }
+
+    pub fn resolve(&self, reference: NaiveDateTime) -> NaiveDateTime {
+        match *self {
+            Self::At(ts) => ts,
+            Self::Ago(span) => reference - span,
+            Self::Today => reference.date().and_time(NaiveTime::MIN),
+            Self::Yesterday => reference.date().and_time(NaiveTime::MIN) - Duration::days(1),
+        }
+    }
+}
+
+// `--keyword-mode`: whether several keywords must all be in the message or
+// any one of them
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum KeywordMode {
+    #[default]
+    All,
+    Any,
+}
+
+/// Which entries to keep: keywords, exclusions, time range, minimum level,
+/// sources and field conditions, all of which must pass. The default keeps
+/// everything. Use it with [`apply`], or hand it to
+/// [`LogParser::with_entry_filter`](crate::parser::LogParser::with_entry_filter)
+/// to drop entries while parsing.
+#[derive(Default)]
 pub struct FilterConfig {
-    pub keyword: Option<String>,
-    pub keyword_regex: Option<Regex>,
+    pub keywords: Vec<TextPattern>,
+    pub keyword_mode: KeywordMode,
+    // entries whose message matches any of these are dropped
+    pub exclude: Vec<TextPattern>,
     pub from: Option<NaiveDateTime>,
     pub to: Option<NaiveDateTime>,
     pub min_level: Option<u8>,
-    pub source: Option<String>,
+    // an entry must match one include (when there are any) and no exclude
+    pub source_include: Vec<SourceMatcher>,
+    pub source_exclude: Vec<SourceMatcher>,
+    // `--where`: every one must hold
+    pub fields: Vec<FieldCondition>,
 }
 
 impl FilterConfig {
     pub fn new() -> Self {
-        Self {
-            keyword: None,
-            keyword_regex: None,
-            from: None,
-            to: None,
-            min_level: None,
-            source: None,
-        }
+        Self::default()
     }
 
-    pub fn with_keyword(mut self, kw: String) -> Self {
-        if let Ok(re) = Regex::new(&format!("(?i){}", regex::escape(&kw))) {
-            self.keyword_regex = Some(re);
-        }
-        self.keyword = Some(kw);
+    pub fn with_keywords(mut self, keywords: Vec<TextPattern>, mode: KeywordMode) -> Self {
+        self.keywords = keywords;
+        self.keyword_mode = mode;
+        self
+    }
+
+    pub fn with_exclude(mut self, patterns: Vec<TextPattern>) -> Self {
+        self.exclude = patterns;
         self
     }
 
@@ -43,66 +188,183 @@ impl FilterConfig {
         self
     }
 
-    pub fn with_source(mut self, source: String) -> Self {
-        self.source = Some(source);
+    pub fn with_sources(mut self, include: Vec<SourceMatcher>, exclude: Vec<SourceMatcher>) -> Self {
+        self.source_include = include;
+        self.source_exclude = exclude;
+        self
+    }
+
+    pub fn with_fields(mut self, conditions: Vec<FieldCondition>) -> Self {
+        self.fields = conditions;
         self
     }
 
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        failed_stage(entry, self).is_none()
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.keyword.is_none()
+        self.keywords.is_empty()
+            && self.exclude.is_empty()
             && self.from.is_none()
             && self.to.is_none()
             && self.min_level.is_none()
-            && self.source.is_none()
+            && self.source_include.is_empty()
+            && self.source_exclude.is_empty()
+            && self.fields.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+    Keyword,
+    Exclude,
+    TimeRange,
+    Level,
+    Source,
+    Field,
+}
+
+// How many entries went into filtering and how many each stage removed. An
+// entry is charged to the first stage it fails, in the order they run;
+// duplicates dropped by the cross-file merge count as a stage of their own.
+// `input` is always `kept` plus every stage.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FilterStats {
+    pub input: usize,
+    pub kept: usize,
+    pub keyword: usize,
+    pub exclude: usize,
+    pub time_range: usize,
+    pub level: usize,
+    pub source: usize,
+    pub field: usize,
+    pub duplicates: usize,
+}
+
+impl FilterStats {
+    pub fn merge(&mut self, other: &FilterStats) {
+        self.input += other.input;
+        self.kept += other.kept;
+        self.keyword += other.keyword;
+        self.exclude += other.exclude;
+        self.time_range += other.time_range;
+        self.level += other.level;
+        self.source += other.source;
+        self.field += other.field;
+        self.duplicates += other.duplicates;
+    }
+
+    // the merge ran on what this stage kept (or on everything, if nothing was filtered while parsing)
+    pub fn deduplicated(&mut self, duplicates: usize) {
+        self.input += duplicates.saturating_sub(self.kept);
+        self.kept = self.kept.saturating_sub(duplicates);
+        self.duplicates += duplicates;
+    }
+
+    // `next` ran on what this stage kept (plus anything that skipped it)
+    pub fn then(&mut self, next: &FilterStats) {
+        self.input += next.input.saturating_sub(self.kept);
+        self.kept = next.kept;
+        self.keyword += next.keyword;
+        self.exclude += next.exclude;
+        self.time_range += next.time_range;
+        self.level += next.level;
+        self.source += next.source;
+        self.field += next.field;
+        self.duplicates += next.duplicates;
+    }
+
+    pub fn removed(&self) -> usize {
+        self.input - self.kept
+    }
+
+    // counts one entry
+    pub fn record(&mut self, outcome: Option<Stage>) {
+        self.input += 1;
+        match outcome {
+            None => self.kept += 1,
+            Some(Stage::Keyword) => self.keyword += 1,
+            Some(Stage::Exclude) => self.exclude += 1,
+            Some(Stage::TimeRange) => self.time_range += 1,
+            Some(Stage::Level) => self.level += 1,
+            Some(Stage::Source) => self.source += 1,
+            Some(Stage::Field) => self.field += 1,
+        }
     }
 }
 
 pub fn apply(entries: Vec<LogEntry>, config: &FilterConfig) -> Vec<LogEntry> {
+    apply_counted(entries, config, &mut FilterStats::default())
+}
+
+pub fn apply_counted(entries: Vec<LogEntry>, config: &FilterConfig, stats: &mut FilterStats) -> Vec<LogEntry> {
     if config.is_empty() {
+        stats.input += entries.len();
+        stats.kept += entries.len();
         return entries;
     }
 
     entries
         .into_iter()
-        .filter(|entry| matches_all(entry, config))
+        .filter(|entry| {
+            let outcome = failed_stage(entry, config);
+            stats.record(outcome);
+            outcome.is_none()
+        })
         .collect()
 }
 
-fn matches_all(entry: &LogEntry, config: &FilterConfig) -> bool {
-    if let Some(re) = &config.keyword_regex {
-        if !re.is_match(&entry.message) {
-            return false;
+pub fn failed_stage(entry: &LogEntry, config: &FilterConfig) -> Option<Stage> {
+    if !config.keywords.is_empty() {
+        let found = |p: &TextPattern| p.is_match(&entry.message);
+        let kept = match config.keyword_mode {
+            KeywordMode::All => config.keywords.iter().all(found),
+            KeywordMode::Any => config.keywords.iter().any(found),
+        };
+        if !kept {
+            return Some(Stage::Keyword);
         }
     }
 
+    if config.exclude.iter().any(|p| p.is_match(&entry.message)) {
+        return Some(Stage::Exclude);
+    }
+
     if let Some(from) = &config.from {
         if entry.timestamp < *from {
-            return false;
+            return Some(Stage::TimeRange);
         }
     }
 
     if let Some(to) = &config.to {
         if entry.timestamp > *to {
-            return false;
+            return Some(Stage::TimeRange);
         }
     }
 
     if let Some(min_sev) = config.min_level {
         if entry.level.severity() < min_sev {
-            return false;
+            return Some(Stage::Level);
         }
     }
 
-    if let Some(src) = &config.source {
-        match &entry.source {
-            Some(s) => {
-                if !s.to_lowercase().contains(&src.to_lowercase()) {
-                    return false;
-                }
-            }
-            None => return false,
+    if !config.source_include.is_empty() || !config.source_exclude.is_empty() {
+        // entries without a source only pass when nothing is included
+        let Some(source) = entry.source.as_deref() else {
+            return (!config.source_include.is_empty()).then_some(Stage::Source);
+        };
+        if !config.source_include.is_empty() && !config.source_include.iter().any(|m| m.is_match(source)) {
+            return Some(Stage::Source);
+        }
+        if config.source_exclude.iter().any(|m| m.is_match(source)) {
+            return Some(Stage::Source);
         }
     }
 
-    true
+    if !config.fields.iter().all(|c| c.is_match(entry)) {
+        return Some(Stage::Field);
+    }
+
+    None
 }
@@ -0,0 +1,50 @@
+use std::io;
+
+use crate::cli::FindHashArgs;
+use crate::parser::{LogEntry, LogFormat, LogParser, ParserLimits};
+use crate::xml::XmlLayout;
+
+// `find-hash`: the entries whose hash (see LogEntry::content_hash) starts
+// with `args.hash`, with the file each is in. Entries are parsed with the
+// options analyze takes for their level, source and message; redaction and
+// truncation don't matter, as hashes are taken before them.
+pub fn run(args: &FindHashArgs) -> io::Result<Vec<(String, LogEntry)>> {
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let parser = LogParser::with_format(format)
+        .with_limits(ParserLimits { max_line_len: args.max_line_len, max_message_len: None })
+        .with_archive_members(args.archive_member.clone())
+        .with_strip_ansi(!args.keep_ansi)
+        .with_torn_line_repair(args.repair_torn_lines)
+        .with_multiline(args.multiline, args.multiline_pattern.clone())
+        .with_level_map(&args.level_map)
+        .with_level_rules(&args.level_rule)
+        .with_xml_layout(XmlLayout::new(args.xml_record.as_deref(), &args.xml_field))
+        .with_slow_request(args.slow_request)
+        .with_latency_unit(args.latency_unit)
+        .with_custom_pattern(
+            args.pattern
+                .clone()
+                .map(|p| p.with_timestamp_format(args.timestamp_format.clone())),
+        );
+    let mut found = Vec::new();
+    for path in &args.file_paths {
+        let (entries, _) =
+            parser.parse_file_counted(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        found.extend(
+            entries
+                .into_iter()
+                .filter(|e| e.content_hash().starts_with(&args.hash))
+                .map(|e| (path.clone(), e)),
+        );
+    }
+    Ok(found)
+}
+
+// hex, at least 6 characters so a prefix doesn't match everything
+pub fn parse_hash(spec: &str) -> Result<String, String> {
+    let hash = spec.trim().to_lowercase();
+    if hash.len() < 6 || hash.len() > LogEntry::HASH_LEN || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(format!("Expected 6 to {} hex digits of an entry hash, got '{}'", LogEntry::HASH_LEN, spec));
+    }
+    Ok(hash)
+}
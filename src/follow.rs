@@ -0,0 +1,621 @@
+use chrono::Local;
+use std::fs::{File, Metadata};
+use std::io::{self, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::alerts::{Alert, AlertRule, AlertState, Alerter};
+use crate::analyzer::{LiveAnalyzer, LiveSnapshot};
+use crate::buckets::Zone;
+use crate::journal::JournalQuery;
+use crate::notify::{self, WebhookAlert};
+use crate::parser::{LogEntry, LogLevel, LogParser};
+use crate::report::{anomaly_label, Intake, LiveFrame, ReportGenerator};
+use crate::scoring::Composite;
+use crate::stats::BurstParams;
+
+const RECENT_ERRORS: usize = 5;
+// alert messages kept on the dashboard
+const RECENT_ALERTS: usize = 5;
+const BELL: &str = "\x07";
+// lines a FIFO or socket may have waiting before new ones are dropped
+const QUEUE_LINES: usize = 100_000;
+// read from one file per tick; the rest waits for the next one
+const TICK_BYTES: u64 = 16 * 1024 * 1024;
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+// entries this much newer than the newest one read wait a tick, so a slower
+// input's older lines can still be sorted in before them
+const REORDER_WINDOW: chrono::Duration = chrono::Duration::seconds(2);
+
+pub struct FollowOptions<'a> {
+    pub interval: Duration,
+    pub color: bool,
+    pub zone: Zone,
+    pub burst: BurstParams,
+    // `--listen-unix`: a datagram socket to bind and read lines from
+    pub listen_unix: Option<&'a str>,
+    // `--journal`: journalctl followed from now on
+    pub journal: Option<&'a JournalQuery>,
+    // `--score-window`: scored on its own next to the whole history
+    pub score_window: chrono::Duration,
+    // `--alert`, checked against the score window on every update
+    pub alerts: &'a [AlertRule],
+    pub alert_cooldown: Duration,
+    // `--alert-webhook`: every alert message is also POSTed here
+    pub webhook: Option<&'a str>,
+}
+
+// bytes from the start of a file kept to tell it from its replacement
+const HEAD_BYTES: u64 = 64;
+
+// Where we are in one followed file. A partial last line is held back until
+// its newline arrives. The file stays open between reads, so after a rename
+// (logrotate's default) what was written to it before the new file appeared
+// is still read; std opens files with delete sharing on Windows, so holding
+// it doesn't block the rotation there either.
+struct Tail {
+    path: String,
+    file: Option<File>,
+    id: Option<FileId>,
+    // the file's first bytes as last read; a copytruncate that was rewritten
+    // past our offset before the next read no longer starts with them
+    head: Vec<u8>,
+    offset: u64,
+    // bytes past `offset` left for the next tick
+    behind: u64,
+    carry: Carry,
+    next_line: usize,
+}
+
+impl Tail {
+    fn new(path: &str, max_line_len: usize) -> io::Result<Self> {
+        let file = File::open(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let id = file_id(&file.metadata()?);
+        Ok(Self {
+            path: path.to_string(),
+            file: Some(file),
+            id,
+            head: Vec::new(),
+            offset: 0,
+            behind: 0,
+            carry: Carry::new(max_line_len),
+            next_line: 1,
+        })
+    }
+
+    // Complete lines written since the last call, as chunks with the number
+    // of their first line: the rest of a rotated-away file, then the start
+    // of the one that replaced it.
+    fn read_new(&mut self) -> io::Result<Vec<(usize, Vec<u8>)>> {
+        let mut chunks = Vec::new();
+        if self.file.is_none() && !self.reopen()? {
+            return Ok(chunks);
+        }
+        chunks.extend(self.drain()?);
+
+        // a rotated-away file is finished before moving on
+        if self.behind == 0 && self.replaced()? {
+            // the old file's unfinished last line never gets its newline
+            if let Some(last) = self.carry.finish() {
+                chunks.push(numbered(last, &mut self.next_line));
+            }
+            if self.reopen()? {
+                chunks.extend(self.drain()?);
+            }
+        }
+        Ok(chunks)
+    }
+
+    // opens whatever is at the path now, from its start; false when nothing
+    // is there yet (mid-rotation)
+    fn reopen(&mut self) -> io::Result<bool> {
+        let file = match File::open(&self.path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.file = None;
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        };
+        self.id = file_id(&file.metadata()?);
+        self.file = Some(file);
+        self.restart();
+        Ok(true)
+    }
+
+    fn restart(&mut self) {
+        self.head.clear();
+        self.offset = 0;
+        self.behind = 0;
+        self.carry.reset();
+        self.next_line = 1;
+    }
+
+    // whether the path now names a different file than the one held
+    fn replaced(&self) -> io::Result<bool> {
+        let mut file = match File::open(&self.path) {
+            Ok(f) => f,
+            // renamed away, not recreated yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let id = file_id(&file.metadata()?);
+        Ok(id != self.id || !read_head(&mut file)?.starts_with(&self.head))
+    }
+
+    fn drain(&mut self) -> io::Result<Option<(usize, Vec<u8>)>> {
+        let Some(mut file) = self.file.take() else {
+            return Ok(None);
+        };
+        // truncated in place (copytruncate): start over
+        let head = read_head(&mut file)?;
+        let len = file.metadata()?.len();
+        if len < self.offset || !head.starts_with(&self.head) {
+            self.restart();
+        }
+        self.head = head;
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        let read = (&mut file).take(TICK_BYTES).read_to_end(&mut buf)?;
+        self.file = Some(file);
+        self.offset += read as u64;
+        self.behind = len.saturating_sub(self.offset);
+        Ok(self.carry.push(&buf).map(|lines| numbered(lines, &mut self.next_line)))
+    }
+}
+
+// Newline-terminated lines without their last newline, with the number of
+// the first one.
+fn numbered(mut lines: Vec<u8>, next_line: &mut usize) -> (usize, Vec<u8>) {
+    let first_line = *next_line;
+    *next_line += memchr::memchr_iter(b'\n', &lines).count();
+    lines.pop();
+    (first_line, lines)
+}
+
+// A partial last line held back until its newline arrives, up to the
+// parser's `max_line_len`: past that the line would be dropped unread
+// anyway, so the rest of it is skipped as it comes in and counted, and a
+// writer that never sends a newline costs no memory. A skipped line leaves
+// an empty one in its place so the lines after it keep their numbers.
+struct Carry {
+    partial: Vec<u8>,
+    skipping: bool,
+    max_len: usize,
+    dropped_lines: usize,
+    dropped_bytes: u64,
+}
+
+impl Carry {
+    fn new(max_len: usize) -> Self {
+        Self { partial: Vec::new(), skipping: false, max_len, dropped_lines: 0, dropped_bytes: 0 }
+    }
+
+    // the complete lines so far, each with its newline
+    fn push(&mut self, mut data: &[u8]) -> Option<Vec<u8>> {
+        if self.skipping {
+            let Some(end) = memchr::memchr(b'\n', data) else {
+                self.dropped_bytes += data.len() as u64;
+                return None;
+            };
+            self.dropped_bytes += end as u64;
+            self.skipping = false;
+            data = &data[end..];
+        }
+        self.partial.extend_from_slice(data);
+        let start = memchr::memrchr(b'\n', &self.partial).map_or(0, |end| end + 1);
+        let tail = self.partial.len() - start;
+        if tail > self.max_len {
+            self.partial.truncate(start);
+            self.skipping = true;
+            self.dropped_lines += 1;
+            self.dropped_bytes += tail as u64;
+        }
+        if start == 0 {
+            return None;
+        }
+        let rest = self.partial.split_off(start);
+        Some(std::mem::replace(&mut self.partial, rest))
+    }
+
+    // the unfinished last line at the end of its input, given a newline
+    fn finish(&mut self) -> Option<Vec<u8>> {
+        self.skipping = false;
+        if self.partial.is_empty() {
+            return None;
+        }
+        let mut last = std::mem::take(&mut self.partial);
+        last.push(b'\n');
+        Some(last)
+    }
+
+    // a new file from its start; what was dropped stays counted
+    fn reset(&mut self) {
+        self.partial.clear();
+        self.skipping = false;
+    }
+}
+
+// A stream's queue between its reader thread and the follow loop, bounded in
+// lines: a block that doesn't fit is dropped whole and counted, so a writer
+// faster than the analysis costs lines rather than memory.
+#[derive(Default)]
+struct Queue {
+    lines: AtomicUsize,
+    peak: AtomicUsize,
+    dropped: AtomicUsize,
+    dropped_bytes: AtomicU64,
+}
+
+// The reader thread's end: blocks of complete lines, each sent with the
+// number of its first line (dropped lines keep their numbers).
+struct Feed {
+    tx: Sender<io::Result<(usize, usize, Vec<u8>)>>,
+    queue: Arc<Queue>,
+    next_line: usize,
+    max_line_len: usize,
+}
+
+impl Feed {
+    // false once the follow loop is gone
+    fn offer(&mut self, block: Vec<u8>) -> bool {
+        let lines = memchr::memchr_iter(b'\n', &block).count();
+        let first = self.next_line;
+        self.next_line += lines;
+        // the only producer, so the count can't grow between check and add
+        if self.queue.lines.load(Ordering::Relaxed) + lines > QUEUE_LINES {
+            self.queue.dropped.fetch_add(lines, Ordering::Relaxed);
+            self.queue.dropped_bytes.fetch_add(block.len() as u64, Ordering::Relaxed);
+            return true;
+        }
+        let queued = self.queue.lines.fetch_add(lines, Ordering::Relaxed) + lines;
+        self.queue.peak.fetch_max(queued, Ordering::Relaxed);
+        self.tx.send(Ok((first, lines, block))).is_ok()
+    }
+
+    // lines the reader's carry skipped for being too long
+    fn skipped(&self, carry: &mut Carry) {
+        let lines = std::mem::take(&mut carry.dropped_lines);
+        self.queue.dropped.fetch_add(lines, Ordering::Relaxed);
+        self.queue.dropped_bytes.fetch_add(std::mem::take(&mut carry.dropped_bytes), Ordering::Relaxed);
+    }
+}
+
+// Reads to the end, offering complete lines as they arrive and an
+// unfinished last line at the end; false once the follow loop is gone.
+fn offer_lines(reader: &mut impl Read, feed: &mut Feed) -> io::Result<bool> {
+    let mut carry = Carry::new(feed.max_line_len);
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            feed.skipped(&mut carry);
+            return Ok(carry.finish().is_none_or(|last| feed.offer(last)));
+        }
+        let lines = carry.push(&buf[..n]);
+        feed.skipped(&mut carry);
+        if lines.is_some_and(|lines| !feed.offer(lines)) {
+            return Ok(false);
+        }
+    }
+}
+
+// A FIFO or socket, read by a thread of its own since reads block until a
+// writer shows up; what it has queued is picked up each tick.
+struct Stream {
+    rx: Receiver<io::Result<(usize, usize, Vec<u8>)>>,
+    queue: Arc<Queue>,
+}
+
+impl Stream {
+    fn spawn(max_line_len: usize, read: impl FnOnce(&mut Feed) -> io::Result<()> + Send + 'static) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let queue = Arc::new(Queue::default());
+        let mut feed = Feed { tx, queue: Arc::clone(&queue), next_line: 1, max_line_len };
+        thread::spawn(move || {
+            if let Err(e) = read(&mut feed) {
+                let _ = feed.tx.send(Err(e));
+            }
+        });
+        Self { rx, queue }
+    }
+
+    // A named pipe: opening it waits for a writer, and when the last writer
+    // closes it is opened again for the next one.
+    fn fifo(path: &str, max_line_len: usize) -> Self {
+        let path = path.to_string();
+        Self::spawn(max_line_len, move |feed| loop {
+            let mut file = File::open(&path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+            if !offer_lines(&mut file, feed)? {
+                return Ok(());
+            }
+        })
+    }
+
+    // `--journal` with --follow: journalctl's output from now on. Started
+    // here rather than on the thread so a missing journalctl stops the run.
+    fn journal(query: &JournalQuery, max_line_len: usize) -> io::Result<Self> {
+        let mut output = query.follow()?;
+        Ok(Self::spawn(max_line_len, move |feed| {
+            if offer_lines(&mut output, feed)? {
+                return Err(io::Error::other("journalctl exited"));
+            }
+            Ok(())
+        }))
+    }
+
+    // A unix datagram socket, one or more lines per datagram (syslog(3)
+    // and journald forwarding send one message each). A socket file left
+    // behind by an earlier run is replaced.
+    #[cfg(unix)]
+    fn unix_datagram(path: &str, max_line_len: usize) -> io::Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::UnixDatagram;
+
+        let labeled = |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", path, e));
+        if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+            std::fs::remove_file(path).map_err(labeled)?;
+        }
+        let socket = UnixDatagram::bind(path).map_err(labeled)?;
+        Ok(Self::spawn(max_line_len, move |feed| {
+            let mut buf = vec![0; 256 * 1024];
+            loop {
+                let n = socket.recv(&mut buf)?;
+                let mut datagram = buf[..n].to_vec();
+                if !datagram.ends_with(b"\n") {
+                    datagram.push(b'\n');
+                }
+                if !feed.offer(datagram) {
+                    return Ok(());
+                }
+            }
+        }))
+    }
+
+    #[cfg(not(unix))]
+    fn unix_datagram(path: &str, _max_line_len: usize) -> io::Result<Self> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("{}: unix sockets need a unix platform", path)))
+    }
+
+    fn read_new(&mut self) -> io::Result<Vec<(usize, Vec<u8>)>> {
+        let mut chunks = Vec::new();
+        while let Ok(block) = self.rx.try_recv() {
+            let (first, lines, mut block) = block?;
+            self.queue.lines.fetch_sub(lines, Ordering::Relaxed);
+            block.pop();
+            chunks.push((first, block));
+        }
+        Ok(chunks)
+    }
+}
+
+enum Input {
+    File(Tail),
+    Stream(Stream),
+}
+
+impl Input {
+    fn open(path: &str, max_line_len: usize) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo()) {
+                return Ok(Self::Stream(Stream::fifo(path, max_line_len)));
+            }
+        }
+        Tail::new(path, max_line_len).map(Self::File)
+    }
+
+    fn read_new(&mut self) -> io::Result<Vec<(usize, Vec<u8>)>> {
+        match self {
+            Self::File(tail) => tail.read_new(),
+            Self::Stream(stream) => stream.read_new(),
+        }
+    }
+}
+
+fn intake(inputs: &[Input]) -> Intake {
+    let mut intake = Intake::default();
+    for input in inputs {
+        match input {
+            Input::File(tail) => {
+                intake.behind_bytes += tail.behind;
+                intake.dropped_lines += tail.carry.dropped_lines;
+                intake.dropped_bytes += tail.carry.dropped_bytes;
+            }
+            Input::Stream(stream) => {
+                intake.streams += 1;
+                intake.queue_peak = intake.queue_peak.max(stream.queue.peak.load(Ordering::Relaxed));
+                intake.queue_capacity = QUEUE_LINES;
+                intake.dropped_lines += stream.queue.dropped.load(Ordering::Relaxed);
+                intake.dropped_bytes += stream.queue.dropped_bytes.load(Ordering::Relaxed);
+            }
+        }
+    }
+    intake
+}
+
+fn read_head(file: &mut File) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(0))?;
+    let mut head = Vec::with_capacity(HEAD_BYTES as usize);
+    (&mut *file).take(HEAD_BYTES).read_to_end(&mut head)?;
+    Ok(head)
+}
+
+// What a file is, beyond its name: device and inode where there are inodes,
+// the creation time elsewhere (a recreated file gets a new one).
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(meta: &Metadata) -> Option<FileId> {
+    let created = meta.created().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some((created.as_secs(), created.subsec_nanos() as u64))
+}
+
+// Parses the files as they grow (and whatever arrives on FIFOs or the
+// socket) and redraws the dashboard every `interval` that brought new
+// entries. Runs until interrupted.
+pub fn run(
+    parser: &LogParser,
+    files: &[String],
+    scorer: Composite,
+    opts: FollowOptions,
+) -> io::Result<()> {
+    let max_line_len = parser.limits().max_line_len;
+    let mut inputs = files.iter().map(|p| Input::open(p, max_line_len)).collect::<io::Result<Vec<_>>>()?;
+    if let Some(path) = opts.listen_unix {
+        inputs.push(Input::Stream(Stream::unix_datagram(path, max_line_len)?));
+    }
+    if let Some(query) = opts.journal {
+        inputs.push(Input::Stream(Stream::journal(query, max_line_len)?));
+    }
+    let mut live = LiveAnalyzer::new(scorer, opts.zone, opts.burst).with_score_window(opts.score_window);
+    let report = ReportGenerator::new(opts.color);
+    let tty = io::stdout().is_terminal();
+    let label = files
+        .iter()
+        .map(String::as_str)
+        .chain(opts.listen_unix)
+        .chain(opts.journal.map(|_| JournalQuery::LABEL))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut shown: Option<Intake> = None;
+    let mut alerter = Alerter::new(opts.alerts, opts.alert_cooldown);
+    let mut alert_log: VecDeque<(String, Alert)> = VecDeque::new();
+    // parsed but not yet inside the reorder window's far edge
+    let mut held: Vec<LogEntry> = Vec::new();
+
+    loop {
+        let mut read_any = false;
+        for input in &mut inputs {
+            for (first_line, lines) in input.read_new()? {
+                held.extend(parser.parse_lines(&lines, first_line).0);
+                read_any = true;
+            }
+        }
+
+        // on a quiet tick nothing is in flight, so everything held can go
+        held.sort_by_key(|e| e.timestamp);
+        let ready = match held.last() {
+            Some(newest) if read_any => held.partition_point(|e| e.timestamp <= newest.timestamp - REORDER_WINDOW),
+            _ => held.len(),
+        };
+        let fresh: Vec<LogEntry> = held.drain(..ready).collect();
+
+        let new_entries = fresh.len();
+        let intake = intake(&inputs);
+        // drops are news even when nothing got through
+        if new_entries > 0 || shown.is_none_or(|s| s.dropped_lines != intake.dropped_lines) {
+            live.update(fresh);
+            let snapshot = live.snapshot();
+            let alerts = match snapshot.window {
+                Some(ref w) if !alerter.is_empty() => alerter.check(w, Instant::now()),
+                _ => Vec::new(),
+            };
+            if let (Some(url), Some(ref w)) = (opts.webhook, &snapshot.window) {
+                for alert in &alerts {
+                    let body = WebhookAlert::new(
+                        &label,
+                        alert.state.as_str(),
+                        &alert.key,
+                        alert.value,
+                        w.anomaly_score,
+                        &w.stats.error_bursts,
+                    )
+                    .with_errors(live.recent())
+                    .to_json();
+                    // off the update loop, so a slow receiver doesn't stall it
+                    let url = url.to_string();
+                    thread::spawn(move || {
+                        if let Err(e) = notify::post_webhook(&url, &body) {
+                            eprintln!("Webhook error: {}", e);
+                        }
+                    });
+                }
+            }
+
+            let mut out = io::stdout().lock();
+            if tty {
+                let errors: Vec<&LogEntry> = live
+                    .recent()
+                    .rev()
+                    .filter(|e| e.level.severity() >= LogLevel::Error.severity())
+                    .take(RECENT_ERRORS)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev()
+                    .collect();
+                let now = Local::now().format("%H:%M:%S").to_string();
+                if alerts.iter().any(|a| a.state == AlertState::Firing) {
+                    write!(out, "{}", BELL)?;
+                }
+                alert_log.extend(alerts.into_iter().map(|a| (now.clone(), a)));
+                if alert_log.len() > RECENT_ALERTS {
+                    alert_log.drain(..alert_log.len() - RECENT_ALERTS);
+                }
+                write!(out, "{}", CLEAR_SCREEN)?;
+                let frame = LiveFrame {
+                    file_path: &label,
+                    snapshot: &snapshot,
+                    new_entries,
+                    recent_errors: &errors,
+                    intake: &intake,
+                    alerts: alert_log.make_contiguous(),
+                };
+                report.render_live(&mut out, &frame)?;
+            } else {
+                for alert in &alerts {
+                    writeln!(out, "{}  ALERT {}", Local::now().format("%H:%M:%S"), alert.describe())?;
+                }
+                status_line(&mut out, &snapshot, new_entries, &intake)?;
+            }
+            out.flush()?;
+            shown = Some(intake);
+        }
+
+        // files with more than a tick's worth left are read again right away
+        if intake.behind_bytes == 0 {
+            thread::sleep(opts.interval);
+        }
+    }
+}
+
+// one line per update when stdout is a pipe or file
+fn status_line(out: &mut dyn Write, snapshot: &LiveSnapshot, new_entries: usize, intake: &Intake) -> io::Result<()> {
+    let stats = &snapshot.stats;
+    write!(
+        out,
+        "{}  entries={} (+{})  error_rate={:.1}%  bursts={}  anomaly={:.1} [{}]",
+        Local::now().format("%H:%M:%S"),
+        stats.total,
+        new_entries,
+        stats.error_rate,
+        stats.error_bursts.len(),
+        snapshot.anomaly_score,
+        anomaly_label(snapshot.anomaly_score)
+    )?;
+    if let Some(ref w) = snapshot.window {
+        write!(out, "  window_anomaly={:.1} [{}]", w.anomaly_score, anomaly_label(w.anomaly_score))?;
+    }
+    if intake.streams > 0 {
+        write!(out, "  queue_peak={}/{}", intake.queue_peak, intake.queue_capacity)?;
+    }
+    if intake.streams > 0 || intake.dropped_lines > 0 {
+        write!(out, "  dropped={} ({:.1}MB)", intake.dropped_lines, intake.dropped_bytes as f64 / 1e6)?;
+    }
+    if intake.behind_bytes > 0 {
+        write!(out, "  behind={:.1}MB", intake.behind_bytes as f64 / 1e6)?;
+    }
+    writeln!(out)
+}
@@ -0,0 +1,291 @@
+use chrono::{Duration, NaiveDateTime};
+use clap::ValueEnum;
+use std::io::{self, Write};
+
+use crate::parser::LogLevel;
+use crate::rng::Rng;
+
+const SOURCES: &[&str] = &["api", "auth", "db", "worker", "cache", "scheduler", "gateway", "billing"];
+const USERS: &[&str] = &["alice", "bob", "carol", "dave", "erin", "frank", "grace", "heidi"];
+const PATHS: &[&str] = &["/api/users/{n}", "/api/orders/{n}", "/api/orders/{n}/items", "/login", "/health", "/static/app.js"];
+const METHODS: &[&str] = &["GET", "GET", "GET", "POST", "PUT", "DELETE"];
+
+// `{n}` a number, `{ip}` an address, `{id}` a hex request ID, `{ms}` a
+// duration, `{user}` a user name. Errors say "error" or "failed" and
+// warnings "warning" so syslog output, which has no level field, keeps them.
+const DEBUG_TEMPLATES: &[&str] = &[
+    "cache lookup for key user:{n} took {ms}ms",
+    "dispatching job {id} to queue default",
+    "sql: SELECT * FROM orders WHERE id = {n}",
+    "heartbeat from {ip}",
+];
+const INFO_TEMPLATES: &[&str] = &[
+    "Request {id} completed in {ms}ms",
+    "User {user} logged in from {ip}",
+    "Processed order {n} for {user}",
+    "Connection established to {ip}:5432",
+    "Scheduled job {id} started",
+    "Cache hit ratio {n}%",
+];
+const WARN_TEMPLATES: &[&str] = &[
+    "warning: slow query took {ms}ms",
+    "warning: retrying request {id} (attempt {n})",
+    "warning: disk usage at {n}%",
+    "warning: rate limit approaching for {user}",
+];
+const ERROR_TEMPLATES: &[&str] = &[
+    "Connection to {ip}:5432 failed: timeout after {ms}ms",
+    "Request {id} failed with status 500",
+    "error: payment declined for order {n}",
+    "Failed to acquire lock on orders table",
+    "error: upstream {ip} returned invalid response",
+];
+const FATAL_TEMPLATES: &[&str] = &[
+    "Fatal error: out of memory, shutting down",
+    "Fatal error: database connection pool exhausted",
+];
+
+// a burst starts on average this often, lasting BURST_SECS
+const BURST_EVERY_SECS: f64 = 600.0;
+const BURST_SECS: (i64, i64) = (20, 90);
+const BURST_ERROR_RATE: f64 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum GenFormat {
+    Bracket,
+    Json,
+    Syslog,
+    Apache,
+}
+
+// `--rate 100/s`: entries per second, minute (`/m`) or hour (`/h`)
+#[derive(Debug, Clone, Copy)]
+pub struct Rate(pub f64);
+
+impl Rate {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (count, unit) = spec.trim().split_once('/').unwrap_or((spec.trim(), "s"));
+        let per = match unit {
+            "s" | "sec" => 1.0,
+            "m" | "min" => 60.0,
+            "h" | "hour" => 3600.0,
+            other => return Err(format!("Unknown rate unit '{}' (expected s, m or h)", other)),
+        };
+        let count: f64 = count
+            .parse()
+            .ok()
+            .filter(|c: &f64| *c > 0.0 && c.is_finite())
+            .ok_or_else(|| format!("Invalid rate '{}'", spec))?;
+        Ok(Self(count / per))
+    }
+}
+
+// `2%` or `0.02`
+pub fn parse_fraction(spec: &str) -> Result<f64, String> {
+    let spec = spec.trim();
+    let value = match spec.strip_suffix('%') {
+        Some(pct) => pct.trim().parse::<f64>().map(|p| p / 100.0),
+        None => spec.parse::<f64>(),
+    };
+    value
+        .ok()
+        .filter(|v| (0.0..=1.0).contains(v))
+        .ok_or_else(|| format!("Invalid rate '{}' (expected e.g. 2% or 0.02)", spec))
+}
+
+// `90s`, `30m`, `1h`, `2d`
+pub fn parse_span(spec: &str) -> Result<Duration, String> {
+    let spec = spec.trim();
+    let split = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+    let (count, unit) = spec.split_at(split);
+    let count: i64 = count.parse().map_err(|_| format!("Invalid duration '{}'", spec))?;
+    match unit {
+        "s" | "" => Ok(Duration::seconds(count)),
+        "m" => Ok(Duration::minutes(count)),
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        _ => Err(format!("Invalid duration '{}' (expected e.g. 90s, 30m, 1h, 2d)", spec)),
+    }
+}
+
+// What to write, as the `generate` command takes it.
+#[derive(Debug, Clone, Copy)]
+pub struct GenerateOptions {
+    pub format: GenFormat,
+    pub rate: Rate,
+    pub error_rate: f64,
+    pub duration: Duration,
+    pub start: NaiveDateTime,
+    pub sources: usize,
+    pub seed: u64,
+}
+
+pub struct GenerateSummary {
+    pub entries: usize,
+    pub errors: usize,
+    pub bursts: usize,
+}
+
+// Poisson arrivals at `--rate` from `--start` for `--duration`, spread over
+// a handful of sources. Now and then one source has a burst during which
+// most of its entries are errors, the way a failing dependency looks.
+pub fn run(args: &GenerateOptions, out: &mut dyn Write) -> io::Result<GenerateSummary> {
+    let mut rng = Rng::new(args.seed);
+    let sources = &SOURCES[..args.sources.clamp(1, SOURCES.len())];
+    let end = args.start + args.duration;
+    let burst_chance = 1.0 / (BURST_EVERY_SECS * args.rate.0);
+
+    let mut summary = GenerateSummary { entries: 0, errors: 0, bursts: 0 };
+    let mut burst: Option<(NaiveDateTime, &str)> = None;
+    let mut t = args.start;
+    let mut fraction = 0.0;
+
+    loop {
+        // exponential inter-arrival times, kept to the millisecond
+        fraction += -(1.0 - rng.unit()).ln() / args.rate.0;
+        let whole_ms = (fraction * 1000.0) as i64;
+        fraction -= whole_ms as f64 / 1000.0;
+        t += Duration::milliseconds(whole_ms);
+        if t >= end {
+            break;
+        }
+
+        if burst.is_some_and(|(until, _)| t >= until) {
+            burst = None;
+        }
+        if burst.is_none() && rng.unit() < burst_chance {
+            let secs = BURST_SECS.0 + rng.below((BURST_SECS.1 - BURST_SECS.0) as usize) as i64;
+            burst = Some((t + Duration::seconds(secs), sources[rng.below(sources.len())]));
+            summary.bursts += 1;
+        }
+
+        let (source, error_rate) = match burst {
+            Some((_, failing)) if rng.unit() < 0.5 => (failing, BURST_ERROR_RATE),
+            _ => (sources[rng.below(sources.len())], args.error_rate),
+        };
+        let level = pick_level(&mut rng, error_rate);
+        if level.severity() >= 3 {
+            summary.errors += 1;
+        }
+
+        writeln!(out, "{}", line(args.format, &mut rng, t, &level, source))?;
+        summary.entries += 1;
+    }
+
+    out.flush()?;
+    Ok(summary)
+}
+
+fn pick_level(rng: &mut Rng, error_rate: f64) -> LogLevel {
+    let roll = rng.unit();
+    if roll < error_rate {
+        // about one error in fifty is fatal
+        if rng.below(50) == 0 {
+            LogLevel::Fatal
+        } else {
+            LogLevel::Error
+        }
+    } else if roll < error_rate + 0.05 {
+        LogLevel::Warn
+    } else if roll < error_rate + 0.20 {
+        LogLevel::Debug
+    } else {
+        LogLevel::Info
+    }
+}
+
+fn message(rng: &mut Rng, level: &LogLevel) -> String {
+    let template = pick(rng, templates(level));
+    fill(rng, template)
+}
+
+fn templates(level: &LogLevel) -> &'static [&'static str] {
+    match level {
+        LogLevel::Debug => DEBUG_TEMPLATES,
+        LogLevel::Warn => WARN_TEMPLATES,
+        LogLevel::Error => ERROR_TEMPLATES,
+        LogLevel::Fatal => FATAL_TEMPLATES,
+        LogLevel::Info | LogLevel::Unknown => INFO_TEMPLATES,
+    }
+}
+
+fn pick<'a>(rng: &mut Rng, items: &[&'a str]) -> &'a str {
+    items[rng.below(items.len())]
+}
+
+fn fill(rng: &mut Rng, template: &str) -> String {
+    let mut out = String::with_capacity(template.len() + 16);
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+        match &rest[open + 1..open + close] {
+            "n" => out.push_str(&(1 + rng.below(9999)).to_string()),
+            "ip" => out.push_str(&format!("10.0.{}.{}", rng.below(8), 1 + rng.below(254))),
+            "id" => out.push_str(&format!("{:012x}", rng.next() >> 16)),
+            "ms" => out.push_str(&latency_ms(rng).to_string()),
+            "user" => out.push_str(pick(rng, USERS)),
+            other => out.push_str(other),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// mostly fast with a long tail
+fn latency_ms(rng: &mut Rng) -> u64 {
+    (20.0 * (-(1.0 - rng.unit()).ln() * 2.0).exp()) as u64
+}
+
+fn line(format: GenFormat, rng: &mut Rng, t: NaiveDateTime, level: &LogLevel, source: &str) -> String {
+    match format {
+        GenFormat::Bracket => format!(
+            "[{}] {} {}: {}",
+            t.format("%Y-%m-%d %H:%M:%S"),
+            level.as_str(),
+            source,
+            message(rng, level)
+        ),
+        GenFormat::Json => serde_json::json!({
+            "timestamp": t.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            "level": level.as_str().to_lowercase(),
+            "source": source,
+            "message": message(rng, level),
+        })
+        .to_string(),
+        GenFormat::Syslog => format!(
+            "{} host-{} {}[{}]: {}",
+            t.format("%b %e %H:%M:%S"),
+            rng.below(4) + 1,
+            source,
+            1000 + rng.below(30000),
+            message(rng, level)
+        ),
+        GenFormat::Apache => apache_line(rng, t, level),
+    }
+}
+
+fn apache_line(rng: &mut Rng, t: NaiveDateTime, level: &LogLevel) -> String {
+    let status = match level {
+        LogLevel::Error | LogLevel::Fatal => [500, 502, 503][rng.below(3)],
+        LogLevel::Warn => [400, 401, 404, 429][rng.below(4)],
+        _ => [200, 200, 200, 201, 204, 304][rng.below(6)],
+    };
+    let template = pick(rng, PATHS);
+    let path = fill(rng, template);
+    format!(
+        "10.0.{}.{} - - [{}] \"{} {} HTTP/1.1\" {} {} {}ms",
+        rng.below(8),
+        1 + rng.below(254),
+        t.format("%d/%b/%Y:%H:%M:%S +0000"),
+        pick(rng, METHODS),
+        path,
+        status,
+        200 + rng.below(20000),
+        latency_ms(rng)
+    )
+}
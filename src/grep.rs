@@ -0,0 +1,117 @@
+use colored::Colorize;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use crate::archive::{self, ArchiveKind};
+use crate::parser::{LogEntry, LogLevel};
+use crate::redact;
+
+pub struct GrepOptions<'a> {
+    // lines of the file around each entry, as grep's -C
+    pub context: usize,
+    pub color: bool,
+    // `--redact`, applied to context lines the way it was to entries
+    pub redact: &'a [Regex],
+}
+
+// `--show-entries`: the entries a run kept, from one file, in file order as
+// `path:line: time LEVEL source: message`, like grep's output. Context
+// lines are read back from the file as `path-line- text`, with `--`
+// between groups that aren't adjacent; archive members have no context.
+pub fn print_entries(out: &mut dyn Write, path: &str, entries: &[LogEntry], opts: &GrepOptions) -> io::Result<()> {
+    let mut entries: Vec<&LogEntry> = entries.iter().collect();
+    entries.sort_by_key(|e| e.line_number);
+    if opts.context == 0 || ArchiveKind::detect(path).is_some() {
+        for entry in entries {
+            print_entry(out, path, entry, opts.color)?;
+        }
+        return Ok(());
+    }
+
+    let file = BufReader::new(archive::open_decompressed(File::open(path)?)?);
+    let mut next = entries.into_iter().peekable();
+    let mut before: VecDeque<(usize, String)> = VecDeque::with_capacity(opts.context);
+    let mut after = 0;
+    let mut last_printed = 0;
+    for (i, line) in file.split(b'\n').enumerate() {
+        let number = i + 1;
+        let Some(upcoming) = next.peek() else {
+            if after == 0 {
+                break;
+            }
+            after -= 1;
+            print_context(out, path, number, &text(&line?, opts.redact), opts.color)?;
+            continue;
+        };
+        let line = line?;
+        if upcoming.line_number == number {
+            let first = before.front().map_or(number, |&(n, _)| n);
+            if last_printed > 0 && first > last_printed + 1 {
+                writeln!(out, "--")?;
+            }
+            for (n, context) in before.drain(..) {
+                print_context(out, path, n, &context, opts.color)?;
+            }
+            while let Some(entry) = next.next_if(|e| e.line_number == number) {
+                print_entry(out, path, entry, opts.color)?;
+            }
+            after = opts.context;
+            last_printed = number;
+        } else if after > 0 {
+            after -= 1;
+            print_context(out, path, number, &text(&line, opts.redact), opts.color)?;
+            last_printed = number;
+        } else {
+            if before.len() == opts.context {
+                before.pop_front();
+            }
+            before.push_back((number, text(&line, opts.redact)));
+        }
+    }
+    // entries past the end of the text (continued lines, formats whose
+    // numbers aren't lines) are still listed
+    for entry in next {
+        print_entry(out, path, entry, opts.color)?;
+    }
+    Ok(())
+}
+
+fn print_entry(out: &mut dyn Write, path: &str, entry: &LogEntry, color: bool) -> io::Result<()> {
+    let level = format!("{:<5}", entry.level.as_str());
+    let level = if color {
+        match entry.level {
+            LogLevel::Fatal => level.red().bold().to_string(),
+            LogLevel::Error => level.red().to_string(),
+            LogLevel::Warn => level.yellow().to_string(),
+            LogLevel::Info => level.green().to_string(),
+            LogLevel::Debug => level.dimmed().to_string(),
+            LogLevel::Unknown => level,
+        }
+    } else {
+        level
+    };
+    let place = format!("{}:{}:", path, entry.line_number);
+    writeln!(
+        out,
+        "{} {} {} {}{}",
+        if color { place.magenta().to_string() } else { place },
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        level,
+        entry.source.as_deref().map(|s| format!("{}: ", s)).unwrap_or_default(),
+        entry.message,
+    )
+}
+
+fn print_context(out: &mut dyn Write, path: &str, number: usize, line: &str, color: bool) -> io::Result<()> {
+    let line = format!("{}-{}- {}", path, number, line);
+    writeln!(out, "{}", if color { line.dimmed().to_string() } else { line })
+}
+
+fn text(line: &[u8], redact: &[Regex]) -> String {
+    let line = String::from_utf8_lossy(line);
+    let mut line = line.trim_end_matches('\r').to_string();
+    redact::mask(&mut line, redact);
+    line
+}
@@ -0,0 +1,84 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::parser::{LogEntry, LogLevel};
+
+// values listed per field; the rest are summed into `other`
+pub const GROUP_LIMIT: usize = 20;
+
+#[derive(Debug, Serialize)]
+pub struct GroupRow {
+    pub value: String,
+    pub entries: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+}
+
+// `--group-by FIELD`: entries and errors per value of a JSON field (or a
+// named group of `--pattern`); `source` and `level` fall back to the
+// entry's own when no field carries the name.
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+    pub field: String,
+    pub groups: Vec<GroupRow>,
+    // values beyond GROUP_LIMIT, summed
+    pub other_values: usize,
+    pub other: GroupRow,
+    // entries without the field
+    pub missing: usize,
+}
+
+pub fn summarize(entries: &[LogEntry], fields: &[String]) -> Vec<GroupSummary> {
+    fields.iter().map(|field| summarize_field(entries, field)).collect()
+}
+
+fn summarize_field(entries: &[LogEntry], field: &str) -> GroupSummary {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut missing = 0;
+    for entry in entries {
+        let Some(value) = value_of(entry, field) else {
+            missing += 1;
+            continue;
+        };
+        let c = counts.entry(value).or_default();
+        c.0 += 1;
+        if matches!(entry.level, LogLevel::Error | LogLevel::Fatal) {
+            c.1 += 1;
+        }
+    }
+
+    let mut groups: Vec<GroupRow> = counts
+        .into_iter()
+        .map(|(value, (entries, errors))| row(value, entries, errors))
+        .collect();
+    groups.sort_unstable_by(|a, b| {
+        b.entries.cmp(&a.entries).then(b.errors.cmp(&a.errors)).then_with(|| a.value.cmp(&b.value))
+    });
+
+    let rest = groups.split_off(groups.len().min(GROUP_LIMIT));
+    let other = row(
+        "(other)".to_string(),
+        rest.iter().map(|r| r.entries).sum(),
+        rest.iter().map(|r| r.errors).sum(),
+    );
+
+    GroupSummary { field: field.to_string(), groups, other_values: rest.len(), other, missing }
+}
+
+fn value_of(entry: &LogEntry, field: &str) -> Option<String> {
+    match entry.fields.get(field) {
+        Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(s)) => Some(s.clone()),
+        Some(v) => Some(v.to_string()),
+        None => match field {
+            "source" => entry.source.as_deref().map(str::to_string),
+            "level" => Some(entry.level.as_str().to_string()),
+            _ => None,
+        },
+    }
+}
+
+fn row(value: String, entries: usize, errors: usize) -> GroupRow {
+    let error_rate = if entries > 0 { errors as f64 / entries as f64 } else { 0.0 };
+    GroupRow { value, entries, errors, error_rate }
+}
@@ -0,0 +1,143 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::buckets::{Bucketing, Zone};
+use crate::endpoints::PathNormalizer;
+use crate::metrics::percentile;
+use crate::parser::LogEntry;
+
+const TREND_BUCKETS: usize = 24;
+
+#[derive(Debug, Serialize)]
+pub struct StatusBucket {
+    pub start: String,
+    pub requests: usize,
+    pub client_errors: usize,
+    pub server_errors: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlowPath {
+    pub method: String,
+    pub template: String,
+    pub requests: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub requests: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HttpSummary {
+    pub requests: usize,
+    // response sizes summed, where the log has them
+    pub bytes: u64,
+    pub status_codes: BTreeMap<u16, usize>,
+    // 4xx and 5xx per time bucket, on `zone`'s wall clock
+    pub trend: Vec<StatusBucket>,
+    pub trend_bucket_secs: i64,
+    // by p95 response time, slowest first; empty without response times
+    pub slowest: Vec<SlowPath>,
+    // most frequent first; empty unless the log is in combined format
+    pub top_user_agents: Vec<ValueCount>,
+    pub top_referrers: Vec<ValueCount>,
+}
+
+impl HttpSummary {
+    pub fn class_count(&self, class: u16) -> usize {
+        self.status_codes.range(class * 100..(class + 1) * 100).map(|(_, n)| n).sum()
+    }
+}
+
+// Requests are the entries with a numeric `status` field: access log lines
+// (see the Apache parser) and JSON logs carrying the same fields. None when
+// there are none.
+pub fn summarize(entries: &[LogEntry], normalizer: &PathNormalizer, limit: usize, zone: Zone) -> Option<HttpSummary> {
+    let requests: Vec<(&LogEntry, u16)> = entries
+        .iter()
+        .filter_map(|e| Some((e, e.fields.get("status")?.as_u64()?.try_into().ok()?)))
+        .collect();
+    let (first, last) = (requests.first()?.0.timestamp, requests.last()?.0.timestamp);
+
+    let buckets = Bucketing::fitting(first, last, TREND_BUCKETS, zone);
+    let (origin, count) = buckets.span(first, last);
+    let mut trend: Vec<StatusBucket> = (0..count)
+        .map(|b| StatusBucket {
+            start: (origin + buckets.width() * b as i32).format("%Y-%m-%d %H:%M:%S").to_string(),
+            requests: 0,
+            client_errors: 0,
+            server_errors: 0,
+        })
+        .collect();
+
+    let mut status_codes: BTreeMap<u16, usize> = BTreeMap::new();
+    let mut bytes = 0;
+    let mut latencies: HashMap<(&str, String), Vec<f64>> = HashMap::new();
+    let mut user_agents: HashMap<&str, usize> = HashMap::new();
+    let mut referrers: HashMap<&str, usize> = HashMap::new();
+    for &(entry, status) in &requests {
+        *status_codes.entry(status).or_insert(0) += 1;
+        bytes += entry.fields.get("bytes").and_then(|b| b.as_u64()).unwrap_or(0);
+
+        let bucket = &mut trend[buckets.index(origin, entry.timestamp).min(count - 1)];
+        bucket.requests += 1;
+        match status {
+            400..=499 => bucket.client_errors += 1,
+            500..=599 => bucket.server_errors += 1,
+            _ => {}
+        }
+
+        let field = |name: &str| entry.fields.get(name).and_then(|v| v.as_str());
+        if let Some(agent) = field("user_agent") {
+            *user_agents.entry(agent).or_insert(0) += 1;
+        }
+        if let Some(referer) = field("referer") {
+            *referrers.entry(referer).or_insert(0) += 1;
+        }
+        if let (Some(ms), Some(path)) = (entry.fields.get("response_ms").and_then(|v| v.as_f64()), field("path")) {
+            let method = field("method").unwrap_or("-");
+            latencies.entry((method, normalizer.normalize(path))).or_default().push(ms);
+        }
+    }
+
+    let mut slowest: Vec<SlowPath> = latencies
+        .into_iter()
+        .map(|((method, template), mut ms)| {
+            ms.sort_unstable_by(f64::total_cmp);
+            SlowPath {
+                method: method.to_string(),
+                template,
+                requests: ms.len(),
+                p50_ms: percentile(&ms, 0.50),
+                p95_ms: percentile(&ms, 0.95),
+                max_ms: ms[ms.len() - 1],
+            }
+        })
+        .collect();
+    slowest.sort_unstable_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms).then_with(|| a.template.cmp(&b.template)));
+    slowest.truncate(limit);
+
+    Some(HttpSummary {
+        requests: requests.len(),
+        bytes,
+        status_codes,
+        trend,
+        trend_bucket_secs: buckets.width().num_seconds(),
+        slowest,
+        top_user_agents: top_values(user_agents, limit),
+        top_referrers: top_values(referrers, limit),
+    })
+}
+
+fn top_values(counts: HashMap<&str, usize>, limit: usize) -> Vec<ValueCount> {
+    let mut top: Vec<ValueCount> =
+        counts.into_iter().map(|(value, requests)| ValueCount { value: value.to_string(), requests }).collect();
+    top.sort_unstable_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.value.cmp(&b.value)));
+    top.truncate(limit);
+    top
+}
@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+// Shared pool of deduplicated strings. Sources repeat on nearly every line,
+// so entries hold a cheap `Arc<str>` handle into the pool instead of each
+// owning a fresh allocation. Safe to use from rayon workers.
+#[derive(Debug, Default)]
+pub struct Interner {
+    pool: RwLock<HashSet<Arc<str>>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&self, s: &str) -> Arc<str> {
+        if let Some(existing) = self.pool.read().unwrap().get(s) {
+            return Arc::clone(existing);
+        }
+
+        let mut pool = self.pool.write().unwrap();
+        // another worker may have inserted it between the two locks
+        if let Some(existing) = pool.get(s) {
+            return Arc::clone(existing);
+        }
+
+        let interned: Arc<str> = Arc::from(s);
+        pool.insert(Arc::clone(&interned));
+        interned
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::NaiveDateTime;
+use std::io::{self, Read};
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+// `--journal`: entries read from systemd-journald through `journalctl -o
+// json`, narrowed by journalctl itself so a busy host's journal isn't read
+// whole to find one unit's lines.
+#[derive(Debug, Clone, Default)]
+pub struct JournalQuery {
+    // `--unit`, any of them
+    pub units: Vec<String>,
+    // `--priority`: this and more severe, as journalctl's -p takes it
+    pub priority: Option<String>,
+    // `--journal-dir`: a journal copied off another host
+    pub directory: Option<String>,
+    pub since: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+}
+
+impl JournalQuery {
+    // the label entries and reports name as their input
+    pub const LABEL: &'static str = "journal";
+
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("journalctl");
+        cmd.args(["--output", "json", "--no-pager", "--quiet"]);
+        for unit in &self.units {
+            cmd.args(["--unit", unit]);
+        }
+        if let Some(ref priority) = self.priority {
+            cmd.args(["--priority", priority]);
+        }
+        if let Some(ref dir) = self.directory {
+            cmd.args(["--directory", dir]);
+        }
+        let format = |ts: NaiveDateTime| ts.format("%Y-%m-%d %H:%M:%S").to_string();
+        if let Some(since) = self.since {
+            cmd.args(["--since", &format(since)]);
+        }
+        if let Some(until) = self.until {
+            cmd.args(["--until", &format(until)]);
+        }
+        cmd
+    }
+
+    // everything matching, as `journalctl -o json` lines
+    pub fn read(&self) -> io::Result<Vec<u8>> {
+        let output = self.command().stderr(Stdio::piped()).output().map_err(spawn_error)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::other(format!("journalctl failed: {}", stderr.trim())));
+        }
+        Ok(output.stdout)
+    }
+
+    // New entries as they are written, starting from now; the child is
+    // killed when the returned handle is dropped.
+    pub fn follow(&self) -> io::Result<JournalFollow> {
+        let mut cmd = self.command();
+        cmd.args(["--follow", "--lines", "0"]).stdout(Stdio::piped()).stderr(Stdio::null());
+        let mut child = cmd.spawn().map_err(spawn_error)?;
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("journalctl has no stdout"))?;
+        Ok(JournalFollow { child, stdout })
+    }
+}
+
+pub struct JournalFollow {
+    child: Child,
+    stdout: ChildStdout,
+}
+
+impl Read for JournalFollow {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stdout.read(buf)
+    }
+}
+
+impl Drop for JournalFollow {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn spawn_error(e: io::Error) -> io::Error {
+    match e.kind() {
+        io::ErrorKind::NotFound => io::Error::new(e.kind(), "journalctl not found; --journal needs systemd's journalctl"),
+        _ => io::Error::new(e.kind(), format!("cannot run journalctl: {}", e)),
+    }
+}
+
+// syslog priorities as journald stores them, 0 (emerg) to 7 (debug)
+pub fn priority_level(priority: &str) -> Option<&'static str> {
+    match priority {
+        "0" | "1" | "2" => Some("fatal"),
+        "3" => Some("error"),
+        "4" => Some("warn"),
+        "5" | "6" => Some("info"),
+        "7" => Some("debug"),
+        _ => None,
+    }
+}
+
+// `--priority`: a name or number journalctl accepts, checked up front
+pub fn parse_priority(spec: &str) -> Result<String, String> {
+    const NAMES: &[&str] = &["emerg", "alert", "crit", "err", "warning", "notice", "info", "debug"];
+    let spec = spec.trim().to_lowercase();
+    let known = |p: &str| NAMES.contains(&p) || p.parse::<u8>().is_ok_and(|n| n <= 7);
+    let valid = match spec.split_once("..") {
+        Some((from, to)) => known(from) && known(to),
+        None => known(&spec),
+    };
+    if valid {
+        Ok(spec)
+    } else {
+        Err(format!("Unknown priority '{}' (expected 0-7 or one of {}, or a FROM..TO range)", spec, NAMES.join(", ")))
+    }
+}
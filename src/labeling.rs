@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use crate::parser::LogEntry;
+use crate::rng::Rng;
+use crate::templates::fingerprint;
+
+// One entry picked for labeling. `weight` is how many entries of its
+// stratum it stands for (the stratum's size over the entries taken from
+// it), so labels can be scaled back up to the strata they came from.
+pub struct Picked<'a> {
+    pub entry: &'a LogEntry,
+    pub template: String,
+    pub stratum_size: usize,
+    pub weight: f64,
+}
+
+// `--output-format labeling`: up to `size` entries for people to label,
+// stratified by level and template so rare kinds of line are there next to
+// the common ones. Each stratum gets one entry, then the rest go out in
+// proportion to the square root of their sizes, which leans toward the
+// common strata without letting them take everything. With more strata than
+// room, strata are drawn with the same weights. The same seed picks the same
+// entries; they come back in log order.
+pub fn sample(entries: &[LogEntry], size: usize, seed: u64) -> Vec<Picked<'_>> {
+    let mut strata: HashMap<(u8, String), Vec<usize>> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        strata.entry((entry.level.severity(), fingerprint(&entry.message))).or_default().push(i);
+    }
+    // sorted, so the seed alone decides what is picked
+    let mut strata: Vec<((u8, String), Vec<usize>)> = strata.into_iter().collect();
+    strata.sort_unstable_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut rng = Rng::new(seed);
+    let weight = |members: &[usize]| (members.len() as f64).sqrt();
+    if strata.len() > size {
+        // weighted sampling without replacement: the largest u^(1/w) win
+        let mut keyed: Vec<(f64, usize)> =
+            strata.iter().enumerate().map(|(i, (_, m))| (rng.unit().powf(1.0 / weight(m)), i)).collect();
+        keyed.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+        let mut chosen: Vec<usize> = keyed.iter().take(size).map(|&(_, i)| i).collect();
+        chosen.sort_unstable();
+        strata = chosen.into_iter().map(|i| std::mem::take(&mut strata[i])).collect();
+    }
+
+    let quotas = allocate(&strata.iter().map(|(_, m)| m.len()).collect::<Vec<_>>(), size);
+    let mut picked: Vec<(usize, Picked)> = Vec::with_capacity(size);
+    for (((_, template), mut members), quota) in strata.into_iter().zip(quotas) {
+        // partial Fisher-Yates: the first `quota` members end up a uniform draw
+        for i in 0..quota {
+            let j = i + rng.below(members.len() - i);
+            members.swap(i, j);
+        }
+        let weight = members.len() as f64 / quota as f64;
+        picked.extend(members[..quota].iter().map(|&i| {
+            let entry = &entries[i];
+            (i, Picked { entry, template: template.clone(), stratum_size: members.len(), weight })
+        }));
+    }
+    picked.sort_unstable_by_key(|&(i, _)| i);
+    picked.into_iter().map(|(_, p)| p).collect()
+}
+
+// One each, then the remainder by sqrt(size), never more than a stratum has.
+fn allocate(sizes: &[usize], total: usize) -> Vec<usize> {
+    let mut quotas = vec![1; sizes.len()];
+    let mut left = total.saturating_sub(sizes.len());
+    while left > 0 {
+        let open: Vec<usize> = (0..sizes.len()).filter(|&i| quotas[i] < sizes[i]).collect();
+        if open.is_empty() {
+            break;
+        }
+        let weights: f64 = open.iter().map(|&i| (sizes[i] as f64).sqrt()).sum();
+        let mut given = 0;
+        for &i in &open {
+            let share = ((left as f64 * (sizes[i] as f64).sqrt() / weights).floor() as usize).min(sizes[i] - quotas[i]);
+            quotas[i] += share;
+            given += share;
+        }
+        if given == 0 {
+            // shares all rounded down: one more each, largest strata first
+            for &i in open.iter().take(left) {
+                quotas[i] += 1;
+                given += 1;
+            }
+        }
+        left -= given;
+    }
+    quotas
+}
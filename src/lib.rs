@@ -0,0 +1,71 @@
+//! Parsing and analysis behind the `logscope` command, for use from other
+//! programs. The usual path is [`parser::LogParser`] to read entries,
+//! optionally [`filter::apply`] to narrow them, [`analyzer::LogAnalyzer`] to
+//! compute a [`analyzer::LogAnalysis`] (its `stats` come from
+//! [`stats::compute`]), and [`export::export_analysis`] to write it out:
+//!
+//! ```no_run
+//! use logscope::analyzer::LogAnalyzer;
+//! use logscope::parser::{LogFormat, LogParser};
+//!
+//! let parser = LogParser::with_format(LogFormat::Auto);
+//! let (entries, parse_stats) = parser.parse_files(&["/var/log/app.log".to_string()])?;
+//! let analysis = LogAnalyzer::new(&entries, parse_stats).analyze(None);
+//! if analysis.stats.error_rate > 5.0 {
+//!     eprintln!("unhealthy: anomaly score {:.0}", analysis.anomaly_score);
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! [`pipeline::analyze`] runs those steps the way the command does, from a
+//! plain [`pipeline::AnalyzeOptions`], with evidence custody, a baseline
+//! model and the focus window on top. The command line itself (argument
+//! parsing, the interactive and live modes, anything that prints to the
+//! terminal) lives in the binary, not here.
+
+pub mod alerts;
+pub mod analyzer;
+pub mod archive;
+pub mod buckets;
+#[cfg(feature = "charts")]
+pub mod charts;
+pub mod container;
+pub mod cost;
+pub mod custody;
+pub mod dashboard;
+pub mod endpoints;
+pub mod entities;
+pub mod export;
+pub mod filter;
+pub mod generate;
+pub mod grep;
+pub mod groups;
+pub mod http;
+pub mod intern;
+pub mod journal;
+pub mod labeling;
+pub mod manifest;
+pub mod metrics;
+#[cfg(feature = "ml")]
+pub mod ml;
+pub mod model;
+pub mod notify;
+pub mod parser;
+pub mod pipeline;
+pub mod prometheus;
+pub mod ratelimit;
+pub mod repair;
+pub mod redact;
+pub mod report;
+pub mod retention;
+mod rng;
+pub mod scan;
+pub mod schema;
+pub mod scoring;
+pub mod sources;
+pub mod startup;
+pub mod stats;
+pub mod templates;
+pub mod units;
+pub mod versions;
+pub mod xml;
@@ -1,102 +1,857 @@
+use chrono::Local;
 use clap::Parser;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
 use std::process;
-use std::time::Duration;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, Level};
 
-mod analyzer;
+use logscope::alerts::{AlertRule, AlertState};
+use logscope::analyzer::{KeywordOptions, LogAnalysis};
+use crate::cli::{Cli, Command, TokenClass};
+use crate::config::Config;
+use logscope::endpoints::{EndpointOptions, PathNormalizer};
+use crate::events::EventStream;
+use logscope::export::{export_analysis, ExportFormat, ExportOptions};
+use logscope::filter::{FilterStats, SourceMatcher};
+use logscope::generate::GenerateOptions;
+use logscope::grep::GrepOptions;
+use logscope::journal::JournalQuery;
+use logscope::model::BaselineModel;
+use logscope::parser::{LogFormat, LogLevel, LogParser, ParseStats, ParserLimits};
+use logscope::pipeline::{
+    self, AnalysisOptions, AnalyzeError, AnalyzeOptions, Analyzed, Evidence, FilterOptions, JournalOptions, Progress,
+};
+use logscope::notify::{Email, MailTransport, WebhookAlert};
+use logscope::report::ReportGenerator;
+use logscope::scoring::Composite;
+use logscope::stats::BurstParams;
+use logscope::xml::XmlLayout;
+#[cfg(feature = "charts")]
+use logscope::charts;
+use logscope::{grep, manifest, prometheus};
+// the library's modules, where the command's own modules below look for them
+use logscope::{
+    alerts, analyzer, archive, buckets, endpoints, export, filter, generate, journal, metrics, notify, parser, ratelimit,
+    report, retention, schema, scoring, sources, stats, templates, xml,
+};
+
+mod bench;
 mod cli;
-mod export;
-mod filter;
-mod parser;
-mod report;
-mod stats;
-
-use analyzer::LogAnalyzer;
-use cli::Cli;
-use export::{export_analysis, ExportFormat};
-use filter::FilterConfig;
-use parser::{LogFormat, LogParser, LogLevel};
-use report::ReportGenerator;
+mod config;
+mod diff;
+mod events;
+mod find;
+mod follow;
+mod profile;
+mod repl;
+mod replay;
+mod trace;
+mod trend;
 
 fn main() {
-    let args = Cli::parse();
+    let mut args = Cli::parse();
 
     if args.no_color {
         colored::control::set_override(false);
     }
 
-    let format = resolve_format(args.format.as_deref());
-    let parser = LogParser::with_format(format);
+    if let Err(e) = init_diagnostics(args.verbose, args.log_file.as_deref(), !args.no_color) {
+        eprintln!("Error: cannot open log file: {}", e);
+        process::exit(1);
+    }
 
-    let spinner = build_spinner("Parsing log file…");
+    if let Some(location) = args.config.clone() {
+        if let Err(e) = Config::load(&location).and_then(|cfg| cfg.apply(&mut args)) {
+            eprintln!("Error: cannot load config {}: {}", location, e);
+            process::exit(1);
+        }
+        info!(config = %location, "loaded config");
+    }
 
-    let (entries, unparsed) = match parser.parse_file_counted(&args.file_path) {
-        Ok(result) => result,
+    if let Some(profile) = args.profile {
+        profile.apply(&mut args);
+        info!(
+            ?profile,
+            metrics = args.metric.len(),
+            thresholds = args.threshold.len(),
+            "applied profile"
+        );
+    }
+
+    // --where values are checked against the field's type up front
+    if let Some(ref schema) = args.schema {
+        match args.conditions.drain(..).map(|c| c.typed(schema)).collect() {
+            Ok(conditions) => args.conditions = conditions,
+            Err(e) => {
+                eprintln!("Error: --where {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    // quoted patterns (or shells that don't glob, like cmd.exe) reach us as-is
+    let expanded = match args.command {
+        Some(Command::Repl(ref mut r)) => pipeline::expand_globs(&mut r.file_paths),
+        Some(Command::Trace(ref mut t)) => pipeline::expand_globs(&mut t.file_paths),
+        Some(Command::FindHash(ref mut f)) => pipeline::expand_globs(&mut f.file_paths),
+        Some(Command::Trend(ref mut t)) => pipeline::expand_globs(&mut t.file_paths),
+        Some(_) => Ok(()),
+        None => pipeline::expand_globs(&mut args.file_paths),
+    };
+    if let Err(e) = expanded {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+
+    match args.command {
+        Some(Command::Bench(ref bench_args)) => run_bench(bench_args, !args.no_color),
+        Some(Command::Diff(ref diff_args)) => run_diff(diff_args, !args.no_color),
+        Some(Command::Repl(ref repl_args)) => {
+            if let Err(e) = repl::run(repl_args, !args.no_color) {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+        Some(Command::Trace(ref trace_args)) => run_trace(trace_args),
+        Some(Command::FindHash(ref find_args)) => run_find_hash(find_args),
+        Some(Command::Replay(ref replay_args)) => run_replay(replay_args),
+        Some(Command::Generate(ref generate_args)) => run_generate(generate_args),
+        Some(Command::Trend(ref trend_args)) => {
+            if let Err(e) = trend::run(trend_args, !args.no_color) {
+                eprintln!("Error: {}: {}", trend_args.state, e);
+                process::exit(1);
+            }
+        }
+        None => run_analyze(&args),
+    }
+}
+
+fn run_analyze(args: &Cli) {
+    let file_label = args
+        .file_paths
+        .iter()
+        .map(String::as_str)
+        .chain(args.journal.then_some(JournalQuery::LABEL))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let events = EventStream::new(args.stream_json);
+    let run_start = Instant::now();
+    events.emit("start", serde_json::json!({ "files": &args.file_paths }));
+
+    let parser = build_parser(args);
+    let filter = filter_options(args);
+    // relative --from/--to: the parser filter only sees a reference when
+    // following; the pipeline resolves them itself otherwise
+    if args.follow {
+        let clock = Local::now().naive_local();
+        run_follow(args, &parser.with_entry_filter(Some(filter.config(Some(clock)))), &filter);
+        return;
+    }
+    if args.show_entries {
+        let clock = args.journal.then(|| Local::now().naive_local());
+        run_show_entries(args, &parser.with_entry_filter(Some(filter.config(clock))), &filter);
+        return;
+    }
+    let export_format = args.output_format.as_deref().and_then(ExportFormat::from_str);
+    if let Some(feature) = export_format.and_then(|f| f.missing_feature()) {
+        eprintln!("Error: {} exports need logscope built with --features {}", feature, feature);
+        process::exit(1);
+    }
+    if args.chart_out.is_some() && !cfg!(feature = "charts") {
+        eprintln!("Error: --chart-out needs logscope built with --features charts");
+        process::exit(1);
+    }
+    if args.export_sample.is_some() && export_format.is_some_and(|f| !f.exports_entries()) {
+        eprintln!(
+            "Error: --export-sample has no effect on {} exports, which hold no entries",
+            args.output_format.as_deref().unwrap_or_default()
+        );
+        process::exit(1);
+    }
+    if args.alert_webhook.is_some() && args.alert_threshold.is_none() {
+        eprintln!("Error: --alert-webhook needs --alert-threshold (or --alert rules with --follow)");
+        process::exit(1);
+    }
+    let push = args.output.as_deref().is_some_and(prometheus::is_push);
+    if args.evidence_mode && push && export_format == Some(ExportFormat::Prometheus) {
+        eprintln!("Error: --evidence-mode can't record custody for a Pushgateway push; export to a file");
+        process::exit(1);
+    }
+
+    let spinner = if events.enabled() {
+        ProgressBar::hidden()
+    } else {
+        build_spinner("Parsing log file…")
+    };
+    let progress = RunProgress {
+        events: &events,
+        spinner: &spinner,
+        files_total: args.file_paths.len(),
+        files_done: AtomicUsize::new(0),
+    };
+    let opts = AnalyzeOptions {
+        file_paths: args.file_paths.clone(),
+        journal: journal_options(args),
+        parser,
+        filter,
+        evidence: args.evidence_mode.then(|| Evidence {
+            command_line: std::env::args().collect(),
+            mutations: mutating_options(args),
+            allow_mutation: args.allow_mutation,
+        }),
+        max_unparsed_ratio: args.max_unparsed_ratio,
+        model: args.model.clone(),
+        analysis: analysis_options(args),
+        focus_errors: args.focus_errors,
+    };
+    let run = match pipeline::analyze(opts, &progress) {
+        Ok(run) => run,
         Err(e) => {
             spinner.finish_and_clear();
-            eprintln!("Error: {}", e);
-            process::exit(1);
+            match e {
+                AnalyzeError::Mutations(mutations) => refuse_mutations(&mutations),
+                AnalyzeError::Unparsed(parse_stats) => {
+                    refuse_unparsed(&parse_stats, args.max_unparsed_ratio.unwrap_or_default())
+                }
+                AnalyzeError::NoEntries => {
+                    eprintln!("No entries matched the given filters.");
+                    process::exit(0);
+                }
+                AnalyzeError::Failed(msg) => {
+                    eprintln!("Error: {}", msg);
+                    process::exit(1);
+                }
+            }
         }
     };
+    let Analyzed { entries: filtered, analysis, focus, baseline } = run;
+    let zone = args.tz.unwrap_or_default();
 
-    spinner.finish_and_clear();
+    let mut report_out: Box<dyn Write> = match args.report_path {
+        Some(ref path) => match File::create(path) {
+            Ok(f) => Box::new(BufWriter::new(f)),
+            Err(e) => {
+                eprintln!("Error: cannot create report file {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+
+    // with --stream-json the analysis events replace the text report on stdout
+    let render_text = !events.enabled() || args.report_path.is_some();
+    let reporter = ReportGenerator::new(!args.no_color && args.report_path.is_none());
+    let mut emit = |label: &str, analysis: &LogAnalysis| {
+        events.emit("analysis", serde_json::json!({ "label": label, "analysis": analysis }));
+        if !render_text {
+            return;
+        }
+        let result = reporter.render(&mut report_out, label, analysis, args.heatmap);
+        // a closed stdout pipe (e.g. `| head`) is not worth reporting
+        if let (Err(e), Some(path)) = (result, &args.report_path) {
+            eprintln!("Report error: {}: {}", path, e);
+        }
+    };
 
-    let filter_cfg = build_filter(&args);
-    let filtered = filter::apply(entries.clone(), &filter_cfg);
+    emit(&file_label, &analysis);
 
-    if filtered.is_empty() {
-        eprintln!("No entries matched the given filters.");
-        process::exit(0);
+    if let Some(pct) = args.focus_errors {
+        match focus {
+            Some(ref focused) => emit(&format!("{} [focus: {}% of errors]", file_label, pct), focused),
+            None => eprintln!("Focus window needs at least two error entries; skipped."),
+        }
     }
 
-    let analyzer = LogAnalyzer::new(filtered, unparsed);
-    let analysis = analyzer.analyze(args.top);
+    if let Some(ref path) = args.report_path {
+        match report_out.flush() {
+            Ok(()) => events.status(&format!("Report written to {}", path)),
+            Err(e) => eprintln!("Report error: {}: {}", path, e),
+        }
+    }
 
-    let reporter = ReportGenerator::new(!args.no_color);
-    reporter.generate(&args.file_path, &analysis, args.heatmap);
+    if let Some(ref path) = args.save_model {
+        let mut model = BaselineModel::learn(&filtered);
+        if let Some(previous) = baseline {
+            model.merge(previous);
+        }
+        match model.save(path) {
+            Ok(()) => events.status(&format!("Model ({} runs) saved to {}", model.runs, path)),
+            Err(e) => eprintln!("Model error: {}: {}", path, e),
+        }
+    }
+
+    if !args.email.is_empty() {
+        send_report_email(args, &file_label, &analysis, &events);
+    }
+
+    if let (Some(url), Some(score)) = (&args.alert_webhook, args.alert_threshold) {
+        if analysis.anomaly_score >= score {
+            let rule = format!("anomaly>={}", score);
+            let alert = WebhookAlert::new(
+                &file_label,
+                AlertState::Firing.as_str(),
+                &rule,
+                analysis.anomaly_score,
+                analysis.anomaly_score,
+                &analysis.stats.error_bursts,
+            )
+            .with_errors(filtered.iter());
+            match notify::post_webhook(url, &alert.to_json()) {
+                Ok(()) => events.status(&format!("Alert sent to {}", url)),
+                Err(e) => eprintln!("Webhook error: {}", e),
+            }
+        }
+    }
+
+    #[cfg(feature = "charts")]
+    if let Some(ref dir) = args.chart_out {
+        match charts::render(std::path::Path::new(dir), &filtered, zone) {
+            Ok(files) => events.status(&format!("Wrote {} charts to {}", files.len(), dir)),
+            Err(e) => eprintln!("Chart error: {}", e),
+        }
+    }
 
     if let (Some(fmt_str), Some(out_path)) = (&args.output_format, &args.output) {
         match ExportFormat::from_str(fmt_str) {
             Some(fmt) => {
-                match export_analysis(&analysis, &entries, fmt, out_path) {
-                    Ok(()) => println!("Exported to {}", out_path),
+                let sampled;
+                let export_entries = match args.export_sample {
+                    Some(ref rates) => {
+                        sampled = rates.apply(&filtered);
+                        info!(kept = sampled.len(), total = filtered.len(), "sampled export entries");
+                        &sampled
+                    }
+                    None => &filtered,
+                };
+
+                let options = ExportOptions {
+                    partition: args.output_partition,
+                    append: args.output_append,
+                    zone,
+                    label_sample: args.label_sample,
+                    label_seed: args.label_seed,
+                };
+
+                match export_analysis(&analysis, export_entries, fmt, out_path, options) {
+                    Ok(files) => {
+                        events.status(&format!("Exported to {}", out_path));
+                        // nothing to hash after a push
+                        if (args.manifest || args.sign_cmd.is_some()) && !files.is_empty() {
+                            write_export_manifest(args, out_path, &files, &events);
+                        }
+                    }
                     Err(e) => eprintln!("Export error: {}", e),
                 }
             }
             None => eprintln!("Unknown export format: {}", fmt_str),
         }
     }
+
+    let failed = failed_checks(args, &analysis);
+    events.emit(
+        "done",
+        serde_json::json!({ "elapsed_ms": run_start.elapsed().as_millis() as u64, "failed": &failed }),
+    );
+    if !failed.is_empty() {
+        for reason in &failed {
+            eprintln!("Failed: {}", reason);
+        }
+        process::exit(CHECKS_FAILED);
+    }
+}
+
+// Exit status when the run worked but a --fail-* check didn't pass, so CI
+// can tell a regression from a run that broke (status 1).
+const CHECKS_FAILED: i32 = 2;
+
+// `--fail-on-errors`, `--fail-above` and `--max-error-rate`: what the run
+// found that should fail a CI job, checked once everything is written.
+fn failed_checks(args: &Cli, analysis: &LogAnalysis) -> Vec<String> {
+    let mut failed = Vec::new();
+    let errors = ["ERROR", "FATAL"].iter().filter_map(|l| analysis.level_counts.get(*l)).sum::<usize>();
+    if args.fail_on_errors && errors > 0 {
+        failed.push(format!("{} ERROR/FATAL entries (--fail-on-errors)", errors));
+    }
+    if let Some(limit) = args.fail_above.filter(|&limit| analysis.anomaly_score > limit) {
+        failed.push(format!("anomaly score {:.1} is above {} (--fail-above)", analysis.anomaly_score, limit));
+    }
+    if let Some(limit) = args.max_error_rate.filter(|&limit| analysis.stats.error_rate > limit) {
+        failed.push(format!("error rate {:.2}% is above {}% (--max-error-rate)", analysis.stats.error_rate, limit));
+    }
+    failed
 }
 
-fn resolve_format(s: Option<&str>) -> LogFormat {
-    match s {
-        Some("bracket") => LogFormat::Bracket,
-        Some("json") => LogFormat::Json,
-        Some("apache") => LogFormat::Apache,
-        Some("syslog") => LogFormat::Syslog,
-        _ => LogFormat::Auto,
+fn refuse_unparsed(parse_stats: &ParseStats, max_ratio: f64) -> ! {
+    let ratio = parse_stats.unparsed as f64 / parse_stats.total_lines as f64;
+    eprintln!(
+        "Error: {} of {} lines ({:.1}%) failed to parse, above --max-unparsed-ratio {}",
+        parse_stats.unparsed,
+        parse_stats.total_lines,
+        ratio * 100.0,
+        max_ratio,
+    );
+    if let Some((line_number, ref sample)) = parse_stats.sample_unparsed {
+        let shown: String = sample.chars().take(200).collect();
+        match parse_stats.sample_file {
+            Some(ref file) => eprintln!("  first unparsed line {} of {}: {}", line_number, file, shown),
+            None => eprintln!("  first unparsed line {}: {}", line_number, shown),
+        }
+    }
+    if parse_stats.oversized > 0 {
+        eprintln!("  {} lines exceeded --max-line-len", parse_stats.oversized);
     }
+    eprintln!("  hint: check --format, or that the file is a log this tool understands");
+    process::exit(1);
 }
 
-fn build_filter(args: &Cli) -> FilterConfig {
-    let mut cfg = FilterConfig::new();
+fn send_report_email(args: &Cli, file_label: &str, analysis: &LogAnalysis, events: &EventStream) {
+    let mut body = Vec::new();
+    let _ = ReportGenerator::new(false).render(&mut body, file_label, analysis, args.heatmap);
+    let body = String::from_utf8_lossy(&body);
+
+    let subject = format!(
+        "logscope: {} - anomaly {:.1} ({})",
+        file_label,
+        analysis.anomaly_score,
+        report::anomaly_label(analysis.anomaly_score),
+    );
 
-    if let Some(ref kw) = args.keyword {
-        cfg = cfg.with_keyword(kw.clone());
+    let transport = match args.smtp {
+        Some(ref addr) => MailTransport::Smtp(addr.clone()),
+        None => MailTransport::Sendmail(args.sendmail_path.clone()),
+    };
+
+    let email = Email {
+        from: &args.email_from,
+        to: &args.email,
+        subject: &subject,
+        body: &body,
+    };
+
+    match notify::send_email(&email, &transport) {
+        Ok(()) => events.status(&format!("Report emailed to {}", args.email.join(", "))),
+        Err(e) => eprintln!("Email error: {}", e),
     }
+}
+
+// Options that change what entries say or which of them are exported.
+fn mutating_options(args: &Cli) -> Vec<String> {
+    let format = args.format.as_deref().map(LogFormat::from_str);
+    let set = [
+        ("--redact", !args.redact.is_empty()),
+        ("--max-message-len", args.max_message_len.is_some()),
+        ("--repair-torn-lines", args.repair_torn_lines),
+        ("--multiline", args.multiline || args.multiline_pattern.is_some()),
+        ("--level-map", !args.level_map.is_empty()),
+        ("--level-rule", !args.level_rule.is_empty()),
+        ("--slow-request", args.slow_request.is_some()),
+        ("--schema", args.schema.is_some()),
+        ("container unwrapping", matches!(format, Some(LogFormat::Docker | LogFormat::Cri))),
+        ("--keyword", !args.keyword.is_empty()),
+        ("--exclude", !args.exclude.is_empty()),
+        ("--level", args.level.is_some()),
+        ("--from", args.from.is_some()),
+        ("--to", args.to.is_some()),
+        ("--source", args.source.is_some()),
+        ("--source-include", !args.source_include.is_empty()),
+        ("--source-exclude", !args.source_exclude.is_empty()),
+        ("--where", !args.conditions.is_empty()),
+        ("--export-sample", args.export_sample.is_some()),
+    ];
+    set.iter().filter(|(_, on)| *on).map(|(name, _)| name.to_string()).collect()
+}
 
-    cfg = cfg.with_time_range(args.from, args.to);
+fn refuse_mutations(mutations: &[String]) -> ! {
+    eprintln!(
+        "Error: --evidence-mode refuses {} as it alters entries; add --allow-mutation to record it and go ahead",
+        mutations.join(", ")
+    );
+    process::exit(1);
+}
 
-    if let Some(ref level_str) = args.level {
-        let level = LogLevel::from_str(level_str);
-        cfg = cfg.with_min_level(&level);
+fn write_export_manifest(args: &Cli, out_path: &str, files: &[PathBuf], events: &EventStream) {
+    let path = manifest::manifest_path(out_path, args.output_partition.is_some());
+    if let Err(e) = manifest::write_manifest(&path, files) {
+        eprintln!("Error: cannot write manifest {}: {}", path.display(), e);
+        process::exit(1);
     }
+    events.status(&format!("Manifest written to {}", path.display()));
 
-    if let Some(ref src) = args.source {
-        cfg = cfg.with_source(src.clone());
+    if let Some(ref cmd) = args.sign_cmd {
+        if let Err(e) = manifest::sign(cmd, &path) {
+            eprintln!("Error: signing {} failed: {}", path.display(), e);
+            process::exit(1);
+        }
+        events.status("Manifest signed");
+    }
+}
+
+fn run_bench(args: &cli::BenchArgs, color: bool) {
+    let spinner = build_spinner("Benchmarking…");
+    let result = bench::run(args);
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(report) => report.print(color),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
     }
+}
 
-    cfg
+fn run_diff(args: &cli::DiffArgs, color: bool) {
+    let spinner = build_spinner("Comparing…");
+    let result = diff::run(args);
+    spinner.finish_and_clear();
+
+    match result {
+        Ok(diff) if args.unified => diff.print_unified(color, args.top),
+        Ok(diff) => diff.print(color, args.top),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_trace(args: &cli::TraceArgs) {
+    let (markdown, count) = match trace::run(args) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    match args.output {
+        Some(ref path) => {
+            if let Err(e) = std::fs::write(path, &markdown) {
+                eprintln!("Error: {}: {}", path, e);
+                process::exit(1);
+            }
+            println!("Wrote {} entries for '{}' to {}", count, args.trace_id, path);
+        }
+        None => print!("{}", markdown),
+    }
+
+    if count == 0 {
+        process::exit(1);
+    }
+}
+
+// One line per occurrence, as `file:line`, then the entry; exits 1 when
+// there are none, like grep.
+fn run_find_hash(args: &cli::FindHashArgs) {
+    let found = match find::run(args) {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+    if found.is_empty() {
+        eprintln!("No entry with hash {} in {}", args.hash, args.file_paths.join(", "));
+        process::exit(1);
+    }
+    let mut out = BufWriter::new(io::stdout().lock());
+    for (path, entry) in &found {
+        // a closed pipe (`| head`) just ends the listing
+        let written = writeln!(
+            out,
+            "{}:{}  {} {:<5} {}{}",
+            path,
+            entry.line_number,
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.level.as_str(),
+            entry.source.as_deref().map(|s| format!("{}: ", s)).unwrap_or_default(),
+            entry.message,
+        );
+        if written.is_err() {
+            return;
+        }
+    }
+    let _ = out.flush();
+}
+
+fn run_generate(args: &cli::GenerateArgs) {
+    let opts = GenerateOptions {
+        format: args.format,
+        rate: args.rate,
+        error_rate: args.error_rate,
+        duration: args.duration,
+        start: args.start,
+        sources: args.sources,
+        seed: args.seed,
+    };
+    let result = match args.output {
+        Some(ref path) => File::create(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))
+            .and_then(|f| generate::run(&opts, &mut BufWriter::new(f))),
+        None => generate::run(&opts, &mut BufWriter::new(io::stdout().lock())),
+    };
+    match result {
+        Ok(summary) => eprintln!(
+            "Generated {} entries ({} errors, {} bursts)",
+            summary.entries, summary.errors, summary.bursts
+        ),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_replay(args: &cli::ReplayArgs) {
+    match replay::run(args) {
+        Ok(summary) => {
+            eprintln!(
+                "Replayed {} entries in {:.1}s ({} lines skipped)",
+                summary.sent,
+                summary.elapsed.as_secs_f64(),
+                summary.skipped
+            );
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_show_entries(args: &Cli, parser: &LogParser, filter: &FilterOptions) {
+    let mut files = Vec::new();
+    for path in &args.file_paths {
+        match parser.parse_file_counted(path) {
+            Ok((entries, _)) => files.push((path, entries)),
+            Err(e) => {
+                eprintln!("Error: {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    }
+    // relative --from/--to count back from the newest entry of any file
+    let newest = files.iter().filter_map(|(_, entries)| entries.iter().map(|e| e.timestamp).max()).max();
+    let filter_cfg = filter.config(newest);
+    let opts = GrepOptions { context: args.context, color: !args.no_color, redact: &args.redact };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    let mut shown = 0;
+    for (path, entries) in files {
+        let entries = filter::apply(entries, &filter_cfg);
+        shown += entries.len();
+        match grep::print_entries(&mut out, path, &entries, &opts) {
+            Ok(()) => {}
+            // a closed pipe (`| head`) just ends the listing
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return,
+            Err(e) => {
+                let _ = out.flush();
+                eprintln!("Error: {}: {}", path, e);
+                process::exit(1);
+            }
+        }
+    }
+    let _ = out.flush();
+    if shown == 0 {
+        eprintln!("No entries matched the given filters.");
+        process::exit(1);
+    }
+}
+
+fn run_follow(args: &Cli, parser: &LogParser, filter: &FilterOptions) {
+    // from now on; the entry filter has --from/--to
+    let journal = journal_options(args).map(|j| JournalQuery { since: None, until: None, ..j.query(filter, None) });
+    let mut alerts = args.alert.clone();
+    if let Some(score) = args.alert_threshold {
+        alerts.extend(AlertRule::parse(&format!("anomaly>={}", score)));
+    }
+    if args.alert_webhook.is_some() && alerts.is_empty() {
+        eprintln!("Error: --alert-webhook needs --alert-threshold or an --alert rule");
+        process::exit(1);
+    }
+    let opts = follow::FollowOptions {
+        interval: Duration::from_secs_f64(args.interval.max(0.1)),
+        color: !args.no_color,
+        zone: args.tz.unwrap_or_default(),
+        burst: burst_params(args),
+        listen_unix: args.listen_unix.as_deref(),
+        journal: journal.as_ref(),
+        score_window: args.score_window,
+        alerts: &alerts,
+        alert_cooldown: args.alert_cooldown.to_std().unwrap_or_default(),
+        webhook: args.alert_webhook.as_deref(),
+    };
+    let scorer = Composite::new(args.scorer.clone(), args.score_combine);
+    if let Err(e) = follow::run(parser, &args.file_paths, scorer, opts) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn burst_params(args: &Cli) -> BurstParams {
+    BurstParams { window_secs: args.burst_window.num_seconds(), threshold: args.burst_threshold }
+}
+
+fn journal_options(args: &Cli) -> Option<JournalOptions> {
+    args.journal.then(|| JournalOptions {
+        units: args.unit.clone(),
+        priority: args.priority.clone(),
+        directory: args.journal_dir.clone(),
+    })
+}
+
+fn filter_options(args: &Cli) -> FilterOptions {
+    FilterOptions {
+        keywords: args.keyword.clone(),
+        keyword_mode: args.keyword_mode,
+        exclude: args.exclude.clone(),
+        from: args.from,
+        to: args.to,
+        min_level: args.level.as_deref().map(LogLevel::from_str),
+        source_include: args
+            .source_include
+            .iter()
+            .cloned()
+            .chain(args.source.as_deref().map(SourceMatcher::containing))
+            .collect(),
+        source_exclude: args.source_exclude.clone(),
+        conditions: args.conditions.clone(),
+    }
+}
+
+// Without the entry filter, which depends on the mode.
+fn build_parser(args: &Cli) -> LogParser {
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let limits = ParserLimits {
+        max_line_len: args.max_line_len.unwrap_or(ParserLimits::default().max_line_len),
+        max_message_len: args.max_message_len,
+    };
+    LogParser::with_format(format)
+        .with_limits(limits)
+        .with_redaction(args.redact.clone())
+        // evidence is analyzed whole; the custody record names the inputs
+        .with_dedup(!args.keep_duplicates && !args.evidence_mode)
+        .with_archive_members(args.archive_member.clone())
+        .with_strip_ansi(!args.keep_ansi)
+        .with_torn_line_repair(args.repair_torn_lines)
+        .with_multiline(args.multiline, args.multiline_pattern.clone())
+        .with_level_map(&args.level_map)
+        .with_level_rules(&args.level_rule)
+        .with_xml_layout(XmlLayout::new(args.xml_record.as_deref(), &args.xml_field))
+        .with_schema(args.schema.clone())
+        .with_slow_request(args.slow_request)
+        .with_latency_unit(args.latency_unit)
+        .with_custom_pattern(
+            args.pattern
+                .clone()
+                .map(|p| p.with_timestamp_format(args.timestamp_format.clone())),
+        )
+}
+
+fn analysis_options(args: &Cli) -> AnalysisOptions {
+    AnalysisOptions {
+        metrics: args.metric.clone(),
+        thresholds: args.threshold.clone(),
+        keywords: build_keyword_options(args),
+        error_budgets: args.error_budget.clone(),
+        group_by: args.group_by.clone(),
+        version_pattern: args.version_pattern.clone(),
+        template_limit: args.top_templates,
+        tail: args.tail,
+        bucket: args.bucket,
+        burst: burst_params(args),
+        endpoints: EndpointOptions {
+            normalizer: PathNormalizer::new(args.path_rule.clone()),
+            limit: args.top_endpoints,
+            weights: args.endpoint_weights,
+        },
+        rate_limit: args.rate_limit.map(|l| l.with_by(args.rate_limit_by)),
+        cost_per_gb: args.cost_per_gb,
+        retention: args.retention.clone(),
+        scorer: Composite::new(args.scorer.clone(), args.score_combine),
+        zone: args.tz.unwrap_or_default(),
+        top: args.top,
+    }
+}
+
+// The pipeline's stages as --stream-json events; the spinner stops once
+// parsing is done, before anything is logged.
+struct RunProgress<'a> {
+    events: &'a EventStream,
+    spinner: &'a ProgressBar,
+    files_total: usize,
+    files_done: AtomicUsize,
+}
+
+impl Progress for RunProgress<'_> {
+    fn file_done(&self, path: &str, entries: usize, parse_stats: &ParseStats) {
+        let done = self.files_done.fetch_add(1, Ordering::Relaxed) + 1;
+        self.events.emit(
+            "progress",
+            serde_json::json!({
+                "stage": "parse",
+                "file": path,
+                "files_done": done,
+                "files_total": self.files_total,
+                "entries": entries,
+                "unparsed": parse_stats.unparsed,
+            }),
+        );
+    }
+
+    fn parsed(&self, entries: usize, parse_stats: &ParseStats) {
+        self.spinner.finish_and_clear();
+        self.events.emit("parsed", serde_json::json!({ "entries": entries, "parse_stats": parse_stats }));
+    }
+
+    fn filtered(&self, kept: usize, filter_stats: &FilterStats) {
+        self.events.emit(
+            "filtered",
+            serde_json::json!({ "kept": kept, "removed": filter_stats.removed(), "stages": filter_stats }),
+        );
+    }
+}
+
+fn init_diagnostics(verbose: u8, log_file: Option<&str>, color: bool) -> std::io::Result<()> {
+    let level = match verbose {
+        0 => Level::WARN,
+        1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_target(false);
+
+    match log_file {
+        Some(path) => {
+            let file = File::create(path)?;
+            builder.with_ansi(false).with_writer(Mutex::new(file)).init();
+        }
+        None => builder.with_ansi(color).with_writer(std::io::stderr).init(),
+    }
+
+    Ok(())
+}
+
+fn build_keyword_options(args: &Cli) -> KeywordOptions {
+    let drops = |class: TokenClass| {
+        args.keyword_drop.contains(&class) || args.keyword_drop.contains(&TokenClass::Ids)
+    };
+
+    KeywordOptions {
+        min_len: args.keyword_min_len,
+        max_len: args.keyword_max_len,
+        drop_numbers: drops(TokenClass::Numbers),
+        drop_hex: drops(TokenClass::Hex),
+        drop_uuids: drops(TokenClass::Uuids),
+    }
 }
 
 fn build_spinner(msg: &str) -> ProgressBar {
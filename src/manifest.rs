@@ -0,0 +1,80 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const MANIFEST_NAME: &str = "SHA256SUMS";
+
+// Where the manifest for an export goes: inside the directory for
+// partitioned exports, next to the file otherwise.
+pub fn manifest_path(output: &str, partitioned: bool) -> PathBuf {
+    if partitioned {
+        Path::new(output).join(MANIFEST_NAME)
+    } else {
+        PathBuf::from(format!("{}.sha256", output))
+    }
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+// `sha256sum`-compatible: "<hex>  <path>" with paths relative to the
+// manifest's directory, so `sha256sum -c` works from there. Entries for
+// files from earlier runs (appended partitions) are carried over.
+pub fn write_manifest(manifest: &Path, files: &[PathBuf]) -> io::Result<()> {
+    let base = manifest.parent().unwrap_or(Path::new(""));
+    let mut lines = BTreeMap::new();
+
+    if let Ok(existing) = fs::read_to_string(manifest) {
+        for line in existing.lines() {
+            if let Some((digest, name)) = line.split_once("  ") {
+                if base.join(name).exists() {
+                    lines.insert(name.to_string(), digest.to_string());
+                }
+            }
+        }
+    }
+
+    for file in files {
+        let name = file.strip_prefix(base).unwrap_or(file);
+        lines.insert(name.display().to_string(), sha256_file(file)?);
+    }
+
+    let mut out = BufWriter::new(File::create(manifest)?);
+    for (name, digest) in &lines {
+        writeln!(out, "{}  {}", digest, name)?;
+    }
+    out.flush()
+}
+
+// Runs a user-supplied signing command such as `minisign -Sm {}` or
+// `gpg --detach-sign --armor {}`; `{}` is replaced by the manifest path,
+// or the path is appended when the command has no placeholder.
+pub fn sign(command: &str, manifest: &Path) -> io::Result<()> {
+    let path = manifest.display().to_string();
+    let cmdline = if command.contains("{}") {
+        command.replace("{}", &shell_quote(&path))
+    } else {
+        format!("{} {}", command, shell_quote(&path))
+    };
+
+    let status = Command::new("sh").arg("-c").arg(&cmdline).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("signing command exited with {}", status)));
+    }
+    Ok(())
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
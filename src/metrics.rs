@@ -0,0 +1,286 @@
+use regex::Regex;
+use serde::Serialize;
+
+use crate::buckets::{Bucketing, Zone};
+use crate::parser::LogEntry;
+use crate::schema;
+use crate::units::{self, Unit};
+
+const METRIC_WINDOWS: usize = 8;
+
+// `--metric 'took (\d+)ms as latency'`: the value is the capture group named
+// after the metric if present, otherwise the first capture group.
+// `--metric field:latency` reads an entry field instead, where a `--schema`
+// duration is in milliseconds. A value carrying its unit (`took (\S+)`
+// capturing `1.2s` or `350ms`, a field of `"4KiB"`) is taken in milliseconds
+// or bytes.
+#[derive(Debug, Clone)]
+pub struct MetricSpec {
+    pub name: String,
+    source: MetricSource,
+}
+
+#[derive(Debug, Clone)]
+enum MetricSource {
+    Message(Regex),
+    Field(String),
+}
+
+impl MetricSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pattern, name) = match spec.rsplit_once(" as ") {
+            Some((p, n)) if !n.trim().is_empty() => (p.trim(), n.trim().to_string()),
+            _ => (spec.trim(), String::new()),
+        };
+
+        if let Some(field) = pattern.strip_prefix("field:") {
+            let field = field.trim();
+            if field.is_empty() {
+                return Err("Metric needs a field name: 'field:NAME'".into());
+            }
+            let name = if name.is_empty() { field.to_string() } else { name };
+            return Ok(Self { name, source: MetricSource::Field(field.to_string()) });
+        }
+
+        let re = Regex::new(pattern).map_err(|e| format!("Invalid metric regex: {}", e))?;
+        if re.captures_len() < 2 {
+            return Err("Metric regex needs a capture group around the number".into());
+        }
+
+        let name = if name.is_empty() {
+            re.capture_names()
+                .flatten()
+                .next()
+                .map(String::from)
+                .ok_or("Metric needs a name: 'REGEX as NAME'")?
+        } else {
+            name
+        };
+
+        Ok(Self { name, source: MetricSource::Message(re) })
+    }
+
+    fn value(&self, entry: &LogEntry) -> Option<(f64, Option<Unit>)> {
+        match self.source {
+            MetricSource::Message(ref re) => {
+                let caps = re.captures(&entry.message)?;
+                units::quantity(caps.name(&self.name).or_else(|| caps.iter().skip(1).flatten().next())?.as_str())
+            }
+            MetricSource::Field(ref field) => match entry.fields.get(field)? {
+                serde_json::Value::String(s) => units::quantity(s),
+                value => schema::number(value).map(|v| (v, None)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+// `--threshold 'latency>2000'`, or with a unit, `latency>2s`, compared in
+// milliseconds or bytes like the metric's values
+#[derive(Debug, Clone)]
+pub struct Threshold {
+    pub metric: String,
+    pub comparison: Comparison,
+    pub value: f64,
+}
+
+impl Threshold {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let ops = [
+            (">=", Comparison::AtLeast),
+            ("<=", Comparison::AtMost),
+            (">", Comparison::Above),
+            ("<", Comparison::Below),
+        ];
+
+        for (op, comparison) in ops {
+            if let Some((metric, value)) = spec.split_once(op) {
+                let (value, _) =
+                    units::quantity(value).ok_or_else(|| format!("Invalid threshold value: {}", value.trim()))?;
+                return Ok(Self {
+                    metric: metric.trim().to_string(),
+                    comparison,
+                    value,
+                });
+            }
+        }
+
+        Err("Threshold must look like NAME>VALUE (also >=, <, <=)".into())
+    }
+
+    pub fn is_crossed(&self, v: f64) -> bool {
+        match self.comparison {
+            Comparison::Above => v > self.value,
+            Comparison::AtLeast => v >= self.value,
+            Comparison::Below => v < self.value,
+            Comparison::AtMost => v <= self.value,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let op = match self.comparison {
+            Comparison::Above => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Below => "<",
+            Comparison::AtMost => "<=",
+        };
+        format!("{}{}{}", self.metric, op, self.value)
+    }
+}
+
+// A run of consecutive metric samples past a threshold.
+#[derive(Debug, Serialize)]
+pub struct ThresholdEvent {
+    pub threshold: String,
+    pub start: String,
+    pub end: String,
+    pub samples: usize,
+    pub peak: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricWindow {
+    pub start: String,
+    pub count: usize,
+    pub avg: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricSummary {
+    pub name: String,
+    // "ms" or "B" when the values carried units
+    pub unit: Option<&'static str>,
+    pub count: usize,
+    pub min: f64,
+    pub avg: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub windows: Vec<MetricWindow>,
+}
+
+// Windows are round wall-clock intervals in `zone`, at most METRIC_WINDOWS.
+pub fn summarize(entries: &[LogEntry], specs: &[MetricSpec], zone: Zone) -> Vec<MetricSummary> {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Vec::new();
+    };
+
+    let buckets = Bucketing::fitting(first.timestamp, last.timestamp, METRIC_WINDOWS, zone);
+    let (origin, window_count) = buckets.span(first.timestamp, last.timestamp);
+
+    specs
+        .iter()
+        .filter_map(|spec| {
+            let mut values = Vec::new();
+            let mut per_window: Vec<Vec<f64>> = vec![Vec::new(); window_count];
+            // the first unit seen; a size among durations is left out
+            let mut unit = None;
+
+            for entry in entries {
+                if let Some((v, value_unit)) = spec.value(entry) {
+                    match (unit, value_unit) {
+                        (Some(u), Some(vu)) if u != vu => continue,
+                        (None, Some(_)) => unit = value_unit,
+                        _ => {}
+                    }
+                    per_window[buckets.index(origin, entry.timestamp).min(window_count - 1)].push(v);
+                    values.push(v);
+                }
+            }
+
+            if values.is_empty() {
+                return None;
+            }
+
+            values.sort_unstable_by(f64::total_cmp);
+            let count = values.len();
+
+            let windows = per_window
+                .into_iter()
+                .enumerate()
+                .map(|(i, vs)| MetricWindow {
+                    start: (origin + buckets.width() * i as i32)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string(),
+                    count: vs.len(),
+                    avg: (!vs.is_empty()).then(|| vs.iter().sum::<f64>() / vs.len() as f64),
+                    max: vs.iter().copied().max_by(f64::total_cmp),
+                })
+                .collect();
+
+            Some(MetricSummary {
+                name: spec.name.clone(),
+                unit: unit.map(|u| u.as_str()),
+                count,
+                min: values[0],
+                avg: values.iter().sum::<f64>() / count as f64,
+                p50: percentile(&values, 0.50),
+                p95: percentile(&values, 0.95),
+                max: values[count - 1],
+                windows,
+            })
+        })
+        .collect()
+}
+
+pub fn detect_crossings(
+    entries: &[LogEntry],
+    specs: &[MetricSpec],
+    thresholds: &[Threshold],
+) -> Vec<ThresholdEvent> {
+    let mut events = Vec::new();
+
+    for threshold in thresholds {
+        let Some(spec) = specs.iter().find(|s| s.name == threshold.metric) else {
+            continue;
+        };
+
+        let mut current: Option<ThresholdEvent> = None;
+        for entry in entries {
+            let Some((v, _)) = spec.value(entry) else {
+                continue;
+            };
+
+            let ts = entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+            if threshold.is_crossed(v) {
+                let event = current.get_or_insert_with(|| ThresholdEvent {
+                    threshold: threshold.label(),
+                    start: ts.clone(),
+                    end: ts.clone(),
+                    samples: 0,
+                    peak: v,
+                });
+                event.end = ts;
+                event.samples += 1;
+                // "peak" is the most extreme value in the crossing direction
+                let further = match threshold.comparison {
+                    Comparison::Above | Comparison::AtLeast => v > event.peak,
+                    Comparison::Below | Comparison::AtMost => v < event.peak,
+                };
+                if further {
+                    event.peak = v;
+                }
+            } else if let Some(event) = current.take() {
+                events.push(event);
+            }
+        }
+
+        events.extend(current);
+    }
+
+    events
+}
+
+// nearest-rank percentile over sorted values
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
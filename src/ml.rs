@@ -0,0 +1,214 @@
+use chrono::NaiveDateTime;
+use std::collections::HashSet;
+
+use crate::buckets::{Bucketing, Zone};
+use crate::parser::LogEntry;
+use crate::rng::Rng;
+use crate::scoring::{ScoreInput, Scorer};
+
+const TREES: usize = 100;
+const SAMPLE_SIZE: usize = 256;
+// below this many windows there is no baseline worth learning
+const MIN_WINDOWS: usize = 16;
+const TARGET_WINDOWS: usize = 200;
+const FEATURES: usize = 5;
+// isolation scores hover around 0.5 for ordinary points
+const NORMAL_SCORE: f64 = 0.5;
+const DETAIL_LIMIT: usize = 3;
+
+type Features = [f64; FEATURES];
+
+// Isolation forest over fixed-width time windows. The forest learns the
+// file's own baseline from all of its windows (unusual ones are too rare to
+// shape it), then scores each window by how quickly random splits isolate it.
+pub struct IsolationForest;
+
+impl Scorer for IsolationForest {
+    fn name(&self) -> String {
+        "iforest".to_string()
+    }
+
+    fn score(&self, input: &ScoreInput) -> f64 {
+        self.explain(input).0
+    }
+
+    fn explain(&self, input: &ScoreInput) -> (f64, Vec<String>) {
+        let windows = windows(input.entries, input.zone);
+        if windows.len() < MIN_WINDOWS {
+            return (0.0, vec![format!("needs at least {} time windows", MIN_WINDOWS)]);
+        }
+
+        let points: Vec<Features> = windows.iter().map(|w| w.features).collect();
+        let forest = Forest::train(&points, &mut Rng::new(0x5eed));
+
+        let mut scored: Vec<(f64, &Window)> =
+            windows.iter().map(|w| (forest.score(&w.features), w)).collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let peak = scored[0].0;
+        let details = scored
+            .iter()
+            .take(DETAIL_LIMIT)
+            .filter(|(s, _)| *s > NORMAL_SCORE)
+            .map(|(s, w)| {
+                format!(
+                    "{}  isolation {:.2}  ({} entries, {} errors)",
+                    w.start.format("%Y-%m-%d %H:%M:%S"),
+                    s,
+                    w.entries,
+                    w.errors
+                )
+            })
+            .collect();
+
+        (((peak - NORMAL_SCORE) * 200.0).clamp(0.0, 100.0), details)
+    }
+}
+
+struct Window {
+    start: NaiveDateTime,
+    entries: usize,
+    errors: usize,
+    features: Features,
+}
+
+// At most TARGET_WINDOWS round wall-clock windows over the file. The first
+// and last are cut short by the ends of the file and would look like lulls,
+// so they are left out.
+fn windows(entries: &[LogEntry], zone: Zone) -> Vec<Window> {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Vec::new();
+    };
+    let buckets = Bucketing::fitting(first.timestamp, last.timestamp, TARGET_WINDOWS, zone);
+    let (origin, count) = buckets.span(first.timestamp, last.timestamp);
+
+    let mut windows = Vec::with_capacity(count);
+    let mut rest = entries;
+    for i in 0..count {
+        let n = rest.partition_point(|e| buckets.index(origin, e.timestamp) <= i);
+        windows.push(window(origin + buckets.width() * i as i32, &rest[..n]));
+        rest = &rest[n..];
+    }
+    windows.pop();
+    if !windows.is_empty() {
+        windows.remove(0);
+    }
+    windows
+}
+
+fn window(start: NaiveDateTime, entries: &[LogEntry]) -> Window {
+    let errors = entries.iter().filter(|e| e.level.severity() >= 3).count();
+    let warnings = entries.iter().filter(|e| e.level.severity() == 2).count();
+    let sources: HashSet<_> = entries.iter().filter_map(|e| e.source.as_deref()).collect();
+    let avg_len = if entries.is_empty() {
+        0.0
+    } else {
+        entries.iter().map(|e| e.message.len()).sum::<usize>() as f64 / entries.len() as f64
+    };
+
+    Window {
+        start,
+        entries: entries.len(),
+        errors,
+        features: [
+            (entries.len() as f64).ln_1p(),
+            (errors as f64).ln_1p(),
+            (warnings as f64).ln_1p(),
+            sources.len() as f64,
+            avg_len.ln_1p(),
+        ],
+    }
+}
+
+enum Node {
+    Leaf { size: usize },
+    Split { feature: usize, value: f64, left: Box<Node>, right: Box<Node> },
+}
+
+struct Forest {
+    trees: Vec<Node>,
+    sample_size: usize,
+}
+
+impl Forest {
+    fn train(points: &[Features], rng: &mut Rng) -> Self {
+        let sample_size = points.len().min(SAMPLE_SIZE);
+        let max_depth = (sample_size as f64).log2().ceil() as usize;
+
+        let trees = (0..TREES)
+            .map(|_| {
+                let mut sample: Vec<Features> =
+                    (0..sample_size).map(|_| points[rng.below(points.len())]).collect();
+                build(&mut sample, 0, max_depth, rng)
+            })
+            .collect();
+
+        Self { trees, sample_size }
+    }
+
+    // 2^(-E[h(x)] / c(n)): near 1 is anomalous, 0.5 and below is ordinary
+    fn score(&self, point: &Features) -> f64 {
+        let mean_depth =
+            self.trees.iter().map(|t| path_length(t, point, 0)).sum::<f64>() / self.trees.len() as f64;
+        2f64.powf(-mean_depth / average_path(self.sample_size))
+    }
+}
+
+fn build(points: &mut [Features], depth: usize, max_depth: usize, rng: &mut Rng) -> Node {
+    if depth >= max_depth || points.len() <= 1 {
+        return Node::Leaf { size: points.len() };
+    }
+
+    // only features that still vary can split
+    let ranges: Vec<(usize, f64, f64)> = (0..FEATURES)
+        .filter_map(|f| {
+            let min = points.iter().map(|p| p[f]).fold(f64::INFINITY, f64::min);
+            let max = points.iter().map(|p| p[f]).fold(f64::NEG_INFINITY, f64::max);
+            (max > min).then_some((f, min, max))
+        })
+        .collect();
+    if ranges.is_empty() {
+        return Node::Leaf { size: points.len() };
+    }
+
+    let (feature, min, max) = ranges[rng.below(ranges.len())];
+    let value = min + rng.unit() * (max - min);
+
+    let mut mid = 0;
+    for i in 0..points.len() {
+        if points[i][feature] < value {
+            points.swap(i, mid);
+            mid += 1;
+        }
+    }
+    let (left, right) = points.split_at_mut(mid);
+
+    Node::Split {
+        feature,
+        value,
+        left: Box::new(build(left, depth + 1, max_depth, rng)),
+        right: Box::new(build(right, depth + 1, max_depth, rng)),
+    }
+}
+
+fn path_length(node: &Node, point: &Features, depth: usize) -> f64 {
+    match node {
+        Node::Leaf { size } => depth as f64 + average_path(*size),
+        Node::Split { feature, value, left, right } => {
+            let next = if point[*feature] < *value { left } else { right };
+            path_length(next, point, depth + 1)
+        }
+    }
+}
+
+// expected path length of an unsuccessful search in a BST of n points
+fn average_path(n: usize) -> f64 {
+    match n {
+        0 | 1 => 0.0,
+        2 => 1.0,
+        _ => {
+            let n = n as f64;
+            2.0 * ((n - 1.0).ln() + 0.577_215_664_9) - 2.0 * (n - 1.0) / n
+        }
+    }
+}
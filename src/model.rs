@@ -0,0 +1,244 @@
+use chrono::{Duration, NaiveDateTime, Timelike};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+
+use crate::parser::LogEntry;
+use crate::templates::fingerprint;
+
+const MODEL_VERSION: u32 = 1;
+// keeps the dictionary bounded on logs whose templates never settle
+const MAX_TEMPLATES: usize = 50_000;
+// a template below this share of the baseline is rare
+const RARE_SHARE: f64 = 0.001;
+const VOLUME_Z: f64 = 3.0;
+// fewer baseline minutes than this say little about normal volume
+const MIN_MINUTES: u64 = 10;
+const FINDING_LIMIT: usize = 10;
+
+// Running mean and variance (Welford), mergeable across runs.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Moments {
+    pub n: u64,
+    pub mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn push_zeros(&mut self, n: u64) {
+        self.merge(&Moments { n, mean: 0.0, m2: 0.0 });
+    }
+
+    fn merge(&mut self, other: &Moments) {
+        if other.n == 0 {
+            return;
+        }
+        let n = self.n + other.n;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.n as f64 / n as f64;
+        self.m2 += other.m2 + delta * delta * self.n as f64 * other.n as f64 / n as f64;
+        self.n = n;
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        if self.n < 2 {
+            return 0.0;
+        }
+        (self.m2 / (self.n - 1) as f64).sqrt()
+    }
+}
+
+// What earlier runs looked like: how often each message template appeared
+// and how many entries a minute usually holds. `--save-model` writes it
+// (gzipped JSON), `--model` compares a run against it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BaselineModel {
+    version: u32,
+    pub runs: usize,
+    pub entries: usize,
+    pub templates: HashMap<String, usize>,
+    // entries per minute, over every minute the runs covered
+    pub per_minute: Moments,
+}
+
+impl BaselineModel {
+    // `entries` must be sorted by timestamp
+    pub fn learn(entries: &[LogEntry]) -> Self {
+        let mut templates: HashMap<String, usize> = HashMap::new();
+        for entry in entries {
+            *templates.entry(fingerprint(&entry.message)).or_insert(0) += 1;
+        }
+
+        let busy = busy_minutes(entries);
+        let mut per_minute = Moments::default();
+        for &(_, count) in &busy {
+            per_minute.push(count as f64);
+        }
+        per_minute.push_zeros(quiet_count(&busy));
+
+        Self { version: MODEL_VERSION, runs: 1, entries: entries.len(), templates, per_minute }
+    }
+
+    pub fn merge(&mut self, other: BaselineModel) {
+        self.runs += other.runs;
+        self.entries += other.entries;
+        for (template, count) in other.templates {
+            *self.templates.entry(template).or_insert(0) += count;
+        }
+        self.per_minute.merge(&other.per_minute);
+    }
+
+    pub fn load(path: &str) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let model: Self = serde_json::from_reader(BufReader::new(GzDecoder::new(file)))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("not a logscope model: {}", e)))?;
+        if model.version != MODEL_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("model version {} is not supported (expected {})", model.version, MODEL_VERSION),
+            ));
+        }
+        Ok(model)
+    }
+
+    pub fn save(&mut self, path: &str) -> io::Result<()> {
+        if self.templates.len() > MAX_TEMPLATES {
+            let mut counts: Vec<(String, usize)> = self.templates.drain().collect();
+            counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            counts.truncate(MAX_TEMPLATES);
+            self.templates = counts.into_iter().collect();
+        }
+
+        let file = File::create(path)?;
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut encoder, self)?;
+        encoder.finish()?.flush()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RareTemplate {
+    pub template: String,
+    pub count: usize,
+    // share of the baseline's entries; 0 when the template is new
+    pub baseline_share: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VolumeAnomaly {
+    pub minute: String,
+    pub count: usize,
+    pub expected: f64,
+    pub z_score: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BaselineComparison {
+    pub runs: usize,
+    pub entries: usize,
+    // busiest first
+    pub rare_templates: Vec<RareTemplate>,
+    pub new_templates: usize,
+    // largest deviation first
+    pub volume_anomalies: Vec<VolumeAnomaly>,
+}
+
+// Templates the baseline has rarely or never seen, and minutes whose volume
+// is more than VOLUME_Z standard deviations from the baseline's usual rate.
+pub fn compare(entries: &[LogEntry], model: &BaselineModel) -> BaselineComparison {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        *counts.entry(fingerprint(&entry.message)).or_insert(0) += 1;
+    }
+
+    let total = model.entries.max(1) as f64;
+    let mut new_templates = 0;
+    let mut rare_templates: Vec<RareTemplate> = counts
+        .into_iter()
+        .filter_map(|(template, count)| {
+            let seen = *model.templates.get(&template).unwrap_or(&0);
+            if seen == 0 {
+                new_templates += 1;
+            }
+            let baseline_share = seen as f64 / total;
+            (baseline_share < RARE_SHARE).then_some(RareTemplate { template, count, baseline_share })
+        })
+        .collect();
+    rare_templates.sort_unstable_by(|a, b| b.count.cmp(&a.count).then_with(|| a.template.cmp(&b.template)));
+    rare_templates.truncate(FINDING_LIMIT);
+
+    let baseline = &model.per_minute;
+    let std_dev = baseline.std_dev();
+    let mut volume_anomalies: Vec<VolumeAnomaly> = if baseline.n < MIN_MINUTES || std_dev <= 0.0 {
+        Vec::new()
+    } else {
+        let busy = busy_minutes(entries);
+        // every quiet minute scores the same, so the earliest few stand for the rest
+        let quiet_z = baseline.mean / std_dev;
+        let quiet = if quiet_z >= VOLUME_Z { quiet_minutes(&busy, FINDING_LIMIT) } else { Vec::new() };
+        busy.into_iter()
+            .chain(quiet.into_iter().map(|minute| (minute, 0)))
+            .filter_map(|(minute, count)| {
+                let z_score = (count as f64 - baseline.mean) / std_dev;
+                (z_score.abs() >= VOLUME_Z).then(|| VolumeAnomaly {
+                    minute: minute.format("%Y-%m-%d %H:%M").to_string(),
+                    count,
+                    expected: baseline.mean,
+                    z_score,
+                })
+            })
+            .collect()
+    };
+    volume_anomalies.sort_by(|a, b| b.z_score.abs().total_cmp(&a.z_score.abs()));
+    volume_anomalies.truncate(FINDING_LIMIT);
+
+    BaselineComparison {
+        runs: model.runs,
+        entries: model.entries,
+        rare_templates,
+        new_templates,
+        volume_anomalies,
+    }
+}
+
+// Entries in each minute that has any, in order. Only busy minutes are kept,
+// so one stray timestamp decades off costs nothing; quiet minutes are counted
+// from the gaps. Minute boundaries are the same in every zone, so none is
+// needed. `entries` must be sorted by timestamp.
+fn busy_minutes(entries: &[LogEntry]) -> Vec<(NaiveDateTime, usize)> {
+    let mut minutes: Vec<(NaiveDateTime, usize)> = Vec::new();
+    for entry in entries {
+        let minute = entry.timestamp.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(entry.timestamp);
+        match minutes.last_mut() {
+            Some((last, count)) if *last == minute => *count += 1,
+            _ => minutes.push((minute, 1)),
+        }
+    }
+    minutes
+}
+
+fn quiet_count(busy: &[(NaiveDateTime, usize)]) -> u64 {
+    match (busy.first(), busy.last()) {
+        (Some((first, _)), Some((last, _))) => ((*last - *first).num_minutes() + 1) as u64 - busy.len() as u64,
+        _ => 0,
+    }
+}
+
+// the first `limit` quiet minutes between busy ones
+fn quiet_minutes(busy: &[(NaiveDateTime, usize)], limit: usize) -> Vec<NaiveDateTime> {
+    busy.windows(2)
+        .flat_map(|pair| (1..(pair[1].0 - pair[0].0).num_minutes()).map(move |i| pair[0].0 + Duration::minutes(i)))
+        .take(limit)
+        .collect()
+}
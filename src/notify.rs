@@ -0,0 +1,257 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::analyzer::cluster_templates;
+use crate::parser::{LogEntry, LogLevel};
+use crate::report::anomaly_label;
+use crate::stats::ErrorBurst;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+// per connect, read and write, so a stalled relay fails the run instead of hanging it
+const SMTP_TIMEOUT_SECS: u64 = 30;
+// raw bytes per RFC 2047 encoded word, which keeps each under 75 characters
+const ENCODED_WORD_BYTES: usize = 45;
+// error templates named in a webhook alert
+const WEBHOOK_TEMPLATES: usize = 5;
+
+pub enum MailTransport {
+    // pipe the message to `sendmail -t`, which reads recipients from headers
+    Sendmail(String),
+    // unauthenticated plain SMTP relay, e.g. an internal mail host on :25
+    Smtp(String),
+}
+
+pub struct Email<'a> {
+    pub from: &'a str,
+    pub to: &'a [String],
+    pub subject: &'a str,
+    pub body: &'a str,
+}
+
+pub fn send_email(email: &Email, transport: &MailTransport) -> io::Result<()> {
+    let message = format_message(email);
+    match transport {
+        MailTransport::Sendmail(path) => send_via_sendmail(path, &message),
+        MailTransport::Smtp(addr) => send_via_smtp(addr, email, &message),
+    }
+}
+
+fn format_message(email: &Email) -> String {
+    let mut msg = String::new();
+    msg.push_str(&format!("From: {}\r\n", email.from));
+    msg.push_str(&format!("To: {}\r\n", email.to.join(", ")));
+    msg.push_str(&format!("Subject: {}\r\n", encode_header(email.subject)));
+    msg.push_str(&format!("Date: {}\r\n", chrono::Local::now().to_rfc2822()));
+    msg.push_str("MIME-Version: 1.0\r\n");
+    msg.push_str("Content-Type: text/plain; charset=utf-8\r\n");
+    msg.push_str("Content-Transfer-Encoding: 8bit\r\n\r\n");
+
+    for line in email.body.lines() {
+        // SMTP dot-stuffing; harmless for sendmail
+        if line.starts_with('.') {
+            msg.push('.');
+        }
+        msg.push_str(line);
+        msg.push_str("\r\n");
+    }
+
+    msg
+}
+
+// Header text as is when it's ASCII, otherwise as RFC 2047 `=?UTF-8?B?...?=`
+// words on folded lines; a raw UTF-8 subject is garbled by strict relays.
+fn encode_header(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    let mut words = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut cut = rest.len().min(ENCODED_WORD_BYTES);
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let (word, tail) = rest.split_at(cut);
+        words.push(format!("=?UTF-8?B?{}?=", STANDARD.encode(word)));
+        rest = tail;
+    }
+    words.join("\r\n ")
+}
+
+fn send_via_sendmail(path: &str, message: &str) -> io::Result<()> {
+    let mut child = Command::new(path)
+        .arg("-t")
+        .arg("-i")
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("{} exited with {}", path, status)));
+    }
+    Ok(())
+}
+
+fn send_via_smtp(addr: &str, email: &Email, message: &str) -> io::Result<()> {
+    let stream = connect(addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    expect_reply(&mut reader, 220)?;
+    command(&mut writer, &mut reader, "HELO logscope", 250)?;
+    command(&mut writer, &mut reader, &format!("MAIL FROM:<{}>", email.from), 250)?;
+    for rcpt in email.to {
+        command(&mut writer, &mut reader, &format!("RCPT TO:<{}>", rcpt), 250)?;
+    }
+    command(&mut writer, &mut reader, "DATA", 354)?;
+    writer.write_all(message.as_bytes())?;
+    command(&mut writer, &mut reader, ".", 250)?;
+    command(&mut writer, &mut reader, "QUIT", 221)?;
+
+    Ok(())
+}
+
+fn connect(addr: &str) -> io::Result<TcpStream> {
+    let timeout = Duration::from_secs(SMTP_TIMEOUT_SECS);
+    let mut last_err = io::Error::new(io::ErrorKind::NotFound, format!("{}: no address", addr));
+    for sock in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&sock, timeout) {
+            Ok(stream) => {
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_err = e,
+        }
+    }
+    Err(last_err)
+}
+
+fn command(
+    writer: &mut TcpStream,
+    reader: &mut BufReader<TcpStream>,
+    line: &str,
+    expected: u16,
+) -> io::Result<()> {
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\r\n")?;
+    expect_reply(reader, expected)
+}
+
+// Replies are judged by their first digit, as RFC 5321 asks of clients:
+// RCPT TO may answer 251 (forwarded) where 250 is listed.
+fn expect_reply(reader: &mut BufReader<TcpStream>, expected: u16) -> io::Result<()> {
+    // multi-line replies use `250-...` for every line but the last
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "SMTP server closed connection"));
+        }
+
+        let code: u16 = line.get(..3).and_then(|c| c.parse().ok()).unwrap_or(0);
+        if code / 100 != expected / 100 {
+            return Err(io::Error::other(format!("SMTP: unexpected reply: {}", line.trim_end())));
+        }
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ErrorTemplate {
+    pub template: String,
+    pub count: usize,
+    pub example: String,
+}
+
+// `--alert-webhook`: what is POSTed as JSON when an alert goes out. `text`
+// is a one-line summary, so a Slack (or Mattermost, Teams workflow)
+// incoming webhook shows something readable without a relay; everything
+// else is there for receivers that route on it.
+#[derive(Serialize)]
+pub struct WebhookAlert<'a> {
+    pub text: String,
+    pub input: &'a str,
+    // FIRING, ONGOING or RESOLVED
+    pub state: &'a str,
+    pub rule: &'a str,
+    pub value: f64,
+    pub anomaly_score: f64,
+    pub bursts: &'a [ErrorBurst],
+    pub top_error_templates: Vec<ErrorTemplate>,
+    pub time: String,
+}
+
+impl<'a> WebhookAlert<'a> {
+    pub fn new(input: &'a str, state: &'a str, rule: &'a str, value: f64, anomaly_score: f64, bursts: &'a [ErrorBurst]) -> Self {
+        let text = format!(
+            "logscope {} {} on {}: anomaly {:.1} ({}), {} error bursts",
+            state,
+            rule,
+            input,
+            anomaly_score,
+            anomaly_label(anomaly_score),
+            bursts.len()
+        );
+        Self {
+            text,
+            input,
+            state,
+            rule,
+            value,
+            anomaly_score,
+            bursts,
+            top_error_templates: Vec::new(),
+            time: chrono::Local::now().to_rfc3339(),
+        }
+    }
+
+    // the commonest templates among ERROR and FATAL entries
+    pub fn with_errors<'e>(mut self, entries: impl Iterator<Item = &'e LogEntry>) -> Self {
+        let errors: Vec<LogEntry> = entries.filter(|e| matches!(e.level, LogLevel::Error | LogLevel::Fatal)).cloned().collect();
+        self.top_error_templates = cluster_templates(&errors, WEBHOOK_TEMPLATES)
+            .into_iter()
+            .map(|t| ErrorTemplate { template: t.template, count: t.count, example: t.example })
+            .collect();
+        if let Some(top) = self.top_error_templates.first() {
+            self.text.push_str(&format!("; top error: {} (x{})", top.template, top.count));
+        }
+        self
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+}
+
+// POSTed through curl, as config URLs and Pushgateway pushes are, to keep
+// a TLS stack out of the binary.
+pub fn post_webhook(url: &str, body: &[u8]) -> io::Result<()> {
+    let mut child = Command::new("curl")
+        .args(["-fsS", "-X", "POST", "--max-time", &WEBHOOK_TIMEOUT_SECS.to_string()])
+        .args(["-H", "Content-Type: application/json", "--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot run curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body)?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("POST to {} failed: {}", url, stderr.trim())));
+    }
+    Ok(())
+}
@@ -1,9 +1,31 @@
-use chrono::NaiveDateTime;
+use chrono::{Datelike, NaiveDateTime};
+use clap::ValueEnum;
 use rayon::prelude::*;
 use regex::Regex;
 use serde::Serialize;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread;
+
+use crate::archive::{self, ArchiveKind};
+use crate::container::{self, ContainerLine};
+use crate::filter::{self, FilterConfig, FilterStats};
+use crate::intern::Interner;
+use crate::journal::{self, JournalQuery};
+use crate::redact;
+use crate::repair;
+use glob::Pattern;
+use crate::scan;
+use crate::schema::Schema;
+use crate::xml::{self, XmlLayout};
+use memchr::{memchr, memrchr};
+use std::time::Instant;
+use tracing::{debug, trace};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
 pub enum LogLevel {
@@ -16,18 +38,20 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
+    // not FromStr: an unknown name is Unknown rather than an error
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_uppercase().as_str() {
-            "DEBUG" | "DBG" | "TRACE" => Self::Debug,
-            "INFO" | "INFORMATION" => Self::Info,
+            "DEBUG" | "DBG" | "TRACE" | "VERBOSE" => Self::Debug,
+            "INFO" | "INFORMATION" | "NOTICE" | "DEFAULT" => Self::Info,
             "WARN" | "WARNING" => Self::Warn,
             "ERROR" | "ERR" => Self::Error,
-            "FATAL" | "CRITICAL" | "CRIT" => Self::Fatal,
+            "FATAL" | "CRITICAL" | "CRIT" | "FAULT" => Self::Fatal,
             _ => Self::Unknown,
         }
     }
 
-    pub fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &'static str {
         match self {
             Self::Debug => "DEBUG",
             Self::Info => "INFO",
@@ -50,13 +74,178 @@ impl LogLevel {
     }
 }
 
+// `--level-map 'SEVERE=error'`: treat a site-specific level name as a known level
+#[derive(Debug, Clone)]
+pub struct LevelAlias {
+    pub name: String,
+    pub level: LogLevel,
+}
+
+impl LevelAlias {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, level) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("Expected NAME=LEVEL, got '{}'", spec))?;
+        let level = LogLevel::from_str(level.trim());
+        if level == LogLevel::Unknown {
+            return Err(format!("Unknown target level in '{}'", spec));
+        }
+        Ok(Self { name: name.trim().to_uppercase(), level })
+    }
+}
+
+// `--level-rule 'connection reset=warn'`: a site rule for formats without a
+// severity, checked in order before the built-in guess. The pattern is a
+// regex matched against the whole message.
+#[derive(Debug, Clone)]
+pub struct LevelRule {
+    pub pattern: Regex,
+    pub level: LogLevel,
+}
+
+impl LevelRule {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (pattern, level) = spec
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Expected REGEX=LEVEL, got '{}'", spec))?;
+        let level = LogLevel::from_str(level.trim());
+        if level == LogLevel::Unknown {
+            return Err(format!("Unknown target level in '{}'", spec));
+        }
+        let pattern = Regex::new(pattern).map_err(|e| format!("invalid level rule pattern: {}", e))?;
+        Ok(Self { pattern, level })
+    }
+}
+
+// `--pattern` for formats none of the built-in parsers know: a regex with
+// named groups `timestamp` and `message`, optionally `level` and `source`.
+// Without a level group the level is guessed from the message, as for
+// syslog. `--timestamp-format` takes a chrono format string; without one a
+// few common layouts are tried.
+#[derive(Debug, Clone)]
+pub struct CustomPattern {
+    regex: Regex,
+    timestamp_format: Option<String>,
+}
+
+impl CustomPattern {
+    const TIMESTAMP_FORMATS: &'static [&'static str] = &[
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y/%m/%d %H:%M:%S%.f",
+        "%d/%b/%Y:%H:%M:%S %z",
+        "%Y-%m-%dT%H:%M:%S%.f%:z",
+    ];
+
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+        let names: Vec<&str> = regex.capture_names().flatten().collect();
+        for required in ["timestamp", "message"] {
+            if !names.contains(&required) {
+                return Err(format!("Pattern needs a named group (?P<{}>...)", required));
+            }
+        }
+        Ok(Self { regex, timestamp_format: None })
+    }
+
+    pub fn with_timestamp_format(mut self, format: Option<String>) -> Self {
+        self.timestamp_format = format;
+        self
+    }
+
+    fn timestamp(&self, text: &str) -> Option<NaiveDateTime> {
+        match self.timestamp_format {
+            Some(ref fmt) => utc_timestamp(text, fmt),
+            None => Self::TIMESTAMP_FORMATS.iter().find_map(|fmt| utc_timestamp(text, fmt)),
+        }
+    }
+}
+
+// One parsed entry, owning its message: redaction, truncation and
+// --multiline rewrite messages after parsing, and entries outlive the chunk
+// buffer a streamed file is read into. See LogEntryRef for the borrowed form
+// the line parsers produce.
 #[derive(Debug, Clone, Serialize)]
 pub struct LogEntry {
     pub timestamp: NaiveDateTime,
     pub level: LogLevel,
     pub message: String,
-    pub source: Option<String>,
+    pub source: Option<Arc<str>>,
     pub line_number: usize,
+    // JSON fields beyond the ones above, and extra named groups of --pattern
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, serde_json::Value>,
+    // content hash as parsed, before redaction or truncation changed the
+    // message; see content_hash
+    #[serde(skip)]
+    pub hash: Option<[u8; LogEntry::HASH_LEN / 2]>,
+    // the last line of an entry read from several (a stack trace joined by
+    // --multiline, a wevtutil or XML record); None when it is `line_number`
+    #[serde(skip)]
+    pub end_line: Option<usize>,
+}
+
+// An entry as a line parser produced it, its message borrowed from the line
+// where the format allows (JSON and journald unescape theirs, and a line
+// ANSI stripping rewrote is copied). Checking whether a line parses, as
+// entry boundaries and torn-line repair do, never copies the message.
+#[derive(Debug, Clone)]
+pub struct LogEntryRef<'a> {
+    pub timestamp: NaiveDateTime,
+    pub level: LogLevel,
+    pub message: Cow<'a, str>,
+    pub source: Option<Arc<str>>,
+    pub line_number: usize,
+    pub fields: HashMap<String, serde_json::Value>,
+}
+
+impl LogEntryRef<'_> {
+    pub fn into_owned(self) -> LogEntry {
+        LogEntry {
+            timestamp: self.timestamp,
+            level: self.level,
+            message: self.message.into_owned(),
+            source: self.source,
+            line_number: self.line_number,
+            fields: self.fields,
+            hash: None,
+            end_line: None,
+        }
+    }
+
+    fn detach(self) -> LogEntryRef<'static> {
+        LogEntryRef { message: Cow::Owned(self.message.into_owned()), ..self }
+    }
+}
+
+impl LogEntry {
+    pub const HASH_LEN: usize = 16;
+
+    // What the entry says, not when or where: level, source and message
+    // (whitespace runs folded), so the same line logged in another
+    // environment or on another day has the same hash. Hex, HASH_LEN long.
+    // The parser records it before masking or cutting the message, so an
+    // export's hashes match the raw logs `find-hash` reads.
+    pub fn content_hash(&self) -> String {
+        self.hash.unwrap_or_else(|| self.hash_content()).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn hash_content(&self) -> [u8; Self::HASH_LEN / 2] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.level.as_str());
+        hasher.update([0]);
+        hasher.update(self.source.as_deref().unwrap_or(""));
+        hasher.update([0]);
+        for (i, word) in self.message.split_whitespace().enumerate() {
+            if i > 0 {
+                hasher.update(" ");
+            }
+            hasher.update(word);
+        }
+        let mut hash = [0; Self::HASH_LEN / 2];
+        hash.copy_from_slice(&hasher.finalize()[..Self::HASH_LEN / 2]);
+        hash
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -64,15 +253,239 @@ pub enum LogFormat {
     Bracket,   // [2026-01-01 12:00:00] LEVEL message
     Syslog,    // Jan  1 12:00:00 host process[pid]: message
     Json,      // {"timestamp":"...","level":"...","message":"..."}
-    Apache,    // 127.0.0.1 - - [01/Jan/2026:12:00:00 +0000] "GET / HTTP/1.1" 200 1234
+    Apache,    // 127.0.0.1 - - [01/Jan/2026:12:00:00 +0000] "GET / HTTP/1.1" 200 1234, or combined (also nginx's)
+    MacOs,     // 2026-01-01 12:00:00.123456-0800  host process[pid]: (subsystem) <Level>: message
+    Wevtutil,  // `wevtutil qe <log> /f:text`: multi-line "Event[n]:" records
+    Xml,       // <event>-per-record XML: log4j's XMLLayout, Windows event XML
+    Journal,   // `journalctl -o json`: {"__CURSOR":"...","__REALTIME_TIMESTAMP":"...","MESSAGE":"..."}
+    Docker,    // Docker's json-file driver: {"log":"...\n","stream":"stderr","time":"..."}
+    Cri,       // containerd/CRI-O (Kubernetes): 2026-01-01T12:00:00.000Z stderr F message
+    Custom,    // user-supplied --pattern
     Auto,
 }
 
+impl LogFormat {
+    // not FromStr: an unknown name falls back to Auto
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "bracket" => Self::Bracket,
+            "json" => Self::Json,
+            "apache" | "nginx" => Self::Apache,
+            "syslog" => Self::Syslog,
+            "macos" => Self::MacOs,
+            "wevtutil" | "windows" => Self::Wevtutil,
+            "xml" => Self::Xml,
+            "journal" | "journald" => Self::Journal,
+            "docker" => Self::Docker,
+            "cri" | "containerd" | "kubernetes" | "k8s" => Self::Cri,
+            _ => Self::Auto,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Bracket => "bracket",
+            Self::Syslog => "syslog",
+            Self::Json => "json",
+            Self::Apache => "apache",
+            Self::MacOs => "macos",
+            Self::Wevtutil => "wevtutil",
+            Self::Xml => "xml",
+            Self::Journal => "journal",
+            Self::Docker => "docker",
+            Self::Cri => "cri",
+            Self::Custom => "custom",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParseStats {
+    pub total_lines: usize,
+    pub format_counts: HashMap<String, usize>,
+    pub unparsed: usize,
+    pub oversized: usize,
+    pub invalid_utf8: usize,
+    pub duplicates: usize,
+    pub repaired: usize,
+    // continuation lines appended to the entry before them
+    pub continued: usize,
+    // parsed entries dropped by the parser's entry filter, by stage
+    pub filtered: FilterStats,
+    // messages cut at the message length limit
+    pub truncated: usize,
+    // field values that didn't fit their --schema type, left as logged
+    pub schema_misfits: usize,
+    pub sample_unparsed: Option<(usize, String)>,
+    // the input the sample is from, once several are merged
+    pub sample_file: Option<String>,
+    // entries parsed from each input file, in the order the files were given
+    pub files: Vec<(String, usize)>,
+}
+
+impl ParseStats {
+    pub fn merge(&mut self, other: ParseStats) {
+        self.total_lines += other.total_lines;
+        self.unparsed += other.unparsed;
+        self.oversized += other.oversized;
+        self.invalid_utf8 += other.invalid_utf8;
+        self.duplicates += other.duplicates;
+        self.repaired += other.repaired;
+        self.continued += other.continued;
+        self.filtered.merge(&other.filtered);
+        self.truncated += other.truncated;
+        self.schema_misfits += other.schema_misfits;
+        for (fmt, count) in other.format_counts {
+            *self.format_counts.entry(fmt).or_insert(0) += count;
+        }
+        if self.sample_unparsed.is_none() {
+            self.sample_unparsed = other.sample_unparsed;
+            self.sample_file = other.sample_file;
+        }
+        self.files.extend(other.files);
+    }
+
+    fn record_match(&mut self, format: LogFormat) {
+        self.total_lines += 1;
+        *self.format_counts.entry(format.as_str().to_string()).or_insert(0) += 1;
+    }
+
+    fn record_unparsed(&mut self, line_number: usize, line: &str) {
+        self.total_lines += 1;
+        self.unparsed += 1;
+        if self.sample_unparsed.is_none() {
+            self.sample_unparsed = Some((line_number, line.to_string()));
+        }
+    }
+
+    fn record_continuation(&mut self) {
+        self.total_lines += 1;
+        self.continued += 1;
+    }
+
+    // oversized lines are dropped unread, so they never become the sample
+    fn record_oversized(&mut self) {
+        self.total_lines += 1;
+        self.unparsed += 1;
+        self.oversized += 1;
+    }
+}
+
+// Guards against pathological input. Lines above `max_line_len` bytes are
+// dropped before decoding and counted in `ParseStats::oversized`; messages
+// above `max_message_len` bytes are cut short and counted in
+// `ParseStats::truncated`.
+#[derive(Debug, Clone, Copy)]
+pub struct ParserLimits {
+    pub max_line_len: usize,
+    pub max_message_len: Option<usize>,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self { max_line_len: 1024 * 1024, max_message_len: None }
+    }
+}
+
+pub const TRUNCATION_MARKER: &str = "…";
+
+// Whether `needle` occurs in `text` not running into neighbouring ID
+// characters, so trace `abc1` doesn't also find `abc12`.
+fn contains_token(text: &str, needle: &str) -> bool {
+    let id_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    text.match_indices(needle).any(|(at, _)| {
+        !text[..at].chars().next_back().is_some_and(id_char)
+            && !text[at + needle.len()..].chars().next().is_some_and(id_char)
+    })
+}
+
+// Cuts messages longer than `max_len` bytes at a character boundary and marks
+// the cut; returns how many were cut.
+pub fn truncate_messages(entries: &mut [LogEntry], max_len: usize) -> usize {
+    let mut truncated = 0;
+    for entry in entries.iter_mut().filter(|e| e.message.len() > max_len) {
+        let mut cut = max_len;
+        while !entry.message.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        entry.message.truncate(cut);
+        entry.message.push_str(TRUNCATION_MARKER);
+        truncated += 1;
+    }
+    truncated
+}
+
+// A date or time at the start of a line, in the layouts logs commonly use:
+// 2026-01-15, 2026/01/15, 15/Jan/2026, 15.01.2026, Jan 15 12:00:00, 12:00:00.
+fn leading_timestamp_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"^[\[(]?(?:\d{4}[-/]\d{2}[-/]\d{2}|\d{1,2}[/.-](?:\d{1,2}|[A-Z][a-z]{2})[/.-]\d{2,4}|[A-Z][a-z]{2} +\d{1,2} +\d{2}:\d{2}|\d{2}:\d{2}:\d{2})",
+        )
+        .unwrap()
+    })
+}
+
+const SYSLOG_PATTERN: &str =
+    r"^(\w{3}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2})\s+\S+\s+(\S+?)(?:\[\d+\])?:\s+(.+)$";
+const APACHE_PATTERN: &str = r#"^(\S+)\s+\S+\s+\S+\s+\[([^\]]+)\]\s+"([^"]*)"\s+(\d{3})\s+(\S+)(?:\s+"([^"]*)"\s+"([^"]*)")?"#;
+// `log show --style syslog`; the subsystem and level tags are optional
+// `log show --style syslog`: fractional seconds, the zone offset, a host
+// and a process with its pid are always there, which keeps other lines that
+// start with an ISO date out
+const MACOS_PATTERN: &str =
+    r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d+[+-]\d{4})\s+[\w.-]+\s+([^\s\[:]+)\[\d+\]:\s*(?:\([^)]*\)\s*)?(?:<(\w+)>:?\s*)?(.*)$";
+
+/// Reads log files into [`LogEntry`] values sorted by timestamp, with
+/// [`ParseStats`] on what matched. Start from [`with_format`](Self::with_format)
+/// and add options with the `with_*` builders; [`parse_files`](Self::parse_files)
+/// reads several files in parallel into one timeline.
 pub struct LogParser {
     format: LogFormat,
     bracket_re: Regex,
     syslog_re: Regex,
     apache_re: Regex,
+    macos_re: Regex,
+    ansi_re: Regex,
+    // remove color and other escape sequences before a line is parsed
+    strip_ansi: bool,
+    // syslog timestamps carry no year; resolved once instead of per line
+    current_year: i32,
+    sources: Interner,
+    limits: ParserLimits,
+    dedup_across_files: bool,
+    // glob selecting which archive members to parse; None parses all
+    archive_members: Option<Pattern>,
+    repair_torn: bool,
+    // site-specific level names, keyed by upper-cased name
+    level_map: HashMap<String, LogLevel>,
+    level_rules: Vec<LevelRule>,
+    // access log requests slower than this many ms are logged as WARN
+    slow_request_ms: Option<f64>,
+    // what a bare response time at the end of an access log line counts in
+    latency_unit: Option<LatencyUnit>,
+    // only lines containing this text are parsed
+    line_filter: Option<String>,
+    // entries failing this are dropped chunk by chunk while parsing
+    entry_filter: Option<FilterConfig>,
+    custom: Option<CustomPattern>,
+    xml: XmlLayout,
+    schema: Schema,
+    // join unparsed continuation lines onto the entry before them
+    multiline: bool,
+    multiline_re: Option<Regex>,
+    // `--redact` patterns, applied to entries and the unparsed-line sample
+    // before the message length limit and the entry filter
+    redact: Vec<Regex>,
+}
+
+impl Default for LogParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl LogParser {
@@ -87,186 +500,1442 @@ impl LogParser {
                 r"^\[(\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2})\]\s+(\w+)\s+(.+)$",
             )
             .unwrap(),
-            syslog_re: Regex::new(
-                r"^(\w{3}\s+\d{1,2}\s+\d{2}:\d{2}:\d{2})\s+\S+\s+(\S+?)(?:\[\d+\])?:\s+(.+)$",
-            )
-            .unwrap(),
-            apache_re: Regex::new(
-                r#"^\S+\s+\S+\s+\S+\s+\[([^\]]+)\]\s+"[^"]*"\s+(\d{3})\s+\S+"#,
+            syslog_re: Regex::new(SYSLOG_PATTERN).unwrap(),
+            apache_re: Regex::new(APACHE_PATTERN).unwrap(),
+            macos_re: Regex::new(MACOS_PATTERN).unwrap(),
+            // CSI (colors, cursor movement), OSC (titles, hyperlinks) and
+            // two-byte escapes, as emitted by CI runners and container logs
+            ansi_re: Regex::new(
+                r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)|[@-Z\\-_])",
             )
             .unwrap(),
+            current_year: chrono::Local::now().year(),
+            sources: Interner::new(),
+            limits: ParserLimits::default(),
+            dedup_across_files: true,
+            archive_members: None,
+            repair_torn: false,
+            strip_ansi: true,
+            level_map: HashMap::new(),
+            level_rules: Vec::new(),
+            slow_request_ms: None,
+            latency_unit: None,
+            line_filter: None,
+            entry_filter: None,
+            custom: None,
+            xml: XmlLayout::default(),
+            schema: Schema::default(),
+            multiline: false,
+            multiline_re: None,
+            redact: Vec::new(),
         }
     }
 
-    pub fn parse_file(&self, file_path: &str) -> Result<Vec<LogEntry>, std::io::Error> {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
+    // Stack traces and other multi-line messages: a line that doesn't parse
+    // is appended to the previous entry's message when it is indented, has
+    // no leading timestamp, or matches `pattern`. A pattern turns this on by
+    // itself.
+    pub fn with_multiline(mut self, enabled: bool, pattern: Option<Regex>) -> Self {
+        self.multiline = enabled || pattern.is_some();
+        self.multiline_re = pattern;
+        self
+    }
 
-        let lines: Vec<(usize, String)> = reader
-            .lines()
-            .enumerate()
-            .filter_map(|(i, l)| l.ok().map(|s| (i + 1, s)))
-            .collect();
+    fn is_continuation(&self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return false;
+        }
+        line.starts_with([' ', '\t'])
+            || !leading_timestamp_re().is_match(line)
+            || self.multiline_re.as_ref().is_some_and(|re| re.is_match(line))
+    }
 
-        let entries: Vec<LogEntry> = lines
-            .par_iter()
-            .filter_map(|(line_num, line)| self.parse_line(line, *line_num))
+    pub fn with_redaction(mut self, patterns: Vec<Regex>) -> Self {
+        self.redact = patterns;
+        self
+    }
+
+    // replaces the built-in formats entirely
+    pub fn with_custom_pattern(mut self, pattern: Option<CustomPattern>) -> Self {
+        if pattern.is_some() {
+            self.format = LogFormat::Custom;
+        }
+        self.custom = pattern;
+        self
+    }
+
+    // typed before the entry filter, so it compares typed values
+    pub fn with_schema(mut self, schema: Option<Schema>) -> Self {
+        self.schema = schema.unwrap_or_default();
+        self
+    }
+
+    pub fn with_xml_layout(mut self, layout: XmlLayout) -> Self {
+        self.xml = layout;
+        self
+    }
+
+    // Filtering while parsing keeps only matching entries in memory, which
+    // is what lets a filtered run over a file larger than RAM finish.
+    pub fn with_entry_filter(mut self, filter: Option<FilterConfig>) -> Self {
+        self.entry_filter = filter.filter(|f| !f.is_empty());
+        self
+    }
+
+    // Skips lines without `needle` as a whole token before parsing, which
+    // also finds IDs kept in JSON fields other than the message. Much cheaper
+    // than filtering entries when looking for a single trace.
+    pub fn with_line_filter(mut self, needle: Option<String>) -> Self {
+        self.line_filter = needle;
+        self
+    }
+
+    pub fn with_slow_request(mut self, ms: Option<f64>) -> Self {
+        self.slow_request_ms = ms;
+        self
+    }
+
+    pub fn with_latency_unit(mut self, unit: Option<LatencyUnit>) -> Self {
+        self.latency_unit = unit;
+        self
+    }
+
+    pub fn with_level_map(mut self, aliases: &[LevelAlias]) -> Self {
+        self.level_map = aliases
+            .iter()
+            .map(|a| (a.name.clone(), a.level.clone()))
             .collect();
+        self
+    }
+
+    pub fn with_level_rules(mut self, rules: &[LevelRule]) -> Self {
+        self.level_rules = rules.to_vec();
+        self
+    }
+
+    // for formats without a severity field
+    fn guessed_level(&self, message: &str) -> LogLevel {
+        match self.level_rules.iter().find(|r| r.pattern.is_match(message)) {
+            Some(rule) => rule.level.clone(),
+            None => guess_level(message),
+        }
+    }
+
+    fn level(&self, s: &str) -> LogLevel {
+        if !self.level_map.is_empty() {
+            if let Some(level) = self.level_map.get(&s.to_uppercase()) {
+                return level.clone();
+            }
+        }
+        LogLevel::from_str(s)
+    }
 
-        let mut sorted = entries;
-        sorted.sort_unstable_by_key(|e| e.timestamp);
+    // on by default: CI runners and container logs color their output
+    pub fn with_strip_ansi(mut self, strip: bool) -> Self {
+        self.strip_ansi = strip;
+        self
+    }
+
+    pub fn with_torn_line_repair(mut self, repair: bool) -> Self {
+        self.repair_torn = repair;
+        self
+    }
+
+    pub fn with_archive_members(mut self, pattern: Option<Pattern>) -> Self {
+        self.archive_members = pattern;
+        self
+    }
+
+    // rotated files (app.log + app.log.1) often overlap at the boundary
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup_across_files = dedup;
+        self
+    }
 
-        Ok(sorted)
+    pub fn with_limits(mut self, limits: ParserLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
-    fn parse_line(&self, line: &str, line_number: usize) -> Option<LogEntry> {
+    pub fn limits(&self) -> ParserLimits {
+        self.limits
+    }
+
+    // Each line of `buf` that parses on its own, its message borrowed from
+    // `buf` where the format allows. Nothing is joined, redacted or filtered:
+    // this is the line parsers alone, for callers that hold the whole buffer
+    // (a mapped file, say) and want to look at entries without copying them.
+    pub fn parse_refs<'a>(&'a self, buf: &'a [u8]) -> impl Iterator<Item = LogEntryRef<'a>> + 'a {
+        buf.split(|&b| b == b'\n')
+            .enumerate()
+            .filter(|(_, l)| l.len() <= self.limits.max_line_len)
+            .filter_map(move |(i, l)| match String::from_utf8_lossy(l) {
+                Cow::Borrowed(l) => self.parse_line(l, i + 1),
+                Cow::Owned(l) => self.parse_line(&l, i + 1).map(|(fmt, e)| (fmt, e.detach())),
+            })
+            .map(|(_, entry)| entry)
+    }
+
+    fn parse_line<'a>(&self, line: &'a str, line_number: usize) -> Option<(LogFormat, LogEntryRef<'a>)> {
+        match self.without_ansi(line) {
+            Cow::Borrowed(line) => self.parse_trimmed(line, line_number),
+            Cow::Owned(line) => self.parse_trimmed(&line, line_number).map(|(fmt, e)| (fmt, e.detach())),
+        }
+    }
+
+    fn parse_trimmed<'a>(&self, line: &'a str, line_number: usize) -> Option<(LogFormat, LogEntryRef<'a>)> {
         let line = line.trim();
         if line.is_empty() {
             return None;
         }
 
         match self.format {
+            LogFormat::Auto => self.parse_auto(line, line_number),
+            fmt => self.parse_as(fmt, line, line_number).map(|e| (fmt, e)),
+        }
+    }
+
+    fn without_ansi<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        if self.strip_ansi && line.contains('\x1b') {
+            self.ansi_re.replace_all(line, "")
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    // Dispatches on the first byte so each line only runs the parsers that
+    // could possibly match it. Candidates keep the bracket → json → apache →
+    // syslog priority, so results are identical to trying all four in turn.
+    fn parse_auto<'a>(&self, line: &'a str, line_number: usize) -> Option<(LogFormat, LogEntryRef<'a>)> {
+        let candidates: &[LogFormat] = match line.as_bytes()[0] {
+            b'[' => &[LogFormat::Bracket, LogFormat::Apache],
+            b'{' if line.starts_with("{\"__CURSOR\"") => &[LogFormat::Journal],
+            b'{' if line.starts_with("{\"log\":") => &[LogFormat::Docker, LogFormat::Json],
+            b'{' => &[LogFormat::Json, LogFormat::Apache],
+            // an ISO date can't start an IP address or a syslog month
+            b if b.is_ascii_digit() && line.as_bytes().get(4) == Some(&b'-') => &[LogFormat::Cri, LogFormat::MacOs],
+            b if b.is_ascii_alphanumeric() || b == b'_' => {
+                if may_be_apache(line) {
+                    &[LogFormat::Apache, LogFormat::Syslog]
+                } else {
+                    &[LogFormat::Syslog]
+                }
+            }
+            _ => &[LogFormat::Apache],
+        };
+
+        candidates
+            .iter()
+            .find_map(|&fmt| self.parse_as(fmt, line, line_number).map(|e| (fmt, e)))
+    }
+
+    fn parse_as<'a>(&self, format: LogFormat, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
+        match format {
             LogFormat::Bracket => self.parse_bracket(line, line_number),
             LogFormat::Syslog => self.parse_syslog(line, line_number),
             LogFormat::Json => self.parse_json(line, line_number),
             LogFormat::Apache => self.parse_apache(line, line_number),
-            LogFormat::Auto => self
-                .parse_bracket(line, line_number)
-                .or_else(|| self.parse_json(line, line_number))
-                .or_else(|| self.parse_apache(line, line_number))
-                .or_else(|| self.parse_syslog(line, line_number)),
+            LogFormat::MacOs => self.parse_macos(line, line_number),
+            LogFormat::Custom => self.parse_custom(line, line_number),
+            LogFormat::Journal => self.parse_journal(line, line_number),
+            LogFormat::Docker => self.parse_container(container::docker(line)?, line_number),
+            LogFormat::Cri => self.parse_container(container::cri(line)?, line_number),
+            // record-oriented; handled on the whole buffer in parse_bytes
+            LogFormat::Wevtutil | LogFormat::Xml | LogFormat::Auto => None,
         }
     }
 
-    fn parse_bracket(&self, line: &str, line_number: usize) -> Option<LogEntry> {
+    fn parse_bracket<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
+        if let Some((timestamp, level, message)) = scan::bracket(line) {
+            return Some(LogEntryRef {
+                timestamp,
+                level: self.level(level),
+                message: Cow::Borrowed(message),
+                source: None,
+                line_number,
+                fields: HashMap::new(),
+            });
+        }
+
         let caps = self.bracket_re.captures(line)?;
-        let ts_str = caps.get(1)?.as_str().replace('T', " ");
-        let timestamp = NaiveDateTime::parse_from_str(&ts_str, "%Y-%m-%d %H:%M:%S").ok()?;
-        let level = LogLevel::from_str(caps.get(2)?.as_str());
-        let message = caps.get(3)?.as_str().to_string();
+        let ts_str = caps.get(1)?.as_str();
+        let timestamp = NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%d %H:%M:%S")
+            .or_else(|_| NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%dT%H:%M:%S"))
+            .map_err(|e| trace!(line_number, error = %e, "unparseable timestamp"))
+            .ok()?;
+        let level = self.level(caps.get(2)?.as_str());
+        let message = caps.get(3)?.as_str();
 
-        Some(LogEntry { timestamp, level, message, source: None, line_number })
+        Some(LogEntryRef {
+            timestamp,
+            level,
+            message: Cow::Borrowed(message),
+            source: None,
+            line_number,
+            fields: HashMap::new(),
+        })
     }
 
-    fn parse_json(&self, line: &str, line_number: usize) -> Option<LogEntry> {
-        let v: serde_json::Value = serde_json::from_str(line).ok()?;
-        let obj = v.as_object()?;
+    fn parse_custom<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
+        let custom = self.custom.as_ref()?;
+        let caps = custom.regex.captures(line)?;
+        let ts_str = caps.name("timestamp")?.as_str();
+        let timestamp = custom
+            .timestamp(ts_str)
+            .or_else(|| {
+                trace!(line_number, timestamp = ts_str, "unparseable timestamp");
+                None
+            })?;
+        let message = caps.name("message")?.as_str();
+        let level = match caps.name("level") {
+            Some(level) => self.level(level.as_str()),
+            None => self.guessed_level(message),
+        };
+        let source = caps.name("source").map(|s| self.sources.intern(s.as_str()));
+        let fields = custom
+            .regex
+            .capture_names()
+            .flatten()
+            .filter(|name| !matches!(*name, "timestamp" | "level" | "message" | "source"))
+            .filter_map(|name| Some((name.to_string(), caps.name(name)?.as_str().into())))
+            .collect();
 
-        let ts_str = obj.get("timestamp")
-            .or_else(|| obj.get("time"))
-            .or_else(|| obj.get("@timestamp"))
-            .and_then(|v| v.as_str())?;
+        Some(LogEntryRef { timestamp, level, message: Cow::Borrowed(message), source, line_number, fields })
+    }
 
-        let timestamp = NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%dT%H:%M:%S")
-            .or_else(|_| NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%d %H:%M:%S"))
-            .ok()?;
+    // The application's line inside is parsed as if it stood alone (with
+    // --pattern if given, otherwise detected), keeping the runtime's time so
+    // lines order the same whether or not they parse; one that doesn't is
+    // the message as it is. The stream is kept as a field.
+    fn parse_container(&self, line: ContainerLine, line_number: usize) -> Option<LogEntryRef<'static>> {
+        let inner = match self.custom {
+            Some(_) => self.parse_custom(&line.log, line_number).map(LogEntryRef::detach),
+            None if line.log.trim().is_empty() => None,
+            None if line.log.starts_with('{') => self.json_entry(&line.log, line_number, Some(line.time)),
+            None => self.parse_auto(&line.log, line_number).map(|(_, entry)| entry.detach()),
+        };
+        let mut entry = match inner {
+            Some(entry) => LogEntryRef { timestamp: line.time, ..entry },
+            None => LogEntryRef {
+                timestamp: line.time,
+                level: self.guessed_level(&line.log),
+                message: Cow::Owned(line.log.into_owned()),
+                source: None,
+                line_number,
+                fields: HashMap::new(),
+            },
+        };
+        entry.fields.insert("stream".to_string(), serde_json::Value::from(line.stream));
+        Some(entry)
+    }
+
+    // The unit, PID and host are kept as fields, along with whatever fields
+    // the application logged itself; journald's other bookkeeping (the
+    // underscore fields) is dropped.
+    fn parse_journal<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
+        let serde_json::Value::Object(mut obj) = serde_json::from_str(line).ok()? else {
+            return None;
+        };
 
-        let level_str = obj.get("level")
-            .or_else(|| obj.get("severity"))
-            .or_else(|| obj.get("lvl"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("UNKNOWN");
+        let micros: i64 = obj.get("__REALTIME_TIMESTAMP")?.as_str()?.parse().ok()?;
+        let timestamp = chrono::DateTime::from_timestamp_micros(micros)?.with_timezone(&chrono::Local).naive_local();
 
-        let message = obj.get("message")
-            .or_else(|| obj.get("msg"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .to_string();
+        let message = match obj.remove("MESSAGE") {
+            Some(serde_json::Value::String(s)) => s,
+            // not valid UTF-8, so journalctl gives the bytes
+            Some(serde_json::Value::Array(bytes)) => {
+                let bytes: Vec<u8> = bytes.iter().filter_map(|b| b.as_u64().map(|b| b as u8)).collect();
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+            _ => String::new(),
+        };
+        let level = match obj.remove("PRIORITY").as_ref().and_then(|p| p.as_str()).and_then(journal::priority_level) {
+            Some(level) => self.level(level),
+            None => self.guessed_level(&message),
+        };
+        let source = take_first(&mut obj, &["SYSLOG_IDENTIFIER", "_COMM", "_SYSTEMD_UNIT"])
+            .and_then(|s| s.as_str().map(|s| self.sources.intern(s)));
 
-        let source = obj.get("logger")
-            .or_else(|| obj.get("source"))
-            .or_else(|| obj.get("service"))
-            .and_then(|v| v.as_str())
-            .map(String::from);
+        let mut fields: HashMap<String, serde_json::Value> = HashMap::new();
+        for (journal_key, name) in [("_SYSTEMD_UNIT", "unit"), ("_PID", "pid"), ("_HOSTNAME", "host")] {
+            if let Some(value) = obj.remove(journal_key) {
+                fields.insert(name.to_string(), value);
+            }
+        }
+        for (key, value) in obj {
+            if !key.starts_with('_') && !key.starts_with("SYSLOG_") {
+                fields.insert(key.to_lowercase(), value);
+            }
+        }
 
-        Some(LogEntry {
+        Some(LogEntryRef { timestamp, level, message: Cow::Owned(message), source, line_number, fields })
+    }
+
+    fn parse_json<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
+        self.json_entry(line, line_number, None)
+    }
+
+    // `time` stands in for a missing timestamp, as where a container
+    // runtime recorded one around the line
+    fn json_entry(&self, line: &str, line_number: usize, time: Option<NaiveDateTime>) -> Option<LogEntryRef<'static>> {
+        let serde_json::Value::Object(mut obj) = serde_json::from_str(line).ok()? else {
+            return None;
+        };
+
+        let ts = take_first(&mut obj, &["timestamp", "time", "@timestamp"]);
+        let timestamp = match time {
+            Some(time) => time,
+            None => {
+                let ts_str = ts.as_ref()?.as_str()?;
+                NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%dT%H:%M:%S")
+                    .or_else(|_| NaiveDateTime::parse_from_str(ts_str, "%Y-%m-%d %H:%M:%S"))
+                    .map_err(|e| trace!(line_number, error = %e, "unparseable timestamp"))
+                    .ok()?
+            }
+        };
+
+        let level = take_first(&mut obj, &["level", "severity", "lvl"]);
+        let level_str = level.as_ref().and_then(|v| v.as_str()).unwrap_or("UNKNOWN");
+
+        let message = take_first(&mut obj, &["message", "msg"]);
+        let message = match message {
+            Some(serde_json::Value::String(s)) => s,
+            _ => String::new(),
+        };
+
+        let source = take_first(&mut obj, &["logger", "source", "service"]);
+        let source = source.as_ref().and_then(|v| v.as_str()).map(|s| self.sources.intern(s));
+
+        Some(LogEntryRef {
             timestamp,
-            level: LogLevel::from_str(level_str),
-            message,
+            level: self.level(level_str),
+            message: Cow::Owned(message),
             source,
             line_number,
+            fields: obj.into_iter().collect(),
         })
     }
 
-    fn parse_apache(&self, line: &str, line_number: usize) -> Option<LogEntry> {
+    fn parse_apache<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
         let caps = self.apache_re.captures(line)?;
-        let ts_str = caps.get(1)?.as_str();
-        let timestamp = NaiveDateTime::parse_from_str(ts_str, "%d/%b/%Y:%H:%M:%S %z")
-            .or_else(|_| NaiveDateTime::parse_from_str(ts_str, "%d/%b/%Y:%H:%M:%S +0000"))
+        let ts_str = caps.get(2)?.as_str();
+        let timestamp = chrono::DateTime::parse_from_str(ts_str, "%d/%b/%Y:%H:%M:%S %z")
+            .map(|d| d.naive_utc())
+            .map_err(|e| trace!(line_number, error = %e, "unparseable timestamp"))
             .ok()?;
 
-        let status: u16 = caps.get(2)?.as_str().parse().ok()?;
-        let level = match status {
+        let status: u16 = caps.get(4)?.as_str().parse().ok()?;
+        let mut level = match status {
             200..=399 => LogLevel::Info,
             400..=499 => LogLevel::Warn,
             500..=599 => LogLevel::Error,
             _ => LogLevel::Unknown,
         };
 
-        Some(LogEntry {
+        let latency_ms = trailing_latency_ms(&line[caps.get(0)?.end()..], self.latency_unit);
+        if let Some(limit) = self.slow_request_ms {
+            if level == LogLevel::Info && latency_ms.is_some_and(|ms| ms > limit) {
+                level = LogLevel::Warn;
+            }
+        }
+
+        // the request as fields, for the HTTP section and --group-by; the
+        // message stays the whole line
+        let mut fields = HashMap::new();
+        let mut request = caps.get(3)?.as_str().split_whitespace();
+        if let (Some(method), Some(path)) = (request.next(), request.next()) {
+            fields.insert("method".to_string(), method.into());
+            fields.insert("path".to_string(), path.into());
+        }
+        fields.insert("status".to_string(), status.into());
+        fields.insert("client".to_string(), caps.get(1)?.as_str().into());
+        if let Ok(bytes) = caps.get(5)?.as_str().parse::<u64>() {
+            fields.insert("bytes".to_string(), bytes.into());
+        }
+        // combined format; `-` is nginx and Apache for none
+        for (group, name) in [(6, "referer"), (7, "user_agent")] {
+            if let Some(value) = caps.get(group).map(|m| m.as_str()).filter(|v| !v.is_empty() && *v != "-") {
+                fields.insert(name.to_string(), value.into());
+            }
+        }
+        if let Some(ms) = latency_ms {
+            fields.insert("response_ms".to_string(), ms.into());
+        }
+
+        Some(LogEntryRef {
             timestamp,
             level,
-            message: line.to_string(),
-            source: Some("apache".into()),
+            message: Cow::Borrowed(line),
+            source: Some(self.sources.intern("apache")),
             line_number,
+            fields,
         })
     }
 
-    fn parse_syslog(&self, line: &str, line_number: usize) -> Option<LogEntry> {
+    fn parse_syslog<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
         let caps = self.syslog_re.captures(line)?;
         let ts_str = caps.get(1)?.as_str();
 
-        let current_year = chrono::Local::now().format("%Y").to_string();
-        let full_ts = format!("{} {}", current_year, ts_str);
+        let full_ts = format!("{} {}", self.current_year, ts_str);
 
         let timestamp = NaiveDateTime::parse_from_str(&full_ts, "%Y %b %e %H:%M:%S")
             .or_else(|_| NaiveDateTime::parse_from_str(&full_ts, "%Y %b %d %H:%M:%S"))
+            .map_err(|e| trace!(line_number, error = %e, "unparseable timestamp"))
+            .ok()?;
+
+        let source = Some(self.sources.intern(caps.get(2)?.as_str()));
+        let message = caps.get(3)?.as_str();
+        let level = self.guessed_level(message);
+
+        Some(LogEntryRef {
+            timestamp,
+            level,
+            message: Cow::Borrowed(message),
+            source,
+            line_number,
+            fields: HashMap::new(),
+        })
+    }
+
+    fn parse_macos<'a>(&self, line: &'a str, line_number: usize) -> Option<LogEntryRef<'a>> {
+        let caps = self.macos_re.captures(line)?;
+        let timestamp = chrono::DateTime::parse_from_str(caps.get(1)?.as_str(), "%Y-%m-%d %H:%M:%S%.f%z")
+            .map(|d| d.naive_utc())
+            .map_err(|e| trace!(line_number, error = %e, "unparseable timestamp"))
             .ok()?;
 
-        let source = Some(caps.get(2)?.as_str().to_string());
-        let message = caps.get(3)?.as_str().to_string();
+        let source = Some(self.sources.intern(caps.get(2)?.as_str()));
+        let message = caps.get(4)?.as_str();
+        let level = match caps.get(3).map(|m| self.level(m.as_str())) {
+            Some(level) if level != LogLevel::Unknown => level,
+            _ => self.guessed_level(message),
+        };
+
+        Some(LogEntryRef {
+            timestamp,
+            level,
+            message: Cow::Borrowed(message),
+            source,
+            line_number,
+            fields: HashMap::new(),
+        })
+    }
+
+    // Records look like
+    //
+    //   Event[0]:
+    //     Source: Service Control Manager
+    //     Date: 2026-01-01T12:00:00.1230000Z
+    //     Level: Error
+    //     ...
+    //     Description:
+    //   The service terminated unexpectedly.
+    //
+    // Each record becomes one entry whose line number is its header line.
+    fn parse_wevtutil(&self, text: &str) -> (Vec<LogEntry>, ParseStats) {
+        let mut entries = Vec::new();
+        let mut parse_stats = ParseStats::default();
+        let mut record: Option<WevtutilRecord> = None;
+
+        let finish = |record: WevtutilRecord, entries: &mut Vec<LogEntry>, stats: &mut ParseStats| {
+            match record.into_entry(self) {
+                Ok(entry) => {
+                    stats.record_match(LogFormat::Wevtutil);
+                    entries.push(entry);
+                }
+                Err((line_number, header)) => {
+                    trace!(line_number, "wevtutil record without a usable date");
+                    stats.record_unparsed(line_number, &header);
+                }
+            }
+        };
+
+        // the same limits and cleanup as line-oriented logs
+        for (i, line) in text.lines().enumerate() {
+            if line.len() > self.limits.max_line_len {
+                trace!(line_number = i + 1, "line exceeds max length");
+                parse_stats.record_oversized();
+                continue;
+            }
+            let line = self.without_ansi(line);
+            let line = line.trim_end();
+            if line.starts_with("Event[") && line.ends_with(':') {
+                if let Some(done) = record.take() {
+                    finish(done, &mut entries, &mut parse_stats);
+                }
+                record = Some(WevtutilRecord::new(i + 1, line));
+                continue;
+            }
+
+            match record {
+                Some(ref mut r) => r.push_line(i + 1, line),
+                None if !line.trim().is_empty() => parse_stats.record_unparsed(i + 1, line.trim()),
+                None => {}
+            }
+        }
+        if let Some(done) = record.take() {
+            finish(done, &mut entries, &mut parse_stats);
+        }
+
+        entries.sort_unstable_by_key(|e| e.timestamp);
+        (entries, parse_stats)
+    }
+
+    // Each record becomes one entry whose line number is where its start tag
+    // is; fields other than timestamp, level, message and source are kept.
+    fn parse_xml(&self, text: &str) -> (Vec<LogEntry>, ParseStats) {
+        let mut entries = Vec::new();
+        let mut parse_stats = ParseStats::default();
+        for record in self.xml.records(text) {
+            let entry = record.and_then(|r| {
+                let line_number = r.line_number;
+                self.xml_entry(r)
+                    .ok_or_else(|| (line_number, format!("record at line {} without a usable timestamp", line_number)))
+            });
+            match entry {
+                Ok(entry) => {
+                    parse_stats.record_match(LogFormat::Xml);
+                    entries.push(entry);
+                }
+                Err((line_number, header)) => {
+                    trace!(line_number, "unusable xml record");
+                    parse_stats.record_unparsed(line_number, &header);
+                }
+            }
+        }
+        entries.sort_by_key(|e| e.timestamp);
+        (entries, parse_stats)
+    }
+
+    fn xml_entry(&self, mut record: xml::XmlRecord) -> Option<LogEntry> {
+        let mut take = |name: &str| record.values.remove(name);
+        let timestamp = loose_timestamp(&take("timestamp")?)?;
+        let message = take("message").unwrap_or_default();
+        let level = match take("level") {
+            Some(level) => self.level(xml::windows_level(&level).unwrap_or(&level)),
+            None => self.guessed_level(&message),
+        };
+        let source = take("source").map(|s| self.sources.intern(&s));
+        Some(LogEntry {
+            timestamp,
+            level,
+            message,
+            source,
+            line_number: record.line_number,
+            fields: record.values.into_iter().map(|(k, v)| (k, serde_json::Value::String(v))).collect(),
+            hash: None,
+            end_line: Some(record.end_line).filter(|&end| end > record.line_number),
+        })
+    }
+}
+
+// Seconds or milliseconds since the epoch (log4j writes the latter), or an
+// ISO timestamp with or without an offset (Windows events, typed fields).
+pub fn loose_timestamp(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(n) = value.parse::<i64>() {
+        return if n > 100_000_000_000 {
+            chrono::DateTime::from_timestamp_millis(n)
+        } else {
+            chrono::DateTime::from_timestamp(n, 0)
+        }
+        .map(|d| d.naive_utc());
+    }
+    let trimmed = value.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .or_else(|| chrono::DateTime::parse_from_rfc3339(value).ok().map(|d| d.naive_utc()))
+}
+
+// `text` in `fmt`; a format with an offset (`%z`, `%:z`) gives the time in
+// UTC, like the timestamps that carry none are taken to be
+fn utc_timestamp(text: &str, fmt: &str) -> Option<NaiveDateTime> {
+    if fmt.contains("%z") || fmt.contains("%:z") {
+        chrono::DateTime::parse_from_str(text, fmt).ok().map(|d| d.naive_utc())
+    } else {
+        NaiveDateTime::parse_from_str(text, fmt).ok()
+    }
+}
+
+#[derive(Default)]
+struct WevtutilRecord {
+    line_number: usize,
+    // the last line with something on it
+    end_line: usize,
+    header: String,
+    source: Option<String>,
+    date: Option<String>,
+    level: Option<String>,
+    event_id: Option<String>,
+    description: Vec<String>,
+    in_description: bool,
+}
+
+impl WevtutilRecord {
+    fn new(line_number: usize, header: &str) -> Self {
+        Self { line_number, end_line: line_number, header: header.to_string(), ..Default::default() }
+    }
+
+    fn push_line(&mut self, line_number: usize, line: &str) {
+        if !line.trim().is_empty() {
+            self.end_line = line_number;
+        }
+        if self.in_description {
+            if !line.trim().is_empty() {
+                self.description.push(line.trim().to_string());
+            }
+            return;
+        }
+
+        let Some((key, value)) = line.trim().split_once(':') else {
+            return;
+        };
+        let value = value.trim().to_string();
+        match key {
+            "Source" => self.source = Some(value),
+            "Date" => self.date = Some(value),
+            "Level" => self.level = Some(value),
+            "Event ID" => self.event_id = Some(value),
+            "Description" => {
+                self.in_description = true;
+                if !value.is_empty() {
+                    self.description.push(value);
+                }
+            }
+            _ => {}
+        }
+    }
 
-        let level = if message.to_lowercase().contains("error") || message.to_lowercase().contains("fail") {
-            LogLevel::Error
-        } else if message.to_lowercase().contains("warn") {
-            LogLevel::Warn
+    fn into_entry(self, parser: &LogParser) -> Result<LogEntry, (usize, String)> {
+        let timestamp = self.date.as_deref().and_then(|d| {
+            let d = d.trim_end_matches('Z');
+            NaiveDateTime::parse_from_str(d, "%Y-%m-%dT%H:%M:%S%.f")
+                .or_else(|_| NaiveDateTime::parse_from_str(d, "%Y-%m-%dT%H:%M:%S"))
+                .ok()
+        });
+        let Some(timestamp) = timestamp else {
+            return Err((self.line_number, self.header));
+        };
+
+        let message = if self.description.is_empty() {
+            format!("Event ID {}", self.event_id.as_deref().unwrap_or("?"))
         } else {
-            LogLevel::Info
+            self.description.join(" ")
         };
 
-        Some(LogEntry { timestamp, level, message, source, line_number })
+        Ok(LogEntry {
+            timestamp,
+            level: self.level.as_deref().map_or(LogLevel::Unknown, |l| parser.level(l)),
+            message,
+            source: self.source.as_deref().map(|s| parser.sources.intern(s)),
+            line_number: self.line_number,
+            fields: HashMap::new(),
+            hash: None,
+            end_line: Some(self.end_line).filter(|&end| end > self.line_number),
+        })
     }
 }
 
+// removes and returns the first of `keys` present in a JSON object; the
+// first key present wins even when its value turns out unusable
+fn take_first(obj: &mut serde_json::Map<String, serde_json::Value>, keys: &[&str]) -> Option<serde_json::Value> {
+    keys.iter().find_map(|key| obj.remove(*key))
+}
+
+// Severity words count only as whole words outside URLs, paths and quoted
+// text, so `GET /error-pages/404.css`, `retry_on_failure=true` or a search
+// for "failed login" don't turn an entry into an error.
+fn guess_level(message: &str) -> LogLevel {
+    let visible = masked_re().replace_all(message, " ");
+    let mut level = LogLevel::Info;
+    for caps in severity_word_re().captures_iter(&visible) {
+        if caps.get(1).is_some() {
+            return LogLevel::Error;
+        }
+        level = LogLevel::Warn;
+    }
+    level
+}
+
+fn severity_word_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:(errors?|errored|fail(?:s|ed|ing|ures?)?)|warn(?:s|ed|ings?)?)\b").unwrap()
+    })
+}
+
+// URLs, anything with a slash in it, and double- or single-quoted text (an
+// apostrophe inside a word doesn't open a quote)
+fn masked_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"\S*/\S*|"[^"]*"|\B'[^']*'\B"#).unwrap())
+}
+
+// wevtutil output redirected from PowerShell is UTF-16LE with a BOM
+pub fn decode_utf16le(buf: &[u8]) -> String {
+    let units: Vec<u16> = buf
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn looks_like_wevtutil(buf: &[u8]) -> bool {
+    let buf = buf.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(buf);
+    let start = buf.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(buf.len());
+    buf[start..].starts_with(b"Event[")
+}
+
 impl LogParser {
+    pub fn parse_file(&self, file_path: &str) -> Result<Vec<LogEntry>, io::Error> {
+        self.parse_file_counted(file_path).map(|(entries, _)| entries)
+    }
+
     pub fn parse_file_counted(
         &self,
         file_path: &str,
-    ) -> Result<(Vec<LogEntry>, usize), std::io::Error> {
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
+    ) -> Result<(Vec<LogEntry>, ParseStats), io::Error> {
+        if let Some(kind) = ArchiveKind::detect(file_path) {
+            return self.parse_archive(file_path, kind);
+        }
+
+        let mut entries = Vec::new();
+        let mut parse_stats = ParseStats::default();
+        for chunk in self.parse_file_streaming(file_path)? {
+            let (chunk_entries, chunk_stats) = chunk?;
+            entries.extend(chunk_entries);
+            parse_stats.merge(chunk_stats);
+        }
+        // chunks are sorted individually; the concatenation is nearly sorted
+        entries.sort_by_key(|e| e.timestamp);
+        Ok((entries, parse_stats))
+    }
+
+    // Reads and parses a file a chunk at a time, so memory holds one chunk of
+    // raw text (plus whatever the caller keeps) instead of the whole file.
+    // Each chunk ends where an entry starts and is parsed in parallel. UTF-16,
+    // wevtutil and XML logs need the whole text and come back as a single
+    // chunk.
+    // gzip, bzip2 and zstd files are decompressed as they are read.
+    pub fn parse_file_streaming(&self, file_path: &str) -> io::Result<EntryChunks<'_>> {
+        let mut file = archive::open_decompressed(File::open(file_path)?)?;
+        let mut head = Vec::with_capacity(WHOLE_FILE_SNIFF_BYTES);
+        (&mut file).take(WHOLE_FILE_SNIFF_BYTES as u64).read_to_end(&mut head)?;
+
+        let whole_file = head.starts_with(b"\xFF\xFE")
+            || matches!(self.format, LogFormat::Wevtutil | LogFormat::Xml)
+            || (self.format == LogFormat::Auto && (looks_like_wevtutil(&head) || xml::looks_like_xml(&head)));
+        if whole_file {
+            file.read_to_end(&mut head)?;
+        }
+
+        Ok(self.entry_chunks(file, head, whole_file))
+    }
+
+    // `head` is text already read from the start of `file`.
+    fn entry_chunks(&self, file: Box<dyn Read + Send>, head: Vec<u8>, whole_file: bool) -> EntryChunks<'_> {
+        EntryChunks {
+            parser: self,
+            file,
+            buf: head,
+            next_line: 1,
+            skipping: false,
+            pending: ParseStats::default(),
+            scanned: 0,
+            whole_file,
+            done: false,
+            chunk_bytes: STREAM_CHUNK_BYTES,
+            max_chunk_bytes: MAX_CHUNK_BYTES,
+        }
+    }
+
+    // Each member is parsed like a separate file and merged into one timeline.
+    fn parse_archive(
+        &self,
+        file_path: &str,
+        kind: ArchiveKind,
+    ) -> Result<(Vec<LogEntry>, ParseStats), io::Error> {
+        let members = archive::read_members(file_path, kind, self.archive_members.as_ref())?;
+        if members.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no matching archive members"));
+        }
+
+        let mut entries = Vec::new();
+        let mut parse_stats = ParseStats::default();
+        for member in members {
+            let (member_entries, member_stats) = self.parse_bytes(&member.data);
+            debug!(
+                archive = %file_path,
+                member = %member.name,
+                entries = member_entries.len(),
+                "parsed archive member"
+            );
+            let (merged, duplicates) = merge_sorted(entries, member_entries, self.dedup_across_files);
+            entries = merged;
+            parse_stats.merge(member_stats);
+            parse_stats.duplicates += duplicates;
+        }
+
+        Ok((entries, parse_stats))
+    }
+
+    // Files are scheduled largest-first onto the rayon pool so one huge file
+    // doesn't end up starting last; each finished file is streamed to a merge
+    // thread that folds it into a single timestamp-ordered timeline.
+    pub fn parse_files(&self, paths: &[String]) -> Result<(Vec<LogEntry>, ParseStats), io::Error> {
+        self.parse_files_with(paths, &|_, _, _| {})
+    }
 
-        let lines: Vec<(usize, String)> = reader
-            .lines()
+    // `on_file(path, entries, stats)` runs on the worker as each file finishes.
+    pub fn parse_files_with(
+        &self,
+        paths: &[String],
+        on_file: &(dyn Fn(&str, usize, &ParseStats) + Sync),
+    ) -> Result<(Vec<LogEntry>, ParseStats), io::Error> {
+        let mut order: Vec<&String> = paths.iter().collect();
+        order.sort_by_cached_key(|p| Reverse(fs::metadata(p).map(|m| m.len()).unwrap_or(0)));
+
+        let (tx, rx) = mpsc::channel();
+
+        thread::scope(|scope| {
+            let dedup = self.dedup_across_files;
+            let merger = scope.spawn(move || {
+                let mut entries: Vec<LogEntry> = Vec::new();
+                let mut parse_stats = ParseStats::default();
+                for result in rx {
+                    let (path, file_entries, mut file_stats): (&String, Vec<LogEntry>, ParseStats) = result?;
+                    parse_stats.files.push((path.clone(), file_entries.len()));
+                    // the sample comes from the earliest file given, not the first to finish
+                    if file_stats.sample_unparsed.is_some() {
+                        file_stats.sample_file = Some(path.clone());
+                        let rank = |p: &String| paths.iter().position(|q| q == p);
+                        if parse_stats.sample_file.as_ref().is_some_and(|held| rank(held) > rank(path)) {
+                            parse_stats.sample_unparsed = None;
+                        }
+                    }
+                    let (merged, duplicates) = merge_sorted(entries, file_entries, dedup);
+                    entries = merged;
+                    parse_stats.merge(file_stats);
+                    parse_stats.duplicates += duplicates;
+                }
+                parse_stats.files.sort_by_key(|(path, _)| paths.iter().position(|p| p == path));
+                Ok((entries, parse_stats))
+            });
+
+            order.par_iter().with_max_len(1).for_each_with(tx, |tx, path| {
+                let start = Instant::now();
+                let result = self
+                    .parse_file_counted(path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)));
+                if let Ok((ref entries, ref stats)) = result {
+                    debug!(
+                        file = %path,
+                        entries = entries.len(),
+                        unparsed = stats.unparsed,
+                        elapsed_ms = start.elapsed().as_millis() as u64,
+                        "parsed file"
+                    );
+                    on_file(path, entries.len(), stats);
+                }
+                let _ = tx.send(result.map(|(entries, stats)| (*path, entries, stats)));
+            });
+
+            merger.join().expect("merge thread panicked")
+        })
+    }
+
+    // `--journal`: what journalctl has for the query, merged into entries
+    // already parsed from files.
+    pub fn read_journal(
+        &self,
+        query: &JournalQuery,
+        entries: &mut Vec<LogEntry>,
+        parse_stats: &mut ParseStats,
+    ) -> io::Result<()> {
+        let (journal_entries, mut journal_stats) = self.parse_bytes(&query.read()?);
+        parse_stats.files.push((JournalQuery::LABEL.to_string(), journal_entries.len()));
+        if journal_stats.sample_unparsed.is_some() {
+            journal_stats.sample_file = Some(JournalQuery::LABEL.to_string());
+        }
+        let (merged, duplicates) = merge_sorted(std::mem::take(entries), journal_entries, self.dedup_across_files);
+        *entries = merged;
+        parse_stats.merge(journal_stats);
+        parse_stats.duplicates += duplicates;
+        Ok(())
+    }
+
+    // Lines are borrowed slices of `buf`; only lines with invalid UTF-8 are
+    // copied (lossily), so reading costs one allocation instead of one per line.
+    pub fn parse_bytes(&self, buf: &[u8]) -> (Vec<LogEntry>, ParseStats) {
+        if let Some(rest) = buf.strip_prefix(b"\xFF\xFE") {
+            return self.parse_bytes(decode_utf16le(rest).as_bytes());
+        }
+        if self.format == LogFormat::Wevtutil
+            || (self.format == LogFormat::Auto && looks_like_wevtutil(buf))
+        {
+            let text = String::from_utf8_lossy(buf);
+            let (mut entries, mut parse_stats) = self.parse_wevtutil(text.trim_start_matches('\u{feff}'));
+            self.finish_entries(&mut entries, &mut parse_stats);
+            return (entries, parse_stats);
+        }
+        if self.format == LogFormat::Xml || (self.format == LogFormat::Auto && xml::looks_like_xml(buf)) {
+            let (mut entries, mut parse_stats) = self.parse_xml(&String::from_utf8_lossy(buf));
+            self.finish_entries(&mut entries, &mut parse_stats);
+            return (entries, parse_stats);
+        }
+
+        self.parse_lines(buf, 1)
+    }
+
+    // Parses newline-separated text whose first line is `first_line`.
+    pub fn parse_lines(&self, buf: &[u8], first_line: usize) -> (Vec<LogEntry>, ParseStats) {
+        let max_len = self.limits.max_line_len;
+        let mut parse_stats = ParseStats::default();
+
+        // None marks a line over the length limit
+        let lines: Vec<(usize, Option<Cow<str>>)> = buf
+            .split(|&b| b == b'\n')
             .enumerate()
-            .filter_map(|(i, l)| l.ok().map(|s| (i + 1, s)))
+            .map(|(i, l)| (i + first_line, (l.len() <= max_len).then(|| String::from_utf8_lossy(l))))
             .collect();
 
-        let total_non_empty = lines
+        parse_stats.invalid_utf8 = lines
             .iter()
-            .filter(|(_, l)| !l.trim().is_empty())
+            .filter(|(_, l)| matches!(l, Some(Cow::Owned(_))))
             .count();
 
-        let entries: Vec<LogEntry> = lines
+        // a line the container runtime split is whole again before anything
+        // looks at it
+        let lines = if matches!(self.format, LogFormat::Auto | LogFormat::Docker | LogFormat::Cri) {
+            let (lines, joined) = container::join_partial(lines);
+            parse_stats.total_lines += joined;
+            parse_stats.continued += joined;
+            lines
+        } else {
+            lines
+        };
+
+        let mut lines = if self.repair_torn {
+            let (lines, repaired) =
+                repair::torn_lines(lines, |l| self.parse_line(l, 0).is_some());
+            parse_stats.repaired = repaired;
+            lines
+        } else {
+            lines
+        };
+
+        if let Some(ref needle) = self.line_filter {
+            lines.retain(|(_, l)| l.as_deref().is_some_and(|l| contains_token(l, needle)));
+        }
+
+        let results: Vec<Option<(LogFormat, LogEntry)>> = lines
             .par_iter()
-            .filter_map(|(num, line)| self.parse_line(line, *num))
+            .map(|(num, line)| line.as_deref().and_then(|l| self.parse_line(l, *num)))
+            .map(|r| r.map(|(fmt, entry)| (fmt, entry.into_owned())))
+            .collect();
+
+        let mut entries = Vec::with_capacity(results.len());
+
+        for ((num, line), result) in lines.iter().zip(results) {
+            let Some(line) = line else {
+                trace!(line_number = *num, "line exceeds max length");
+                parse_stats.record_oversized();
+                continue;
+            };
+
+            match result {
+                Some((fmt, entry)) => {
+                    parse_stats.record_match(fmt);
+                    entries.push(entry);
+                }
+                // chunks start at an entry, so a trace is never cut off from
+                // the line it continues
+                None if self.multiline && self.is_continuation(line) && !entries.is_empty() => {
+                    if let Some(entry) = entries.last_mut() {
+                        entry.message.push('\n');
+                        entry.message.push_str(line.trim_end());
+                        entry.end_line = Some(*num);
+                    }
+                    parse_stats.record_continuation();
+                }
+                None if !line.trim().is_empty() => {
+                    trace!(line_number = *num, "no format matched");
+                    parse_stats.record_unparsed(*num, line.trim());
+                }
+                None => {}
+            }
+        }
+
+        self.finish_entries(&mut entries, &mut parse_stats);
+        // stable, so entries sharing a timestamp stay in file order
+        entries.sort_by_key(|e| e.timestamp);
+
+        (entries, parse_stats)
+    }
+
+    // Records content hashes, types fields, masks entries and the
+    // unparsed-line sample, applies the message length limit, then the entry
+    // filter. Redacting first means a secret straddling the length limit is
+    // still recognized, and keyword filters never match what was masked.
+    fn finish_entries(&self, entries: &mut Vec<LogEntry>, parse_stats: &mut ParseStats) {
+        entries.par_iter_mut().for_each(|e| e.hash = Some(e.hash_content()));
+        if !self.schema.is_empty() {
+            parse_stats.schema_misfits += entries.iter_mut().map(|e| self.schema.apply(e)).sum::<usize>();
+        }
+        redact::apply(entries, &self.redact);
+        if let Some((_, ref mut sample)) = parse_stats.sample_unparsed {
+            redact::mask(sample, &self.redact);
+        }
+        if let Some(max_len) = self.limits.max_message_len {
+            parse_stats.truncated += truncate_messages(entries, max_len);
+        }
+        if let Some(ref filter) = self.entry_filter {
+            entries.retain(|e| {
+                let outcome = filter::failed_stage(e, filter);
+                parse_stats.filtered.record(outcome);
+                outcome.is_none()
+            });
+        }
+    }
+}
+
+const STREAM_CHUNK_BYTES: usize = 8 << 20;
+// a chunk still searching for an entry start is cut at a newline past this,
+// so a long stretch where no line parses can't fill memory
+const MAX_CHUNK_BYTES: usize = 2 * STREAM_CHUNK_BYTES;
+// lines looked back over for a container line still waiting for its end
+const SPLIT_LOOKBACK: usize = 64;
+// enough to spot a UTF-16 BOM, a wevtutil header or an XML start tag
+const WHOLE_FILE_SNIFF_BYTES: usize = 4096;
+
+// Iterator over a file's entries one chunk at a time; see
+// `LogParser::parse_file_streaming`.
+pub struct EntryChunks<'p> {
+    parser: &'p LogParser,
+    file: Box<dyn Read + Send>,
+    // unparsed bytes: read-ahead plus the partial line carried between chunks
+    buf: Vec<u8>,
+    // line number of the first line in `buf`
+    next_line: usize,
+    // inside a line over the length limit, dropping bytes up to its newline
+    skipping: bool,
+    // counts for lines dropped between chunks, reported with the next one
+    pending: ParseStats,
+    // lines of `buf` starting before this offset can't start the next chunk
+    scanned: usize,
+    whole_file: bool,
+    done: bool,
+    // bytes read at a time
+    chunk_bytes: usize,
+    max_chunk_bytes: usize,
+}
+
+impl Iterator for EntryChunks<'_> {
+    type Item = io::Result<(Vec<LogEntry>, ParseStats)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.whole_file {
+            self.done = true;
+            return Some(Ok(self.parser.parse_bytes(&std::mem::take(&mut self.buf))));
+        }
+
+        let max_len = self.parser.limits.max_line_len;
+        loop {
+            let before = self.buf.len();
+            if let Err(e) = (&mut self.file)
+                .take(self.chunk_bytes as u64)
+                .read_to_end(&mut self.buf)
+            {
+                self.done = true;
+                return Some(Err(e));
+            }
+            let eof = self.buf.len() == before;
+
+            if self.skipping {
+                self.scanned = 0;
+                match memchr(b'\n', &self.buf) {
+                    Some(i) => {
+                        self.buf.drain(..=i);
+                        self.skipping = false;
+                        self.pending.record_oversized();
+                        self.next_line += 1;
+                    }
+                    None => {
+                        self.buf.clear();
+                        if eof {
+                            self.pending.record_oversized();
+                            self.done = true;
+                            return Some(Ok((Vec::new(), std::mem::take(&mut self.pending))));
+                        }
+                        continue;
+                    }
+                }
+            }
+
+            let cut = if eof {
+                self.buf.len()
+            } else {
+                match self.entry_boundary() {
+                    Some(at) => at,
+                    None => {
+                        let tail = memrchr(b'\n', &self.buf).map_or(0, |i| i + 1);
+                        if tail > 0 && self.buf.len() >= self.max_chunk_bytes {
+                            // no entry starts anywhere in reach: cut at the
+                            // last newline anyway, though a continuation
+                            // running past the cut then reads as unparsed
+                            tail
+                        } else if self.buf.len() - tail <= max_len {
+                            // the partial line at the end; past the limit it
+                            // is dropped unread, so the lines before it can go
+                            continue;
+                        } else if tail == 0 {
+                            self.buf.clear();
+                            self.skipping = true;
+                            continue;
+                        } else {
+                            tail
+                        }
+                    }
+                }
+            };
+
+            let chunk = &self.buf[..cut];
+            let (entries, mut parse_stats) = self.parser.parse_lines(chunk, self.next_line);
+            self.next_line += memchr::memchr_iter(b'\n', chunk).count();
+            self.buf.drain(..cut);
+            self.scanned = self.scanned.saturating_sub(cut);
+
+            parse_stats.merge(std::mem::take(&mut self.pending));
+            self.done = eof;
+            return Some(Ok((entries, parse_stats)));
+        }
+    }
+}
+
+impl EntryChunks<'_> {
+    // Where the next chunk can start: before the newest complete line that
+    // begins an entry on its own, so stack-trace continuations, a container
+    // runtime's split lines and torn tails stay in one chunk with the line
+    // they belong to. None when no line past the first qualifies yet; the
+    // chunk then grows, up to MAX_CHUNK_BYTES.
+    fn entry_boundary(&mut self) -> Option<usize> {
+        let end = memrchr(b'\n', &self.buf)? + 1;
+        let parser = self.parser;
+        let containers = matches!(parser.format, LogFormat::Auto | LogFormat::Docker | LogFormat::Cri);
+        if !containers && !parser.repair_torn && !parser.multiline {
+            return Some(end);
+        }
+
+        // complete line i is starts[i]..starts[i + 1] - 1
+        let starts: Vec<usize> = std::iter::once(0)
+            .chain(memchr::memchr_iter(b'\n', &self.buf[..end]).map(|i| i + 1))
             .collect();
+        let text = |i: usize| String::from_utf8_lossy(&self.buf[starts[i]..starts[i + 1] - 1]);
+        let parses = |line: &str| parser.parse_line(line, 0).is_some();
+        let starts_entry = |i: usize| {
+            starts[i + 1] - 1 - starts[i] <= parser.limits.max_line_len
+                && parses(&text(i))
+                && !(containers && container::split_pending((0..i).rev().take(SPLIT_LOOKBACK).map(text)))
+                && !(parser.repair_torn
+                    && (i.saturating_sub(repair::REPAIR_WINDOW)..i).any(|j| !repair::is_intact(&text(j), parses)))
+        };
+        // lines before `scanned` were turned down before and still would be
+        let first = starts.partition_point(|&s| s < self.scanned).max(1);
+        let found = (first..starts.len() - 1).rev().find(|&i| starts_entry(i));
+        self.scanned = end;
+        found.map(|i| starts[i])
+    }
+}
+
+// `--latency-unit`: what a bare number at the end of an access log line
+// counts in. Apache's %D is microseconds, nginx's $request_time seconds and
+// custom formats often milliseconds, so without it only a number with a
+// decimal point (nginx) is read.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum LatencyUnit {
+    Us,
+    Ms,
+    S,
+}
+
+impl LatencyUnit {
+    fn millis(self, value: f64) -> f64 {
+        match self {
+            Self::Us => value / 1000.0,
+            Self::Ms => value,
+            Self::S => value * 1000.0,
+        }
+    }
+}
+
+// nginx variables logged as `rt=$request_time` and the like, all seconds
+const NGINX_TIME_KEYS: &[&str] = &["rt", "urt", "uct", "uht", "request_time", "upstream_response_time"];
+
+// Response time logged after the size field, past any quoted referer and
+// user agent: `123ms`, `1.5s`, `250us`, `rt=0.123` or a bare number in
+// `bare` units (see LatencyUnit).
+fn trailing_latency_ms(rest: &str, bare: Option<LatencyUnit>) -> Option<f64> {
+    let rest = rest.rsplit('"').next()?;
+    let token = rest.split_whitespace().last()?;
+    let (key, token) = token.rsplit_once('=').map_or((None, token), |(k, v)| (Some(k), v));
+
+    let (value, unit) = if let Some(us) = token.strip_suffix("us").or_else(|| token.strip_suffix("µs")) {
+        (us, Some(LatencyUnit::Us))
+    } else if let Some(ms) = token.strip_suffix("ms") {
+        (ms, Some(LatencyUnit::Ms))
+    } else if let Some(secs) = token.strip_suffix('s') {
+        (secs, Some(LatencyUnit::S))
+    } else if key.is_some_and(|k| NGINX_TIME_KEYS.contains(&k)) {
+        (token, Some(LatencyUnit::S))
+    } else {
+        (token, bare.or(token.contains('.').then_some(LatencyUnit::S)))
+    };
+    Some(unit?.millis(value.parse().ok()?))
+}
 
-        let mut sorted = entries;
-        sorted.sort_unstable_by_key(|e| e.timestamp);
+// Necessary condition for APACHE_PATTERN: a `[` right after whitespace (the
+// timestamp field) and a quoted request. Much cheaper than a failed match.
+fn may_be_apache(line: &str) -> bool {
+    memchr(b'"', line.as_bytes()).is_some()
+        && line
+            .match_indices('[')
+            .any(|(i, _)| line[..i].chars().next_back().is_some_and(char::is_whitespace))
+}
+
+// Merges two timestamp-ordered runs. With `dedup`, an entry from `b` that
+// has the same timestamp, source and message as one in `a` is dropped; each
+// entry in `a` cancels at most one in `b`, so repeats within a single file
+// (two identical requests in the same second) are kept.
+fn merge_sorted(a: Vec<LogEntry>, b: Vec<LogEntry>, dedup: bool) -> (Vec<LogEntry>, usize) {
+    if a.is_empty() {
+        return (b, 0);
+    }
+    if b.is_empty() {
+        return (a, 0);
+    }
+
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let mut duplicates = 0;
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+
+    while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+        if x.timestamp < y.timestamp || (!dedup && x.timestamp == y.timestamp) {
+            merged.push(a.next().unwrap());
+        } else if y.timestamp < x.timestamp {
+            merged.push(b.next().unwrap());
+        } else {
+            let ts = x.timestamp;
+            let mut seen: HashMap<(Option<Arc<str>>, String), usize> = HashMap::new();
+            while let Some(entry) = a.next_if(|e| e.timestamp == ts) {
+                *seen.entry((entry.source.clone(), entry.message.clone())).or_insert(0) += 1;
+                merged.push(entry);
+            }
+            while let Some(entry) = b.next_if(|e| e.timestamp == ts) {
+                match seen.get_mut(&(entry.source.clone(), entry.message.clone())) {
+                    Some(n) if *n > 0 => {
+                        *n -= 1;
+                        duplicates += 1;
+                    }
+                    _ => merged.push(entry),
+                }
+            }
+        }
+    }
+
+    merged.extend(a);
+    merged.extend(b);
+    (merged, duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stack_trace_across_a_chunk_boundary_stays_whole() {
+        let frames: Vec<String> =
+            (0..20).map(|i| format!("    at com.example.Handler.step{}(Handler.java:{})", i, i)).collect();
+        let text = format!(
+            "[2026-01-15 10:00:00] INFO started\n\
+             [2026-01-15 10:00:01] ERROR request failed\n{}\n\
+             [2026-01-15 10:00:02] INFO recovered\n",
+            frames.join("\n")
+        );
+        let parser = LogParser::with_format(LogFormat::Auto).with_multiline(true, None);
+        let mut chunks = parser.entry_chunks(Box::new(io::Cursor::new(text.into_bytes())), Vec::new(), false);
+        // several reads inside the trace
+        chunks.chunk_bytes = 100;
+
+        let mut entries = Vec::new();
+        let mut parse_stats = ParseStats::default();
+        for chunk in chunks {
+            let (chunk_entries, chunk_stats) = chunk.unwrap();
+            entries.extend(chunk_entries);
+            parse_stats.merge(chunk_stats);
+        }
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(parse_stats.unparsed, 0);
+        assert_eq!(parse_stats.continued, frames.len());
+        assert_eq!(entries[1].message, format!("request failed\n{}", frames.join("\n")));
+        assert_eq!(entries[2].line_number, frames.len() + 3);
+    }
+
+    #[test]
+    fn chunk_without_an_entry_start_stays_bounded() {
+        let garbage: String = (0..500).map(|i| format!("no timestamp on line {}\n", i)).collect();
+        let text = format!("[2026-01-15 10:00:00] INFO started\n{}", garbage);
+        let parser = LogParser::with_format(LogFormat::Auto);
+        let mut chunks = parser.entry_chunks(Box::new(io::Cursor::new(text.into_bytes())), Vec::new(), false);
+        chunks.chunk_bytes = 256;
+        chunks.max_chunk_bytes = 512;
+
+        let mut entries = Vec::new();
+        let mut parse_stats = ParseStats::default();
+        let mut reads = 0;
+        while let Some(chunk) = chunks.next() {
+            assert!(chunks.buf.len() <= chunks.max_chunk_bytes + chunks.chunk_bytes);
+            let (chunk_entries, chunk_stats) = chunk.unwrap();
+            entries.extend(chunk_entries);
+            parse_stats.merge(chunk_stats);
+            reads += 1;
+        }
+
+        assert!(reads > 10);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(parse_stats.unparsed, 500);
+    }
+
+    #[test]
+    fn torn_line_from_the_repair_example_is_rejoined() {
+        let text = "[2026-01-15 12:00:00] INFO request from clie[2026-01-15 12:00:00] WARN disk at 91%\n\
+                    nt 10.0.0.7 completed\n";
+        let parser = LogParser::with_format(LogFormat::Auto).with_torn_line_repair(true);
+        let (entries, parse_stats) = parser.parse_bytes(text.as_bytes());
+
+        assert_eq!(parse_stats.repaired, 2);
+        assert_eq!(parse_stats.unparsed, 0);
+        let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["request from client 10.0.0.7 completed", "disk at 91%"]);
+    }
 
-        let unparsed = total_non_empty.saturating_sub(sorted.len());
+    #[test]
+    fn refs_borrow_plain_messages_from_the_buffer() {
+        let text = "[2026-01-15 12:00:00] INFO plain\n\
+                    \x1b[31m[2026-01-15 12:00:01] ERROR colored\x1b[0m\n\
+                    {\"timestamp\":\"2026-01-15T12:00:02\",\"level\":\"warn\",\"message\":\"json\"}\n";
+        let parser = LogParser::with_format(LogFormat::Auto);
+        let refs: Vec<LogEntryRef> = parser.parse_refs(text.as_bytes()).collect();
 
-        Ok((sorted, unparsed))
+        let messages: Vec<&str> = refs.iter().map(|e| e.message.as_ref()).collect();
+        assert_eq!(messages, ["plain", "colored", "json"]);
+        assert!(matches!(refs[0].message, Cow::Borrowed(_)));
+        assert!(matches!(refs[1].message, Cow::Owned(_)));
+        assert_eq!(refs[2].line_number, 3);
+        assert_eq!(refs[1].clone().into_owned().level, LogLevel::Error);
     }
 }
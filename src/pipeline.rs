@@ -0,0 +1,395 @@
+// The analyze run as one call: read the inputs, drop what the filters say,
+// analyze the rest. What the command does around it (progress on the
+// terminal, the report, exports, notifications) is left to the caller, which
+// hears about each stage through `Progress`.
+
+use chrono::{Duration, Local, NaiveDateTime};
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use std::time::Instant;
+use tracing::{info, warn};
+
+use crate::analyzer::{KeywordOptions, LogAnalysis, LogAnalyzer};
+use crate::buckets::Zone;
+use crate::cost;
+use crate::custody::Custody;
+use crate::endpoints::EndpointOptions;
+use crate::filter::{self, FilterConfig, FilterStats, KeywordMode, SourceMatcher, TextPattern, TimeBound};
+use crate::journal::JournalQuery;
+use crate::metrics::{MetricSpec, Threshold};
+use crate::model::BaselineModel;
+use crate::parser::{LogEntry, LogLevel, LogParser, ParseStats};
+use crate::ratelimit::RateLimit;
+use crate::retention::RetentionPolicy;
+use crate::schema::FieldCondition;
+use crate::scoring::Composite;
+use crate::sources::ErrorBudget;
+use crate::stats::{self, BurstParams};
+
+// Which entries to keep. Relative `from`/`to` need a reference time, see
+// `config`.
+#[derive(Debug, Clone, Default)]
+pub struct FilterOptions {
+    pub keywords: Vec<TextPattern>,
+    pub keyword_mode: KeywordMode,
+    pub exclude: Vec<TextPattern>,
+    pub from: Option<TimeBound>,
+    pub to: Option<TimeBound>,
+    pub min_level: Option<LogLevel>,
+    pub source_include: Vec<SourceMatcher>,
+    pub source_exclude: Vec<SourceMatcher>,
+    pub conditions: Vec<FieldCondition>,
+}
+
+impl FilterOptions {
+    // Relative bounds are resolved against `reference` and left out without
+    // one.
+    pub fn config(&self, reference: Option<NaiveDateTime>) -> FilterConfig {
+        let resolve = |bound: Option<TimeBound>| match (bound, reference) {
+            (Some(b), Some(reference)) => Some(b.resolve(reference)),
+            (Some(b), None) if !b.is_relative() => Some(b.resolve(NaiveDateTime::MIN)),
+            _ => None,
+        };
+        let mut cfg = FilterConfig::new()
+            .with_keywords(self.keywords.clone(), self.keyword_mode)
+            .with_exclude(self.exclude.clone())
+            .with_time_range(resolve(self.from), resolve(self.to));
+        if let Some(ref level) = self.min_level {
+            cfg = cfg.with_min_level(level);
+        }
+        cfg.with_sources(self.source_include.clone(), self.source_exclude.clone())
+            .with_fields(self.conditions.clone())
+    }
+}
+
+// Reading the systemd journal besides (or instead of) files.
+#[derive(Debug, Clone, Default)]
+pub struct JournalOptions {
+    pub units: Vec<String>,
+    pub priority: Option<String>,
+    pub directory: Option<String>,
+}
+
+impl JournalOptions {
+    // journalctl is given the filter's time range itself, so it doesn't read
+    // what the filter would drop; relative bounds need `reference`.
+    pub fn query(&self, filter: &FilterOptions, reference: Option<NaiveDateTime>) -> JournalQuery {
+        let resolve = |bound: Option<TimeBound>| match (bound, reference) {
+            (Some(b), Some(reference)) => Some(b.resolve(reference)),
+            (Some(TimeBound::At(ts)), None) => Some(ts),
+            _ => None,
+        };
+        JournalQuery {
+            units: self.units.clone(),
+            priority: self.priority.clone(),
+            directory: self.directory.clone(),
+            since: resolve(filter.from),
+            until: resolve(filter.to),
+        }
+    }
+}
+
+// Evidence mode: the inputs are hashed before they are read and again after,
+// and options that alter entries are refused unless allowed. `mutations`
+// names the ones in effect; detected container unwrapping is added to them.
+#[derive(Debug, Clone, Default)]
+pub struct Evidence {
+    pub command_line: Vec<String>,
+    pub mutations: Vec<String>,
+    pub allow_mutation: bool,
+}
+
+// What the analyzer is given; see the LogAnalyzer builders.
+#[derive(Clone)]
+pub struct AnalysisOptions {
+    pub metrics: Vec<MetricSpec>,
+    pub thresholds: Vec<Threshold>,
+    pub keywords: KeywordOptions,
+    pub error_budgets: Vec<ErrorBudget>,
+    pub group_by: Vec<String>,
+    pub version_pattern: Option<Regex>,
+    pub template_limit: usize,
+    pub tail: usize,
+    pub bucket: Option<Duration>,
+    pub burst: BurstParams,
+    pub endpoints: EndpointOptions,
+    pub rate_limit: Option<RateLimit>,
+    pub cost_per_gb: Option<f64>,
+    pub retention: Vec<RetentionPolicy>,
+    pub scorer: Composite,
+    pub zone: Zone,
+    pub top: Option<usize>,
+}
+
+pub struct AnalyzeOptions {
+    pub file_paths: Vec<String>,
+    pub journal: Option<JournalOptions>,
+    // the filter below is added to it, to drop entries as they are read
+    pub parser: LogParser,
+    pub filter: FilterOptions,
+    pub evidence: Option<Evidence>,
+    // the share of lines that may fail to parse, 0.0 to 1.0
+    pub max_unparsed_ratio: Option<f64>,
+    // a saved baseline model to compare against
+    pub model: Option<String>,
+    pub analysis: AnalysisOptions,
+    // also analyze the window holding this share (in percent) of the errors
+    pub focus_errors: Option<f64>,
+}
+
+// What a run reports as it goes; each method does nothing unless overridden.
+pub trait Progress: Sync {
+    fn file_done(&self, _path: &str, _entries: usize, _parse_stats: &ParseStats) {}
+    fn parsed(&self, _entries: usize, _parse_stats: &ParseStats) {}
+    fn filtered(&self, _kept: usize, _filter_stats: &FilterStats) {}
+}
+
+impl Progress for () {}
+
+pub struct Analyzed {
+    // what the filters kept, oldest first
+    pub entries: Vec<LogEntry>,
+    pub analysis: LogAnalysis,
+    // None also when there were too few errors to find a window
+    pub focus: Option<LogAnalysis>,
+    pub baseline: Option<BaselineModel>,
+}
+
+#[derive(Debug)]
+pub enum AnalyzeError {
+    // reading, hashing or loading something failed; the message says what
+    Failed(String),
+    // evidence mode met options or input that alter entries
+    Mutations(Vec<String>),
+    // more lines failed to parse than max_unparsed_ratio allows
+    Unparsed(Box<ParseStats>),
+    // the filters kept nothing
+    NoEntries,
+}
+
+impl fmt::Display for AnalyzeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Failed(msg) => write!(f, "{}", msg),
+            Self::Mutations(m) => write!(f, "evidence mode refuses {} as it alters entries", m.join(", ")),
+            Self::Unparsed(s) => write!(f, "{} of {} lines failed to parse", s.unparsed, s.total_lines),
+            Self::NoEntries => write!(f, "no entries matched the given filters"),
+        }
+    }
+}
+
+impl std::error::Error for AnalyzeError {}
+
+pub fn analyze(opts: AnalyzeOptions, progress: &dyn Progress) -> Result<Analyzed, AnalyzeError> {
+    let AnalyzeOptions { file_paths, journal, parser, filter, evidence, max_unparsed_ratio, model, analysis, focus_errors } =
+        opts;
+    if let Some(t) = analysis.thresholds.iter().find(|t| !analysis.metrics.iter().any(|m| m.name == t.metric)) {
+        return Err(AnalyzeError::Failed(format!("--threshold {} refers to unknown metric '{}'", t.label(), t.metric)));
+    }
+
+    let mut custody = match evidence {
+        Some(ref evidence) => Some(acquire_custody(&file_paths, evidence)?),
+        None => None,
+    };
+
+    // relative from/to: the parser filter only sees a reference when reading
+    // the journal, which is read up to now; otherwise they wait for the
+    // newest entry after parsing
+    let clock = journal.is_some().then(|| Local::now().naive_local());
+    let parser = parser.with_entry_filter(Some(filter.config(clock)));
+    let start = Instant::now();
+    let on_file = |path: &str, entries: usize, parse_stats: &ParseStats| progress.file_done(path, entries, parse_stats);
+    let (mut entries, mut parse_stats) =
+        parser.parse_files_with(&file_paths, &on_file).map_err(|e| AnalyzeError::Failed(e.to_string()))?;
+    if let Some(ref journal) = journal {
+        parser
+            .read_journal(&journal.query(&filter, clock), &mut entries, &mut parse_stats)
+            .map_err(|e| AnalyzeError::Failed(e.to_string()))?;
+    }
+    if let Some(ref mut custody) = custody {
+        custody.verify().map_err(|e| AnalyzeError::Failed(format!("cannot hash inputs again: {}", e)))?;
+        for path in &custody.changed_during_run {
+            warn!(file = %path, "input changed while it was being read; the recorded hash is from before");
+        }
+        // detected rather than asked for, so only known once parsed
+        let unwrapped = ["docker", "cri"].iter().any(|f| parse_stats.format_counts.get(*f).is_some_and(|&n| n > 0));
+        let unwrap = "container unwrapping".to_string();
+        if unwrapped && !custody.mutations.contains(&unwrap) {
+            if !evidence.as_ref().is_some_and(|e| e.allow_mutation) {
+                return Err(AnalyzeError::Mutations(vec![unwrap]));
+            }
+            custody.mutations.push(unwrap);
+        }
+    }
+    progress.parsed(entries.len(), &parse_stats);
+
+    log_parse(&parser, &parse_stats, file_paths.len(), entries.len(), start);
+    if let Some(max_ratio) = max_unparsed_ratio {
+        let total = parse_stats.total_lines;
+        if total > 0 && parse_stats.unparsed as f64 / total as f64 > max_ratio {
+            return Err(AnalyzeError::Unparsed(Box::new(parse_stats)));
+        }
+    }
+
+    // entries are sorted, so the last one is the newest
+    let filter_cfg = filter.config(entries.last().map(|e| e.timestamp));
+    let start = Instant::now();
+    let mut after_parse = FilterStats::default();
+    let filtered = filter::apply_counted(entries, &filter_cfg, &mut after_parse);
+    let mut filter_stats = parse_stats.filtered.clone();
+    filter_stats.deduplicated(parse_stats.duplicates);
+    filter_stats.then(&after_parse);
+    info!(
+        kept = filtered.len(),
+        removed = filter_stats.removed(),
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "filter complete"
+    );
+    progress.filtered(filtered.len(), &filter_stats);
+    if filtered.is_empty() {
+        return Err(AnalyzeError::NoEntries);
+    }
+
+    let baseline = match model {
+        Some(ref path) => {
+            let model = BaselineModel::load(path)
+                .map_err(|e| AnalyzeError::Failed(format!("cannot load model {}: {}", path, e)))?;
+            info!(model = %path, runs = model.runs, templates = model.templates.len(), "loaded baseline model");
+            Some(model)
+        }
+        None => None,
+    };
+
+    let start = Instant::now();
+    // the inputs' size stands for the entries' only when all of them were kept
+    let all_kept = journal.is_none() && filter_stats.removed() == 0;
+    let sized = analysis.cost_per_gb.is_some() || !analysis.retention.is_empty();
+    let input_bytes = (sized && all_kept).then(|| cost::plain_input_bytes(&file_paths)).flatten();
+    let result = analyzer(&filtered, &parse_stats, &analysis, baseline.as_ref())
+        .with_cost(analysis.cost_per_gb)
+        .with_retention(analysis.retention.clone())
+        .with_input_bytes(input_bytes)
+        .with_filter_stats(filter_stats.clone())
+        .with_custody(custody)
+        .analyze(analysis.top);
+    info!(elapsed_ms = start.elapsed().as_millis() as u64, "analysis complete");
+
+    let focus = focus_errors.and_then(|pct| {
+        let (from, to) = stats::error_focus_window(&filtered, pct / 100.0)?;
+        let lo = filtered.partition_point(|e| e.timestamp < from);
+        let hi = filtered.partition_point(|e| e.timestamp <= to);
+        // the window is one more time range on top of the filters
+        let window = FilterStats {
+            input: filtered.len(),
+            kept: hi - lo,
+            time_range: filtered.len() - (hi - lo),
+            ..Default::default()
+        };
+        let mut focus_stats = filter_stats;
+        focus_stats.then(&window);
+        let focused = analyzer(&filtered[lo..hi], &parse_stats, &analysis, baseline.as_ref())
+            .with_filter_stats(focus_stats)
+            .analyze(analysis.top);
+        Some(focused)
+    });
+
+    Ok(Analyzed { entries: filtered, analysis: result, focus, baseline })
+}
+
+// Hashes the inputs before anything reads them.
+fn acquire_custody(file_paths: &[String], evidence: &Evidence) -> Result<Custody, AnalyzeError> {
+    if !evidence.mutations.is_empty() && !evidence.allow_mutation {
+        return Err(AnalyzeError::Mutations(evidence.mutations.clone()));
+    }
+    Custody::acquire(file_paths, evidence.command_line.clone(), evidence.mutations.clone())
+        .map_err(|e| AnalyzeError::Failed(format!("cannot hash inputs: {}", e)))
+}
+
+fn analyzer<'a>(
+    entries: &'a [LogEntry],
+    parse_stats: &ParseStats,
+    opts: &AnalysisOptions,
+    baseline: Option<&'a BaselineModel>,
+) -> LogAnalyzer<'a> {
+    LogAnalyzer::new(entries, parse_stats.clone())
+        .with_metrics(opts.metrics.clone(), opts.thresholds.clone())
+        .with_keyword_options(opts.keywords)
+        .with_error_budgets(opts.error_budgets.clone())
+        .with_group_by(opts.group_by.clone())
+        .with_version_pattern(opts.version_pattern.clone())
+        .with_template_limit(opts.template_limit)
+        .with_tail(opts.tail)
+        .with_bucket(opts.bucket)
+        .with_burst(opts.burst)
+        .with_endpoint_options(opts.endpoints.clone())
+        .with_rate_limit(opts.rate_limit)
+        .with_scorer(opts.scorer.clone())
+        .with_baseline(baseline)
+        .with_zone(opts.zone)
+}
+
+fn log_parse(parser: &LogParser, parse_stats: &ParseStats, files: usize, entries: usize, start: Instant) {
+    info!(
+        files,
+        entries,
+        unparsed = parse_stats.unparsed,
+        duplicates = parse_stats.duplicates,
+        repaired = parse_stats.repaired,
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "parse complete"
+    );
+    if parse_stats.unparsed > 0 {
+        warn!(
+            unparsed = parse_stats.unparsed,
+            total = parse_stats.total_lines,
+            "lines did not match any known format and were dropped"
+        );
+    }
+    if parse_stats.oversized > 0 {
+        warn!(
+            oversized = parse_stats.oversized,
+            max_line_len = parser.limits().max_line_len,
+            "lines exceeded --max-line-len and were dropped"
+        );
+    }
+    if parse_stats.invalid_utf8 > 0 {
+        warn!(lines = parse_stats.invalid_utf8, "lines contained invalid UTF-8 and were decoded lossily");
+    }
+    if parse_stats.truncated > 0 {
+        info!(
+            truncated = parse_stats.truncated,
+            max_message_len = parser.limits().max_message_len,
+            "messages exceeded --max-message-len and were cut"
+        );
+    }
+}
+
+// Replaces glob patterns that don't name an existing file with their matches,
+// sorted; a pattern without matches is an error.
+pub fn expand_globs(paths: &mut Vec<String>) -> Result<(), String> {
+    if !paths.iter().any(|p| p.contains(['*', '?', '[']) && !Path::new(p).exists()) {
+        return Ok(());
+    }
+
+    let mut expanded = Vec::new();
+    for path in paths.iter() {
+        if !path.contains(['*', '?', '[']) || Path::new(path).exists() {
+            expanded.push(path.clone());
+            continue;
+        }
+        let matches = glob::glob(path).map_err(|e| format!("invalid pattern {}: {}", path, e))?;
+        let mut found: Vec<String> = matches
+            .filter_map(Result::ok)
+            .filter(|p| p.is_file())
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        if found.is_empty() {
+            return Err(format!("{}: no files match", path));
+        }
+        found.sort();
+        expanded.extend(found);
+    }
+    *paths = expanded;
+    Ok(())
+}
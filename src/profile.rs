@@ -0,0 +1,105 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::cli::{Cli, TokenClass};
+use crate::metrics::{MetricSpec, Threshold};
+
+// Bundles of analysis options for common kinds of logs. A profile only fills
+// in what the user left unset: explicit flags always win, and its metrics
+// and thresholds are added alongside any given on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    // access logs: response sizes, request latency, time-of-day load
+    Web,
+    // application logs: request latency and the window around error storms
+    Backend,
+    // auth and audit logs: time-of-day activity, error storms, no ID noise
+    Security,
+    // database server logs: query duration and affected rows
+    Db,
+}
+
+struct Preset {
+    metrics: &'static [&'static str],
+    thresholds: &'static [&'static str],
+    heatmap: bool,
+    focus_errors: bool,
+    keyword_drop: &'static [TokenClass],
+    slow_request_ms: Option<f64>,
+}
+
+const LATENCY: &str =
+    r"(?i)(?:took|in|latency[=:]?|duration[=:]?|elapsed[=:]?)\s*(\d+(?:\.\d+)?)\s*ms\b as latency_ms";
+
+impl Profile {
+    fn preset(&self) -> Preset {
+        match self {
+            Self::Web => Preset {
+                metrics: &[r#"" \d{3} (\d+) as response_bytes"#, LATENCY],
+                thresholds: &["latency_ms>1000"],
+                heatmap: true,
+                focus_errors: false,
+                keyword_drop: &[TokenClass::Ids],
+                slow_request_ms: Some(1000.0),
+            },
+            Self::Backend => Preset {
+                metrics: &[LATENCY],
+                thresholds: &["latency_ms>1000"],
+                heatmap: false,
+                focus_errors: true,
+                keyword_drop: &[TokenClass::Ids],
+                slow_request_ms: None,
+            },
+            Self::Security => Preset {
+                metrics: &[],
+                thresholds: &[],
+                heatmap: true,
+                focus_errors: true,
+                keyword_drop: &[TokenClass::Numbers, TokenClass::Hex],
+                slow_request_ms: None,
+            },
+            Self::Db => Preset {
+                metrics: &[
+                    r"duration:\s*(\d+(?:\.\d+)?)\s*ms as query_ms",
+                    r"(?i)\brows(?: affected)?[=:]?\s*(\d+) as rows",
+                ],
+                thresholds: &["query_ms>1000"],
+                heatmap: false,
+                focus_errors: true,
+                keyword_drop: &[TokenClass::Ids],
+                slow_request_ms: None,
+            },
+        }
+    }
+
+    pub fn apply(&self, args: &mut Cli) {
+        let preset = self.preset();
+
+        for spec in preset.metrics {
+            let metric = MetricSpec::parse(spec).expect("built-in profile metric");
+            if !args.metric.iter().any(|m| m.name == metric.name) {
+                args.metric.push(metric);
+            }
+        }
+
+        // a user threshold on the same metric replaces the built-in one
+        for spec in preset.thresholds {
+            let threshold = Threshold::parse(spec).expect("built-in profile threshold");
+            if !args.threshold.iter().any(|t| t.metric == threshold.metric) {
+                args.threshold.push(threshold);
+            }
+        }
+
+        args.heatmap |= preset.heatmap;
+        if preset.focus_errors && args.focus_errors.is_none() {
+            args.focus_errors = Some(95.0);
+        }
+        if args.keyword_drop.is_empty() {
+            args.keyword_drop = preset.keyword_drop.to_vec();
+        }
+        if args.slow_request.is_none() {
+            args.slow_request = preset.slow_request_ms;
+        }
+    }
+}
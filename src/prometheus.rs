@@ -0,0 +1,146 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::analyzer::LogAnalysis;
+use crate::parser::LogLevel;
+
+const PUSH_TIMEOUT_SECS: u64 = 10;
+const LEVELS: [LogLevel; 6] =
+    [LogLevel::Debug, LogLevel::Info, LogLevel::Warn, LogLevel::Error, LogLevel::Fatal, LogLevel::Unknown];
+
+// `--output-format prometheus`: the run's headline numbers for a monitoring
+// stack, written as an OpenMetrics file (for node_exporter's textfile
+// collector) or, when the output is an http(s) URL, pushed to a Pushgateway.
+// Every series carries an `input` label naming what was analyzed, and a push
+// goes to a group keyed by job and input, so several cron jobs can share one
+// collector directory or gateway without replacing each other's series.
+// Everything is a gauge: the numbers are this run's, not running totals.
+// Returns the file written, none for a push.
+pub fn export(analysis: &LogAnalysis, output: &str) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if is_push(output) {
+        push(&render(analysis, false), &push_url(output, &input_label(analysis)))?;
+        return Ok(Vec::new());
+    }
+    // written aside and renamed, so the collector never reads half a file
+    let tmp = format!("{}.tmp", output);
+    fs::write(&tmp, render(analysis, true))?;
+    fs::rename(&tmp, output)?;
+    Ok(vec![PathBuf::from(output)])
+}
+
+pub fn is_push(output: &str) -> bool {
+    output.starts_with("http://") || output.starts_with("https://")
+}
+
+fn input_label(analysis: &LogAnalysis) -> String {
+    analysis.parse_stats.files.iter().map(|(path, _)| path.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+// The Pushgateway reads the older text format, without OpenMetrics' `# EOF`.
+fn render(analysis: &LogAnalysis, openmetrics: bool) -> String {
+    let label = format!("input=\"{}\"", escape(&input_label(analysis)));
+    let mut out = String::new();
+    let mut family = |name: &str, kind: &str, help: &str, samples: &[(String, f64)]| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+        }
+    };
+
+    let stats = &analysis.stats;
+    family("logscope_entries", "gauge", "Entries analyzed.", &[(label.clone(), stats.total as f64)]);
+    let per_level: Vec<(String, f64)> = LEVELS
+        .iter()
+        .map(|level| {
+            let count = analysis.level_counts.get(level.as_str()).copied().unwrap_or(0);
+            (format!("{},level=\"{}\"", label, level.as_str().to_lowercase()), count as f64)
+        })
+        .collect();
+    family("logscope_level_entries", "gauge", "Entries analyzed, by level.", &per_level);
+    family(
+        "logscope_unparsed_lines",
+        "gauge",
+        "Lines that matched no format.",
+        &[(label.clone(), analysis.parse_stats.unparsed as f64)],
+    );
+    family(
+        "logscope_error_ratio",
+        "gauge",
+        "Share of entries at ERROR or above.",
+        &[(label.clone(), stats.error_rate / 100.0)],
+    );
+    family(
+        "logscope_error_bursts",
+        "gauge",
+        "Error bursts found.",
+        &[(label.clone(), stats.error_bursts.len() as f64)],
+    );
+    family(
+        "logscope_anomaly_score",
+        "gauge",
+        "Anomaly score, 0 to 100.",
+        &[(label.clone(), analysis.anomaly_score)],
+    );
+    family(
+        "logscope_last_run_timestamp_seconds",
+        "gauge",
+        "When the analysis ran; alert on it to catch a job that stopped.",
+        &[(label, Utc::now().timestamp() as f64)],
+    );
+
+    if openmetrics {
+        out.push_str("# EOF\n");
+    }
+    out
+}
+
+// A bare gateway address gets the default job. The input joins the grouping
+// key (base64, as paths have slashes) unless the URL already sets it.
+fn push_url(output: &str, input: &str) -> String {
+    let base = output.trim_end_matches('/');
+    let group = if base.contains("/metrics/job/") {
+        base.to_string()
+    } else {
+        format!("{}/metrics/job/logscope", base)
+    };
+    if group.contains("/input/") || group.contains("/input@base64/") {
+        return group;
+    }
+    match input {
+        // an empty label value is a single `=` in base64 form
+        "" => format!("{}/input@base64/=", group),
+        input => format!("{}/input@base64/{}", group, URL_SAFE_NO_PAD.encode(input)),
+    }
+}
+
+// PUT replaces the whole group (this job and input), so it holds this run's
+// series and nothing left from an earlier one. curl, as for config URLs, keeps a TLS
+// stack out of the binary.
+fn push(body: &str, url: &str) -> Result<(), Box<dyn Error>> {
+    let mut child = Command::new("curl")
+        .args(["-fsS", "-X", "PUT", "--max-time", &PUSH_TIMEOUT_SECS.to_string(), "--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    child.stdin.take().ok_or("curl has no stdin")?.write_all(body.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("pushing to {} failed: {}", url, stderr.trim()).into());
+    }
+    Ok(())
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,214 @@
+use chrono::{Duration, NaiveDateTime};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+use crate::endpoints::PathNormalizer;
+use crate::generate::parse_span;
+use crate::parser::LogEntry;
+
+// fields a request's client address is read from: the access log parser's,
+// then common JSON names
+const CLIENT_FIELDS: &[&str] = &["client", "client_ip", "remote_addr", "ip"];
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum LimitBy {
+    Client,
+    Endpoint,
+}
+
+impl LimitBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Client => "client",
+            Self::Endpoint => "endpoint",
+        }
+    }
+}
+
+// `--rate-limit 100/min`: at most `requests` in any `per` (a sliding window,
+// not clock minutes), counted per client or per endpoint.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub requests: usize,
+    pub per: Duration,
+    pub by: LimitBy,
+}
+
+impl RateLimit {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (count, per) = spec
+            .split_once('/')
+            .ok_or_else(|| format!("Expected REQUESTS/PERIOD like 100/min or 20/10s, got '{}'", spec))?;
+        let requests: usize = count.trim().parse().map_err(|_| format!("Invalid request count '{}'", count))?;
+        if requests == 0 {
+            return Err("A rate limit allows at least 1 request".to_string());
+        }
+        let per = match per.trim() {
+            "s" | "sec" | "second" => Duration::seconds(1),
+            "m" | "min" | "minute" => Duration::minutes(1),
+            "h" | "hour" => Duration::hours(1),
+            "d" | "day" => Duration::days(1),
+            span => parse_span(span)?,
+        };
+        if per <= Duration::zero() {
+            return Err("A rate limit period must be at least 1s".to_string());
+        }
+        Ok(Self { requests, per, by: LimitBy::Client })
+    }
+
+    pub fn with_by(mut self, by: LimitBy) -> Self {
+        self.by = by;
+        self
+    }
+
+    pub fn label(&self) -> String {
+        let secs = self.per.num_seconds();
+        let per = match secs {
+            1 => "s".to_string(),
+            60 => "min".to_string(),
+            3600 => "h".to_string(),
+            86400 => "d".to_string(),
+            _ if secs % 3600 == 0 => format!("{}h", secs / 3600),
+            _ if secs % 60 == 0 => format!("{}m", secs / 60),
+            _ => format!("{}s", secs),
+        };
+        format!("{}/{} per {}", self.requests, per, self.by.as_str())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LimitedKey {
+    pub key: String,
+    pub requests: usize,
+    pub rejected: usize,
+    pub first_rejected: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitReport {
+    pub limit: String,
+    // what requests were counted per: client or endpoint
+    pub by: &'static str,
+    // requests with a client (or endpoint) to count them against
+    pub requests: usize,
+    pub unkeyed: usize,
+    pub rejected: usize,
+    pub keys: usize,
+    pub keys_limited: usize,
+    // distinct clients that would have seen at least one rejection
+    pub clients_affected: usize,
+    // most rejections first
+    pub top_limited: Vec<LimitedKey>,
+    // with a per-endpoint limit: the clients those rejections fell on
+    pub top_clients: Vec<LimitedKey>,
+}
+
+// What-if for `--rate-limit`: replays the access log's requests in order
+// through a sliding-window limiter and counts what it would have turned
+// away. Rejected requests don't use up the window, as with a limiter that
+// answers 429 before doing any work. None without requests.
+pub fn simulate(entries: &[LogEntry], limit: &RateLimit, normalizer: &PathNormalizer, top: usize) -> Option<RateLimitReport> {
+    struct Window {
+        accepted: VecDeque<NaiveDateTime>,
+        requests: usize,
+        rejected: usize,
+        first_rejected: Option<NaiveDateTime>,
+    }
+    let mut windows: HashMap<String, Window> = HashMap::new();
+    let mut clients: HashMap<String, (usize, usize, Option<NaiveDateTime>)> = HashMap::new();
+    let (mut requests, mut unkeyed, mut rejected) = (0, 0, 0);
+    for entry in entries.iter().filter(|e| e.fields.get("status").is_some_and(|s| s.is_u64())) {
+        let who = CLIENT_FIELDS.iter().find_map(|name| field(entry, name));
+        let key = match limit.by {
+            LimitBy::Client => who.clone(),
+            LimitBy::Endpoint => field(entry, "path").map(|path| {
+                let method = field(entry, "method").unwrap_or_else(|| "-".to_string());
+                format!("{} {}", method, normalizer.normalize(&path))
+            }),
+        };
+        let Some(key) = key else {
+            unkeyed += 1;
+            continue;
+        };
+        requests += 1;
+
+        let window = windows.entry(key).or_insert_with(|| Window {
+            accepted: VecDeque::new(),
+            requests: 0,
+            rejected: 0,
+            first_rejected: None,
+        });
+        window.requests += 1;
+        while window.accepted.front().is_some_and(|&t| entry.timestamp - t >= limit.per) {
+            window.accepted.pop_front();
+        }
+        let turned_away = window.accepted.len() >= limit.requests;
+        if turned_away {
+            rejected += 1;
+            window.rejected += 1;
+            window.first_rejected.get_or_insert(entry.timestamp);
+        } else {
+            window.accepted.push_back(entry.timestamp);
+        }
+
+        if let Some(who) = who {
+            let c = clients.entry(who).or_insert((0, 0, None));
+            c.0 += 1;
+            if turned_away {
+                c.1 += 1;
+                c.2.get_or_insert(entry.timestamp);
+            }
+        }
+    }
+    if requests == 0 {
+        return None;
+    }
+
+    let ranked = |keys: Vec<(String, usize, usize, Option<NaiveDateTime>)>| -> Vec<LimitedKey> {
+        let mut limited: Vec<LimitedKey> = keys
+            .into_iter()
+            .filter_map(|(key, requests, rejected, first)| {
+                Some(LimitedKey {
+                    key,
+                    requests,
+                    rejected,
+                    first_rejected: first?.format("%Y-%m-%d %H:%M:%S").to_string(),
+                })
+            })
+            .collect();
+        limited.sort_unstable_by(|a, b| b.rejected.cmp(&a.rejected).then_with(|| a.key.cmp(&b.key)));
+        limited
+    };
+    let keys = windows.len();
+    let mut top_limited =
+        ranked(windows.into_iter().map(|(k, w)| (k, w.requests, w.rejected, w.first_rejected)).collect());
+    let keys_limited = top_limited.len();
+    top_limited.truncate(top);
+    let clients_affected = clients.values().filter(|c| c.1 > 0).count();
+    let top_clients = match limit.by {
+        LimitBy::Client => Vec::new(),
+        LimitBy::Endpoint => {
+            let mut top_clients = ranked(clients.into_iter().map(|(k, (n, r, first))| (k, n, r, first)).collect());
+            top_clients.truncate(top);
+            top_clients
+        }
+    };
+
+    Some(RateLimitReport {
+        limit: limit.label(),
+        by: limit.by.as_str(),
+        requests,
+        unkeyed,
+        rejected,
+        keys,
+        keys_limited,
+        clients_affected,
+        top_limited,
+        top_clients,
+    })
+}
+
+fn field(entry: &LogEntry, name: &str) -> Option<String> {
+    entry.fields.get(name).and_then(|v| v.as_str()).map(str::to_string)
+}
@@ -0,0 +1,51 @@
+use rayon::prelude::*;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::parser::LogEntry;
+
+pub const REDACTED: &str = "<redacted>";
+
+// Masks every match of the patterns in entry messages and fields (an access
+// log's client address and path among them, and every string or number
+// nested in JSON objects and arrays), before anything is analyzed, reported
+// or exported.
+pub fn apply(entries: &mut [LogEntry], patterns: &[Regex]) {
+    if patterns.is_empty() {
+        return;
+    }
+
+    entries.par_iter_mut().for_each(|entry| {
+        mask(&mut entry.message, patterns);
+        for value in entry.fields.values_mut() {
+            redact_value(value, patterns);
+        }
+    });
+}
+
+// A number that matches (a card or account number logged unquoted) becomes
+// the masked string.
+fn redact_value(value: &mut Value, patterns: &[Regex]) {
+    match value {
+        Value::String(s) => mask(s, patterns),
+        Value::Number(n) => {
+            let text = n.to_string();
+            if patterns.iter().any(|re| re.is_match(&text)) {
+                let mut text = text;
+                mask(&mut text, patterns);
+                *value = Value::String(text);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_value(item, patterns)),
+        Value::Object(map) => map.values_mut().for_each(|item| redact_value(item, patterns)),
+        Value::Bool(_) | Value::Null => {}
+    }
+}
+
+pub fn mask(text: &mut String, patterns: &[Regex]) {
+    for re in patterns {
+        if re.is_match(text) {
+            *text = re.replace_all(text, REDACTED).into_owned();
+        }
+    }
+}
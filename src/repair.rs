@@ -0,0 +1,109 @@
+use rayon::prelude::*;
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+// How many physical lines after a torn head its tail may still arrive.
+pub const REPAIR_WINDOW: usize = 8;
+
+// A record header that can start mid-line when another writer's output got
+// glued onto a partial line: bracket, JSON, syslog and ISO (macOS) prefixes.
+fn embedded_header_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"\[\d{4}-\d{2}-\d{2}[ T]\d{2}:\d{2}:\d{2}\]|\{"|\b[A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2} |\b\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}"#,
+        )
+        .unwrap()
+    })
+}
+
+// (line number, text); None marks a line over the length limit
+pub type Line<'a> = (usize, Option<Cow<'a, str>>);
+
+// Re-joins lines torn by concurrent writers. A writer that is interrupted
+// mid-line leaves its head glued to the front of another writer's line and
+// its tail on a later line of its own:
+//
+//   [2026-01-15 12:00:00] INFO request from clie[2026-01-15 12:00:00] WARN disk at 91%
+//   nt 10.0.0.7 completed
+//
+// Lines with an embedded header are split at the header (when the rest
+// parses on its own), and each unparseable line is appended to the newest
+// pending head within REPAIR_WINDOW lines that yields a parseable record.
+// Oversized lines are passed through untouched.
+pub fn torn_lines<'a>(
+    lines: Vec<Line<'a>>,
+    parses: impl Fn(&str) -> bool + Sync,
+) -> (Vec<Line<'a>>, usize) {
+    // most lines are intact: find those in parallel so the sequential pass
+    // below only does real work around torn ones
+    let intact: Vec<bool> = lines
+        .par_iter()
+        .map(|(_, line)| line.as_deref().is_none_or(|text| is_intact(text, &parses)))
+        .collect();
+
+    let mut out: Vec<Line<'a>> = Vec::with_capacity(lines.len());
+    // indices into `out` of heads that may still receive a tail
+    let mut pending: Vec<usize> = Vec::new();
+    let mut repaired = 0;
+
+    for ((num, line), intact) in lines.into_iter().zip(intact) {
+        pending.retain(|&i| out[i].0 + REPAIR_WINDOW >= num);
+
+        let text = match line {
+            Some(text) if !intact => text,
+            line => {
+                out.push((num, line));
+                continue;
+            }
+        };
+
+        if !parses(&text) {
+            let joined = pending.iter().rposition(|&i| {
+                let head = out[i].1.as_deref().unwrap_or("");
+                parses(&format!("{}{}", head, text.trim_start()))
+            });
+            if let Some(p) = joined {
+                let i = pending.remove(p);
+                let head = out[i].1.take().unwrap_or_default();
+                out[i].1 = Some(Cow::Owned(format!("{}{}", head, text.trim_start())));
+                repaired += 1;
+                continue;
+            }
+        }
+
+        match split_at_embedded_header(&text, &parses) {
+            Some(at) => {
+                let (head, rest) = text.split_at(at);
+                pending.push(out.len());
+                out.push((num, Some(Cow::Owned(head.to_string()))));
+                out.push((num, Some(Cow::Owned(rest.to_string()))));
+                repaired += 1;
+            }
+            None => {
+                // an unparseable line may itself be a torn head
+                if !parses(&text) {
+                    pending.push(out.len());
+                }
+                out.push((num, Some(text)));
+            }
+        }
+    }
+
+    (out, repaired)
+}
+
+// Neither a torn head waiting for its tail nor glued to another line.
+pub fn is_intact(text: &str, parses: impl Fn(&str) -> bool) -> bool {
+    text.trim().is_empty() || (parses(text) && !embedded_header_re().find_iter(text).any(|m| m.start() > 0))
+}
+
+fn split_at_embedded_header(text: &str, parses: &impl Fn(&str) -> bool) -> Option<usize> {
+    let trimmed_start = text.len() - text.trim_start().len();
+    embedded_header_re()
+        .find_iter(text)
+        .map(|m| m.start())
+        .filter(|&at| at > trimmed_start)
+        .find(|&at| parses(&text[at..]))
+}
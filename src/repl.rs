@@ -0,0 +1,510 @@
+use chrono::{Duration, NaiveDateTime};
+use colored::Colorize;
+use std::io::{self, BufRead, Write};
+
+use crate::analyzer::{self, LogAnalyzer, TemplateEntry};
+use crate::buckets::{Bucketing, Zone};
+use crate::cli::ReplArgs;
+use crate::filter::{FilterConfig, KeywordMode, SourceMatcher, TextPattern};
+use crate::parser::{LogEntry, LogFormat, LogLevel, LogParser, ParseStats};
+use crate::stats::format_duration;
+
+const HELP: &str = "\
+Filters (cumulative; each prints the new match count):
+  level <debug|info|warn|error|fatal>   minimum level
+  keyword <text|re:regex>                message contains text (case-insensitive)
+  exclude <text|re:regex>                drop messages containing text (adds to the list)
+  source <text|glob|re:regex>            source contains text, or matches (case-insensitive)
+  from <YYYY-MM-DD HH:MM:SS>             start time
+  to <YYYY-MM-DD HH:MM:SS>               end time
+  clear [level|keyword|exclude|source|from|to]
+                                         drop one filter, or all of them
+Queries:
+  show                                   current filters
+  count                                  matching entries
+  summary                                levels, time range and top keywords
+  head [N] / tail [N]                    first or last N matches (default 10)
+  templates [N]                          top N message templates of the matches (default 10)
+  template <N> [PAGE]                    page through template N's entries with context
+  timeline                               matches per time bucket, numbered
+  zoom [N|N-M]                           narrow the time range to timeline buckets N..M,
+                                         or to the bucket with the most errors
+  zoom out                               back to the range before the last zoom
+  help, quit";
+
+const TEMPLATE_PAGE: usize = 5;
+const TIMELINE_BUCKETS: usize = 20;
+const TIMELINE_BAR: usize = 40;
+// entries shown before and after each template member
+const CONTEXT_LINES: usize = 2;
+
+// Filter state is kept as the raw values so single filters can be cleared
+#[derive(Default)]
+struct Filters {
+    level: Option<LogLevel>,
+    keyword: Option<TextPattern>,
+    exclude: Vec<TextPattern>,
+    source: Option<(String, SourceMatcher)>,
+    from: Option<NaiveDateTime>,
+    to: Option<NaiveDateTime>,
+}
+
+impl Filters {
+    fn config(&self) -> FilterConfig {
+        let mut cfg = FilterConfig::new().with_time_range(self.from, self.to);
+        if let Some(ref level) = self.level {
+            cfg = cfg.with_min_level(level);
+        }
+        cfg = cfg
+            .with_keywords(self.keyword.iter().cloned().collect(), KeywordMode::All)
+            .with_exclude(self.exclude.clone());
+        if let Some((_, ref matcher)) = self.source {
+            cfg = cfg.with_sources(vec![matcher.clone()], Vec::new());
+        }
+        cfg
+    }
+
+    fn describe(&self) -> Vec<String> {
+        let mut parts = Vec::new();
+        if let Some(ref l) = self.level {
+            parts.push(format!("level >= {}", l.as_str()));
+        }
+        if let Some(ref k) = self.keyword {
+            parts.push(format!("keyword '{}'", k.spec));
+        }
+        for e in &self.exclude {
+            parts.push(format!("not '{}'", e.spec));
+        }
+        if let Some((ref s, _)) = self.source {
+            parts.push(format!("source '{}'", s));
+        }
+        if let Some(f) = self.from {
+            parts.push(format!("from {}", f.format("%Y-%m-%d %H:%M:%S")));
+        }
+        if let Some(t) = self.to {
+            parts.push(format!("to {}", t.format("%Y-%m-%d %H:%M:%S")));
+        }
+        parts
+    }
+}
+
+pub fn run(args: &ReplArgs, color: bool) -> io::Result<()> {
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let (entries, parse_stats) = LogParser::with_format(format).parse_files(&args.file_paths)?;
+
+    println!(
+        "Loaded {} entries from {}. Type 'help' for commands.",
+        entries.len(),
+        args.file_paths.join(", ")
+    );
+
+    let mut filters = Filters::default();
+    // indices into `entries`, recomputed only when a filter changes
+    let mut matching: Vec<usize> = (0..entries.len()).collect();
+    // the last `templates` listing, dropped when the filters change
+    let mut listed: Vec<TemplateEntry> = Vec::new();
+    // the last `timeline`, which `zoom N` numbers refer to
+    let mut timeline: Option<Timeline> = None;
+    // time ranges from before each zoom, newest last
+    let mut zooms: Vec<(Option<NaiveDateTime>, Option<NaiveDateTime>)> = Vec::new();
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut line = String::new();
+    loop {
+        print!("logscope> ");
+        io::stdout().flush()?;
+
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        let line = line.trim();
+        let (cmd, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        let changed = match cmd {
+            "" => false,
+            "quit" | "exit" | "q" => return Ok(()),
+            "help" | "?" => {
+                println!("{}", HELP);
+                false
+            }
+            "level" => match LogLevel::from_str(rest) {
+                LogLevel::Unknown => {
+                    println!("unknown level '{}'", rest);
+                    false
+                }
+                level => {
+                    filters.level = Some(level);
+                    true
+                }
+            },
+            "keyword" | "kw" | "exclude" if !rest.is_empty() => match TextPattern::parse(rest) {
+                Ok(pattern) if cmd == "exclude" => {
+                    filters.exclude.push(pattern);
+                    true
+                }
+                Ok(pattern) => {
+                    filters.keyword = Some(pattern);
+                    true
+                }
+                Err(e) => {
+                    println!("{}", e);
+                    false
+                }
+            },
+            "source" if !rest.is_empty() => match SourceMatcher::parse(rest) {
+                Ok(matcher) => {
+                    // A plain name still matches anywhere in the source, as before patterns.
+                    let matcher = match matcher {
+                        SourceMatcher::Exact(_) => SourceMatcher::containing(rest),
+                        other => other,
+                    };
+                    filters.source = Some((rest.to_string(), matcher));
+                    true
+                }
+                Err(e) => {
+                    println!("{}", e);
+                    false
+                }
+            },
+            "from" | "to" => match NaiveDateTime::parse_from_str(rest, "%Y-%m-%d %H:%M:%S") {
+                Ok(ts) => {
+                    if cmd == "from" {
+                        filters.from = Some(ts);
+                    } else {
+                        filters.to = Some(ts);
+                    }
+                    true
+                }
+                Err(e) => {
+                    println!("invalid time '{}': {}", rest, e);
+                    false
+                }
+            },
+            "clear" => {
+                match rest {
+                    "" => {
+                        filters = Filters::default();
+                        zooms.clear();
+                    }
+                    "level" => filters.level = None,
+                    "keyword" | "kw" => filters.keyword = None,
+                    "exclude" => filters.exclude.clear(),
+                    "source" => filters.source = None,
+                    "from" => filters.from = None,
+                    "to" => filters.to = None,
+                    other => println!("unknown filter '{}'", other),
+                }
+                true
+            }
+            "show" => {
+                let parts = filters.describe();
+                if parts.is_empty() {
+                    println!("no filters");
+                } else {
+                    println!("{}", parts.join(", "));
+                }
+                false
+            }
+            "count" => {
+                println!("{} of {} entries", matching.len(), entries.len());
+                false
+            }
+            "summary" => {
+                print_summary(&entries, &matching, &parse_stats, color);
+                false
+            }
+            "head" | "tail" => {
+                let n = if rest.is_empty() { Ok(10) } else { rest.parse::<usize>() };
+                match n {
+                    Ok(n) => {
+                        let skip = if cmd == "tail" { matching.len().saturating_sub(n) } else { 0 };
+                        for &i in matching.iter().skip(skip).take(n) {
+                            print_entry(&entries[i], color);
+                        }
+                    }
+                    Err(_) => println!("expected a number, got '{}'", rest),
+                }
+                false
+            }
+            "templates" => {
+                let n = if rest.is_empty() { Ok(10) } else { rest.parse::<usize>() };
+                match n {
+                    Ok(n) => {
+                        let subset: Vec<LogEntry> = matching.iter().map(|&i| entries[i].clone()).collect();
+                        listed = analyzer::cluster_templates(&subset, n);
+                        print_templates(&listed, color);
+                    }
+                    Err(_) => println!("expected a number, got '{}'", rest),
+                }
+                false
+            }
+            "template" => {
+                let mut args = rest.split_whitespace().map(str::parse::<usize>);
+                match (args.next(), args.next().unwrap_or(Ok(1))) {
+                    (Some(Ok(n)), Ok(page)) if n >= 1 && page >= 1 => match listed.get(n - 1) {
+                        Some(t) => print_members(&entries, &matching, t, n, page, color),
+                        None if listed.is_empty() => println!("no templates listed yet (try 'templates')"),
+                        None => println!("no template {}; the listing has {}", n, listed.len()),
+                    },
+                    _ => println!("usage: template <N> [PAGE]"),
+                }
+                false
+            }
+            "timeline" => {
+                let t = Timeline::of(&entries, &matching);
+                t.print(color);
+                timeline = Some(t);
+                false
+            }
+            "zoom" if rest == "out" => match zooms.pop() {
+                Some((from, to)) => {
+                    (filters.from, filters.to) = (from, to);
+                    true
+                }
+                None => {
+                    println!("not zoomed in");
+                    false
+                }
+            },
+            "zoom" => {
+                let range = if rest.is_empty() {
+                    Timeline::of(&entries, &matching).spike().ok_or_else(|| "no entries match".to_string())
+                } else {
+                    match timeline {
+                        Some(ref t) => t.range(rest),
+                        None => Err("no timeline shown yet (try 'timeline')".to_string()),
+                    }
+                };
+                match range {
+                    Ok((from, to)) => {
+                        zooms.push((filters.from, filters.to));
+                        (filters.from, filters.to) = (Some(from), Some(to));
+                        true
+                    }
+                    Err(e) => {
+                        println!("{}", e);
+                        false
+                    }
+                }
+            }
+            other => {
+                println!("unknown command '{}' (try 'help')", other);
+                false
+            }
+        };
+
+        if changed {
+            let cfg = filters.config();
+            matching = (0..entries.len()).filter(|&i| cfg.matches(&entries[i])).collect();
+            listed.clear();
+            timeline = None;
+            println!("→ {} of {} entries", matching.len(), entries.len());
+            // zooming again is one more command away
+            if cmd == "zoom" {
+                let t = Timeline::of(&entries, &matching);
+                t.print(color);
+                timeline = Some(t);
+            }
+        }
+    }
+}
+
+// Matches and their errors per bucket, numbered for `zoom`. Timestamps are
+// shown as logged.
+struct Timeline {
+    buckets: Vec<(NaiveDateTime, usize, usize)>,
+    width: Duration,
+}
+
+impl Timeline {
+    fn of(entries: &[LogEntry], matching: &[usize]) -> Self {
+        let (Some(&first), Some(&last)) = (matching.first(), matching.last()) else {
+            return Self { buckets: Vec::new(), width: Duration::zero() };
+        };
+        let (first, last) = (entries[first].timestamp, entries[last].timestamp);
+        let bucketing = Bucketing::fitting(first, last, TIMELINE_BUCKETS, Zone::default());
+        let (origin, count) = bucketing.span(first, last);
+        let mut buckets: Vec<(NaiveDateTime, usize, usize)> =
+            (0..count).map(|b| (origin + bucketing.width() * b as i32, 0, 0)).collect();
+        for &i in matching {
+            let bucket = &mut buckets[bucketing.index(origin, entries[i].timestamp).min(count - 1)];
+            bucket.1 += 1;
+            if matches!(entries[i].level, LogLevel::Error | LogLevel::Fatal) {
+                bucket.2 += 1;
+            }
+        }
+        Self { buckets, width: bucketing.width() }
+    }
+
+    fn print(&self, color: bool) {
+        if self.buckets.is_empty() {
+            println!("no entries match");
+            return;
+        }
+        let max = self.buckets.iter().map(|b| b.1).max().unwrap_or(0).max(1);
+        for (n, &(start, total, errors)) in self.buckets.iter().enumerate() {
+            let bar = "█".repeat((total * TIMELINE_BAR).div_ceil(max));
+            let line = format!(
+                "{:>3}. {}  {:<width$} {:>7}  {:>6} err",
+                n + 1,
+                start.format("%Y-%m-%d %H:%M:%S"),
+                bar,
+                total,
+                errors,
+                width = TIMELINE_BAR
+            );
+            if color && errors > 0 && errors * 2 >= total {
+                println!("{}", line.red());
+            } else {
+                println!("{}", line);
+            }
+        }
+        println!(
+            "{} per bucket; 'zoom N' or 'zoom N-M' to narrow, 'zoom out' to go back",
+            format_duration(self.width.num_seconds())
+        );
+    }
+
+    // `N` or `N-M`, numbered from 1, as an inclusive time range
+    fn range(&self, spec: &str) -> Result<(NaiveDateTime, NaiveDateTime), String> {
+        let (a, b) = spec.split_once('-').unwrap_or((spec, spec));
+        match (a.trim().parse::<usize>(), b.trim().parse::<usize>()) {
+            (Ok(a), Ok(b)) if a >= 1 && a <= b && b <= self.buckets.len() => Ok(self.bounds(a - 1, b - 1)),
+            (Ok(_), Ok(_)) => Err(format!("buckets run from 1 to {}", self.buckets.len())),
+            _ => Err("usage: zoom [N|N-M|out]".to_string()),
+        }
+    }
+
+    // the bucket with the most errors, or the busiest one when there are none
+    fn spike(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let (i, _) = self.buckets.iter().enumerate().max_by_key(|(i, b)| (b.2, b.1, std::cmp::Reverse(*i)))?;
+        Some(self.bounds(i, i))
+    }
+
+    fn bounds(&self, first: usize, last: usize) -> (NaiveDateTime, NaiveDateTime) {
+        (self.buckets[first].0, self.buckets[last].0 + self.width - Duration::nanoseconds(1))
+    }
+}
+
+fn print_entry(entry: &LogEntry, color: bool) {
+    let level = format!("{:<5}", entry.level.as_str());
+    let level = match entry.level {
+        LogLevel::Error | LogLevel::Fatal if color => level.red().to_string(),
+        LogLevel::Warn if color => level.yellow().to_string(),
+        _ => level,
+    };
+    match entry.source {
+        Some(ref src) => println!("{} {} [{}] {}", entry.timestamp, level, src, entry.message),
+        None => println!("{} {} {}", entry.timestamp, level, entry.message),
+    }
+}
+
+fn print_templates(templates: &[TemplateEntry], color: bool) {
+    if templates.is_empty() {
+        println!("no entries match");
+        return;
+    }
+    for (i, t) in templates.iter().enumerate() {
+        let line = format!("{:>3}. {:>7}  {:>5.1}% err  {}", i + 1, t.count, t.error_ratio * 100.0, t.template);
+        if color && t.error_ratio >= 0.5 {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+// One page of a template's matching entries (marked `>`), each with the
+// entries logged around it as context whatever the filters; overlapping
+// context is merged and gaps marked `--`.
+fn print_members(
+    entries: &[LogEntry],
+    matching: &[usize],
+    template: &TemplateEntry,
+    n: usize,
+    page: usize,
+    color: bool,
+) {
+    let members: Vec<usize> = matching
+        .iter()
+        .copied()
+        .filter(|&i| analyzer::in_template(&template.template, &entries[i].message))
+        .collect();
+    let pages = members.len().div_ceil(TEMPLATE_PAGE).max(1);
+    if page > pages {
+        println!("template {} has {} page{}", n, pages, if pages == 1 { "" } else { "s" });
+        return;
+    }
+
+    let header = format!("{} — page {} of {} ({} entries)", template.template, page, pages, members.len());
+    println!("{}", if color { header.bold().to_string() } else { header });
+
+    let shown = &members[(page - 1) * TEMPLATE_PAGE..(page * TEMPLATE_PAGE).min(members.len())];
+    let mut next = 0;
+    for &i in shown {
+        let start = i.saturating_sub(CONTEXT_LINES).max(next);
+        let end = (i + CONTEXT_LINES + 1).min(entries.len());
+        if start >= end {
+            continue;
+        }
+        if next > 0 && start > next {
+            println!("--");
+        }
+        for (j, entry) in entries.iter().enumerate().take(end).skip(start) {
+            if shown.binary_search(&j).is_ok() {
+                print!("> ");
+                print_entry(entry, color);
+            } else if color {
+                println!("  {}", context_line(entry).dimmed());
+            } else {
+                println!("  {}", context_line(entry));
+            }
+        }
+        next = end;
+    }
+    if page < pages {
+        println!("('template {} {}' for more)", n, page + 1);
+    }
+}
+
+fn context_line(entry: &LogEntry) -> String {
+    match entry.source {
+        Some(ref src) => format!("{} {:<5} [{}] {}", entry.timestamp, entry.level.as_str(), src, entry.message),
+        None => format!("{} {:<5} {}", entry.timestamp, entry.level.as_str(), entry.message),
+    }
+}
+
+fn print_summary(entries: &[LogEntry], matching: &[usize], parse_stats: &ParseStats, color: bool) {
+    let subset: Vec<LogEntry> = matching.iter().map(|&i| entries[i].clone()).collect();
+    let analysis = LogAnalyzer::new(&subset, parse_stats.clone()).analyze(Some(5));
+
+    println!("{} entries", analysis.stats.total);
+    if let Some(ref t) = analysis.stats.time {
+        println!("{} → {} ({})", t.start, t.end, t.span_human);
+    }
+
+    let levels: Vec<String> = ["FATAL", "ERROR", "WARN", "INFO", "DEBUG", "UNKNOWN"]
+        .iter()
+        .filter_map(|l| analysis.level_counts.get(*l).map(|n| format!("{} {}", l, n)))
+        .collect();
+    println!("{}", levels.join("  "));
+
+    if !analysis.top_keywords.is_empty() {
+        let words: Vec<String> = analysis
+            .top_keywords
+            .iter()
+            .map(|k| format!("{} ×{}", k.word, k.count))
+            .collect();
+        let line = format!("top: {}", words.join(", "));
+        if color {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::archive;
+use crate::cli::ReplayArgs;
+use crate::parser::{self, LogFormat, LogParser};
+
+// `--speed 10x`: how much faster than real time to replay; `max` drops the
+// delays entirely.
+#[derive(Debug, Clone, Copy)]
+pub struct Speed(pub Option<f64>);
+
+impl Speed {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if spec == "max" {
+            return Ok(Self(None));
+        }
+        let factor: f64 = spec
+            .strip_suffix(['x', 'X'])
+            .unwrap_or(spec)
+            .parse()
+            .ok()
+            .filter(|f: &f64| *f > 0.0 && f.is_finite())
+            .ok_or_else(|| format!("Invalid speed '{}' (expected e.g. 1x, 10x, 0.5x or max)", spec))?;
+        Ok(Self(Some(factor)))
+    }
+}
+
+// `--to stdout` or `--to udp://host:514`; one datagram per entry, as syslog
+// relays expect.
+#[derive(Debug, Clone)]
+pub enum ReplayTarget {
+    Stdout,
+    Udp(String),
+}
+
+impl ReplayTarget {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.trim() {
+            "stdout" | "-" => Ok(Self::Stdout),
+            s => match s.strip_prefix("udp://") {
+                Some(addr) if addr.contains(':') => Ok(Self::Udp(addr.to_string())),
+                _ => Err(format!("Invalid target '{}' (expected stdout or udp://HOST:PORT)", s)),
+            },
+        }
+    }
+}
+
+enum Sink {
+    Stdout(io::StdoutLock<'static>),
+    // unconnected, so a receiver that isn't listening yet doesn't end the
+    // replay with "connection refused"
+    Udp(UdpSocket, SocketAddr),
+}
+
+impl Sink {
+    fn open(target: &ReplayTarget) -> io::Result<Self> {
+        match target {
+            ReplayTarget::Stdout => Ok(Self::Stdout(io::stdout().lock())),
+            ReplayTarget::Udp(addr) => {
+                let target = addr
+                    .to_socket_addrs()
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", addr, e)))?
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{}: no address", addr)))?;
+                let local = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                Ok(Self::Udp(UdpSocket::bind(local)?, target))
+            }
+        }
+    }
+
+    fn send(&mut self, line: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Stdout(out) => {
+                out.write_all(line)?;
+                out.write_all(b"\n")?;
+                out.flush()
+            }
+            Self::Udp(socket, target) => socket.send_to(line, *target).map(|_| ()),
+        }
+    }
+}
+
+pub struct ReplaySummary {
+    pub sent: usize,
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+// Re-emits the original lines of every parsed entry in timestamp order,
+// spaced like the originals were (scaled by `--speed`). An entry read from
+// several lines (a stack trace, a wevtutil or XML record) is sent whole.
+// Lines no format recognizes carry no timestamp and are skipped. Compressed
+// files are read like everywhere else.
+pub fn run(args: &ReplayArgs) -> io::Result<ReplaySummary> {
+    let labeled = |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", args.file_path, e));
+    let mut buf = Vec::new();
+    archive::open_decompressed(File::open(&args.file_path).map_err(labeled)?)
+        .and_then(|mut file| file.read_to_end(&mut buf))
+        .map_err(labeled)?;
+    // the lines sent are the ones parsed, so UTF-16 is decoded up front
+    if let Some(rest) = buf.strip_prefix(b"\xFF\xFE") {
+        buf = parser::decode_utf16le(rest).into_bytes();
+    }
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let (entries, parse_stats) = LogParser::with_format(format)
+        .with_multiline(args.multiline, args.multiline_pattern.clone())
+        .parse_bytes(&buf);
+    let lines: Vec<&[u8]> = buf.split(|&b| b == b'\n').map(|l| l.strip_suffix(b"\r").unwrap_or(l)).collect();
+
+    let mut sink = Sink::open(&args.to)?;
+    let start = Instant::now();
+    let first = entries.first().map(|e| e.timestamp);
+
+    for entry in &entries {
+        if let (Some(factor), Some(first)) = (args.speed.0, first) {
+            let offset = (entry.timestamp - first).to_std().unwrap_or_default();
+            let due = start + offset.div_f64(factor);
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+        }
+        let span = &lines[entry.line_number - 1..entry.end_line.unwrap_or(entry.line_number)];
+        sink.send(&span.join(&b'\n'))?;
+    }
+
+    Ok(ReplaySummary {
+        sent: entries.len(),
+        skipped: parse_stats.unparsed + parse_stats.oversized,
+        elapsed: start.elapsed(),
+    })
+}
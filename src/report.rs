@@ -1,7 +1,45 @@
 use colored::Colorize;
+use std::collections::HashMap;
+use std::io::{self, Write};
 
-use crate::analyzer::LogAnalysis;
-use crate::parser::LogLevel;
+use crate::alerts::{Alert, AlertState};
+use crate::analyzer::{LiveSnapshot, LogAnalysis, Timeline, TimelineBucket, Trend, WindowScore};
+use crate::groups::GroupSummary;
+use crate::cost::CostReport;
+use crate::http::{HttpSummary, StatusBucket};
+use crate::ratelimit::RateLimitReport;
+use crate::retention::RetentionResult;
+use crate::model::BaselineComparison;
+use crate::parser::{LogEntry, LogLevel};
+use crate::scoring::ScorePart;
+use crate::sources::SourceStats;
+use crate::stats::{ErrorBurst, Stats};
+use crate::versions::VersionComparison;
+
+const LIVE_BURST_LIMIT: usize = 5;
+
+// How the live inputs keep up: what the stream queues held at most and
+// dropped, and how far the followed files are ahead of what was read.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Intake {
+    pub streams: usize,
+    pub queue_peak: usize,
+    pub queue_capacity: usize,
+    pub dropped_lines: usize,
+    pub dropped_bytes: u64,
+    pub behind_bytes: u64,
+}
+
+// What one redraw of the follow-mode dashboard shows.
+pub struct LiveFrame<'a> {
+    pub file_path: &'a str,
+    pub snapshot: &'a LiveSnapshot,
+    pub new_entries: usize,
+    pub recent_errors: &'a [&'a LogEntry],
+    pub intake: &'a Intake,
+    // (local time sent, alert), oldest first
+    pub alerts: &'a [(String, Alert)],
+}
 
 pub struct ReportGenerator {
     color: bool,
@@ -12,55 +50,410 @@ impl ReportGenerator {
         Self { color }
     }
 
-    pub fn generate(&self, file_path: &str, analysis: &LogAnalysis, show_heatmap: bool) {
-        self.print_header(file_path, analysis);
-        self.print_level_distribution(analysis);
-        self.print_stats(analysis);
-        self.print_top_keywords(analysis);
+    pub fn render(
+        &self,
+        out: &mut dyn Write,
+        file_path: &str,
+        analysis: &LogAnalysis,
+        show_heatmap: bool,
+    ) -> io::Result<()> {
+        self.print_header(out, file_path, analysis)?;
+
+        let ps = &analysis.parse_stats;
+        if ps.format_counts.len() > 1 || ps.unparsed > 0 || ps.repaired > 0 || ps.continued > 0 {
+            self.print_parse_coverage(out, analysis)?;
+        }
+
+        if !analysis.startup.is_empty() {
+            self.print_startup(out, analysis)?;
+        }
+
+        self.print_level_distribution(out, &analysis.level_counts, analysis.stats.total)?;
+        self.print_stats(out, &analysis.stats)?;
+
+        if analysis.timeline.buckets.len() > 1 {
+            self.print_timeline(out, &analysis.timeline)?;
+        }
+
+        self.print_top_keywords(out, analysis)?;
+
+        if !analysis.top_templates.is_empty() {
+            self.print_top_templates(out, analysis)?;
+        }
+
+        if !analysis.source_coverage.is_empty() {
+            self.print_source_coverage(out, analysis)?;
+        }
+
+        if analysis.source_stats.first().is_some_and(|s| s.errors > 0) {
+            self.print_error_sources(out, &analysis.source_stats)?;
+        }
+
+        if let Some(ref versions) = analysis.versions {
+            self.print_versions(out, versions)?;
+        }
+
+        for group in &analysis.groups {
+            self.print_group(out, group)?;
+        }
+
+        if let Some(ref http) = analysis.http {
+            self.print_http(out, http)?;
+        }
+
+        if let Some(ref limit) = analysis.rate_limit {
+            self.print_rate_limit(out, limit)?;
+        }
+
+        if !analysis.endpoints.is_empty() {
+            self.print_endpoints(out, analysis)?;
+        }
+
+        if !analysis.endpoints.worst.is_empty() {
+            self.print_worst_endpoints(out, analysis)?;
+        }
+
+        if !analysis.error_budgets.is_empty() {
+            self.print_error_budgets(out, analysis)?;
+        }
+
+        if let Some(ref cost) = analysis.cost {
+            self.print_cost(out, cost)?;
+        }
+
+        if !analysis.retention.is_empty() {
+            self.print_retention(out, &analysis.retention)?;
+        }
+
+        if !analysis.metrics.is_empty() {
+            self.print_metrics(out, analysis)?;
+        }
+
+        if !analysis.error_precursors.is_empty() {
+            self.print_error_precursors(out, analysis)?;
+        }
+
+        if !analysis.threshold_events.is_empty() {
+            self.print_threshold_events(out, analysis)?;
+        }
+
+        if let Some(ref baseline) = analysis.baseline {
+            self.print_baseline(out, baseline)?;
+        }
+
+        if !analysis.entities.is_empty() {
+            self.print_entities(out, analysis)?;
+        }
 
         if !analysis.stats.error_bursts.is_empty() {
-            self.print_bursts(analysis);
+            self.print_bursts(out, &analysis.stats, &analysis.stats.error_bursts)?;
         }
 
         if show_heatmap {
-            self.print_heatmap(analysis);
+            self.print_heatmap(out, analysis)?;
+        }
+
+        if !analysis.tail.is_empty() {
+            self.print_tail(out, analysis)?;
         }
 
-        self.print_anomaly_score(analysis);
+        self.print_anomaly_score(out, analysis.anomaly_score, &analysis.anomaly_parts)
     }
 
-    fn print_header(&self, file_path: &str, analysis: &LogAnalysis) {
+    // The follow-mode dashboard, redrawn in place each time new lines arrive:
+    // the parts of the report that can be kept up to date incrementally, plus
+    // the latest alerts and errors.
+    pub fn render_live(&self, out: &mut dyn Write, frame: &LiveFrame) -> io::Result<()> {
+        let LiveFrame { file_path, snapshot, new_entries, recent_errors, intake, alerts } = *frame;
+        let title = "logscope — following (Ctrl-C to stop)";
+        if self.color {
+            writeln!(out, "{}", title.bold().cyan())?;
+        } else {
+            writeln!(out, "{}", title)?;
+        }
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let stats = &snapshot.stats;
+        writeln!(out, "File    : {}", file_path)?;
+        writeln!(out, "Entries : {} (+{})", stats.total, new_entries)?;
+        if let Some(ref t) = stats.time {
+            writeln!(out, "Range   : {} → {}", t.start, t.end)?;
+        }
+        writeln!(out, "Rate    : {:.1} entries/min", stats.rate_per_minute)?;
+        if intake.streams > 0 {
+            writeln!(out, "Queue   : peak {} of {} lines", intake.queue_peak, intake.queue_capacity)?;
+        }
+        if intake.streams > 0 || intake.dropped_lines > 0 {
+            let line = format!(
+                "Dropped : {} lines ({:.1} MB)",
+                intake.dropped_lines,
+                intake.dropped_bytes as f64 / 1e6
+            );
+            if self.color && intake.dropped_lines > 0 {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        if intake.behind_bytes > 0 {
+            writeln!(out, "Behind  : {:.1} MB not read yet", intake.behind_bytes as f64 / 1e6)?;
+        }
+        writeln!(out)?;
+
+        if stats.total > 0 {
+            self.print_level_distribution(out, &snapshot.level_counts, stats.total)?;
+            self.print_stats(out, stats)?;
+        }
+
+        if !stats.error_bursts.is_empty() {
+            let skip = stats.error_bursts.len().saturating_sub(LIVE_BURST_LIMIT);
+            self.print_bursts(out, stats, &stats.error_bursts[skip..])?;
+        }
+
+        if !alerts.is_empty() {
+            writeln!(out, "Alerts")?;
+            writeln!(out, "{}", "─".repeat(30))?;
+            for (at, alert) in alerts {
+                let line = format!("  {}  {}", at, alert.describe());
+                if self.color && alert.state != AlertState::Resolved {
+                    writeln!(out, "{}", line.red().bold())?;
+                } else {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+            writeln!(out)?;
+        }
+
+        if !recent_errors.is_empty() {
+            writeln!(out, "Latest Errors")?;
+            writeln!(out, "{}", "─".repeat(30))?;
+            for entry in recent_errors {
+                let line = format!(
+                    "  {}  {:<5}  {}",
+                    entry.timestamp.format("%H:%M:%S"),
+                    entry.level.as_str(),
+                    truncate(&entry.message, 80)
+                );
+                if self.color {
+                    writeln!(out, "{}", line.red())?;
+                } else {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+            writeln!(out)?;
+        }
+
+        self.print_anomaly_score(out, snapshot.anomaly_score, &snapshot.anomaly_parts)?;
+        if let Some(ref w) = snapshot.window {
+            self.print_window_score(out, w)?;
+        }
+        Ok(())
+    }
+
+    fn print_window_score(&self, out: &mut dyn Write, window: &WindowScore) -> io::Result<()> {
+        let score = window.anomaly_score;
+        let line = format!(
+            "Last {}: {:.1} / 100  [{}]  over {} entries",
+            format_duration(window.span.num_seconds()),
+            score,
+            anomaly_label(score),
+            window.entries
+        );
+        if self.color {
+            let colored = match score as u32 {
+                0..=20 => line.green().to_string(),
+                21..=50 => line.yellow().to_string(),
+                _ => line.red().to_string(),
+            };
+            writeln!(out, "{}", colored)?;
+        } else {
+            writeln!(out, "{}", line)?;
+        }
+        if window.anomaly_parts.len() > 1 {
+            for part in &window.anomaly_parts {
+                writeln!(out, "  {:<24} ×{:<5} {:5.1}", part.scorer, part.weight, part.score)?;
+            }
+        }
+        writeln!(out)
+    }
+
+    fn print_startup(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        writeln!(out, "Startup")?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        for line in &analysis.startup {
+            let kind = format!("{:<7}", line.kind.as_str());
+            let kind = if self.color { kind.dimmed().to_string() } else { kind };
+            writeln!(out, "  {}  {:>6}  {}", kind, line.line_number, truncate(&line.message, 90))?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    // the last warnings and errors, where responders look first
+    fn print_tail(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        writeln!(out, "How It Ended (last {} WARN+)", analysis.tail.len())?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        for t in &analysis.tail {
+            let e = &t.entry;
+            let before = if t.before_end > 0 {
+                format!("-{}", format_duration(t.before_end))
+            } else {
+                "end".to_string()
+            };
+            let message = match e.source {
+                Some(ref source) => format!("{}: {}", source, e.message),
+                None => e.message.clone(),
+            };
+            let line = format!(
+                "  {} {:>10}  {:<5}  {}",
+                e.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                before,
+                e.level.as_str(),
+                truncate(message.lines().next().unwrap_or(""), 80)
+            );
+            if !self.color {
+                writeln!(out, "{}", line)?;
+            } else if e.level == LogLevel::Warn {
+                writeln!(out, "{}", line.yellow())?;
+            } else {
+                writeln!(out, "{}", line.red())?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_header(
+        &self,
+        out: &mut dyn Write,
+        file_path: &str,
+        analysis: &LogAnalysis,
+    ) -> io::Result<()> {
         let title = "logscope — Log Analysis Report";
         if self.color {
-            println!("\n{}", title.bold().cyan());
+            writeln!(out, "\n{}", title.bold().cyan())?;
         } else {
-            println!("\n{}", title);
+            writeln!(out, "\n{}", title)?;
         }
-        println!("{}", "─".repeat(50));
+        writeln!(out, "{}", "─".repeat(50))?;
 
-        println!("File    : {}", file_path);
-        println!("Entries : {}", analysis.stats.total);
+        let files = &analysis.parse_stats.files;
+        if files.len() > 1 {
+            writeln!(out, "Files   : {}", files.len())?;
+            let width = files.iter().map(|(path, _)| path.chars().count()).max().unwrap_or(0);
+            for (path, entries) in files {
+                writeln!(out, "  {:<width$}  {:>8} entries", path, entries, width = width)?;
+            }
+        } else {
+            writeln!(out, "File    : {}", file_path)?;
+        }
+        writeln!(out, "Entries : {}", analysis.stats.total)?;
 
-        if analysis.unparsed_lines > 0 {
-            let msg = format!("Skipped : {} unparsed lines", analysis.unparsed_lines);
+        if analysis.parse_stats.unparsed > 0 {
+            let msg = format!("Skipped : {} unparsed lines", analysis.parse_stats.unparsed);
             if self.color {
-                println!("{}", msg.yellow());
+                writeln!(out, "{}", msg.yellow())?;
             } else {
-                println!("{}", msg);
+                writeln!(out, "{}", msg)?;
             }
         }
 
+        if analysis.parse_stats.truncated > 0 {
+            writeln!(
+                out,
+                "Cut     : {} messages over the message length limit",
+                analysis.parse_stats.truncated
+            )?;
+        }
+
+        if analysis.parse_stats.schema_misfits > 0 {
+            writeln!(
+                out,
+                "Untyped : {} field values that don't fit the schema, kept as logged",
+                analysis.parse_stats.schema_misfits
+            )?;
+        }
+
+        if analysis.parse_stats.duplicates > 0 {
+            writeln!(
+                out,
+                "Deduped : {} entries duplicated across input files",
+                analysis.parse_stats.duplicates
+            )?;
+        }
+
         if let Some(ref t) = analysis.stats.time {
-            println!("Range   : {} → {}", t.start, t.end);
-            println!("Span    : {}", t.span_human);
+            writeln!(out, "Range   : {} → {}", t.start, t.end)?;
+            writeln!(out, "Span    : {}", t.span_human)?;
         }
 
-        println!("Rate    : {:.1} entries/min\n", analysis.stats.rate_per_minute);
+        writeln!(out, "Rate    : {:.1} entries/min\n", analysis.stats.rate_per_minute)?;
+        Ok(())
     }
 
-    fn print_level_distribution(&self, analysis: &LogAnalysis) {
-        println!("{}", "Log Level Distribution");
-        println!("{}", "─".repeat(30));
+    fn print_parse_coverage(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        let ps = &analysis.parse_stats;
+
+        writeln!(out, "Parse Coverage")?;
+        writeln!(out, "{}", "─".repeat(30))?;
+
+        let mut formats: Vec<(&String, &usize)> = ps.format_counts.iter().collect();
+        formats.sort_unstable_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+        let total = ps.total_lines.max(1) as f64;
+        for (name, count) in formats {
+            let pct = *count as f64 / total * 100.0;
+            writeln!(out, "  {:<9} {:>6}  ({:5.1}%)", name, count, pct)?;
+        }
+
+        if ps.continued > 0 {
+            let pct = ps.continued as f64 / total * 100.0;
+            writeln!(out, "  {:<9} {:>6}  ({:5.1}%)", "continued", ps.continued, pct)?;
+        }
+
+        if ps.unparsed > 0 {
+            let pct = ps.unparsed as f64 / total * 100.0;
+            let line = format!("  {:<9} {:>6}  ({:5.1}%)", "unparsed", ps.unparsed, pct);
+            if self.color {
+                writeln!(out, "{}", line.yellow())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        if ps.oversized > 0 {
+            writeln!(out, "  ({} of the unparsed lines exceeded the line length limit)", ps.oversized)?;
+        }
+
+        if ps.repaired > 0 {
+            writeln!(out, "  ({} torn lines were split or re-joined)", ps.repaired)?;
+        }
+
+        if let Some((line_number, ref sample)) = ps.sample_unparsed {
+            let shown: String = sample.chars().take(100).collect();
+            let ellipsis = if shown.len() < sample.len() { "…" } else { "" };
+            match ps.sample_file {
+                Some(ref file) => writeln!(out, "  e.g. line {} of {}: {}{}", line_number, file, shown, ellipsis)?,
+                None => writeln!(out, "  e.g. line {}: {}{}", line_number, shown, ellipsis)?,
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_level_distribution(
+        &self,
+        out: &mut dyn Write,
+        level_counts: &HashMap<String, usize>,
+        total: usize,
+    ) -> io::Result<()> {
+        writeln!(out, "Log Level Distribution")?;
+        writeln!(out, "{}", "─".repeat(30))?;
 
         let levels = [
             LogLevel::Fatal,
@@ -72,12 +465,12 @@ impl ReportGenerator {
 
         for level in &levels {
             let key = level.as_str();
-            let count = *analysis.level_counts.get(key).unwrap_or(&0);
+            let count = *level_counts.get(key).unwrap_or(&0);
             if count == 0 {
                 continue;
             }
 
-            let pct = count as f64 / analysis.stats.total as f64 * 100.0;
+            let pct = count as f64 / total as f64 * 100.0;
             let bar_len = (pct / 2.0) as usize;
             let bar = "█".repeat(bar_len);
 
@@ -92,39 +485,53 @@ impl ReportGenerator {
                     LogLevel::Debug => label.dimmed().to_string(),
                     LogLevel::Unknown => label,
                 };
-                println!("{}", colored);
+                writeln!(out, "{}", colored)?;
             } else {
-                println!("{}", label);
+                writeln!(out, "{}", label)?;
             }
         }
 
-        println!();
+        writeln!(out)?;
+        Ok(())
     }
 
-    fn print_stats(&self, analysis: &LogAnalysis) {
-        println!("{}", "Statistics");
-        println!("{}", "─".repeat(30));
-        println!("  Error rate  : {:.1}%", analysis.stats.error_rate);
+    fn print_stats(&self, out: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+        writeln!(out, "Statistics")?;
+        writeln!(out, "{}", "─".repeat(30))?;
+        writeln!(out, "  Error rate  : {:.1}%", stats.error_rate)?;
 
-        if let Some(mtbf) = analysis.stats.mtbf_seconds {
+        if let Some(mtbf) = stats.mtbf_seconds {
             let formatted = format_duration(mtbf as i64);
-            println!("  MTBF errors : {}", formatted);
+            writeln!(out, "  MTBF errors : {}", formatted)?;
         }
 
-        if let Some(peak) = analysis.stats.peak_hour {
-            println!("  Peak hour   : {:02}:00 – {:02}:59", peak, peak);
+        if let Some(peak) = stats.peak_hour {
+            writeln!(out, "  Peak hour   : {:02}:00 – {:02}:59", peak, peak)?;
+        }
+
+        if let Some(ref coarse) = stats.coarse_timestamps {
+            let note = format!(
+                "  Precision   : 1s; {:.0}% of entries share a timestamp (up to {} on one), so rates and bursts are approximate",
+                coarse.shared_pct, coarse.busiest
+            );
+            if self.color {
+                writeln!(out, "{}", note.yellow())?;
+            } else {
+                writeln!(out, "{}", note)?;
+            }
         }
 
-        println!();
+        writeln!(out)?;
+        Ok(())
     }
 
-    fn print_top_keywords(&self, analysis: &LogAnalysis) {
+    fn print_top_keywords(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
         if analysis.top_keywords.is_empty() {
-            return;
+            return Ok(());
         }
 
-        println!("{}", "Top Keywords");
-        println!("{}", "─".repeat(30));
+        writeln!(out, "Top Keywords")?;
+        writeln!(out, "{}", "─".repeat(30))?;
 
         for (i, kw) in analysis.top_keywords.iter().enumerate() {
             let ratio_bar = if kw.error_ratio > 0.0 {
@@ -133,43 +540,864 @@ impl ReportGenerator {
                 String::new()
             };
 
+            let arrow = match kw.trend {
+                Trend::Rising => "↑",
+                Trend::Falling => "↓",
+                Trend::Stable => "→",
+            };
+
             let line = format!(
-                "  {:>2}. {:>15}  ×{:<6}{}",
+                "  {:>2}. {:>15}  ×{:<6} {} {}{}",
                 i + 1,
                 kw.word,
                 kw.count,
+                sparkline(&kw.series.iter().map(|&c| c as f64).collect::<Vec<_>>()),
+                arrow,
                 ratio_bar,
             );
 
             if self.color && kw.error_ratio > 0.5 {
-                println!("{}", line.red());
+                writeln!(out, "{}", line.red())?;
             } else {
-                println!("{}", line);
+                writeln!(out, "{}", line)?;
             }
         }
 
-        println!();
+        writeln!(out)?;
+        Ok(())
     }
 
-    fn print_bursts(&self, analysis: &LogAnalysis) {
-        let header = format!("Error Bursts Detected ({})", analysis.stats.error_bursts.len());
+    fn print_top_templates(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        writeln!(out, "Top Templates")?;
+        writeln!(out, "{}", "─".repeat(30))?;
+
+        for (i, t) in analysis.top_templates.iter().enumerate() {
+            let ratio = if t.error_ratio > 0.0 {
+                format!("  [{:.0}% errors]", t.error_ratio * 100.0)
+            } else {
+                String::new()
+            };
+            let template: String = t.template.chars().take(100).collect();
+            let line = format!("  {:>2}. ×{:<7} {}{}", i + 1, t.count, template, ratio);
+
+            if self.color && t.error_ratio > 0.5 {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_source_coverage(
+        &self,
+        out: &mut dyn Write,
+        analysis: &LogAnalysis,
+    ) -> io::Result<()> {
+        const MAX_SOURCES: usize = 20;
+
+        writeln!(out, "Source Coverage")?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let width = analysis
+            .source_coverage
+            .iter()
+            .take(MAX_SOURCES)
+            .map(|s| s.source.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(24);
+
+        // time of day alone is ambiguous once the file spans several days
+        let multi_day = analysis
+            .stats
+            .time
+            .as_ref()
+            .is_some_and(|t| t.start.get(..10) != t.end.get(..10));
+        let short = |ts: &str| -> String {
+            if multi_day { ts.to_string() } else { ts.get(11..).unwrap_or(ts).to_string() }
+        };
+
+        for src in analysis.source_coverage.iter().take(MAX_SOURCES) {
+            let bar: String = src
+                .activity
+                .iter()
+                .map(|&n| if n > 0 { '█' } else { '·' })
+                .collect();
+            let name: String = src.source.chars().take(width).collect();
+            writeln!(
+                out,
+                "  {:<width$} │{}│ {} → {}  ({})",
+                name,
+                bar,
+                short(&src.first_seen),
+                short(&src.last_seen),
+                src.entries,
+                width = width,
+            )?;
+        }
+
+        if analysis.source_coverage.len() > MAX_SOURCES {
+            writeln!(out, "  … {} more sources", analysis.source_coverage.len() - MAX_SOURCES)?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_error_sources(&self, out: &mut dyn Write, sources: &[SourceStats]) -> io::Result<()> {
+        const TOP_SOURCES: usize = 10;
+
+        writeln!(out, "Top Error Sources")?;
+        writeln!(out, "{}", "─".repeat(50))?;
+        let shown: Vec<&SourceStats> = sources.iter().filter(|s| s.errors > 0).take(TOP_SOURCES).collect();
+        let width = shown.iter().map(|s| s.source.chars().count()).max().unwrap_or(0).min(24);
+        for src in &shown {
+            let name: String = src.source.chars().take(width).collect();
+            let line = format!(
+                "  {:<width$}  {:>7} errors  {:>6.2}% of {:>8}  {} → {}",
+                name,
+                src.errors,
+                src.error_rate,
+                src.entries,
+                src.first_seen,
+                src.last_seen,
+                width = width,
+            );
+            if self.color && src.error_rate >= 10.0 {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        let rest = sources.iter().filter(|s| s.errors > 0).count() - shown.len();
+        if rest > 0 {
+            writeln!(out, "  … {} more sources with errors", rest)?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_http(&self, out: &mut dyn Write, http: &HttpSummary) -> io::Result<()> {
+        const STATUS_LIMIT: usize = 8;
+
+        writeln!(out, "HTTP ({} requests, {:.1} MB sent)", http.requests, http.bytes as f64 / 1e6)?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let classes: Vec<String> = (1..=5)
+            .map(|c| (c, http.class_count(c)))
+            .filter(|&(_, n)| n > 0)
+            .map(|(c, n)| format!("{}xx {} ({:.1}%)", c, n, n as f64 / http.requests as f64 * 100.0))
+            .collect();
+        writeln!(out, "  {}", classes.join("   "))?;
+
+        let mut codes: Vec<(&u16, &usize)> = http.status_codes.iter().collect();
+        codes.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let codes: Vec<String> = codes.iter().take(STATUS_LIMIT).map(|(c, n)| format!("{} ×{}", c, n)).collect();
+        writeln!(out, "  codes: {}", codes.join(", "))?;
+
+        // share of each bucket's requests, so quiet hours don't hide a bad one
+        let rate = |n: fn(&StatusBucket) -> usize| -> Vec<f64> {
+            http.trend.iter().map(|b| n(b) as f64 / b.requests.max(1) as f64).collect()
+        };
+        let per_bucket = format_duration(http.trend_bucket_secs);
+        for (label, rates) in [("4xx", rate(|b| b.client_errors)), ("5xx", rate(|b| b.server_errors))] {
+            let peak = rates.iter().copied().fold(0.0, f64::max);
+            let line = format!("  {} │{}│ peak {:.1}% per {}", label, sparkline(&rates), peak * 100.0, per_bucket);
+            if self.color && label == "5xx" && peak > 0.0 {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        if !http.slowest.is_empty() {
+            writeln!(out)?;
+            let width = http
+                .slowest
+                .iter()
+                .map(|p| p.method.len() + 1 + p.template.chars().count())
+                .max()
+                .unwrap_or(0)
+                .clamp(13, 48);
+            writeln!(
+                out,
+                "  {:<width$} {:>8} {:>9} {:>9} {:>9}",
+                "slowest paths", "requests", "p50 ms", "p95 ms", "max ms",
+                width = width,
+            )?;
+            for p in &http.slowest {
+                let name: String = format!("{} {}", p.method, p.template).chars().take(width).collect();
+                writeln!(
+                    out,
+                    "  {:<width$} {:>8} {:>9.1} {:>9.1} {:>9.1}",
+                    name, p.requests, p.p50_ms, p.p95_ms, p.max_ms,
+                    width = width,
+                )?;
+            }
+        }
+
+        for (title, top) in [("top user agents", &http.top_user_agents), ("top referrers", &http.top_referrers)] {
+            if top.is_empty() {
+                continue;
+            }
+            writeln!(out)?;
+            writeln!(out, "  {}", title)?;
+            for v in top {
+                let share = v.requests as f64 / http.requests as f64 * 100.0;
+                writeln!(out, "  {:>8} {:>5.1}%  {}", v.requests, share, truncate(&v.value, 90))?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_rate_limit(&self, out: &mut dyn Write, report: &RateLimitReport) -> io::Result<()> {
+        writeln!(out, "Rate Limit What-If ({})", report.limit)?;
+        writeln!(out, "{}", "─".repeat(50))?;
+        let share = report.rejected as f64 / report.requests as f64 * 100.0;
+        let mut line = format!(
+            "  {} of {} requests rejected ({:.1}%), {} of {} {}s limited",
+            report.rejected, report.requests, share, report.keys_limited, report.keys, report.by,
+        );
+        // per client, the limited keys are the clients
+        if report.by != "client" {
+            line.push_str(&format!(", {} clients affected", report.clients_affected));
+        }
+        if self.color && report.rejected > 0 {
+            writeln!(out, "{}", line.yellow())?;
+        } else {
+            writeln!(out, "{}", line)?;
+        }
+        if report.unkeyed > 0 {
+            writeln!(out, "  {} requests had nothing to count them against and were let through", report.unkeyed)?;
+        }
+
+        let tables = [("most rejected", &report.top_limited), ("clients hit most", &report.top_clients)];
+        for (title, keys) in tables {
+            if keys.is_empty() {
+                continue;
+            }
+            writeln!(out)?;
+            writeln!(out, "  {:<48} {:>8} {:>8}  first rejected", title, "requests", "rejected")?;
+            for k in keys {
+                writeln!(
+                    out,
+                    "  {:<48} {:>8} {:>8}  {}",
+                    truncate(&k.key, 48),
+                    k.requests,
+                    k.rejected,
+                    k.first_rejected
+                )?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_cost(&self, out: &mut dyn Write, cost: &CostReport) -> io::Result<()> {
+        let money = |v: f64| format!("{:.2}", v);
+        writeln!(out, "Ingestion Cost (at {} per GB)", cost.price_per_gb)?;
+        writeln!(out, "{}", "─".repeat(50))?;
+        let how = if cost.measured { "read" } else { "estimated" };
+        let span = format_duration(cost.span_secs);
+        let (Some(gb), Some(monthly)) = (cost.monthly_gb, cost.monthly_cost) else {
+            let mb = cost.bytes as f64 / 1e6;
+            writeln!(out, "  {:.1} MB {} over {}: too short a span to scale up to a month", mb, how, span)?;
+            writeln!(out)?;
+            return Ok(());
+        };
+        writeln!(
+            out,
+            "  {:.1} MB {} over {} → {:.1} GB a month → {} a month",
+            cost.bytes as f64 / 1e6,
+            how,
+            span,
+            gb,
+            money(monthly)
+        )?;
+
+        let tables = [("level", &cost.by_level), ("source", &cost.by_source)];
+        for (title, shares) in tables {
+            writeln!(out)?;
+            writeln!(out, "  {:<24} {:>10} {:>10} {:>6} {:>12}", title, "entries", "MB", "share", "per month")?;
+            for s in shares {
+                writeln!(
+                    out,
+                    "  {:<24} {:>10} {:>10.1} {:>5.1}% {:>12}",
+                    truncate(&s.name, 24),
+                    s.entries,
+                    s.bytes as f64 / 1e6,
+                    s.bytes as f64 / cost.bytes.max(1) as f64 * 100.0,
+                    money(s.monthly_cost)
+                )?;
+            }
+        }
+
+        writeln!(out)?;
+        let pct = |saved: f64| saved / monthly.max(f64::MIN_POSITIVE) * 100.0;
+        for (label, saved) in [
+            ("drop DEBUG", cost.drop_debug_savings),
+            ("drop repeated entries", cost.dedup_savings),
+            ("both", cost.combined_savings),
+        ] {
+            let line = format!("  {:<24} saves {:>12} a month ({:.1}%)", label, money(saved), pct(saved));
+            if self.color && saved > 0.0 {
+                writeln!(out, "{}", line.green())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_retention(&self, out: &mut dyn Write, results: &[RetentionResult]) -> io::Result<()> {
+        writeln!(out, "Retention (stored once each policy has run its course, at this log's rate)")?;
+        writeln!(out, "{}", "─".repeat(50))?;
+        let width = results.iter().map(|r| r.policy.chars().count()).max().unwrap_or(0).clamp(6, 48);
+        writeln!(out, "  {:<width$} {:>12} {:>10} {:>10}", "policy", "entries", "stored", "of all", width = width)?;
+        for r in results {
+            writeln!(
+                out,
+                "  {:<width$} {:>12.0} {:>10} {:>9.1}%",
+                truncate(&r.policy, width),
+                r.entries,
+                format_bytes(r.bytes),
+                r.bytes / r.full_bytes.max(1.0) * 100.0,
+                width = width,
+            )?;
+        }
+        writeln!(out, "  (all: every entry kept for the policy's longest time)")?;
+
+        for r in results {
+            writeln!(out)?;
+            writeln!(out, "  {}", r.policy)?;
+            for s in &r.by_source {
+                writeln!(
+                    out,
+                    "    {:<24} {:>10} of {:>10} ({:.1}%)",
+                    truncate(&s.source, 24),
+                    format_bytes(s.bytes),
+                    format_bytes(s.full_bytes),
+                    s.bytes / s.full_bytes.max(1.0) * 100.0
+                )?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_endpoints(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        let summary = &analysis.endpoints;
+        writeln!(
+            out,
+            "Endpoints ({} paths → {} templates)",
+            summary.distinct_paths, summary.distinct_templates
+        )?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let width = summary
+            .endpoints
+            .iter()
+            .map(|e| e.method.len() + 1 + e.template.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(48);
+        writeln!(
+            out,
+            "  {:<width$} {:>8} {:>7} {:>7} {:>7} {:>9}",
+            "endpoint",
+            "requests",
+            "4xx",
+            "5xx",
+            "paths",
+            "p95 ms",
+            width = width,
+        )?;
+
+        for e in &summary.endpoints {
+            let name: String = format!("{} {}", e.method, e.template).chars().take(width).collect();
+            let line = format!(
+                "  {:<width$} {:>8} {:>7} {:>7} {:>7} {:>9}",
+                name,
+                e.requests,
+                e.client_errors,
+                e.server_errors,
+                e.distinct_paths,
+                e.p95_ms.map_or("-".to_string(), |p| format!("{:.1}", p)),
+                width = width,
+            );
+            if self.color && e.server_errors > 0 {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_worst_endpoints(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        let worst = &analysis.endpoints.worst;
+        writeln!(out, "Worst Endpoints")?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let width = worst
+            .iter()
+            .map(|e| e.method.len() + 1 + e.template.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(48);
+        writeln!(
+            out,
+            "  {:<width$} {:>6} {:>7} {:>9} {:>8}",
+            "endpoint",
+            "score",
+            "5xx %",
+            "p95 ms",
+            "requests",
+            width = width,
+        )?;
+
+        for e in worst {
+            let name: String = format!("{} {}", e.method, e.template).chars().take(width).collect();
+            let line = format!(
+                "  {:<width$} {:>6.2} {:>6.1}% {:>9} {:>8}",
+                name,
+                e.score,
+                e.server_error_rate() * 100.0,
+                e.p95_ms.map_or("-".to_string(), |p| format!("{:.1}", p)),
+                e.requests,
+                width = width,
+            );
+            if self.color && e.server_errors > 0 {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_versions(&self, out: &mut dyn Write, cmp: &VersionComparison) -> io::Result<()> {
+        writeln!(out, "By Version ({})", cmp.versions.len())?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let width = cmp.versions.iter().map(|v| v.version.chars().count()).max().unwrap_or(0).clamp(7, 20);
+        let worst = cmp.versions.iter().map(|v| v.error_rate).fold(0.0, f64::max);
+        for v in &cmp.versions {
+            let name: String = v.version.chars().take(width).collect();
+            let line = format!(
+                "  {:<width$} {:>8} entries {:>7} errors {:>6.2}%  {} → {}",
+                name,
+                v.entries,
+                v.errors,
+                v.error_rate * 100.0,
+                v.first_seen,
+                v.last_seen,
+                width = width,
+            );
+            if self.color && v.errors > 0 && v.error_rate >= worst {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+        if cmp.unattributed > 0 {
+            writeln!(out, "  {} entries before their source logged a version", cmp.unattributed)?;
+        }
+
+        // share of each version's entries, so unequal traffic compares
+        writeln!(out)?;
+        let header: Vec<String> = cmp
+            .versions
+            .iter()
+            .map(|v| format!("{:>9}", v.version.chars().take(9).collect::<String>()))
+            .collect();
+        writeln!(out, "  {}  template", header.join(" "))?;
+        for t in &cmp.templates {
+            let shares: Vec<String> = t
+                .counts
+                .iter()
+                .zip(&cmp.versions)
+                .map(|(&n, v)| {
+                    if n == 0 {
+                        format!("{:>9}", "—")
+                    } else {
+                        format!("{:>8.1}%", n as f64 / v.entries.max(1) as f64 * 100.0)
+                    }
+                })
+                .collect();
+            let line = format!("  {}  {}", shares.join(" "), truncate(&t.template, 60));
+            if self.color && t.is_error {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_group(&self, out: &mut dyn Write, group: &GroupSummary) -> io::Result<()> {
+        writeln!(out, "By {}", group.field)?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        if group.groups.is_empty() {
+            writeln!(out, "  no entries carry '{}'", group.field)?;
+            writeln!(out)?;
+            return Ok(());
+        }
+
+        let width = group
+            .groups
+            .iter()
+            .map(|g| g.value.chars().count())
+            .max()
+            .unwrap_or(0)
+            .clamp(7, 32);
+
+        // values erroring at twice the field's overall rate stand out
+        let (entries, errors) = group
+            .groups
+            .iter()
+            .chain(std::iter::once(&group.other))
+            .fold((0, 0), |(n, e), g| (n + g.entries, e + g.errors));
+        let hot = 2.0 * errors as f64 / entries.max(1) as f64;
+
+        let rows = group.groups.iter().chain((group.other_values > 0).then_some(&group.other));
+        for g in rows {
+            let value: String = g.value.chars().take(width).collect();
+            let line = format!(
+                "  {:<width$} {:>8} entries {:>7} errors {:>6.1}%",
+                value,
+                g.entries,
+                g.errors,
+                g.error_rate * 100.0,
+                width = width,
+            );
+            if self.color && g.errors > 0 && g.error_rate >= hot {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        if group.other_values > 0 {
+            writeln!(out, "  … {} more values in (other)", group.other_values)?;
+        }
+        if group.missing > 0 {
+            writeln!(out, "  {} entries without '{}'", group.missing, group.field)?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_error_budgets(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        let over = analysis.error_budgets.iter().filter(|b| b.exceeded()).count();
+        let header = format!("Error Budget ({} of {} over)", over, analysis.error_budgets.len());
+        if self.color && over > 0 {
+            writeln!(out, "{}", header.yellow().bold())?;
+        } else {
+            writeln!(out, "{}", header)?;
+        }
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let width = analysis
+            .error_budgets
+            .iter()
+            .map(|b| b.source.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(24);
+
+        for b in &analysis.error_budgets {
+            let name: String = b.source.chars().take(width).collect();
+            let burn = if b.burn().is_finite() {
+                format!("{:5.0}%", b.burn() * 100.0)
+            } else {
+                "    ∞%".to_string()
+            };
+            let mut line = format!(
+                "  {:<width$} {:>7} / {:>9.1} allowed {}",
+                name,
+                b.errors,
+                b.allowed,
+                burn,
+                width = width,
+            );
+            if b.exceeded() {
+                line.push_str(&format!("  over by {:.0}", b.errors as f64 - b.allowed));
+            }
+            if let Some(ref hour) = b.worst_hour {
+                line.push_str(&format!("  (worst hour {}: {})", hour, b.worst_hour_errors));
+            }
+
+            if self.color && b.exceeded() {
+                writeln!(out, "{}", line.red())?;
+            } else {
+                writeln!(out, "{}", line)?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_metrics(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        writeln!(out, "Metrics")?;
+        writeln!(out, "{}", "─".repeat(30))?;
+        writeln!(
+            out,
+            "  {:<12} {:>7} {:>10} {:>10} {:>10} {:>10}  trend",
+            "name", "count", "min", "avg", "p95", "max"
+        )?;
+
+        for m in &analysis.metrics {
+            let avgs: Vec<f64> = m.windows.iter().map(|w| w.avg.unwrap_or(0.0)).collect();
+            // sizes in the decimal unit their largest value reaches
+            let (scale, unit) = match m.unit {
+                Some("B") => [(1e12, "TB"), (1e9, "GB"), (1e6, "MB"), (1e3, "KB")]
+                    .into_iter()
+                    .find(|&(scale, _)| m.max >= scale)
+                    .unwrap_or((1.0, "B")),
+                other => (1.0, other.unwrap_or("")),
+            };
+            let name = if unit.is_empty() { m.name.clone() } else { format!("{} ({})", m.name, unit) };
+            writeln!(
+                out,
+                "  {:<12} {:>7} {:>10.1} {:>10.1} {:>10.1} {:>10.1}  {}",
+                name,
+                m.count,
+                m.min / scale,
+                m.avg / scale,
+                m.p95 / scale,
+                m.max / scale,
+                sparkline(&avgs),
+            )?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_error_precursors(
+        &self,
+        out: &mut dyn Write,
+        analysis: &LogAnalysis,
+    ) -> io::Result<()> {
+        writeln!(out, "Error Precursors")?;
+        writeln!(out, "{}", "─".repeat(30))?;
+
+        for p in &analysis.error_precursors {
+            let error = format!("  ERROR '{}' (×{})", truncate(&p.error_template, 60), p.occurrences);
+            if self.color {
+                writeln!(out, "{}", error.red())?;
+            } else {
+                writeln!(out, "{}", error)?;
+            }
+            writeln!(
+                out,
+                "    preceded by '{}' {:.0}% of the time",
+                truncate(&p.predecessor, 60),
+                p.share * 100.0
+            )?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_threshold_events(
+        &self,
+        out: &mut dyn Write,
+        analysis: &LogAnalysis,
+    ) -> io::Result<()> {
+        let header = format!("Threshold Crossings ({})", analysis.threshold_events.len());
+        if self.color {
+            writeln!(out, "{}", header.yellow().bold())?;
+        } else {
+            writeln!(out, "{}", header)?;
+        }
+        writeln!(out, "{}", "─".repeat(30))?;
+
+        for ev in &analysis.threshold_events {
+            writeln!(
+                out,
+                "  {} → {}  {:<18} {} samples, peak {:.1}",
+                ev.start, ev.end, ev.threshold, ev.samples, ev.peak
+            )?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_baseline(&self, out: &mut dyn Write, baseline: &BaselineComparison) -> io::Result<()> {
+        writeln!(
+            out,
+            "Against Baseline ({} runs, {} entries)",
+            baseline.runs, baseline.entries
+        )?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        if baseline.rare_templates.is_empty() && baseline.volume_anomalies.is_empty() {
+            writeln!(out, "  Nothing unusual compared to earlier runs")?;
+        }
+
+        if !baseline.rare_templates.is_empty() {
+            writeln!(out, "  Rare templates ({} never seen before)", baseline.new_templates)?;
+            for t in &baseline.rare_templates {
+                let seen = if t.baseline_share > 0.0 {
+                    format!("{:.3}% of baseline", t.baseline_share * 100.0)
+                } else {
+                    "new".to_string()
+                };
+                let line = format!("    {:>6}×  {:<16} {}", t.count, seen, truncate(&t.template, 60));
+                if self.color && t.baseline_share == 0.0 {
+                    writeln!(out, "{}", line.yellow())?;
+                } else {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+        }
+
+        if !baseline.volume_anomalies.is_empty() {
+            writeln!(out, "  Unusual volume (entries per minute)")?;
+            for v in &baseline.volume_anomalies {
+                writeln!(
+                    out,
+                    "    {}  {:>6}  expected ~{:.0}  ({:+.1}σ)",
+                    v.minute, v.count, v.expected, v.z_score
+                )?;
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_entities(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        writeln!(out, "Top Entities")?;
+        writeln!(out, "{}", "─".repeat(30))?;
+
+        let tables = [
+            ("IPs", &analysis.entities.ips),
+            ("URLs", &analysis.entities.urls),
+            ("Paths", &analysis.entities.paths),
+            ("Emails", &analysis.entities.emails),
+        ];
+
+        for (title, items) in tables {
+            if items.is_empty() {
+                continue;
+            }
+
+            writeln!(out, "  {}", title)?;
+            for item in items {
+                let errors = if item.error_count > 0 {
+                    format!("  [{} in errors]", item.error_count)
+                } else {
+                    String::new()
+                };
+                let line = format!("    ×{:<6} {}{}", item.count, item.value, errors);
+
+                if self.color && item.error_count * 2 > item.count {
+                    writeln!(out, "{}", line.red())?;
+                } else {
+                    writeln!(out, "{}", line)?;
+                }
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    // `bursts` may be the latest few of `total`
+    fn print_bursts(&self, out: &mut dyn Write, stats: &Stats, bursts: &[ErrorBurst]) -> io::Result<()> {
+        let header = format!("Error Bursts Detected ({})", stats.error_bursts.len());
         if self.color {
-            println!("{}", header.red().bold());
+            writeln!(out, "{}", header.red().bold())?;
         } else {
-            println!("{}", header);
+            writeln!(out, "{}", header)?;
         }
-        println!("{}", "─".repeat(30));
+        writeln!(out, "{}", "─".repeat(30))?;
 
-        for burst in &analysis.stats.error_bursts {
-            println!("  {} — {} errors in 60s", burst.window_start, burst.count);
+        for burst in bursts {
+            writeln!(
+                out,
+                "  {} — {} errors in {}",
+                burst.window_start,
+                burst.count,
+                format_duration(stats.burst_params.window_secs)
+            )?;
         }
 
-        println!();
+        writeln!(out)?;
+        Ok(())
     }
 
-    fn print_heatmap(&self, analysis: &LogAnalysis) {
-        println!("{}", "Hourly Activity Heatmap");
-        println!("{}", "─".repeat(50));
+    // Entries and errors per bucket as sparklines; with more buckets than
+    // columns, neighbouring ones are summed into one column.
+    fn print_timeline(&self, out: &mut dyn Write, timeline: &Timeline) -> io::Result<()> {
+        const COLUMNS: usize = 60;
+
+        let buckets = &timeline.buckets;
+        writeln!(
+            out,
+            "Timeline ({} × {})",
+            buckets.len(),
+            format_duration(timeline.bucket_secs)
+        )?;
+        writeln!(out, "{}", "─".repeat(50))?;
+
+        let per_column = buckets.len().div_ceil(COLUMNS);
+        let errors = |b: &TimelineBucket| -> usize {
+            b.levels.get("ERROR").copied().unwrap_or(0) + b.levels.get("FATAL").copied().unwrap_or(0)
+        };
+        let (totals, error_counts): (Vec<f64>, Vec<f64>) = buckets
+            .chunks(per_column)
+            .map(|c| {
+                (c.iter().map(|b| b.total).sum::<usize>() as f64, c.iter().map(errors).sum::<usize>() as f64)
+            })
+            .unzip();
+
+        writeln!(out, "  entries │{}│", sparkline(&totals))?;
+        let line = format!("  errors  │{}│", sparkline(&error_counts));
+        if self.color {
+            writeln!(out, "{}", line.red())?;
+        } else {
+            writeln!(out, "{}", line)?;
+        }
+
+        let first = &buckets[0].start;
+        let last = &buckets[buckets.len() - 1].start;
+        writeln!(out, "           {} → {}", first, last)?;
+        // the earliest of equally bad buckets
+        if let Some(peak) = buckets.iter().rev().filter(|b| errors(b) > 0).max_by_key(|b| errors(b)) {
+            writeln!(out, "  Most errors: {} at {}", errors(peak), peak.start)?;
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn print_heatmap(&self, out: &mut dyn Write, analysis: &LogAnalysis) -> io::Result<()> {
+        writeln!(out, "Hourly Activity Heatmap")?;
+        writeln!(out, "{}", "─".repeat(50))?;
 
         let max = *analysis.stats.hourly_counts.iter().max().unwrap_or(&1).max(&1);
 
@@ -177,34 +1405,88 @@ impl ReportGenerator {
             let count = analysis.stats.hourly_counts[hour];
             let bar_len = count * 40 / max;
             let bar = "▪".repeat(bar_len);
-            println!("  {:02}h │{:<40}│ {}", hour, bar, count);
+            writeln!(out, "  {:02}h │{:<40}│ {}", hour, bar, count)?;
         }
 
-        println!();
+        writeln!(out)?;
+        Ok(())
     }
 
-    fn print_anomaly_score(&self, analysis: &LogAnalysis) {
-        let score = analysis.anomaly_score;
-        let label = match score as u32 {
-            0..=20 => "Healthy",
-            21..=50 => "Moderate",
-            51..=79 => "Elevated",
-            _ => "Critical",
-        };
+    fn print_anomaly_score(
+        &self,
+        out: &mut dyn Write,
+        score: f64,
+        parts: &[ScorePart],
+    ) -> io::Result<()> {
+        let label = anomaly_label(score);
 
         let line = format!("Anomaly Score: {:.1} / 100  [{}]", score, label);
 
-        println!("{}", "─".repeat(50));
+        writeln!(out, "{}", "─".repeat(50))?;
         if self.color {
             let colored = match score as u32 {
                 0..=20 => line.green().bold().to_string(),
                 21..=50 => line.yellow().bold().to_string(),
                 _ => line.red().bold().to_string(),
             };
-            println!("{}\n", colored);
+            writeln!(out, "{}", colored)?;
         } else {
-            println!("{}\n", line);
+            writeln!(out, "{}", line)?;
         }
+
+        if parts.len() > 1 || parts.iter().any(|p| !p.details.is_empty()) {
+            for part in parts {
+                writeln!(out, "  {:<24} ×{:<5} {:5.1}", part.scorer, part.weight, part.score)?;
+                for detail in &part.details {
+                    writeln!(out, "      {}", detail)?;
+                }
+            }
+        }
+
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        return s.to_string();
+    }
+    let mut t: String = s.chars().take(max_chars - 1).collect();
+    t.push('…');
+    t
+}
+
+fn sparkline(values: &[f64]) -> String {
+    const TICKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|&v| {
+            if v <= 0.0 || max <= 0.0 {
+                ' '
+            } else {
+                TICKS[((v / max * 7.0) as usize).min(7)]
+            }
+        })
+        .collect()
+}
+
+// in the largest decimal unit it reaches
+fn format_bytes(bytes: f64) -> String {
+    let (scale, unit) = [(1e12, "TB"), (1e9, "GB"), (1e6, "MB"), (1e3, "KB")]
+        .into_iter()
+        .find(|&(scale, _)| bytes >= scale)
+        .unwrap_or((1.0, "B"));
+    format!("{:.1} {}", bytes / scale, unit)
+}
+
+pub fn anomaly_label(score: f64) -> &'static str {
+    match score as u32 {
+        0..=20 => "Healthy",
+        21..=50 => "Moderate",
+        51..=79 => "Elevated",
+        _ => "Critical",
     }
 }
 
@@ -0,0 +1,170 @@
+use chrono::Duration;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::cost::line_overhead;
+use crate::generate::{parse_fraction, parse_span};
+use crate::parser::{LogEntry, LogLevel};
+
+// shorter spans are too little to project from
+const MIN_SPAN_SECS: i64 = 60;
+const SOURCE_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    keep: Duration,
+    // share of entries sampled in, 1.0 for all
+    sample: f64,
+}
+
+// `--retention 'error=90d,info=7d,debug=7d@1%'`: how long entries of each
+// level are kept, and what share of them, with `*` for levels not named.
+// Levels without a rule (and no `*`) are kept whole for the policy's longest
+// time.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    spec: String,
+    rules: Vec<(Option<LogLevel>, Rule)>,
+}
+
+impl RetentionPolicy {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut rules = Vec::new();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, rule) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected LEVEL=SPAN[@SAMPLE] like info=7d or debug=1d@1%, got '{}'", part))?;
+            let level = match name.trim() {
+                "*" => None,
+                name => Some(parse_level(name)?),
+            };
+            let (keep, sample) = match rule.split_once('@') {
+                Some((keep, sample)) => (keep, parse_fraction(sample)?),
+                None => (rule, 1.0),
+            };
+            let keep = parse_span(keep)?;
+            if keep <= Duration::zero() {
+                return Err(format!("Retention for '{}' must be longer than 0", part));
+            }
+            if rules.iter().any(|(l, _)| *l == level) {
+                return Err(format!("More than one rule for '{}' in '{}'", name.trim(), spec));
+            }
+            rules.push((level, Rule { keep, sample }));
+        }
+        if rules.is_empty() {
+            return Err("A retention policy needs at least one LEVEL=SPAN rule".to_string());
+        }
+        Ok(Self { spec: spec.trim().to_string(), rules })
+    }
+
+    fn rule(&self, level: &LogLevel) -> Rule {
+        let find = |want: Option<&LogLevel>| self.rules.iter().find(|(l, _)| l.as_ref() == want).map(|&(_, r)| r);
+        find(Some(level)).or_else(|| find(None)).unwrap_or(Rule { keep: self.longest(), sample: 1.0 })
+    }
+
+    fn longest(&self) -> Duration {
+        self.rules.iter().map(|(_, r)| r.keep).max().unwrap_or_else(Duration::zero)
+    }
+}
+
+// `errors` and `warnings` read as their level; an unknown name is an error
+// here, unlike in log lines
+fn parse_level(name: &str) -> Result<LogLevel, String> {
+    let lower = name.trim().to_lowercase();
+    if lower == "unknown" {
+        return Ok(LogLevel::Unknown);
+    }
+    let singular = lower.strip_suffix('s').unwrap_or(&lower);
+    match [lower.as_str(), singular].map(LogLevel::from_str) {
+        [LogLevel::Unknown, LogLevel::Unknown] => {
+            Err(format!("Unknown level '{}' (expected debug, info, warn, error, fatal, unknown or *)", name))
+        }
+        [LogLevel::Unknown, level] | [level, _] => Ok(level),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionShare {
+    pub source: String,
+    pub entries: f64,
+    pub bytes: f64,
+    // what keeping all of the source's entries for the longest time stores
+    pub full_bytes: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionResult {
+    pub policy: String,
+    pub longest_days: f64,
+    // stored once the policy has run for its longest retention, at the
+    // rate the entries were written
+    pub entries: f64,
+    pub bytes: f64,
+    // keeping everything for the longest retention, for comparison
+    pub full_entries: f64,
+    pub full_bytes: f64,
+    // most stored first
+    pub by_source: Vec<RetentionShare>,
+}
+
+// What each `--retention` policy would hold once it is in steady state:
+// per level, the entries written a day times the days kept times the share
+// sampled in, projected from the analyzed span. Entry sizes are estimated
+// as for `--cost-per-gb`. Empty when the entries span too little time.
+pub fn simulate(entries: &[LogEntry], policies: &[RetentionPolicy], input_bytes: Option<u64>) -> Vec<RetentionResult> {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Vec::new();
+    };
+    let span_secs = (last.timestamp - first.timestamp).num_seconds();
+    if policies.is_empty() || span_secs < MIN_SPAN_SECS {
+        return Vec::new();
+    }
+    let span_days = span_secs as f64 / 86400.0;
+    let overhead = line_overhead(entries, input_bytes);
+
+    // entries and bytes written per (source, level)
+    let mut written: HashMap<(&str, &LogLevel), (f64, f64)> = HashMap::new();
+    for entry in entries {
+        let w = written.entry((entry.source.as_deref().unwrap_or("-"), &entry.level)).or_default();
+        w.0 += 1.0;
+        w.1 += entry.message.len() as f64 + 1.0 + overhead;
+    }
+
+    policies
+        .iter()
+        .map(|policy| {
+            let longest = policy.longest().num_seconds() as f64 / 86400.0;
+            let mut sources: HashMap<&str, RetentionShare> = HashMap::new();
+            for (&(source, level), &(n, bytes)) in &written {
+                let rule = policy.rule(level);
+                let kept = rule.keep.num_seconds() as f64 / 86400.0 / span_days * rule.sample;
+                let full = longest / span_days;
+                let share = sources.entry(source).or_insert_with(|| RetentionShare {
+                    source: source.to_string(),
+                    entries: 0.0,
+                    bytes: 0.0,
+                    full_bytes: 0.0,
+                });
+                share.entries += n * kept;
+                share.bytes += bytes * kept;
+                share.full_bytes += bytes * full;
+            }
+            let full_entries = entries.len() as f64 * longest / span_days;
+            let mut by_source: Vec<RetentionShare> = sources.into_values().collect();
+            let (stored, bytes, full_bytes) = by_source
+                .iter()
+                .fold((0.0, 0.0, 0.0), |acc, s| (acc.0 + s.entries, acc.1 + s.bytes, acc.2 + s.full_bytes));
+            by_source.sort_unstable_by(|a, b| b.bytes.total_cmp(&a.bytes).then_with(|| a.source.cmp(&b.source)));
+            by_source.truncate(SOURCE_LIMIT);
+            RetentionResult {
+                policy: policy.spec.clone(),
+                longest_days: longest,
+                entries: stored,
+                bytes,
+                full_entries,
+                full_bytes,
+                by_source,
+            }
+        })
+        .collect()
+}
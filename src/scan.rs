@@ -0,0 +1,51 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use memchr::{memchr, memchr2};
+
+// Fast path for `[YYYY-MM-DD HH:MM:SS] LEVEL message`, the format most large
+// files use. Field boundaries are located with memchr instead of running the
+// bracket regex. Any deviation from the fixed layout returns None and the
+// caller falls back to the regex, so this never changes what gets parsed.
+pub fn bracket(line: &str) -> Option<(NaiveDateTime, &str, &str)> {
+    let bytes = line.as_bytes();
+    if bytes.len() < 24 || bytes[0] != b'[' || memchr(b']', &bytes[..21]) != Some(20) {
+        return None;
+    }
+
+    let timestamp = timestamp(&bytes[1..20])?;
+
+    let rest = &line[21..];
+    let level_start = rest.len() - rest.trim_start().len();
+    if level_start == 0 {
+        return None;
+    }
+
+    let rest = &rest[level_start..];
+    let level_end = memchr2(b' ', b'\t', rest.as_bytes())?;
+    let level = &rest[..level_end];
+    if level.is_empty() || !level.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        return None;
+    }
+
+    let message = rest[level_end..].trim_start();
+    if message.is_empty() {
+        return None;
+    }
+
+    Some((timestamp, level, message))
+}
+
+// `YYYY-MM-DD HH:MM:SS` or `YYYY-MM-DDTHH:MM:SS`, validated digit by digit
+fn timestamp(b: &[u8]) -> Option<NaiveDateTime> {
+    if b[4] != b'-' || b[7] != b'-' || !(b[10] == b' ' || b[10] == b'T') || b[13] != b':' || b[16] != b':' {
+        return None;
+    }
+
+    let num = |range: std::ops::Range<usize>| -> Option<u32> {
+        b[range].iter().try_fold(0u32, |acc, &d| {
+            d.is_ascii_digit().then(|| acc * 10 + (d - b'0') as u32)
+        })
+    };
+
+    NaiveDate::from_ymd_opt(num(0..4)? as i32, num(5..7)?, num(8..10)?)?
+        .and_hms_opt(num(11..13)?, num(14..16)?, num(17..19)?)
+}
@@ -0,0 +1,296 @@
+use chrono::NaiveDateTime;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::parser::{loose_timestamp, LogEntry};
+use crate::units::duration_ms;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    Number,
+    // stored as milliseconds; `unit_ms` is what a bare number is in
+    Duration { unit_ms: f64 },
+    Timestamp,
+    // ordered as declared, so `severity>=high` means high or worse
+    Enum(Vec<String>),
+    String,
+}
+
+impl FieldType {
+    fn parse(name: &str, unit: Option<&str>) -> Result<Self, String> {
+        match name {
+            "number" | "integer" | "float" => Ok(Self::Number),
+            "duration" => {
+                let unit_ms = match unit {
+                    Some(u) => {
+                        duration_ms(&format!("1{}", u), 1.0).ok_or_else(|| format!("Unknown duration unit '{}'", u))?
+                    }
+                    None => 1.0,
+                };
+                Ok(Self::Duration { unit_ms })
+            }
+            "timestamp" | "date-time" | "datetime" => Ok(Self::Timestamp),
+            "string" => Ok(Self::String),
+            other => Err(format!(
+                "Unknown field type '{}' (expected number, duration, timestamp, string or a list of values)",
+                other
+            )),
+        }
+    }
+
+    // the typed form of a value, or None when it doesn't fit
+    fn coerce(&self, value: &Value) -> Option<Value> {
+        match self {
+            Self::Number => number(value).and_then(json_number),
+            Self::Duration { unit_ms } => match value {
+                Value::Number(n) => json_number(n.as_f64()? * unit_ms),
+                Value::String(s) => json_number(duration_ms(s, *unit_ms)?),
+                _ => None,
+            },
+            Self::Timestamp => {
+                let ts = loose_timestamp(&text(value)?)?;
+                Some(Value::String(ts.format("%Y-%m-%dT%H:%M:%S%.f").to_string()))
+            }
+            Self::Enum(values) => {
+                let v = text(value)?;
+                values.iter().find(|d| d.eq_ignore_ascii_case(&v)).map(|d| Value::String(d.clone()))
+            }
+            Self::String => Some(Value::String(text(value)?)),
+        }
+    }
+}
+
+// `--schema fields.toml`: the types of entry fields, so `--where` and
+// `--metric field:NAME` compare and measure them as what they are rather
+// than as text. Either TOML:
+//
+//   [fields]
+//   status = "number"
+//   latency = "duration"                  # "250ms", "1.5s"; bare numbers are ms
+//   elapsed = { type = "duration", unit = "s" }
+//   seen_at = "timestamp"
+//   severity = ["low", "medium", "high", "critical"]
+//
+// or a JSON Schema (`.json`), whose top-level `properties` are read:
+// `"type": "number"`/`"integer"`, `"enum": [...]`, and `"format":
+// "date-time"` or `"duration"` on strings.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    fields: HashMap<String, FieldType>,
+}
+
+impl Schema {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        if path.ends_with(".json") {
+            let doc: Value = serde_json::from_str(&text).map_err(|e| format!("invalid schema: {}", e))?;
+            Self::from_json_schema(&doc)
+        } else {
+            let doc: toml::Value = toml::from_str(&text).map_err(|e| format!("invalid schema: {}", e))?;
+            Self::from_toml(&doc)
+        }
+    }
+
+    fn from_toml(doc: &toml::Value) -> Result<Self, String> {
+        let fields = doc
+            .get("fields")
+            .and_then(|f| f.as_table())
+            .ok_or("schema needs a [fields] table")?;
+        let fields = fields
+            .iter()
+            .map(|(name, spec)| {
+                let kind = match spec {
+                    toml::Value::String(kind) => FieldType::parse(kind, None)?,
+                    toml::Value::Array(values) => FieldType::Enum(values.iter().map(toml_text).collect()),
+                    toml::Value::Table(t) => match t.get("enum").and_then(|e| e.as_array()) {
+                        Some(values) => FieldType::Enum(values.iter().map(toml_text).collect()),
+                        None => FieldType::parse(
+                            t.get("type").and_then(|t| t.as_str()).ok_or_else(|| format!("field '{}' needs a type", name))?,
+                            t.get("unit").and_then(|u| u.as_str()),
+                        )?,
+                    },
+                    _ => return Err(format!("field '{}': expected a type name, a list of values or a table", name)),
+                };
+                Ok((name.clone(), kind))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { fields })
+    }
+
+    fn from_json_schema(doc: &Value) -> Result<Self, String> {
+        let properties = doc
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .ok_or("JSON Schema needs top-level properties")?;
+        let mut fields = HashMap::new();
+        for (name, spec) in properties {
+            let kind = if let Some(values) = spec.get("enum").and_then(|e| e.as_array()) {
+                FieldType::Enum(values.iter().filter_map(text).collect())
+            } else {
+                let format = spec.get("format").and_then(|f| f.as_str());
+                match (spec.get("type").and_then(|t| t.as_str()), format) {
+                    (Some("number" | "integer"), _) => FieldType::Number,
+                    (_, Some("date-time")) => FieldType::Timestamp,
+                    (_, Some("duration")) => FieldType::Duration { unit_ms: 1.0 },
+                    (Some("string"), _) => FieldType::String,
+                    // objects, arrays and booleans are left alone
+                    _ => continue,
+                }
+            };
+            fields.insert(name.clone(), kind);
+        }
+        Ok(Self { fields })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldType> {
+        self.fields.get(name)
+    }
+
+    // Rewrites declared fields in their typed form; returns how many values
+    // didn't fit their type and were left as logged.
+    pub fn apply(&self, entry: &mut LogEntry) -> usize {
+        let mut misfits = 0;
+        for (name, value) in entry.fields.iter_mut() {
+            let Some(kind) = self.fields.get(name) else {
+                continue;
+            };
+            match kind.coerce(value) {
+                Some(typed) => *value = typed,
+                None if !value.is_null() => misfits += 1,
+                None => {}
+            }
+        }
+        misfits
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+// `--where 'latency>=1.5s'`: a comparison on an entry field. Declared fields
+// compare by their schema type; otherwise JSON numbers compare as numbers and
+// anything else as text, case-insensitively. Entries without the field fail.
+#[derive(Debug, Clone)]
+pub struct FieldCondition {
+    pub field: String,
+    op: Op,
+    value: String,
+    kind: Option<FieldType>,
+}
+
+impl FieldCondition {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let ops = [("!=", Op::Ne), (">=", Op::Ge), ("<=", Op::Le), ("=", Op::Eq), (">", Op::Gt), ("<", Op::Lt)];
+        let (at, op, len) = ops
+            .iter()
+            .filter_map(|&(token, op)| spec.find(token).map(|at| (at, op, token.len())))
+            .min_by_key(|&(at, _, len)| (at, std::cmp::Reverse(len)))
+            .ok_or_else(|| format!("Expected FIELD=VALUE (also !=, >, >=, <, <=), got '{}'", spec))?;
+        let field = spec[..at].trim();
+        if field.is_empty() {
+            return Err(format!("Missing field name in '{}'", spec));
+        }
+        Ok(Self { field: field.to_string(), op, value: spec[at + len..].trim().to_string(), kind: None })
+    }
+
+    // Takes the field's type from `schema`, checking the value against it.
+    pub fn typed(mut self, schema: &Schema) -> Result<Self, String> {
+        let Some(kind) = schema.field(&self.field) else {
+            return Ok(self);
+        };
+        let value = Value::String(self.value.clone());
+        let typed = kind
+            .coerce(&value)
+            .ok_or_else(|| format!("'{}' is not a valid value for {}", self.value, self.field))?;
+        if let Value::String(s) = typed {
+            self.value = s;
+        } else {
+            self.value = typed.to_string();
+        }
+        self.kind = Some(kind.clone());
+        Ok(self)
+    }
+
+    pub fn is_match(&self, entry: &LogEntry) -> bool {
+        let Some(value) = entry.fields.get(&self.field).filter(|v| !v.is_null()) else {
+            return false;
+        };
+        let ordering = match self.kind {
+            Some(FieldType::Number) | Some(FieldType::Duration { .. }) => {
+                compare_numbers(number(value), self.value.parse().ok())
+            }
+            Some(FieldType::Timestamp) => text(value)
+                .and_then(|v| loose_timestamp(&v))
+                .zip(loose_timestamp(&self.value))
+                .map(|(a, b): (NaiveDateTime, NaiveDateTime)| a.cmp(&b)),
+            Some(FieldType::Enum(ref values)) => {
+                let rank = |v: &str| values.iter().position(|d| d.eq_ignore_ascii_case(v));
+                text(value).and_then(|v| rank(&v)).zip(rank(&self.value)).map(|(a, b)| a.cmp(&b))
+            }
+            Some(FieldType::String) | None => match value {
+                Value::Number(n) => compare_numbers(n.as_f64(), self.value.parse().ok()),
+                _ => text(value).map(|v| v.to_lowercase().cmp(&self.value.to_lowercase())),
+            },
+        };
+        let Some(ordering) = ordering else {
+            return false;
+        };
+        match self.op {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Le => ordering != Ordering::Greater,
+        }
+    }
+}
+
+fn compare_numbers(a: Option<f64>, b: Option<f64>) -> Option<Ordering> {
+    a?.partial_cmp(&b?)
+}
+
+// a number, or a string holding one
+pub fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn json_number(v: f64) -> Option<Value> {
+    if v.fract() == 0.0 && v.abs() < i64::MAX as f64 {
+        Some(Value::from(v as i64))
+    } else {
+        serde_json::Number::from_f64(v).map(Value::Number)
+    }
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn toml_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,258 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::buckets::Zone;
+use crate::metrics::Threshold;
+use crate::parser::LogEntry;
+use crate::stats::Stats;
+
+// What a scorer gets to look at: the analyzed entries plus the statistics
+// the analyzer already computed for them.
+pub struct ScoreInput<'a> {
+    pub entries: &'a [LogEntry],
+    pub stats: &'a Stats,
+    pub level_counts: &'a HashMap<String, usize>,
+    // for scorers that bucket entries by time
+    #[cfg_attr(not(feature = "ml"), allow(dead_code))]
+    pub zone: Zone,
+}
+
+// An anomaly scoring strategy. Implementations return 0 (nothing unusual)
+// to 100 (clearly broken); `Composite` blends several of them into the
+// score shown in the report.
+pub trait Scorer: Send + Sync {
+    fn name(&self) -> String;
+    fn score(&self, input: &ScoreInput) -> f64;
+
+    // the score plus short notes on what drove it, shown under the score
+    fn explain(&self, input: &ScoreInput) -> (f64, Vec<String>) {
+        (self.score(input), Vec::new())
+    }
+}
+
+// The original formula: error rate, bursts, fatals and time between failures.
+pub struct Heuristic;
+
+impl Scorer for Heuristic {
+    fn name(&self) -> String {
+        "heuristic".to_string()
+    }
+
+    fn score(&self, input: &ScoreInput) -> f64 {
+        let stats = input.stats;
+        let mut score = 0.0_f64;
+
+        // error rate weight
+        score += stats.error_rate * 0.4;
+
+        // burst penalty
+        score += stats.error_bursts.len() as f64 * 5.0;
+
+        // fatal presence
+        if *input.level_counts.get("FATAL").unwrap_or(&0) > 0 {
+            score += 20.0;
+        }
+
+        // MTBF: shorter = worse
+        if let Some(mtbf) = stats.mtbf_seconds {
+            if mtbf < 60.0 {
+                score += 15.0;
+            } else if mtbf < 300.0 {
+                score += 8.0;
+            }
+        }
+
+        score.min(100.0)
+    }
+}
+
+// The share of error and fatal entries, in percent.
+pub struct ErrorRate;
+
+impl Scorer for ErrorRate {
+    fn name(&self) -> String {
+        "error-rate".to_string()
+    }
+
+    fn score(&self, input: &ScoreInput) -> f64 {
+        input.stats.error_rate.min(100.0)
+    }
+}
+
+// 100 while a statistic is past a limit, 0 otherwise, e.g. `error_rate>5`.
+pub struct Rule {
+    threshold: Threshold,
+}
+
+impl Rule {
+    const STATS: &'static [&'static str] =
+        &["error_rate", "bursts", "fatal", "rate_per_minute", "mtbf_seconds", "entries"];
+
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let threshold = Threshold::parse(spec)?;
+        if !Self::STATS.contains(&threshold.metric.as_str()) {
+            return Err(format!(
+                "Unknown statistic '{}' (expected one of {})",
+                threshold.metric,
+                Self::STATS.join(", ")
+            ));
+        }
+        Ok(Self { threshold })
+    }
+
+    fn value(&self, input: &ScoreInput) -> Option<f64> {
+        let stats = input.stats;
+        match self.threshold.metric.as_str() {
+            "error_rate" => Some(stats.error_rate),
+            "bursts" => Some(stats.error_bursts.len() as f64),
+            "fatal" => Some(*input.level_counts.get("FATAL").unwrap_or(&0) as f64),
+            "rate_per_minute" => Some(stats.rate_per_minute),
+            "mtbf_seconds" => stats.mtbf_seconds,
+            "entries" => Some(input.entries.len() as f64),
+            _ => None,
+        }
+    }
+}
+
+impl Scorer for Rule {
+    fn name(&self) -> String {
+        self.threshold.label()
+    }
+
+    fn score(&self, input: &ScoreInput) -> f64 {
+        match self.value(input) {
+            Some(v) if self.threshold.is_crossed(v) => 100.0,
+            _ => 0.0,
+        }
+    }
+}
+
+// `--scorer 'heuristic*0.7'`: a built-in scorer (heuristic, error-rate, and
+// iforest with the `ml` feature) or a rule on a statistic, with an optional
+// weight (default 1).
+#[derive(Clone)]
+pub struct ScorerSpec {
+    pub scorer: Arc<dyn Scorer>,
+    pub weight: f64,
+}
+
+impl ScorerSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, weight) = match spec.rsplit_once('*') {
+            Some((name, weight)) => {
+                let weight: f64 = weight
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|w: &f64| *w >= 0.0)
+                    .ok_or_else(|| format!("Invalid scorer weight: '{}'", weight.trim()))?;
+                (name.trim(), weight)
+            }
+            None => (spec.trim(), 1.0),
+        };
+
+        let scorer: Arc<dyn Scorer> = match name {
+            "heuristic" => Arc::new(Heuristic),
+            "error-rate" => Arc::new(ErrorRate),
+            #[cfg(feature = "ml")]
+            "iforest" => Arc::new(crate::ml::IsolationForest),
+            #[cfg(not(feature = "ml"))]
+            "iforest" => return Err("the iforest scorer needs logscope built with --features ml".into()),
+            _ if name.contains(['>', '<']) => Arc::new(Rule::parse(name)?),
+            _ => {
+                return Err(format!(
+                    "Unknown scorer '{}' (expected heuristic, error-rate or a rule like 'error_rate>5')",
+                    name
+                ))
+            }
+        };
+        Ok(Self { scorer, weight })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Combine {
+    // weighted average of all scorers
+    #[default]
+    Mean,
+    // the highest weighted score, so any one rule can raise the alarm
+    Max,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScorePart {
+    pub scorer: String,
+    pub weight: f64,
+    pub score: f64,
+    pub details: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct Composite {
+    parts: Vec<ScorerSpec>,
+    combine: Combine,
+}
+
+impl Default for Composite {
+    fn default() -> Self {
+        Self {
+            parts: vec![ScorerSpec { scorer: Arc::new(Heuristic), weight: 1.0 }],
+            combine: Combine::Mean,
+        }
+    }
+}
+
+impl Composite {
+    // falls back to the heuristic alone when no scorer is given
+    pub fn new(parts: Vec<ScorerSpec>, combine: Combine) -> Self {
+        if parts.is_empty() {
+            return Self { combine, ..Self::default() };
+        }
+        Self { parts, combine }
+    }
+
+    pub fn breakdown(&self, input: &ScoreInput) -> Vec<ScorePart> {
+        self.parts
+            .iter()
+            .map(|p| {
+                let (score, details) = p.scorer.explain(input);
+                ScorePart {
+                    scorer: p.scorer.name(),
+                    weight: p.weight,
+                    score: score.clamp(0.0, 100.0),
+                    details,
+                }
+            })
+            .collect()
+    }
+
+    pub fn combine(&self, parts: &[ScorePart]) -> f64 {
+        match self.combine {
+            Combine::Mean => {
+                let total: f64 = parts.iter().map(|p| p.weight).sum();
+                if total <= 0.0 {
+                    return 0.0;
+                }
+                parts.iter().map(|p| p.score * p.weight).sum::<f64>() / total
+            }
+            Combine::Max => parts
+                .iter()
+                .map(|p| (p.score * p.weight).min(100.0))
+                .fold(0.0, f64::max),
+        }
+    }
+}
+
+impl Scorer for Composite {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.parts.iter().map(|p| p.scorer.name()).collect();
+        format!("{:?}({})", self.combine, names.join(", ")).to_lowercase()
+    }
+
+    fn score(&self, input: &ScoreInput) -> f64 {
+        self.combine(&self.breakdown(input))
+    }
+}
@@ -0,0 +1,241 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::buckets::{Bucketing, Zone};
+use crate::parser::{LogEntry, LogLevel};
+
+pub const COVERAGE_COLUMNS: usize = 40;
+
+#[derive(Debug, Serialize)]
+pub struct SourceCoverage {
+    pub source: String,
+    pub entries: usize,
+    pub first_seen: String,
+    pub last_seen: String,
+    // entries per column of the file's overall time span
+    pub activity: Vec<usize>,
+}
+
+pub fn coverage(entries: &[LogEntry]) -> Vec<SourceCoverage> {
+    if entries.is_empty() {
+        return Vec::new();
+    }
+
+    let first = entries[0].timestamp;
+    let span = (entries[entries.len() - 1].timestamp - first).num_seconds().max(0) + 1;
+
+    struct Acc {
+        entries: usize,
+        first: NaiveDateTime,
+        last: NaiveDateTime,
+        activity: Vec<usize>,
+    }
+
+    let mut by_source: HashMap<Arc<str>, Acc> = HashMap::new();
+    for entry in entries {
+        let Some(ref source) = entry.source else {
+            continue;
+        };
+
+        let acc = by_source.entry(Arc::clone(source)).or_insert_with(|| Acc {
+            entries: 0,
+            first: entry.timestamp,
+            last: entry.timestamp,
+            activity: vec![0; COVERAGE_COLUMNS],
+        });
+
+        let offset = (entry.timestamp - first).num_seconds().max(0);
+        let col = ((offset * COVERAGE_COLUMNS as i64 / span) as usize).min(COVERAGE_COLUMNS - 1);
+        acc.entries += 1;
+        acc.first = acc.first.min(entry.timestamp);
+        acc.last = acc.last.max(entry.timestamp);
+        acc.activity[col] += 1;
+    }
+
+    let mut result: Vec<SourceCoverage> = by_source
+        .into_iter()
+        .map(|(source, acc)| SourceCoverage {
+            source: source.to_string(),
+            entries: acc.entries,
+            first_seen: acc.first.format("%Y-%m-%d %H:%M:%S").to_string(),
+            last_seen: acc.last.format("%Y-%m-%d %H:%M:%S").to_string(),
+            activity: acc.activity,
+        })
+        .collect();
+
+    // chronological by first appearance reads like a timeline
+    result.sort_unstable_by(|a, b| a.first_seen.cmp(&b.first_seen).then(a.source.cmp(&b.source)));
+    result
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceStats {
+    pub source: String,
+    pub entries: usize,
+    pub errors: usize,
+    // percent of the source's entries, like `Stats::error_rate`
+    pub error_rate: f64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+// Every source that logged at least one entry, most errors first.
+pub fn source_stats(entries: &[LogEntry]) -> Vec<SourceStats> {
+    // (entries, errors, first, last)
+    let mut by_source: HashMap<Arc<str>, (usize, usize, NaiveDateTime, NaiveDateTime)> = HashMap::new();
+    for entry in entries {
+        let Some(ref source) = entry.source else {
+            continue;
+        };
+        let acc = by_source
+            .entry(Arc::clone(source))
+            .or_insert((0, 0, entry.timestamp, entry.timestamp));
+        acc.0 += 1;
+        if matches!(entry.level, LogLevel::Error | LogLevel::Fatal) {
+            acc.1 += 1;
+        }
+        acc.2 = acc.2.min(entry.timestamp);
+        acc.3 = acc.3.max(entry.timestamp);
+    }
+
+    let mut result: Vec<SourceStats> = by_source
+        .into_iter()
+        .map(|(source, (total, errors, first, last))| SourceStats {
+            source: source.to_string(),
+            entries: total,
+            errors,
+            error_rate: errors as f64 / total as f64 * 100.0,
+            first_seen: first.format("%Y-%m-%d %H:%M:%S").to_string(),
+            last_seen: last.format("%Y-%m-%d %H:%M:%S").to_string(),
+        })
+        .collect();
+    result.sort_unstable_by(|a, b| {
+        b.errors
+            .cmp(&a.errors)
+            .then_with(|| b.error_rate.total_cmp(&a.error_rate))
+            .then_with(|| a.source.cmp(&b.source))
+    });
+    result
+}
+
+// `--error-budget 'api=10'`: errors per hour a source may log; `*` sets the
+// budget for every source without one of its own.
+#[derive(Debug, Clone)]
+pub struct ErrorBudget {
+    pub source: String,
+    pub per_hour: f64,
+}
+
+impl ErrorBudget {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (source, rate) = spec
+            .rsplit_once('=')
+            .ok_or_else(|| format!("Expected SOURCE=ERRORS_PER_HOUR, got '{}'", spec))?;
+        let source = source.trim();
+        if source.is_empty() {
+            return Err(format!("Missing source in error budget '{}'", spec));
+        }
+        let per_hour: f64 = rate
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid error budget: '{}'", rate.trim()))?;
+        if per_hour < 0.0 {
+            return Err(format!("Error budget must not be negative: '{}'", spec));
+        }
+        Ok(Self { source: source.to_string(), per_hour })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BudgetResult {
+    pub source: String,
+    pub errors: usize,
+    // budget over the whole analyzed span
+    pub allowed: f64,
+    pub worst_hour: Option<String>,
+    pub worst_hour_errors: usize,
+}
+
+impl BudgetResult {
+    pub fn exceeded(&self) -> bool {
+        self.errors as f64 > self.allowed
+    }
+
+    // fraction of the budget used; 1.0 is exactly on budget
+    pub fn burn(&self) -> f64 {
+        if self.allowed > 0.0 {
+            self.errors as f64 / self.allowed
+        } else if self.errors > 0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
+// Budgets are evaluated over the span of the whole file (at least one hour)
+// so a source that logs nothing for a while earns headroom like an SLO would.
+// The worst hour is a wall-clock hour in `zone`.
+pub fn error_budgets(entries: &[LogEntry], budgets: &[ErrorBudget], zone: Zone) -> Vec<BudgetResult> {
+    if budgets.is_empty() || entries.is_empty() {
+        return Vec::new();
+    }
+
+    let span = entries[entries.len() - 1].timestamp - entries[0].timestamp;
+    let hours = (span.num_seconds() as f64 / 3600.0).max(1.0);
+
+    let mut errors: HashMap<&str, usize> = HashMap::new();
+    let mut hourly: HashMap<(&str, NaiveDateTime), usize> = HashMap::new();
+    let mut seen: Vec<&str> = Vec::new();
+    let hours_of = Bucketing::new(Duration::hours(1), zone);
+    for entry in entries {
+        let Some(ref source) = entry.source else {
+            continue;
+        };
+        if !errors.contains_key(source.as_ref()) {
+            seen.push(source);
+        }
+        let count = errors.entry(source).or_insert(0);
+        if entry.level.severity() >= 3 {
+            *count += 1;
+            *hourly.entry((source, hours_of.start(entry.timestamp))).or_insert(0) += 1;
+        }
+    }
+
+    let budget_for = |source: &str| {
+        budgets
+            .iter()
+            .find(|b| b.source.eq_ignore_ascii_case(source))
+            .or_else(|| budgets.iter().find(|b| b.source == "*"))
+    };
+
+    // named budgets are reported even for sources that never showed up
+    for b in budgets {
+        if b.source != "*" && !seen.iter().any(|s| s.eq_ignore_ascii_case(&b.source)) {
+            seen.push(&b.source);
+        }
+    }
+
+    let mut results: Vec<BudgetResult> = seen
+        .into_iter()
+        .filter_map(|source| {
+            let budget = budget_for(source)?;
+            let worst = hourly
+                .iter()
+                .filter(|((s, _), _)| *s == source)
+                .max_by(|a, b| a.1.cmp(b.1).then(b.0 .1.cmp(&a.0 .1)));
+            Some(BudgetResult {
+                source: source.to_string(),
+                errors: errors.get(source).copied().unwrap_or(0),
+                allowed: budget.per_hour * hours,
+                worst_hour: worst.map(|((_, h), _)| h.format("%Y-%m-%d %H:00").to_string()),
+                worst_hour_errors: worst.map_or(0, |(_, &n)| n),
+            })
+        })
+        .collect();
+
+    results.sort_unstable_by(|a, b| b.burn().total_cmp(&a.burn()).then(a.source.cmp(&b.source)));
+    results
+}
@@ -0,0 +1,94 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+use crate::parser::LogEntry;
+
+// how far into the file a banner line may appear
+const HEAD_ENTRIES: usize = 500;
+const STARTUP_LIMIT: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BannerKind {
+    Version,
+    Listen,
+    Config,
+}
+
+impl BannerKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Version => "version",
+            Self::Listen => "listen",
+            Self::Config => "config",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BannerLine {
+    pub kind: BannerKind,
+    pub line_number: usize,
+    pub timestamp: String,
+    pub message: String,
+}
+
+fn version_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:version|build|release|revision|commit)\b[\s:=]*v?[\w.+-]*\d|\bv\d+\.\d+(?:\.\d+)?\b")
+            .unwrap()
+    })
+}
+
+fn listen_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:listening|bound|binding|serving)\b.*(?::\d{2,5}\b|\bport\b)|\bport[\s:=]+\d{2,5}\b")
+            .unwrap()
+    })
+}
+
+fn config_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(?:config(?:uration)?|settings|environment)\b|\b[\w.-]+\.(?:ya?ml|toml|ini|conf|properties)\b")
+            .unwrap()
+    })
+}
+
+fn classify(message: &str) -> Option<BannerKind> {
+    if listen_re().is_match(message) {
+        Some(BannerKind::Listen)
+    } else if version_re().is_match(message) {
+        Some(BannerKind::Version)
+    } else if config_re().is_match(message) {
+        Some(BannerKind::Config)
+    } else {
+        None
+    }
+}
+
+// Version strings, listening ports and config dumps near the start of the
+// log: which build and configuration produced it. Repeats of a line
+// already listed are skipped.
+pub fn banner(entries: &[LogEntry]) -> Vec<BannerLine> {
+    let mut seen = HashSet::new();
+    entries
+        .iter()
+        .take(HEAD_ENTRIES)
+        .filter_map(|entry| {
+            let message = entry.message.lines().next().unwrap_or("");
+            let kind = classify(message)?;
+            seen.insert(message).then(|| BannerLine {
+                kind,
+                line_number: entry.line_number,
+                timestamp: entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                message: message.to_string(),
+            })
+        })
+        .take(STARTUP_LIMIT)
+        .collect()
+}
@@ -1,6 +1,8 @@
-use chrono::Timelike;
+use chrono::{NaiveDateTime, Timelike};
 use serde::Serialize;
+use std::collections::VecDeque;
 
+use crate::buckets::Zone;
 use crate::parser::{LogEntry, LogLevel};
 
 #[derive(Debug, Serialize)]
@@ -11,12 +13,23 @@ pub struct TimeStats {
     pub span_human: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorBurst {
     pub window_start: String,
     pub count: usize,
 }
 
+// Whole-second timestamps on a log busy enough that most of them are shared:
+// rates and burst windows can't be resolved below a second.
+#[derive(Debug, Serialize)]
+pub struct CoarseTimestamps {
+    // entries sharing their timestamp with at least one other
+    pub shared: usize,
+    pub shared_pct: f64,
+    // most entries on a single timestamp
+    pub busiest: usize,
+}
+
 #[derive(Debug, Serialize)]
 pub struct Stats {
     pub total: usize,
@@ -26,10 +39,36 @@ pub struct Stats {
     pub hourly_counts: [usize; 24],
     pub error_rate: f64,
     pub error_bursts: Vec<ErrorBurst>,
+    // what counted as a burst for `error_bursts`
+    pub burst_params: BurstParams,
     pub mtbf_seconds: Option<f64>,
+    pub coarse_timestamps: Option<CoarseTimestamps>,
+}
+
+// `--burst-window` / `--burst-threshold`: a burst is at least `threshold`
+// errors within `window_secs` of the first of them.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BurstParams {
+    pub window_secs: i64,
+    pub threshold: usize,
+}
+
+impl Default for BurstParams {
+    fn default() -> Self {
+        Self { window_secs: 60, threshold: 3 }
+    }
 }
 
-pub fn compute(entries: &[LogEntry]) -> Stats {
+impl BurstParams {
+    fn window_end(&self, start: NaiveDateTime) -> NaiveDateTime {
+        start
+            .checked_add_signed(chrono::Duration::seconds(self.window_secs))
+            .unwrap_or(NaiveDateTime::MAX)
+    }
+}
+
+// Hours of the day are on `zone`'s wall clock.
+pub fn compute(entries: &[LogEntry], zone: Zone, burst: BurstParams) -> Stats {
     let total = entries.len();
 
     if total == 0 {
@@ -41,7 +80,9 @@ pub fn compute(entries: &[LogEntry]) -> Stats {
             hourly_counts: [0; 24],
             error_rate: 0.0,
             error_bursts: vec![],
+            burst_params: burst,
             mtbf_seconds: None,
+            coarse_timestamps: None,
         };
     }
 
@@ -60,7 +101,7 @@ pub fn compute(entries: &[LogEntry]) -> Stats {
 
     let mut hourly_counts = [0usize; 24];
     for entry in entries {
-        hourly_counts[entry.timestamp.hour() as usize] += 1;
+        hourly_counts[zone.local(entry.timestamp).hour() as usize] += 1;
     }
 
     let peak_hour = hourly_counts
@@ -75,9 +116,14 @@ pub fn compute(entries: &[LogEntry]) -> Stats {
         .count();
     let error_rate = error_count as f64 / total as f64 * 100.0;
 
-    let error_bursts = detect_bursts(entries);
+    let error_bursts = detect_bursts(entries, burst);
     let mtbf_seconds = compute_mtbf(entries, span_seconds);
 
+    let mut precision = Precision::default();
+    for entry in entries {
+        precision.push(entry.timestamp);
+    }
+
     Stats {
         total,
         time,
@@ -86,34 +132,113 @@ pub fn compute(entries: &[LogEntry]) -> Stats {
         hourly_counts,
         error_rate,
         error_bursts,
+        burst_params: burst,
         mtbf_seconds,
+        coarse_timestamps: precision.coarse(total),
     }
 }
 
-fn detect_bursts(entries: &[LogEntry]) -> Vec<ErrorBurst> {
-    // sliding 60-second window, burst threshold = 3 errors
-    const WINDOW_SECS: i64 = 60;
-    const BURST_THRESHOLD: usize = 3;
+// below this many entries shared timestamps say little about the rate
+const COARSE_MIN_ENTRIES: usize = 100;
+const COARSE_SHARED_PCT: f64 = 50.0;
 
-    let mut bursts = Vec::new();
-    let errors: Vec<&LogEntry> = entries
+// Tracks how many entries repeat a timestamp; entries must arrive sorted.
+#[derive(Default)]
+struct Precision {
+    subsecond: bool,
+    prev: Option<NaiveDateTime>,
+    run: usize,
+    shared: usize,
+    busiest: usize,
+}
+
+impl Precision {
+    fn push(&mut self, ts: NaiveDateTime) {
+        self.subsecond |= ts.nanosecond() != 0;
+        if self.prev == Some(ts) {
+            self.run += 1;
+            // the first entry of a run is shared too, once a second one joins
+            self.shared += if self.run == 2 { 2 } else { 1 };
+        } else {
+            self.run = 1;
+            self.prev = Some(ts);
+        }
+        self.busiest = self.busiest.max(self.run);
+    }
+
+    fn coarse(&self, total: usize) -> Option<CoarseTimestamps> {
+        let shared_pct = self.shared as f64 / total.max(1) as f64 * 100.0;
+        (!self.subsecond && total >= COARSE_MIN_ENTRIES && shared_pct >= COARSE_SHARED_PCT).then_some(
+            CoarseTimestamps { shared: self.shared, shared_pct, busiest: self.busiest },
+        )
+    }
+}
+
+// Smallest [start, end] span containing `coverage` (0–1] of the error/fatal
+// entries. `entries` must be sorted by timestamp.
+pub fn error_focus_window(
+    entries: &[LogEntry],
+    coverage: f64,
+) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let errors: Vec<NaiveDateTime> = entries
+        .iter()
+        .filter(|e| matches!(e.level, LogLevel::Error | LogLevel::Fatal))
+        .map(|e| e.timestamp)
+        .collect();
+
+    if errors.len() < 2 {
+        return None;
+    }
+
+    let k = ((errors.len() as f64 * coverage.clamp(0.0, 1.0)).ceil() as usize).clamp(1, errors.len());
+
+    (0..=errors.len() - k)
+        .map(|i| (errors[i], errors[i + k - 1]))
+        .min_by_key(|(start, end)| *end - *start)
+}
+
+fn detect_bursts(entries: &[LogEntry], params: BurstParams) -> Vec<ErrorBurst> {
+    let errors: Vec<NaiveDateTime> = entries
         .iter()
         .filter(|e| matches!(e.level, LogLevel::Error | LogLevel::Fatal))
+        .map(|e| e.timestamp)
         .collect();
+    bursts_in(&spread(&errors), params)
+}
 
+// Whole-second timestamps hide the order of what happened within the
+// second, so a window ending on second S would take in every error logged
+// during S. Errors sharing a whole-second timestamp are spread evenly across
+// that second in line order instead: the k-th of n lands at S + k/n.
+// `errors` must be sorted.
+fn spread(errors: &[NaiveDateTime]) -> Vec<NaiveDateTime> {
+    errors
+        .chunk_by(|a, b| a == b)
+        .flat_map(|group| spread_group(group[0], group.len()))
+        .collect()
+}
+
+fn spread_group(ts: NaiveDateTime, n: usize) -> impl Iterator<Item = NaiveDateTime> {
+    let whole_second = ts.nanosecond() == 0;
+    (0..n).map(move |k| {
+        if whole_second {
+            ts + chrono::Duration::nanoseconds(k as i64 * 1_000_000_000 / n as i64)
+        } else {
+            ts
+        }
+    })
+}
+
+// `errors` are sorted error timestamps
+fn bursts_in(errors: &[NaiveDateTime], params: BurstParams) -> Vec<ErrorBurst> {
+    let mut bursts = Vec::new();
     let mut i = 0;
     while i < errors.len() {
-        let window_end = errors[i].timestamp + chrono::Duration::seconds(WINDOW_SECS);
-        let count = errors[i..]
-            .iter()
-            .take_while(|e| e.timestamp <= window_end)
-            .count();
-
-        if count >= BURST_THRESHOLD {
-            bursts.push(ErrorBurst {
-                window_start: errors[i].timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
-                count,
-            });
+        let window_end = params.window_end(errors[i]);
+        let count = errors[i..].iter().take_while(|t| **t <= window_end).count();
+
+        if count >= params.threshold {
+            bursts.push(burst(errors[i], count));
             i += count;
         } else {
             i += 1;
@@ -123,6 +248,13 @@ fn detect_bursts(entries: &[LogEntry]) -> Vec<ErrorBurst> {
     bursts
 }
 
+fn burst(start: NaiveDateTime, count: usize) -> ErrorBurst {
+    ErrorBurst {
+        window_start: start.format("%Y-%m-%d %H:%M:%S").to_string(),
+        count,
+    }
+}
+
 fn compute_mtbf(entries: &[LogEntry], span_seconds: i64) -> Option<f64> {
     let error_count = entries
         .iter()
@@ -136,7 +268,120 @@ fn compute_mtbf(entries: &[LogEntry], span_seconds: i64) -> Option<f64> {
     Some(span_seconds as f64 / (error_count - 1) as f64)
 }
 
-fn format_duration(secs: i64) -> String {
+// Incremental counterpart of `compute` for entries that keep arriving
+// (follow mode); `stats()` gives the same result `compute` would for
+// everything pushed so far, as long as entries arrive in timestamp order
+// (follow mode holds a reorder window for that). A straggler older than the
+// newest entry still counts toward the totals, span and error rate, but not
+// toward bursts or timestamp precision, which only look forward.
+#[derive(Default)]
+pub struct LiveStats {
+    total: usize,
+    first: Option<NaiveDateTime>,
+    last: Option<NaiveDateTime>,
+    hourly_counts: [usize; 24],
+    errors: usize,
+    bursts: Vec<ErrorBurst>,
+    // errors whose burst window is still open, already spread
+    pending: VecDeque<NaiveDateTime>,
+    // errors on the newest timestamp, spread once a later one arrives
+    open: Option<(NaiveDateTime, usize)>,
+    precision: Precision,
+    zone: Zone,
+    burst: BurstParams,
+}
+
+impl LiveStats {
+    pub fn new(zone: Zone, burst: BurstParams) -> Self {
+        Self { zone, burst, ..Default::default() }
+    }
+
+    pub fn push(&mut self, entry: &LogEntry) {
+        let ts = entry.timestamp;
+        self.total += 1;
+        let late = self.last.is_some_and(|last| ts < last);
+        self.first = Some(self.first.map_or(ts, |first| first.min(ts)));
+        self.last = Some(self.last.map_or(ts, |last| last.max(ts)));
+        self.hourly_counts[self.zone.local(ts).hour() as usize] += 1;
+        if !late {
+            self.precision.push(ts);
+        }
+
+        if !matches!(entry.level, LogLevel::Error | LogLevel::Fatal) {
+            return;
+        }
+        self.errors += 1;
+        if late {
+            return;
+        }
+        match self.open {
+            Some((open_ts, ref mut n)) if open_ts == ts => *n += 1,
+            _ => {
+                if let Some((open_ts, n)) = self.open {
+                    self.pending.extend(spread_group(open_ts, n));
+                }
+                self.open = Some((ts, 1));
+            }
+        }
+
+        // a window that closed before this error can be settled the same
+        // way detect_bursts would
+        while let Some(&start) = self.pending.front() {
+            let window_end = self.burst.window_end(start);
+            if ts <= window_end {
+                break;
+            }
+            let count = self.pending.iter().take_while(|t| **t <= window_end).count();
+            if count >= self.burst.threshold {
+                self.bursts.push(burst(start, count));
+                self.pending.drain(..count);
+            } else {
+                self.pending.pop_front();
+            }
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        let (Some(first), Some(last)) = (self.first, self.last) else {
+            return compute(&[], self.zone, self.burst);
+        };
+        let span_seconds = (last - first).num_seconds().max(1);
+
+        let peak_hour = self
+            .hourly_counts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &c)| c)
+            .map(|(h, _)| h as u32);
+
+        let mut pending: Vec<NaiveDateTime> = self.pending.iter().copied().collect();
+        if let Some((open_ts, n)) = self.open {
+            pending.extend(spread_group(open_ts, n));
+        }
+        let mut error_bursts = self.bursts.clone();
+        error_bursts.extend(bursts_in(&pending, self.burst));
+
+        Stats {
+            total: self.total,
+            time: Some(TimeStats {
+                start: first.format("%Y-%m-%d %H:%M:%S").to_string(),
+                end: last.format("%Y-%m-%d %H:%M:%S").to_string(),
+                span_seconds,
+                span_human: format_duration(span_seconds),
+            }),
+            rate_per_minute: self.total as f64 / (span_seconds as f64 / 60.0),
+            peak_hour,
+            hourly_counts: self.hourly_counts,
+            error_rate: self.errors as f64 / self.total as f64 * 100.0,
+            error_bursts,
+            burst_params: self.burst,
+            mtbf_seconds: (self.errors >= 2).then(|| span_seconds as f64 / (self.errors - 1) as f64),
+            coarse_timestamps: self.precision.coarse(self.total),
+        }
+    }
+}
+
+pub fn format_duration(secs: i64) -> String {
     let h = secs / 3600;
     let m = (secs % 3600) / 60;
     let s = secs % 60;
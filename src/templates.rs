@@ -0,0 +1,98 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::parser::{LogEntry, LogLevel};
+
+pub const WILDCARD: &str = "<*>";
+
+// Masks the variable parts of a message (anything with a digit, paths, URLs,
+// emails) so `Connection to 10.0.0.4 timed out after 3000 ms` and its
+// siblings share the template `Connection to <*> timed out after <*> ms`.
+pub fn fingerprint(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    for token in message.split_whitespace() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        if is_variable(token) {
+            out.push_str(WILDCARD);
+        } else {
+            out.push_str(token);
+        }
+    }
+    out
+}
+
+fn is_variable(token: &str) -> bool {
+    token.bytes().any(|b| b.is_ascii_digit())
+        || token.contains("://")
+        || token.contains('@')
+        || (token.starts_with('/') && token[1..].contains('/'))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorPrecursor {
+    pub error_template: String,
+    pub occurrences: usize,
+    pub predecessor: String,
+    pub predecessor_count: usize,
+    pub share: f64,
+}
+
+// For every error template, the template that most often came immediately
+// before it in the same source's stream.
+pub fn error_precursors(entries: &[LogEntry], min_occurrences: usize, limit: usize) -> Vec<ErrorPrecursor> {
+    let mut last_by_source: HashMap<Option<Arc<str>>, String> = HashMap::new();
+    let mut transitions: HashMap<String, HashMap<String, usize>> = HashMap::new();
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+
+    for entry in entries {
+        let template = fingerprint(&entry.message);
+        let is_error = matches!(entry.level, LogLevel::Error | LogLevel::Fatal);
+
+        if is_error {
+            *occurrences.entry(template.clone()).or_insert(0) += 1;
+            if let Some(prev) = last_by_source.get(&entry.source) {
+                *transitions
+                    .entry(template.clone())
+                    .or_default()
+                    .entry(prev.clone())
+                    .or_insert(0) += 1;
+            }
+        }
+
+        last_by_source.insert(entry.source.clone(), template);
+    }
+
+    let mut result: Vec<ErrorPrecursor> = transitions
+        .into_iter()
+        .filter_map(|(error_template, preds)| {
+            let total = occurrences[&error_template];
+            if total < min_occurrences {
+                return None;
+            }
+
+            let (predecessor, predecessor_count) = preds
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))?;
+
+            Some(ErrorPrecursor {
+                share: predecessor_count as f64 / total as f64,
+                error_template,
+                occurrences: total,
+                predecessor,
+                predecessor_count,
+            })
+        })
+        .collect();
+
+    result.sort_unstable_by(|a, b| {
+        b.occurrences
+            .cmp(&a.occurrences)
+            .then(b.share.total_cmp(&a.share))
+            .then(a.error_template.cmp(&b.error_template))
+    });
+    result.truncate(limit);
+    result
+}
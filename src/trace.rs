@@ -0,0 +1,152 @@
+use chrono::{Duration, NaiveDateTime};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::io;
+
+use crate::cli::TraceArgs;
+use crate::parser::{LogEntry, LogFormat, LogParser};
+use crate::stats::format_duration;
+
+pub fn run(args: &TraceArgs) -> io::Result<(String, usize)> {
+    let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+    let parser = LogParser::with_format(format).with_line_filter(Some(args.trace_id.clone()));
+    let (entries, _) = parser.parse_files(&args.file_paths)?;
+
+    let markdown = narrative(&args.trace_id, &args.file_paths, &entries, args.gap);
+    Ok((markdown, entries.len()))
+}
+
+// A chronological write-up of every entry mentioning `id`, as Markdown that
+// pastes cleanly into a ticket: a short summary, then one bullet per entry
+// with its offset from the first, annotated where something changes (a new
+// source joins, the first error, a quiet gap of at least `gap_secs`).
+pub fn narrative(id: &str, files: &[String], entries: &[LogEntry], gap_secs: u64) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "# Trace {}", code(id));
+    let _ = writeln!(md);
+
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        let _ = writeln!(md, "No entries mention {} in {}.", code(id), files.join(", "));
+        return md;
+    };
+
+    let mut sources: Vec<&str> = Vec::new();
+    for entry in entries {
+        let name = source_name(entry);
+        if !sources.contains(&name) {
+            sources.push(name);
+        }
+    }
+
+    let mut levels: BTreeMap<u8, (&str, usize)> = BTreeMap::new();
+    for entry in entries {
+        levels.entry(entry.level.severity()).or_insert((entry.level.as_str(), 0)).1 += 1;
+    }
+    let level_summary: Vec<String> = levels
+        .values()
+        .rev()
+        .map(|(name, count)| format!("{} {}", count, name))
+        .collect();
+
+    let span = last.timestamp - first.timestamp;
+    let first_error = entries.iter().position(|e| e.level.severity() >= 3);
+
+    let _ = writeln!(
+        md,
+        "- **Entries:** {} across {} source{} ({})",
+        entries.len(),
+        sources.len(),
+        if sources.len() == 1 { "" } else { "s" },
+        sources.join(", ")
+    );
+    let _ = writeln!(md, "- **Levels:** {}", level_summary.join(", "));
+    let _ = writeln!(
+        md,
+        "- **Span:** {} → {} ({})",
+        first.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        last.timestamp.format("%Y-%m-%d %H:%M:%S"),
+        format_duration(span.num_seconds())
+    );
+    if let Some(i) = first_error {
+        let e = &entries[i];
+        let _ = writeln!(
+            md,
+            "- **First error:** {} after the first entry, from {}",
+            offset(e.timestamp - first.timestamp),
+            source_name(e)
+        );
+    }
+    let _ = writeln!(md, "- **Files:** {}", files.join(", "));
+    let _ = writeln!(md);
+    let _ = writeln!(md, "## Timeline");
+    let _ = writeln!(md);
+
+    let same_day = first.timestamp.date() == last.timestamp.date();
+    let gap = Duration::seconds(gap_secs as i64);
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut prev: Option<NaiveDateTime> = None;
+
+    for (i, entry) in entries.iter().enumerate() {
+        if let Some(prev) = prev {
+            let quiet = entry.timestamp - prev;
+            if gap_secs > 0 && quiet >= gap {
+                let _ = writeln!(md, "- _… {} with no entries …_", format_duration(quiet.num_seconds()));
+            }
+        }
+        prev = Some(entry.timestamp);
+
+        let time = if same_day {
+            entry.timestamp.format("%H:%M:%S%.f")
+        } else {
+            entry.timestamp.format("%Y-%m-%d %H:%M:%S%.f")
+        };
+        let source = source_name(entry);
+
+        let mut notes = Vec::new();
+        if Some(i) == first_error {
+            notes.push("first error".to_string());
+        }
+        if seen.insert(source) && i > 0 {
+            notes.push(format!("first entry from {}", source));
+        }
+
+        let _ = write!(
+            md,
+            "- `{}` +{} **{}** `{}` — {}",
+            time,
+            offset(entry.timestamp - first.timestamp),
+            entry.level.as_str(),
+            source,
+            code(&entry.message)
+        );
+        if !notes.is_empty() {
+            let _ = write!(md, " ⚠ _{}_", notes.join("; "));
+        }
+        let _ = writeln!(md);
+    }
+
+    md
+}
+
+fn source_name(entry: &LogEntry) -> &str {
+    entry.source.as_deref().unwrap_or("unknown")
+}
+
+// sub-second precision for short traces, whole units beyond a minute
+fn offset(d: Duration) -> String {
+    let ms = d.num_milliseconds();
+    if ms < 60_000 {
+        format!("{:.3}s", ms as f64 / 1000.0)
+    } else {
+        format_duration(d.num_seconds())
+    }
+}
+
+// inline code span that survives backticks in the text
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
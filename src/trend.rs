@@ -0,0 +1,210 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+
+use crate::cli::TrendArgs;
+use crate::parser::{LogEntry, LogFormat, LogParser};
+
+const STATE_VERSION: u32 = 1;
+// run history kept for `trend`'s header; day counts are never dropped
+const MAX_RUNS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DayCount {
+    pub entries: usize,
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub recorded_at: String,
+    pub input: String,
+    pub entries: usize,
+    pub days: usize,
+}
+
+// `--state`: entries and errors per log day, per input, across runs. The
+// input is the file list as given, so analyzing the same (or a grown) file
+// again replaces the days it covers rather than counting them twice, while
+// different files logging the same day add up.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrendDb {
+    version: u32,
+    pub days: BTreeMap<NaiveDate, BTreeMap<String, DayCount>>,
+    pub runs: Vec<RunRecord>,
+}
+
+impl TrendDb {
+    // a missing file is an empty store, so the first run creates it
+    pub fn load(path: &str) -> io::Result<Self> {
+        let file = match File::open(path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Self { version: STATE_VERSION, ..Default::default() })
+            }
+            Err(e) => return Err(e),
+        };
+        let db: Self = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("not a logscope trend store: {}", e)))?;
+        if db.version != STATE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("trend store version {} is not supported (expected {})", db.version, STATE_VERSION),
+            ));
+        }
+        Ok(db)
+    }
+
+    // written beside the target and renamed over it, so an interrupted run
+    // never leaves a truncated store
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let tmp = format!("{}.tmp", path);
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        serde_json::to_writer(&mut out, self)?;
+        out.flush()?;
+        drop(out);
+        fs::rename(&tmp, path)
+    }
+
+    pub fn record(&mut self, input: &str, entries: &[LogEntry]) -> RunRecord {
+        let mut per_day: BTreeMap<NaiveDate, DayCount> = BTreeMap::new();
+        for entry in entries {
+            let day = per_day.entry(entry.timestamp.date()).or_default();
+            day.entries += 1;
+            if entry.level.severity() >= 3 {
+                day.errors += 1;
+            }
+        }
+
+        let days = per_day.len();
+        for (date, count) in per_day {
+            self.days.entry(date).or_default().insert(input.to_string(), count);
+        }
+
+        let run = RunRecord {
+            recorded_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            input: input.to_string(),
+            entries: entries.len(),
+            days,
+        };
+        self.runs.push(run.clone());
+        if self.runs.len() > MAX_RUNS {
+            self.runs.drain(..self.runs.len() - MAX_RUNS);
+        }
+        run
+    }
+
+    // Monday-started weeks holding at least one recorded day, oldest first
+    pub fn weeks(&self) -> Vec<WeekTrend> {
+        let mut weeks: BTreeMap<NaiveDate, WeekTrend> = BTreeMap::new();
+        for (date, inputs) in &self.days {
+            let start = *date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            let week = weeks.entry(start).or_insert_with(|| WeekTrend { start, ..Default::default() });
+            week.days += 1;
+            for count in inputs.values() {
+                week.entries += count.entries;
+                week.errors += count.errors;
+            }
+        }
+        weeks.into_values().collect()
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WeekTrend {
+    pub start: NaiveDate,
+    // days with entries; the current week is usually partial
+    pub days: usize,
+    pub entries: usize,
+    pub errors: usize,
+}
+
+impl WeekTrend {
+    pub fn per_day(&self) -> f64 {
+        self.entries as f64 / self.days.max(1) as f64
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        self.errors as f64 / self.entries.max(1) as f64
+    }
+}
+
+pub fn run(args: &TrendArgs, color: bool) -> io::Result<()> {
+    let mut db = TrendDb::load(&args.state)?;
+
+    if !args.file_paths.is_empty() {
+        let format = args.format.as_deref().map_or(LogFormat::Auto, LogFormat::from_str);
+        let (entries, _) = LogParser::with_format(format).parse_files(&args.file_paths)?;
+        let run = db.record(&args.file_paths.join(", "), &entries);
+        db.save(&args.state)?;
+        println!(
+            "Recorded {} entries over {} day{} from {} into {}",
+            run.entries,
+            run.days,
+            if run.days == 1 { "" } else { "s" },
+            run.input,
+            args.state
+        );
+    }
+
+    print_weeks(&db, args.weeks, color);
+    Ok(())
+}
+
+fn print_weeks(db: &TrendDb, limit: usize, color: bool) {
+    let title = "logscope — Weekly Trend";
+    if color {
+        println!("\n{}", title.bold().cyan());
+    } else {
+        println!("\n{}", title);
+    }
+    println!("{}", "─".repeat(74));
+
+    let weeks = db.weeks();
+    if weeks.is_empty() {
+        println!("No runs recorded yet; pass log files to record one.\n");
+        return;
+    }
+    println!("{} runs recorded, last {}\n", db.runs.len(), db.runs.last().map_or("-", |r| r.recorded_at.as_str()));
+
+    println!(
+        "  {:<10}  {:>4}  {:>10}  {:>10}  {:>8}  {:>7}  {:>9}  {:>9}",
+        "week of", "days", "entries", "per day", "errors", "rate", "Δ volume", "Δ rate"
+    );
+    let skip = weeks.len().saturating_sub(limit);
+    for (i, week) in weeks.iter().enumerate().skip(skip) {
+        // only a directly preceding week compares; a gap in recording is no trend
+        let prev = i
+            .checked_sub(1)
+            .map(|j| &weeks[j])
+            .filter(|p| week.start - p.start == Duration::days(7));
+        let (volume, rate, worse) = match prev {
+            Some(p) => {
+                let volume = (week.per_day() / p.per_day().max(f64::MIN_POSITIVE) - 1.0) * 100.0;
+                let rate = (week.error_rate() - p.error_rate()) * 100.0;
+                (format!("{:+.1}%", volume), format!("{:+.2}pp", rate), rate > 0.0)
+            }
+            None => ("—".to_string(), "—".to_string(), false),
+        };
+        let line = format!(
+            "  {:<10}  {:>4}  {:>10}  {:>10.0}  {:>8}  {:>6.2}%  {:>9}  {:>9}",
+            week.start.format("%Y-%m-%d"),
+            week.days,
+            week.entries,
+            week.per_day(),
+            week.errors,
+            week.error_rate() * 100.0,
+            volume,
+            rate,
+        );
+        if color && worse {
+            println!("{}", line.red());
+        } else {
+            println!("{}", line);
+        }
+    }
+    println!();
+}
@@ -0,0 +1,97 @@
+// Quantities written with their unit — `1.2s`, `350ms`, `2.5MB`, `4KiB` —
+// brought to one unit per kind so values logged in different units can be
+// added up and compared.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Millis,
+    Bytes,
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Millis => "ms",
+            Self::Bytes => "B",
+        }
+    }
+}
+
+// A number with an optional duration or size unit, in milliseconds or bytes;
+// a bare number comes back as it is, without a unit.
+pub fn quantity(text: &str) -> Option<(f64, Option<Unit>)> {
+    let text = text.trim();
+    if let Ok(v) = text.parse() {
+        return Some((v, None));
+    }
+    if let Some(bytes) = size_bytes(text) {
+        return Some((bytes, Some(Unit::Bytes)));
+    }
+    // a bare count would be taken as `unit_ms` here; that case is above
+    duration_ms(text, 1.0).map(|ms| (ms, Some(Unit::Millis)))
+}
+
+// `512B`, `4KB` (1000), `4KiB` (1024), up to TB/TiB; case doesn't matter
+pub fn size_bytes(spec: &str) -> Option<f64> {
+    let spec = spec.trim();
+    let split = spec.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(spec.len());
+    let (count, unit) = spec.split_at(split);
+    let count: f64 = count.parse().ok()?;
+    let unit = unit.trim().to_ascii_lowercase();
+    let (prefix, base) = if let Some(prefix) = unit.strip_suffix("ib") {
+        (prefix, 1024.0_f64)
+    } else {
+        (unit.strip_suffix('b').or_else(|| unit.strip_suffix("bytes"))?, 1000.0)
+    };
+    let power = match prefix {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        _ => return None,
+    };
+    Some(count * base.powi(power))
+}
+
+// `250ms`, `1.5s`, `2m`, `1h`, `1d`, `500us`, ISO 8601 `PT1.5S`, or a bare
+// number of `unit_ms`, in milliseconds
+pub fn duration_ms(spec: &str, unit_ms: f64) -> Option<f64> {
+    let spec = spec.trim();
+    if let Some(iso) = spec.strip_prefix("PT").or_else(|| spec.strip_prefix("pt")) {
+        return iso_duration_ms(iso);
+    }
+    let split = spec.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(spec.len());
+    let (count, unit) = spec.split_at(split);
+    let count: f64 = count.parse().ok()?;
+    let ms = match unit.trim() {
+        "" => unit_ms,
+        "ns" => 1e-6,
+        "us" | "µs" => 1e-3,
+        "ms" | "msec" | "millis" => 1.0,
+        "s" | "sec" | "secs" | "second" | "seconds" => 1000.0,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60_000.0,
+        "h" | "hour" | "hours" => 3_600_000.0,
+        "d" | "day" | "days" => 86_400_000.0,
+        _ => return None,
+    };
+    Some(count * ms)
+}
+
+fn iso_duration_ms(spec: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut rest = spec;
+    while !rest.is_empty() {
+        let split = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let count: f64 = rest[..split].parse().ok()?;
+        total += count
+            * match &rest[split..split + 1] {
+                "H" | "h" => 3_600_000.0,
+                "M" | "m" => 60_000.0,
+                "S" | "s" => 1000.0,
+                _ => return None,
+            };
+        rest = &rest[split + 1..];
+    }
+    Some(total)
+}
@@ -0,0 +1,146 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use crate::parser::{LogEntry, LogLevel};
+use crate::templates::fingerprint;
+
+// versions compared side by side; further ones are left out
+const VERSION_LIMIT: usize = 6;
+// templates per version that make the comparison table
+const TEMPLATES_PER_VERSION: usize = 5;
+const TEMPLATE_ROWS: usize = 12;
+
+// `version 2.14.3`, `version: v2.14.3-rc1`, `v2.14`
+fn default_version_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\bversion[\s:=]+v?(\d+(?:\.\d+)+[\w.+-]*)|\bv(\d+\.\d+(?:\.\d+)?)\b").unwrap()
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionSummary {
+    pub version: String,
+    pub entries: usize,
+    pub errors: usize,
+    pub error_rate: f64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionTemplate {
+    pub template: String,
+    // entries per version, in the order of `VersionComparison::versions`
+    pub counts: Vec<usize>,
+    pub is_error: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionComparison {
+    pub versions: Vec<VersionSummary>,
+    pub templates: Vec<VersionTemplate>,
+    // entries logged before their source announced a version, or by one
+    // beyond the versions compared
+    pub unattributed: usize,
+}
+
+// Which version wrote each entry: a `version` field when the entry has one,
+// otherwise the last version its source announced (a startup banner, say),
+// so during a rolling deploy old and new instances are told apart. `pattern`
+// replaces the built-in version regex; its first group that matched is the
+// version, or the whole match without groups.
+pub fn compare(entries: &[LogEntry], pattern: Option<&Regex>) -> Option<VersionComparison> {
+    let re = pattern.unwrap_or_else(|| default_version_re());
+
+    let mut current: HashMap<Option<Arc<str>>, usize> = HashMap::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut versions: Vec<VersionSummary> = Vec::new();
+    // version of each entry, by position in `versions`
+    let mut tags: Vec<Option<usize>> = Vec::with_capacity(entries.len());
+    let mut unattributed = 0;
+
+    for entry in entries {
+        let announced = match entry.fields.get("version") {
+            Some(serde_json::Value::String(v)) => Some(v.clone()),
+            Some(v) if !v.is_null() => Some(v.to_string()),
+            _ => extract(re, &entry.message),
+        };
+        let ts = entry.timestamp.format("%Y-%m-%d %H:%M:%S").to_string();
+        if let Some(version) = announced {
+            let i = *index.entry(version.clone()).or_insert_with(|| {
+                versions.push(VersionSummary {
+                    version,
+                    entries: 0,
+                    errors: 0,
+                    error_rate: 0.0,
+                    first_seen: ts.clone(),
+                    last_seen: ts.clone(),
+                });
+                versions.len() - 1
+            });
+            current.insert(entry.source.clone(), i);
+        }
+
+        let tag = current.get(&entry.source).copied().filter(|&i| i < VERSION_LIMIT);
+        tags.push(tag);
+        let Some(i) = tag else {
+            unattributed += 1;
+            continue;
+        };
+        let v = &mut versions[i];
+        v.entries += 1;
+        if matches!(entry.level, LogLevel::Error | LogLevel::Fatal) {
+            v.errors += 1;
+        }
+        v.last_seen = ts;
+    }
+
+    versions.truncate(VERSION_LIMIT);
+    if versions.len() < 2 {
+        return None;
+    }
+    for v in &mut versions {
+        v.error_rate = v.errors as f64 / v.entries.max(1) as f64;
+    }
+
+    // template -> (entries per version, any of them an error)
+    let mut counts: HashMap<String, (Vec<usize>, bool)> = HashMap::new();
+    for (entry, tag) in entries.iter().zip(&tags) {
+        let Some(i) = *tag else { continue };
+        let c = counts.entry(fingerprint(&entry.message)).or_insert_with(|| (vec![0; versions.len()], false));
+        c.0[i] += 1;
+        c.1 |= matches!(entry.level, LogLevel::Error | LogLevel::Fatal);
+    }
+
+    // each version's busiest templates, compared across all of them
+    let mut rows: Vec<&String> = Vec::new();
+    for i in 0..versions.len() {
+        let mut top: Vec<(&String, usize)> = counts.iter().map(|(t, c)| (t, c.0[i])).filter(|&(_, n)| n > 0).collect();
+        top.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        for (template, _) in top.into_iter().take(TEMPLATES_PER_VERSION) {
+            if !rows.contains(&template) {
+                rows.push(template);
+            }
+        }
+    }
+    rows.truncate(TEMPLATE_ROWS);
+
+    let templates = rows
+        .into_iter()
+        .map(|template| {
+            let (counts, is_error) = counts[template].clone();
+            VersionTemplate { template: template.clone(), counts, is_error }
+        })
+        .collect();
+
+    Some(VersionComparison { versions, templates, unattributed })
+}
+
+fn extract(re: &Regex, message: &str) -> Option<String> {
+    let caps = re.captures(message)?;
+    let m = caps.iter().skip(1).flatten().next().or_else(|| caps.get(0))?;
+    Some(m.as_str().to_string())
+}
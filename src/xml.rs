@@ -0,0 +1,304 @@
+use regex::Regex;
+use roxmltree::{Document, Node};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+// Where each field lives when `--xml-field` doesn't say, first path present
+// wins: log4j's XMLLayout, Windows event XML (`wevtutil qe /f:xml`), then
+// generic `<event><timestamp>…</timestamp>…</event>` records.
+const DEFAULT_FIELDS: &[(&str, &[&str])] = &[
+    ("timestamp", &["@timestamp", "System/TimeCreated/@SystemTime", "timestamp", "time", "@time", "date"]),
+    ("level", &["@level", "System/Level", "level", "severity", "@severity"]),
+    ("message", &["message", "RenderingInfo/Message", "msg", "text"]),
+    ("source", &["@logger", "System/Provider/@Name", "logger", "source", "@source"]),
+    ("thread", &["@thread"]),
+    ("throwable", &["throwable"]),
+    ("event_id", &["System/EventID"]),
+    ("computer", &["System/Computer"]),
+];
+
+// `--xml-field 'user=EventData/Data[@Name="TargetUserName"]'`: a field taken
+// from each record by a small XPath subset — child elements by name
+// separated by `/`, an optional `[@attr='value']` test per step and a final
+// `@attr` for an attribute. Names are matched case-insensitively and without
+// their namespace prefix; an element's value is its text.
+#[derive(Debug, Clone)]
+pub struct XmlField {
+    pub name: String,
+    path: XmlPath,
+}
+
+impl XmlField {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, path) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("Expected NAME=PATH, got '{}'", spec))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("Missing field name in '{}'", spec));
+        }
+        Ok(Self { name: name.to_string(), path: XmlPath::parse(path.trim())? })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Step {
+    name: String,
+    // [@attr='value']
+    test: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+struct XmlPath {
+    steps: Vec<Step>,
+    attribute: Option<String>,
+}
+
+impl XmlPath {
+    fn parse(path: &str) -> Result<Self, String> {
+        let path = path.trim_start_matches("./").trim_start_matches('/');
+        let mut parts = split_steps(path);
+        let attribute = match parts.last() {
+            Some(last) if last.starts_with('@') => parts.pop().map(|a| a[1..].to_string()),
+            _ => None,
+        };
+        let steps = parts
+            .into_iter()
+            .map(|part| {
+                let Some((name, test)) = part.split_once('[') else {
+                    return Ok(Step { name: part, test: None });
+                };
+                let (attr, value) = test
+                    .strip_suffix(']')
+                    .and_then(|t| t.strip_prefix('@'))
+                    .and_then(|t| t.split_once('='))
+                    .ok_or_else(|| format!("Expected [@attr='value'] in '{}'", path))?;
+                let value = value.trim().trim_matches(|c| c == '\'' || c == '"');
+                Ok(Step { name: name.to_string(), test: Some((attr.trim().to_string(), value.to_string())) })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        if steps.iter().any(|s| s.name.is_empty()) || (steps.is_empty() && attribute.is_none()) {
+            return Err(format!("Invalid path '{}'", path));
+        }
+        Ok(Self { steps, attribute })
+    }
+
+    fn select(&self, record: Node) -> Option<String> {
+        let mut nodes = vec![record];
+        for step in &self.steps {
+            nodes = nodes
+                .iter()
+                .flat_map(|n| n.children().filter(|c| step.matches(c)))
+                .collect();
+        }
+        match self.attribute {
+            Some(ref attr) => nodes.iter().find_map(|n| attribute(n, attr)).map(str::to_string),
+            None => nodes.first().map(text).filter(|t| !t.is_empty()),
+        }
+    }
+}
+
+impl Step {
+    fn matches(&self, node: &Node) -> bool {
+        node.is_element()
+            && node.tag_name().name().eq_ignore_ascii_case(local_name(&self.name))
+            && self.test.as_ref().is_none_or(|(attr, value)| attribute(node, attr) == Some(value.as_str()))
+    }
+}
+
+// `/` outside of `[...]`
+fn split_steps(path: &str) -> Vec<String> {
+    let mut steps = vec![String::new()];
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '/' if depth == 0 => steps.push(String::new()),
+            _ => {
+                depth += (c == '[') as i32 - (c == ']') as i32;
+                steps.last_mut().unwrap().push(c);
+            }
+        }
+    }
+    steps.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn attribute<'a>(node: &Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes().find(|a| a.name().eq_ignore_ascii_case(local_name(name))).map(|a| a.value())
+}
+
+fn text(node: &Node) -> String {
+    let text: String = node.descendants().filter(|d| d.is_text()).filter_map(|d| d.text()).collect();
+    text.trim().to_string()
+}
+
+// One record's fields by name, before they become an entry.
+pub struct XmlRecord {
+    pub line_number: usize,
+    // where the record's end tag is
+    pub end_line: usize,
+    pub values: HashMap<String, String>,
+}
+
+// Which elements are records and where their fields are. Records are found
+// by name wherever they are, so a file of bare log4j fragments and a
+// `<Events>` document both work, and a record cut off at the end of a file
+// being written costs only that record.
+#[derive(Debug, Clone)]
+pub struct XmlLayout {
+    record_re: Regex,
+    fields: Vec<(String, Vec<XmlPath>)>,
+}
+
+impl Default for XmlLayout {
+    fn default() -> Self {
+        Self::new(None, &[])
+    }
+}
+
+impl XmlLayout {
+    // `fields` replace the built-in paths for the names they give
+    pub fn new(record: Option<&str>, fields: &[XmlField]) -> Self {
+        let record = record.unwrap_or("event");
+        let record_re =
+            Regex::new(&format!(r"(?i)<((?:[\w.-]+:)?{})(?:\s[^>]*)?/?>", regex::escape(local_name(record)))).unwrap();
+
+        let mut by_name: Vec<(String, Vec<XmlPath>)> = Vec::new();
+        for field in fields {
+            match by_name.iter_mut().find(|(name, _)| *name == field.name) {
+                Some((_, paths)) => paths.push(field.path.clone()),
+                None => by_name.push((field.name.clone(), vec![field.path.clone()])),
+            }
+        }
+        for (name, paths) in DEFAULT_FIELDS {
+            if !by_name.iter().any(|(n, _)| n == name) {
+                let paths = paths.iter().map(|p| XmlPath::parse(p).unwrap()).collect();
+                by_name.push((name.to_string(), paths));
+            }
+        }
+        Self { record_re, fields: by_name }
+    }
+
+    // Each record, or the line number and first line of one that isn't
+    // well-formed.
+    pub fn records(&self, text: &str) -> Vec<Result<XmlRecord, (usize, String)>> {
+        let mut records = Vec::new();
+        let (mut line_number, mut counted) = (1, 0);
+        let mut pos = 0;
+        while let Some(caps) = self.record_re.captures_at(text, pos) {
+            let start_tag = caps.get(0).unwrap();
+            line_number += memchr::memchr_iter(b'\n', &text.as_bytes()[counted..start_tag.start()]).count();
+            counted = start_tag.start();
+
+            let end = if start_tag.as_str().ends_with("/>") {
+                Some(start_tag.end())
+            } else {
+                closing_tag(text, start_tag.end(), &caps[1])
+            };
+            let Some(end) = end else {
+                let first_line = text[start_tag.start()..].lines().next().unwrap_or("");
+                records.push(Err((line_number, first_line.trim().to_string())));
+                break;
+            };
+            let fragment = &text[start_tag.start()..end];
+            records.push(
+                self.record(fragment, line_number)
+                    .ok_or_else(|| (line_number, fragment.lines().next().unwrap_or("").trim().to_string())),
+            );
+            pos = end;
+        }
+        records
+    }
+
+    fn record(&self, fragment: &str, line_number: usize) -> Option<XmlRecord> {
+        // log4j fragments use a prefix declared only by the file that would
+        // include them, so every prefix in sight is declared on a wrapper
+        let prefixes: BTreeSet<&str> = prefix_re()
+            .captures_iter(fragment)
+            .filter_map(|c| c.get(1))
+            .map(|m| m.as_str())
+            .filter(|p| !matches!(*p, "xml" | "xmlns"))
+            .collect();
+        let declarations: String = prefixes.iter().map(|p| format!(" xmlns:{}=\"urn:logscope:{}\"", p, p)).collect();
+        let wrapped = format!("<logscope{}>{}</logscope>", declarations, fragment);
+        let doc = Document::parse(&wrapped).ok()?;
+        let record = doc.root_element().first_element_child()?;
+
+        let mut values: HashMap<String, String> = self
+            .fields
+            .iter()
+            .filter_map(|(name, paths)| Some((name.clone(), paths.iter().find_map(|p| p.select(record))?)))
+            .collect();
+        if !values.contains_key("message") {
+            let event_id = values.get("event_id").map(|id| format!("Event ID {}", id));
+            if let Some(message) = event_data(record).or(event_id) {
+                values.insert("message".to_string(), message);
+            }
+        }
+        let end_line = line_number + memchr::memchr_iter(b'\n', fragment.as_bytes()).count();
+        Some(XmlRecord { line_number, end_line, values })
+    }
+}
+
+// `<EventData><Data Name="param1">…</Data>…` as `param1=… param2=…`, for
+// Windows events exported without their rendered message
+fn event_data(record: Node) -> Option<String> {
+    let data = record.children().find(|c| c.is_element() && c.tag_name().name() == "EventData")?;
+    let parts: Vec<String> = data
+        .children()
+        .filter(|c| c.is_element())
+        .map(|d| match attribute(&d, "Name") {
+            Some(name) => format!("{}={}", name, text(&d)),
+            None => text(&d),
+        })
+        .filter(|p| !p.is_empty())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+fn closing_tag(text: &str, from: usize, name: &str) -> Option<usize> {
+    let needle = format!("</{}", name);
+    let mut at = from;
+    loop {
+        let i = at + text[at..].find(&needle)?;
+        let rest = &text[i + needle.len()..];
+        let close = rest.find(|c: char| !c.is_whitespace())?;
+        if rest[close..].starts_with('>') {
+            return Some(i + needle.len() + close + 1);
+        }
+        at = i + needle.len();
+    }
+}
+
+fn prefix_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?:</?|\s)([A-Za-z_][\w.-]*):[A-Za-z_]").unwrap())
+}
+
+// Windows event levels are numbers
+pub fn windows_level(level: &str) -> Option<&'static str> {
+    match level {
+        "1" => Some("fatal"),
+        "2" => Some("error"),
+        "3" => Some("warn"),
+        "0" | "4" => Some("info"),
+        "5" => Some("debug"),
+        _ => None,
+    }
+}
+
+// a document, or log4j's fragments, at the start of a file
+pub fn looks_like_xml(buf: &[u8]) -> bool {
+    let buf = buf.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(buf);
+    let start = buf.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(buf.len());
+    let head = &buf[start..buf.len().min(start + 64)];
+    head.starts_with(b"<?xml")
+        || (head.starts_with(b"<")
+            && String::from_utf8_lossy(head)
+                .split(|c: char| c.is_whitespace() || c == '>')
+                .next()
+                .is_some_and(|tag| tag.to_lowercase().contains("event")))
+}